@@ -0,0 +1,15 @@
+// Build script: compiles protobuf definitions when the gRPC feature is enabled
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    #[cfg(feature = "grpc")]
+    tonic_build::configure().compile(
+        &[
+            "proto/authz.proto",
+            "proto/envoy/service/auth/v3/external_auth.proto",
+        ],
+        &["proto"],
+    )?;
+
+    println!("cargo:rerun-if-changed=proto");
+    Ok(())
+}
@@ -0,0 +1,471 @@
+// Administrative endpoints
+
+use crate::api::routes::AppState;
+use crate::auth::jwt::JwtClaims;
+use crate::auth::jwt_rotation;
+use crate::auth::middleware::{require_admin, require_platform_admin};
+use crate::db::sessions::{self, BatchRevocation, SessionSelector};
+use crate::domain::audit::{AuditEvent, AuditEventType};
+use crate::domain::identity::get_identity_by_id;
+use crate::errors::{AppError, Result};
+use crate::rate_limit::BucketUsage;
+use crate::redis::account_tokens::{self, TokenPurpose};
+use axum::{
+    extract::{Path, State},
+    http::HeaderMap,
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use uuid::Uuid;
+
+// ============================================================================
+// Request/Response Types
+// ============================================================================
+
+#[derive(Debug, Serialize)]
+pub struct JwtRotationResponse {
+    pub message: String,
+    /// Whether tokens signed with the previous secret are still accepted
+    pub secondary_active: bool,
+    /// How long the previous secret remains valid (one refresh-token lifetime), in seconds
+    pub secondary_window_seconds: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RateLimitBucketStatus {
+    pub bucket: String,
+    pub window_seconds: u64,
+    pub limit: u64,
+    pub count: u64,
+    pub remaining: u64,
+}
+
+impl From<BucketUsage> for RateLimitBucketStatus {
+    fn from(usage: BucketUsage) -> Self {
+        Self {
+            bucket: usage.bucket.as_str().to_string(),
+            window_seconds: usage.bucket.window_seconds(),
+            limit: usage.limit,
+            count: usage.count,
+            remaining: usage.limit.saturating_sub(usage.count),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct RateLimitStatusResponse {
+    pub identifier: String,
+    pub buckets: Vec<RateLimitBucketStatus>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct VerificationTokenResponse {
+    pub identity_id: Uuid,
+    /// Deliver to the user; it is not shown again
+    pub token: String,
+    pub expires_in: u64,
+}
+
+/// Sessions to revoke; exactly one selector must be given
+#[derive(Debug, Deserialize)]
+pub struct RevokeSessionsRequest {
+    pub identity_id: Option<Uuid>,
+    pub family_id: Option<String>,
+    /// Must be the caller's own tenant
+    pub tenant_id: Option<Uuid>,
+}
+
+impl RevokeSessionsRequest {
+    fn selector(&self, caller_tenant: Uuid) -> Result<SessionSelector> {
+        match (self.identity_id, self.family_id.as_deref(), self.tenant_id) {
+            (Some(identity_id), None, None) => Ok(SessionSelector::Identity(identity_id)),
+            (None, Some(family_id), None) if !family_id.trim().is_empty() => {
+                Ok(SessionSelector::Family(family_id.to_string()))
+            }
+            (None, None, Some(tenant_id)) if tenant_id == caller_tenant => {
+                Ok(SessionSelector::Tenant)
+            }
+            (None, None, Some(_)) => Err(AppError::Forbidden),
+            _ => Err(AppError::ValidationError(
+                "Exactly one of identity_id, family_id or tenant_id is required".to_string(),
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct RevokeSessionsResponse {
+    pub revoked_sessions: u64,
+    /// Unexpired tokens added to the revocation list
+    pub revoked_tokens: u64,
+    /// Identities that lost at least one session
+    pub identities: usize,
+}
+
+// ============================================================================
+// Handlers
+// ============================================================================
+
+/// POST /v1/admin/jwt/rotate
+///
+/// Generate and persist a new JWT signing secret (platform admins only),
+/// keeping the previous one for validation until the refresh-token lifetime
+/// has elapsed
+pub async fn rotate_jwt_secret(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<JwtRotationResponse>> {
+    let claims = require_platform_admin(&state, &headers).await?;
+    let actor_id = claims.identity_id()?;
+
+    jwt_rotation::rotate(
+        &state.db_pool,
+        &state.jwt_manager,
+        state.mfa_cipher.as_deref(),
+        actor_id,
+    )
+    .await?;
+
+    tracing::info!("JWT signing secret rotated by identity: {}", claims.sub);
+    log_jwt_key_change(&state, &claims, "rotate_jwt_secret").await?;
+
+    Ok(Json(JwtRotationResponse {
+        message: "JWT signing secret rotated".to_string(),
+        secondary_active: true,
+        secondary_window_seconds: state.jwt_manager.refresh_token_expiration(),
+    }))
+}
+
+/// DELETE /v1/admin/jwt/secondary
+///
+/// Drop the previous JWT secret immediately, invalidating any tokens it signed
+pub async fn clear_jwt_secondary(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<JwtRotationResponse>> {
+    let claims = require_platform_admin(&state, &headers).await?;
+
+    jwt_rotation::clear_previous(&state.db_pool, &state.jwt_manager).await?;
+
+    tracing::info!("JWT secondary key cleared by identity: {}", claims.sub);
+    log_jwt_key_change(&state, &claims, "clear_jwt_secondary").await?;
+
+    Ok(Json(JwtRotationResponse {
+        message: "Previous JWT signing secret dropped".to_string(),
+        secondary_active: false,
+        secondary_window_seconds: 0,
+    }))
+}
+
+/// Audit a change to the JWT signing secrets (never the secrets themselves)
+async fn log_jwt_key_change(state: &AppState, claims: &JwtClaims, action: &str) -> Result<()> {
+    let event = AuditEvent::new(
+        claims.tenant_id_uuid()?,
+        AuditEventType::ConfigurationChanged,
+        action.to_string(),
+        "jwt_signing_key".to_string(),
+    )
+    .with_actor(claims.identity_id()?);
+    state.audit_logger.log(event).await
+}
+
+/// GET /v1/admin/rate-limits/:identifier
+///
+/// Show current usage of every rate-limit bucket for an identifier
+/// (e.g. `ip:203.0.113.42`); platform admins only, as identifiers are not
+/// scoped to a tenant
+pub async fn get_rate_limits(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(identifier): Path<String>,
+) -> Result<Json<RateLimitStatusResponse>> {
+    require_platform_admin(&state, &headers).await?;
+
+    let usage = state.rate_limiter.lock().await.bucket_usage(&identifier).await?;
+
+    Ok(Json(RateLimitStatusResponse {
+        identifier,
+        buckets: usage.into_iter().map(RateLimitBucketStatus::from).collect(),
+    }))
+}
+
+/// DELETE /v1/admin/rate-limits/:identifier
+///
+/// Clear every rate-limit bucket for an identifier (platform admins only)
+pub async fn reset_rate_limits(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(identifier): Path<String>,
+) -> Result<Json<RateLimitStatusResponse>> {
+    let claims = require_platform_admin(&state, &headers).await?;
+
+    let mut limiter = state.rate_limiter.lock().await;
+    limiter.reset_all(&identifier).await?;
+    let usage = limiter.bucket_usage(&identifier).await?;
+    drop(limiter);
+
+    tracing::info!(
+        identifier = %identifier,
+        "Rate limits reset by identity: {}",
+        claims.sub
+    );
+
+    let event = AuditEvent::new(
+        claims.tenant_id_uuid()?,
+        AuditEventType::RateLimitReset,
+        "reset_rate_limits".to_string(),
+        "rate_limit".to_string(),
+    )
+    .with_actor(claims.identity_id()?)
+    .with_resource_id(identifier.clone());
+    state.audit_logger.log(event).await?;
+
+    Ok(Json(RateLimitStatusResponse {
+        identifier,
+        buckets: usage.into_iter().map(RateLimitBucketStatus::from).collect(),
+    }))
+}
+
+/// POST /v1/admin/revoke
+///
+/// Revoke every active session of an identity, a refresh-token family or the
+/// whole tenant, e.g. during a security incident
+pub async fn revoke_sessions(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(req): Json<RevokeSessionsRequest>,
+) -> Result<Json<RevokeSessionsResponse>> {
+    let claims = require_admin(&state, &headers).await?;
+    let tenant_id = claims.tenant_id_uuid()?;
+    let actor_id = claims.identity_id()?;
+    let selector = req.selector(tenant_id)?;
+
+    let mut redis_conn = state.redis_manager.clone();
+    let outcome =
+        sessions::revoke_matching(&state.db_pool, &mut redis_conn, tenant_id, &selector).await?;
+
+    tracing::warn!(
+        tenant_id = %tenant_id,
+        selector = ?selector,
+        sessions = outcome.sessions,
+        identities = outcome.per_identity.len(),
+        "Sessions batch revoked by identity: {}",
+        actor_id
+    );
+
+    for event in batch_revocation_events(tenant_id, actor_id, &selector, &outcome) {
+        state.audit_logger.log(event).await?;
+    }
+
+    Ok(Json(RevokeSessionsResponse {
+        revoked_sessions: outcome.sessions,
+        revoked_tokens: outcome.tokens,
+        identities: outcome.per_identity.len(),
+    }))
+}
+
+/// Audit events for a batch revocation: one for the request as a whole and
+/// one for each identity that lost sessions
+fn batch_revocation_events(
+    tenant_id: Uuid,
+    actor_id: Uuid,
+    selector: &SessionSelector,
+    outcome: &BatchRevocation,
+) -> Vec<AuditEvent> {
+    let selector = match selector {
+        SessionSelector::Identity(identity_id) => json!({ "identity_id": identity_id }),
+        SessionSelector::Family(family_id) => json!({ "family_id": family_id }),
+        SessionSelector::Tenant => json!({ "tenant_id": tenant_id }),
+    };
+
+    let summary = AuditEvent::new(
+        tenant_id,
+        AuditEventType::SessionRevoked,
+        "batch_revoke_sessions".to_string(),
+        "session".to_string(),
+    )
+    .with_actor(actor_id)
+    .with_metadata(json!({
+        "selector": selector,
+        "revoked_sessions": outcome.sessions,
+        "revoked_tokens": outcome.tokens,
+        "identities": outcome.per_identity.len(),
+    }));
+
+    let per_identity = outcome.per_identity.iter().map(|(identity_id, count)| {
+        AuditEvent::new(
+            tenant_id,
+            AuditEventType::SessionRevoked,
+            "batch_revoke_sessions".to_string(),
+            "identity".to_string(),
+        )
+        .with_actor(actor_id)
+        .with_resource_id(identity_id.to_string())
+        .with_metadata(json!({
+            "selector": selector,
+            "revoked_sessions": count,
+        }))
+    });
+
+    std::iter::once(summary).chain(per_identity).collect()
+}
+
+/// POST /v1/admin/identities/:id/verification-token
+///
+/// Issue an email verification token for a user awaiting verification, for
+/// the tenant's mail service to send
+pub async fn issue_verification_token(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(identity_id): Path<Uuid>,
+) -> Result<Json<VerificationTokenResponse>> {
+    let claims = require_admin(&state, &headers).await?;
+    let tenant_id = claims.tenant_id_uuid()?;
+
+    let identity = get_identity_by_id(&state.db_pool, identity_id).await?;
+    if identity.tenant_id != tenant_id {
+        return Err(AppError::IdentityNotFound);
+    }
+    if identity.status != "pending_verification" {
+        return Err(AppError::ValidationError(
+            "Identity is not awaiting email verification".to_string(),
+        ));
+    }
+
+    let mut redis_conn = state.redis_manager.clone();
+    let token =
+        account_tokens::issue_token(&mut redis_conn, TokenPurpose::EmailVerification, identity.id)
+            .await?;
+
+    let event = AuditEvent::new(
+        tenant_id,
+        AuditEventType::TokenGenerated,
+        "issue_verification_token".to_string(),
+        "identity".to_string(),
+    )
+    .with_actor(claims.identity_id()?)
+    .with_resource_id(identity.id.to_string());
+    state.audit_logger.log(event).await?;
+
+    Ok(Json(VerificationTokenResponse {
+        identity_id: identity.id,
+        token,
+        expires_in: TokenPurpose::EmailVerification.ttl_seconds(),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rate_limit::RateLimitBucket;
+
+    #[test]
+    fn test_rate_limit_bucket_status_from_usage() {
+        let status = RateLimitBucketStatus::from(BucketUsage {
+            bucket: RateLimitBucket::Hour,
+            limit: 1000,
+            count: 42,
+        });
+
+        assert_eq!(status.bucket, "hour");
+        assert_eq!(status.window_seconds, 3600);
+        assert_eq!(status.remaining, 958);
+    }
+
+    fn revoke_request(
+        identity_id: Option<Uuid>,
+        family_id: Option<&str>,
+        tenant_id: Option<Uuid>,
+    ) -> RevokeSessionsRequest {
+        RevokeSessionsRequest {
+            identity_id,
+            family_id: family_id.map(str::to_string),
+            tenant_id,
+        }
+    }
+
+    #[test]
+    fn test_revoke_request_needs_exactly_one_selector() {
+        let tenant_id = Uuid::new_v4();
+        let identity_id = Uuid::new_v4();
+
+        assert_eq!(
+            revoke_request(Some(identity_id), None, None)
+                .selector(tenant_id)
+                .unwrap(),
+            SessionSelector::Identity(identity_id)
+        );
+        assert_eq!(
+            revoke_request(None, Some("family-1"), None)
+                .selector(tenant_id)
+                .unwrap(),
+            SessionSelector::Family("family-1".to_string())
+        );
+        assert_eq!(
+            revoke_request(None, None, Some(tenant_id))
+                .selector(tenant_id)
+                .unwrap(),
+            SessionSelector::Tenant
+        );
+
+        for request in [
+            revoke_request(None, None, None),
+            revoke_request(Some(identity_id), Some("family-1"), None),
+            revoke_request(None, Some(" "), None),
+        ] {
+            assert!(matches!(
+                request.selector(tenant_id),
+                Err(AppError::ValidationError(_))
+            ));
+        }
+        assert!(matches!(
+            revoke_request(None, None, Some(Uuid::new_v4())).selector(tenant_id),
+            Err(AppError::Forbidden)
+        ));
+    }
+
+    #[test]
+    fn test_batch_revocation_audits_each_identity() {
+        let tenant_id = Uuid::new_v4();
+        let actor_id = Uuid::new_v4();
+        let first = Uuid::new_v4();
+        let second = Uuid::new_v4();
+        let outcome = BatchRevocation {
+            sessions: 3,
+            tokens: 2,
+            per_identity: [(first, 2), (second, 1)].into_iter().collect(),
+        };
+
+        let events =
+            batch_revocation_events(tenant_id, actor_id, &SessionSelector::Tenant, &outcome);
+
+        assert_eq!(events.len(), 3);
+        assert!(events
+            .iter()
+            .all(|e| e.event_type == AuditEventType::SessionRevoked
+                && e.actor_identity_id == Some(actor_id)));
+        assert_eq!(events[0].metadata["revoked_sessions"], 3);
+        assert_eq!(
+            events[0].metadata["selector"]["tenant_id"],
+            tenant_id.to_string()
+        );
+        let per_identity: Vec<_> = events[1..]
+            .iter()
+            .map(|e| e.resource_id.clone().unwrap())
+            .collect();
+        assert!(per_identity.contains(&first.to_string()));
+        assert!(per_identity.contains(&second.to_string()));
+    }
+
+    #[test]
+    fn test_rate_limit_bucket_status_over_limit() {
+        let status = RateLimitBucketStatus::from(BucketUsage {
+            bucket: RateLimitBucket::Minute,
+            limit: 10,
+            count: 12,
+        });
+
+        assert_eq!(status.remaining, 0);
+    }
+}
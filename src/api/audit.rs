@@ -0,0 +1,319 @@
+// Audit log endpoints
+
+use axum::{
+    body::Body,
+    extract::{Path, Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse, Response,
+    },
+    Json,
+};
+use futures::stream::{self, Stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::convert::Infallible;
+use tokio::sync::broadcast::{self, error::RecvError};
+use uuid::Uuid;
+
+use crate::{
+    api::routes::AppState,
+    audit::{
+        export::{self, AuditExportFilter},
+        query,
+        storage::ReplayOutcome,
+    },
+    auth::middleware::{authenticate, require_admin, require_platform_admin},
+    crypto::merkle::{self, ProofStep},
+    domain::audit::{AuditEvent, AuditEventType},
+    errors::{AppError, Result},
+};
+
+/// Response for the inclusion proof endpoint
+#[derive(Debug, Serialize)]
+pub struct EventProofResponse {
+    pub event_id: Uuid,
+    pub batch_id: Uuid,
+    pub leaf_index: usize,
+    pub event_hash: String,
+    pub path: Vec<ProofStep>,
+    pub merkle_root: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub root_signature: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signing_key_id: Option<String>,
+}
+
+/// GET /v1/audit/events/:id/proof
+/// Returns a Merkle inclusion proof for one of the caller's tenant's audit
+/// events against its batch root
+#[tracing::instrument(skip(state, headers))]
+pub async fn get_event_proof(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(event_id): Path<Uuid>,
+) -> Result<Json<EventProofResponse>> {
+    let claims = authenticate(&state, &headers).await?;
+
+    // Events of other tenants are reported as missing
+    let position =
+        query::get_event_batch_position(&state.db_pool, claims.tenant_id_uuid()?, event_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Audit event not found".to_string()))?;
+
+    let (event_hash, batch_id, batch_index) =
+        match (position.event_hash, position.batch_id, position.batch_index) {
+            (Some(hash), Some(batch_id), Some(index)) => (hash, batch_id, index as usize),
+            _ => {
+                return Err(AppError::NotFound(
+                    "Audit event is not anchored in a batch".to_string(),
+                ))
+            }
+        };
+
+    let batch = query::get_batch(&state.db_pool, batch_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Audit batch not found".to_string()))?;
+
+    let leaves = query::get_batch_leaves(&state.db_pool, batch_id).await?;
+    let tree = merkle::build_tree(&leaves)?;
+    let proof = merkle::inclusion_proof(&tree, batch_index)?;
+
+    // The stored root is authoritative; a mismatch means the batch was altered
+    if !merkle::verify_proof(&event_hash, &proof, &batch.merkle_root)? {
+        tracing::error!(
+            event_id = %event_id,
+            batch_id = %batch_id,
+            "Audit batch Merkle root mismatch"
+        );
+        return Err(AppError::Internal(
+            "Audit batch integrity check failed".to_string(),
+        ));
+    }
+
+    Ok(Json(EventProofResponse {
+        event_id,
+        batch_id,
+        leaf_index: proof.leaf_index,
+        event_hash,
+        path: proof.path,
+        merkle_root: batch.merkle_root,
+        root_signature: batch.root_signature,
+        signing_key_id: batch.signing_key_id,
+    }))
+}
+
+/// Query parameters for the live audit stream
+#[derive(Debug, Deserialize)]
+pub struct AuditStreamQuery {
+    /// Only stream events of this type
+    pub event_type: Option<AuditEventType>,
+}
+
+/// An item delivered to a live audit stream subscriber
+#[derive(Debug)]
+pub enum AuditStreamMessage {
+    Event(AuditEvent),
+    /// The subscriber fell behind and this many events were dropped
+    Lagged(u64),
+}
+
+/// GET /v1/audit/stream
+/// Server-sent events feed of audit events for the caller's tenant
+#[tracing::instrument(skip(state, headers))]
+pub async fn stream_events(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(params): Query<AuditStreamQuery>,
+) -> Result<Sse<impl Stream<Item = std::result::Result<Event, Infallible>>>> {
+    let claims = require_admin(&state, &headers).await?;
+    let tenant_id = claims.tenant_id_uuid()?;
+
+    tracing::info!(tenant_id = %tenant_id, "Audit stream subscriber connected");
+
+    let messages = audit_stream(state.audit_logger.subscribe(), tenant_id, params.event_type);
+    let events = messages.map(|message| Ok(to_sse_event(message)));
+
+    Ok(Sse::new(events).keep_alive(KeepAlive::default()))
+}
+
+/// Turn a broadcast subscription into a stream of events for one tenant
+///
+/// A lagging subscriber gets a single `Lagged` notice and resumes from the
+/// oldest retained event; the stream ends when the logger shuts down.
+pub fn audit_stream(
+    receiver: broadcast::Receiver<AuditEvent>,
+    tenant_id: Uuid,
+    event_type: Option<AuditEventType>,
+) -> impl Stream<Item = AuditStreamMessage> {
+    stream::unfold(receiver, move |mut receiver| async move {
+        loop {
+            match receiver.recv().await {
+                Ok(event) => {
+                    if event.tenant_id != tenant_id
+                        || event_type.is_some_and(|t| t != event.event_type)
+                    {
+                        continue;
+                    }
+                    return Some((AuditStreamMessage::Event(event), receiver));
+                }
+                Err(RecvError::Lagged(skipped)) => {
+                    tracing::warn!(skipped, "Audit stream subscriber lagged, events dropped");
+                    return Some((AuditStreamMessage::Lagged(skipped), receiver));
+                }
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    })
+}
+
+fn to_sse_event(message: AuditStreamMessage) -> Event {
+    match message {
+        AuditStreamMessage::Event(event) => Event::default()
+            .event(event.event_type.as_str())
+            .json_data(&event)
+            .unwrap_or_else(|e| {
+                tracing::error!("Failed to serialize audit event for stream: {}", e);
+                Event::default().event("error").data("serialization failed")
+            }),
+        AuditStreamMessage::Lagged(skipped) => Event::default()
+            .event("lagged")
+            .data(format!("{{\"dropped\":{}}}", skipped)),
+    }
+}
+
+/// POST /v1/audit/exports
+/// Starts exporting the caller's tenant's audit events in the background and
+/// returns a signed, expiring download URL for the result
+#[tracing::instrument(skip(state, headers))]
+pub async fn start_export(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(filter): Json<AuditExportFilter>,
+) -> Result<impl IntoResponse> {
+    let claims = require_admin(&state, &headers).await?;
+    let tenant_id = claims.tenant_id_uuid()?;
+
+    let ticket = state
+        .audit_exports
+        .start(state.db_pool.clone(), tenant_id, filter)?;
+
+    let event = AuditEvent::new(
+        tenant_id,
+        AuditEventType::SystemEvent,
+        "export_audit_log".to_string(),
+        "audit_export".to_string(),
+    )
+    .with_actor(claims.identity_id()?)
+    .with_resource_id(ticket.export_id.to_string());
+    state.audit_logger.log(event).await?;
+
+    Ok((StatusCode::ACCEPTED, Json(ticket)))
+}
+
+/// POST /v1/admin/audit/dead-letter/replay
+/// Writes audit batches that ran out of retries back to the database
+#[tracing::instrument(skip(state, headers))]
+pub async fn replay_dead_letters(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<ReplayOutcome>> {
+    let claims = require_platform_admin(&state, &headers).await?;
+
+    let outcome = state.audit_logger.replay_dead_letters().await?;
+
+    let event = AuditEvent::new(
+        claims.tenant_id_uuid()?,
+        AuditEventType::SystemEvent,
+        "replay_audit_dead_letters".to_string(),
+        "audit_batch".to_string(),
+    )
+    .with_actor(claims.identity_id()?)
+    .with_metadata(serde_json::json!({
+        "batches": outcome.batches,
+        "events": outcome.events,
+        "remaining": outcome.remaining,
+    }));
+    state.audit_logger.log(event).await?;
+
+    Ok(Json(outcome))
+}
+
+/// GET /v1/audit/downloads/:token
+/// Serves a finished audit export; the signed token is the only credential
+#[tracing::instrument(skip_all)]
+pub async fn download_export(
+    State(state): State<AppState>,
+    Path(token): Path<String>,
+) -> Result<Response> {
+    let file = state.audit_exports.open_download(&token).await?;
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/x-ndjson"),
+            (
+                header::CONTENT_DISPOSITION,
+                "attachment; filename=\"audit-export.ndjson\"",
+            ),
+        ],
+        Body::from_stream(export::file_stream(file)),
+    )
+        .into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audit::logger::{AuditLogger, AuditLoggerConfig};
+    use crate::audit::storage::InMemoryAuditStorage;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    fn event(tenant_id: Uuid, event_type: AuditEventType) -> AuditEvent {
+        AuditEvent::new(tenant_id, event_type, "test_action".to_string(), "test".to_string())
+    }
+
+    #[tokio::test]
+    async fn test_stream_delivers_logged_event_for_tenant() {
+        let logger = AuditLogger::new(
+            Arc::new(InMemoryAuditStorage::new()),
+            AuditLoggerConfig::default(),
+        );
+        let tenant_id = Uuid::new_v4();
+        let stream = audit_stream(logger.subscribe(), tenant_id, Some(AuditEventType::PolicyUpdated));
+        futures::pin_mut!(stream);
+
+        // Other tenants and other event types are filtered out
+        logger.log(event(Uuid::new_v4(), AuditEventType::PolicyUpdated)).await.unwrap();
+        logger.log(event(tenant_id, AuditEventType::Authentication)).await.unwrap();
+        logger.log(event(tenant_id, AuditEventType::PolicyUpdated)).await.unwrap();
+
+        let message = tokio::time::timeout(Duration::from_secs(1), stream.next())
+            .await
+            .expect("Subscriber should receive the event")
+            .unwrap();
+        match message {
+            AuditStreamMessage::Event(received) => {
+                assert_eq!(received.tenant_id, tenant_id);
+                assert_eq!(received.event_type, AuditEventType::PolicyUpdated);
+            }
+            other => panic!("Expected event, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_stream_reports_lag_instead_of_blocking() {
+        let (sender, receiver) = broadcast::channel(2);
+        let tenant_id = Uuid::new_v4();
+        let stream = audit_stream(receiver, tenant_id, None);
+        futures::pin_mut!(stream);
+
+        // The writer never blocks; the slow subscriber is told what it missed
+        for _ in 0..5 {
+            sender.send(event(tenant_id, AuditEventType::SystemEvent)).unwrap();
+        }
+
+        assert!(matches!(stream.next().await, Some(AuditStreamMessage::Lagged(3))));
+        assert!(matches!(stream.next().await, Some(AuditStreamMessage::Event(_))));
+    }
+}
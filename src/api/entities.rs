@@ -0,0 +1,97 @@
+// Entity attribute endpoints
+
+use crate::api::routes::AppState;
+use crate::auth::middleware::require_admin;
+use crate::authz::entities::EntityLoader;
+use crate::errors::Result;
+use axum::{
+    extract::{Path, State},
+    http::HeaderMap,
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+/// Request body for setting entity attributes
+#[derive(Debug, Deserialize)]
+pub struct SetEntityAttributesRequest {
+    /// Attributes exposed to Cedar policies, e.g. {"clearance": 3}
+    pub attributes: Map<String, Value>,
+}
+
+/// Response body with the stored attributes
+#[derive(Debug, Serialize)]
+pub struct EntityAttributesResponse {
+    pub entity_uid: String,
+    pub attributes: Map<String, Value>,
+}
+
+/// Request body for setting entity parents
+#[derive(Debug, Deserialize)]
+pub struct SetEntityParentsRequest {
+    /// Direct parents, e.g. ["Folder::\"finance\""]
+    pub parents: Vec<String>,
+}
+
+/// Response body with the stored parents
+#[derive(Debug, Serialize)]
+pub struct EntityParentsResponse {
+    pub entity_uid: String,
+    pub parents: Vec<String>,
+}
+
+/// PUT /v1/entities/:uid/attributes
+///
+/// Replace the custom attributes of an entity (e.g. `User::"alice"`)
+#[tracing::instrument(skip(state, headers, req))]
+pub async fn set_entity_attributes(
+    State(state): State<AppState>,
+    Path(entity_uid): Path<String>,
+    headers: HeaderMap,
+    Json(req): Json<SetEntityAttributesRequest>,
+) -> Result<Json<EntityAttributesResponse>> {
+    let claims = require_admin(&state, &headers).await?;
+
+    let loader = EntityLoader::new(state.db_pool.clone(), Some(claims.tenant_id_uuid()?));
+    let entity_uid = loader.set_attributes(&entity_uid, &req.attributes).await?;
+
+    tracing::info!(
+        entity_uid = %entity_uid,
+        count = req.attributes.len(),
+        "Entity attributes updated by identity: {}",
+        claims.sub
+    );
+
+    Ok(Json(EntityAttributesResponse {
+        entity_uid,
+        attributes: req.attributes,
+    }))
+}
+
+/// PUT /v1/entities/:uid/parents
+///
+/// Replace the direct parents of an entity so policies can use `resource in ...`
+#[tracing::instrument(skip(state, headers, req))]
+pub async fn set_entity_parents(
+    State(state): State<AppState>,
+    Path(entity_uid): Path<String>,
+    headers: HeaderMap,
+    Json(req): Json<SetEntityParentsRequest>,
+) -> Result<Json<EntityParentsResponse>> {
+    let claims = require_admin(&state, &headers).await?;
+
+    let loader = EntityLoader::new(state.db_pool.clone(), Some(claims.tenant_id_uuid()?));
+    let (entity_uid, parents) = loader.set_parents(&entity_uid, &req.parents).await?;
+
+    tracing::info!(
+        entity_uid = %entity_uid,
+        count = parents.len(),
+        "Entity parents updated by identity: {}",
+        claims.sub
+    );
+
+    Ok(Json(EntityParentsResponse {
+        entity_uid,
+        parents,
+    }))
+}
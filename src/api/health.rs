@@ -1,5 +1,13 @@
-use crate::observability::{HealthChecker, HealthStatus, MetricsRecorder};
-use axum::{extract::State, http::StatusCode, response::IntoResponse, Json};
+use crate::api::routes::AppState;
+use crate::auth::middleware::require_admin;
+use crate::errors::Result as AppResult;
+use crate::observability::{DetailedHealth, HealthChecker, HealthStatus, MetricsRecorder};
+use axum::{
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    response::IntoResponse,
+    Json,
+};
 use std::sync::Arc;
 
 /// GET /health/live - Liveness probe
@@ -39,7 +47,152 @@ pub async fn startup(
     }
 }
 
+/// GET /health/detailed - Dependency latencies, pool stats and build info (admin only)
+#[tracing::instrument(skip(state, headers))]
+pub async fn detailed(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> AppResult<Json<DetailedHealth>> {
+    require_admin(&state, &headers).await?;
+
+    Ok(Json(state.health_checker.detailed().await))
+}
+
 /// GET /metrics - Prometheus metrics
 pub async fn metrics() -> Result<String, StatusCode> {
     MetricsRecorder::export().map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::api::{create_router, AppState};
+    use crate::audit::export::AuditExports;
+    use crate::audit::logger::{AuditLogger, AuditLoggerConfig};
+    use crate::audit::storage::InMemoryAuditStorage;
+    use crate::auth::{
+        biscuit::BiscuitManager, jwt::JwtManager, password::PasswordPolicy,
+        webauthn::WebauthnService,
+    };
+    use crate::config::Config;
+    use crate::crypto::signing::AuditSigner;
+    use crate::domain::role;
+    use crate::observability::HealthChecker;
+    use crate::oidc::{upstream::UpstreamClient, OidcProvider};
+    use crate::rate_limit::RateLimiter;
+    use crate::webhooks::AccountNotifier;
+    use axum::body::Body;
+    use axum::http::{header, Request, StatusCode};
+    use sqlx::postgres::PgPoolOptions;
+    use std::sync::Arc;
+    use tower::ServiceExt;
+    use uuid::Uuid;
+
+    #[tokio::test]
+    #[ignore] // Requires database and Redis
+    async fn test_detailed_health_requires_admin_and_reports_latencies() {
+        std::env::set_var(
+            "AGENT_IAM__AUTH__JWT_SECRET",
+            "test-secret-key-for-jwt-signing-minimum-length-requirement",
+        );
+        let config = Config::load().unwrap();
+        let pool = PgPoolOptions::new()
+            .max_connections(5)
+            .connect(&config.database.url)
+            .await
+            .expect("Failed to create test pool");
+        let redis_manager = crate::redis::create_client(&config.redis).await.unwrap();
+        let jwt_manager = Arc::new(JwtManager::new(&config).unwrap());
+
+        let state = AppState {
+            db_pool: pool.clone(),
+            redis_manager: redis_manager.clone(),
+            health_checker: Arc::new(HealthChecker::new(pool.clone(), redis_manager.clone())),
+            jwt_manager: jwt_manager.clone(),
+            audit_logger: Arc::new(AuditLogger::new(
+                Arc::new(InMemoryAuditStorage::new()),
+                AuditLoggerConfig::default(),
+            )),
+            rate_limiter: Arc::new(tokio::sync::Mutex::new(RateLimiter::new(
+                redis_manager,
+                config.rate_limit.clone(),
+            ))),
+            mfa_cipher: None,
+            webauthn: Arc::new(WebauthnService::from_config(&config.webauthn).unwrap()),
+            oidc: Arc::new(OidcProvider::from_config(&config).unwrap()),
+            upstream_oidc: Arc::new(UpstreamClient::from_config(&config).unwrap()),
+            password_policy: Arc::new(PasswordPolicy::from_config(&config.auth)),
+            account_notifier: Arc::new(AccountNotifier::from_config(&config.webhooks).unwrap()),
+            biscuit: Arc::new(BiscuitManager::from_config(&config.auth, &config.crypto).unwrap()),
+            policy_signer: Arc::new(AuditSigner::generate(
+                config.crypto.audit_signing_key_id.clone(),
+            )),
+            audit_exports: Arc::new(AuditExports::from_config(&config).unwrap()),
+        };
+        let app = create_router(state, &config.security);
+
+        let slug = format!("health-{}", Uuid::new_v4());
+        let tenant_id: Uuid =
+            sqlx::query_scalar("INSERT INTO tenants (name, slug) VALUES ($1, $1) RETURNING id")
+                .bind(&slug)
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        let mut identities = Vec::new();
+        for name in ["operator", "viewer"] {
+            let identity_id: Uuid = sqlx::query_scalar(
+                "INSERT INTO identities (tenant_id, identity_type, name) VALUES ($1, 'user', $2) RETURNING id",
+            )
+            .bind(tenant_id)
+            .bind(name)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+            identities.push(identity_id);
+        }
+
+        let audit = crate::audit::logger::RecordingAuditSink::new();
+        let admin = role::create_role(&pool, tenant_id, "admin", None, None)
+            .await
+            .unwrap();
+        role::assign_role(&pool, &audit, tenant_id, identities[0], admin.id, None, None)
+            .await
+            .unwrap();
+
+        let request = |token: Option<String>| {
+            let mut request = Request::get("/health/detailed");
+            if let Some(token) = token {
+                request = request.header(header::AUTHORIZATION, format!("Bearer {}", token));
+            }
+            request.body(Body::empty()).unwrap()
+        };
+        let token = |identity_id: Uuid| {
+            jwt_manager
+                .generate_access_token(identity_id, tenant_id, "user")
+                .unwrap()
+        };
+
+        let response = app.clone().oneshot(request(None)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        let response = app
+            .clone()
+            .oneshot(request(Some(token(identities[1]))))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+        let response = app
+            .oneshot(request(Some(token(identities[0]))))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let health: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert!(health["database"]["latency_ms"].is_number());
+        assert!(health["redis"]["latency_ms"].is_number());
+        assert!(health["pool"]["max_connections"].is_number());
+        assert!(health["uptime_seconds"].is_number());
+    }
+}
@@ -0,0 +1,240 @@
+// Idempotency-Key handling for create endpoints
+//
+// Clients that retry a create request after a network failure send the same
+// `Idempotency-Key`; the original response is returned instead of creating
+// the resources again.
+
+use crate::audit::tamper_proof::canonical_json;
+use crate::errors::{AppError, Result};
+use crate::redis::idempotency::{self, IdempotencyState};
+use axum::http::HeaderMap;
+use redis::aio::ConnectionLike;
+use serde::{de::DeserializeOwned, Serialize};
+use sha2::{Digest, Sha256};
+use std::future::Future;
+use uuid::Uuid;
+
+/// Header carrying the client's idempotency key
+pub const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
+
+/// Longest accepted idempotency key
+const MAX_KEY_LEN: usize = 255;
+
+/// The request's idempotency key, if it sent one
+pub fn idempotency_key(headers: &HeaderMap) -> Result<Option<String>> {
+    let Some(value) = headers.get(IDEMPOTENCY_KEY_HEADER) else {
+        return Ok(None);
+    };
+
+    let key = value
+        .to_str()
+        .ok()
+        .filter(|key| !key.is_empty() && key.len() <= MAX_KEY_LEN)
+        .filter(|key| key.bytes().all(|b| b.is_ascii_graphic()))
+        .ok_or_else(|| {
+            AppError::ValidationError(format!(
+                "Idempotency-Key must be 1 to {} printable ASCII characters",
+                MAX_KEY_LEN
+            ))
+        })?;
+
+    Ok(Some(key.to_string()))
+}
+
+/// Hash identifying a request body for an operation
+///
+/// Object keys are sorted first, so the same body serialized in a different
+/// order hashes the same.
+pub fn request_hash<T: Serialize>(operation: &str, body: &T) -> Result<String> {
+    let body = serde_json::to_value(body)
+        .map_err(|e| AppError::Internal(format!("Failed to serialize request: {}", e)))?;
+
+    let mut hasher = Sha256::new();
+    hasher.update(operation.as_bytes());
+    hasher.update(b"\n");
+    hasher.update(canonical_json(&body)?.as_bytes());
+    Ok(hex::encode(hasher.finalize()))
+}
+
+/// Run `op` at most once per idempotency key
+///
+/// Keys are scoped to the tenant, the caller and the operation. Without a key
+/// `op` simply runs. A failed `op` releases the key so the client can retry;
+/// a successful one is stored and replayed for later requests with the same
+/// key and body.
+pub async fn run_idempotent<C, T, F, Fut>(
+    manager: &mut C,
+    key: Option<String>,
+    tenant_id: Uuid,
+    caller_id: Uuid,
+    operation: &str,
+    request_hash: &str,
+    op: F,
+) -> Result<T>
+where
+    C: ConnectionLike + Clone + Send + Sync,
+    T: Serialize + DeserializeOwned,
+    F: FnOnce() -> Fut,
+    Fut: Future<Output = Result<T>>,
+{
+    let Some(key) = key else {
+        return op().await;
+    };
+    let scoped_key = format!("{}:{}:{}:{}", tenant_id, caller_id, operation, key);
+
+    if let IdempotencyState::Replay(response) =
+        idempotency::begin(manager, &scoped_key, request_hash).await?
+    {
+        tracing::info!(operation = operation, "Replaying idempotent response");
+        return serde_json::from_value(response)
+            .map_err(|e| AppError::Internal(format!("Corrupt idempotent response: {}", e)));
+    }
+
+    match op().await {
+        Ok(response) => {
+            let stored = serde_json::to_value(&response)
+                .map_err(|e| AppError::Internal(format!("Failed to serialize response: {}", e)))?;
+            if let Err(e) = idempotency::complete(manager, &scoped_key, request_hash, &stored).await
+            {
+                tracing::error!(
+                    operation = operation,
+                    "Failed to store idempotent response: {}",
+                    e
+                );
+            }
+            Ok(response)
+        }
+        Err(err) => {
+            if let Err(e) = idempotency::release(manager, &scoped_key).await {
+                tracing::error!(
+                    operation = operation,
+                    "Failed to release idempotency key: {}",
+                    e
+                );
+            }
+            Err(err)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::redis::mock::FlakyConnection;
+    use axum::http::HeaderValue;
+    use serde_json::{json, Value};
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    fn stored_response(request_hash: &str, response: Value) -> FlakyConnection {
+        let record = json!({ "request_hash": request_hash, "response": response });
+        FlakyConnection::responding(redis::Value::Data(record.to_string().into_bytes()))
+    }
+
+    #[test]
+    fn test_idempotency_key_header() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(idempotency_key(&headers).unwrap(), None);
+
+        headers.insert(IDEMPOTENCY_KEY_HEADER, HeaderValue::from_static("retry-1"));
+        assert_eq!(
+            idempotency_key(&headers).unwrap().as_deref(),
+            Some("retry-1")
+        );
+
+        for invalid in ["", "has space"] {
+            headers.insert(IDEMPOTENCY_KEY_HEADER, HeaderValue::from_static(invalid));
+            assert!(idempotency_key(&headers).is_err());
+        }
+        headers.insert(
+            IDEMPOTENCY_KEY_HEADER,
+            HeaderValue::from_str(&"k".repeat(MAX_KEY_LEN + 1)).unwrap(),
+        );
+        assert!(idempotency_key(&headers).is_err());
+    }
+
+    #[test]
+    fn test_request_hash_ignores_key_order() {
+        let a = request_hash("op", &json!({"name": "a", "ttl": 60})).unwrap();
+        let b = request_hash("op", &json!({"ttl": 60, "name": "a"})).unwrap();
+
+        assert_eq!(a, b);
+        assert_ne!(
+            a,
+            request_hash("op", &json!({"name": "b", "ttl": 60})).unwrap()
+        );
+        assert_ne!(
+            a,
+            request_hash("other", &json!({"name": "a", "ttl": 60})).unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_repeated_key_returns_cached_result() {
+        let hash = request_hash("op", &json!({"name": "a"})).unwrap();
+        let mut conn = stored_response(&hash, json!({"created": 1}));
+        let runs = AtomicU32::new(0);
+
+        let response: Value = run_idempotent(
+            &mut conn,
+            Some("retry-1".to_string()),
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            "op",
+            &hash,
+            || async {
+                runs.fetch_add(1, Ordering::SeqCst);
+                Ok(json!({"created": 2}))
+            },
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(response, json!({"created": 1}));
+        assert_eq!(runs.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn test_conflicting_body_with_same_key_rejected() {
+        let original = request_hash("op", &json!({"name": "a"})).unwrap();
+        let retry = request_hash("op", &json!({"name": "b"})).unwrap();
+        let mut conn = stored_response(&original, json!({"created": 1}));
+
+        let result: Result<Value> = run_idempotent(
+            &mut conn,
+            Some("retry-1".to_string()),
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            "op",
+            &retry,
+            || async { Ok(json!({"created": 2})) },
+        )
+        .await;
+
+        let err = result.unwrap_err();
+        assert!(matches!(err, AppError::Conflict(_)));
+        assert_eq!(
+            axum::response::IntoResponse::into_response(err).status(),
+            axum::http::StatusCode::CONFLICT
+        );
+    }
+
+    #[tokio::test]
+    async fn test_without_key_runs_directly() {
+        let mut conn = FlakyConnection::down();
+
+        let response: Value = run_idempotent(
+            &mut conn,
+            None,
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            "op",
+            "hash",
+            || async { Ok(json!({"created": 1})) },
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(response, json!({"created": 1}));
+        assert_eq!(conn.calls(), 0);
+    }
+}
@@ -1,8 +1,10 @@
 // Identity management endpoints
 
 use axum::{
+    body::Body,
     extract::{Path, State},
-    response::IntoResponse,
+    http::{header, HeaderMap},
+    response::{IntoResponse, Response},
     Json,
 };
 use serde::{Deserialize, Serialize};
@@ -11,6 +13,11 @@ use uuid::Uuid;
 
 use crate::{
     api::routes::AppState,
+    auth::middleware::{authenticate, ensure_admin, require_admin},
+    authz::access::{self, EffectiveAccess},
+    domain::audit::{AuditEvent, AuditEventType},
+    domain::export::load_identity_export,
+    domain::identity::{delegation_query_max_depth, get_identity_by_id},
     errors::{AppError, Result},
 };
 
@@ -19,6 +26,8 @@ use crate::{
 pub struct DelegationChainResponse {
     pub identity_id: Uuid,
     pub chain: Vec<DelegationChainNode>,
+    /// The chain reached the configured query depth and may continue beyond it
+    pub truncated: bool,
 }
 
 /// Represents a node in the delegation chain
@@ -53,7 +62,9 @@ pub async fn get_delegation_chain(
         .map_err(|e| AppError::Internal(format!("Invalid tenant ID: {}", e)))?;
 
     // Get the delegation chain
-    let chain = get_delegation_chain_query(&state.db_pool, identity_id, tenant_id).await?;
+    let max_depth = delegation_query_max_depth();
+    let chain =
+        get_delegation_chain_query(&state.db_pool, identity_id, tenant_id, max_depth).await?;
 
     if chain.is_empty() {
         return Err(AppError::IdentityNotFound);
@@ -61,18 +72,88 @@ pub async fn get_delegation_chain(
 
     let response = DelegationChainResponse {
         identity_id,
+        truncated: chain_truncated(&chain, max_depth),
         chain,
     };
 
     Ok(Json(response))
 }
 
-/// Database query to get the full delegation chain for an identity
-/// Uses a recursive CTE to traverse from the given identity up to the root
-async fn get_delegation_chain_query(
+/// GET /v1/identities/:id/effective-access
+/// Explains what an identity can do: its roles, the permissions they grant and
+/// the policies whose principal scope includes it
+#[tracing::instrument(skip(state, headers))]
+pub async fn get_effective_access(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(identity_id): Path<Uuid>,
+) -> Result<Json<EffectiveAccess>> {
+    let claims = require_admin(&state, &headers).await?;
+
+    let identity = get_identity_by_id(&state.db_pool, identity_id).await?;
+    if identity.tenant_id != claims.tenant_id_uuid()? {
+        return Err(AppError::IdentityNotFound);
+    }
+
+    Ok(Json(access::effective_access(&state.db_pool, &identity).await?))
+}
+
+/// GET /v1/identities/:id/export
+/// Streams everything stored about an identity as one JSON document: the
+/// identity, its sessions, its delegation chain and its audit events.
+/// Credential hashes are left out. Identities can export themselves; admins
+/// can export any identity in their tenant.
+#[tracing::instrument(skip(state, headers))]
+pub async fn export_identity(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(identity_id): Path<Uuid>,
+) -> Result<Response> {
+    let claims = authenticate(&state, &headers).await?;
+    let caller_id = claims.identity_id()?;
+
+    let identity = get_identity_by_id(&state.db_pool, identity_id).await?;
+    if identity.tenant_id != claims.tenant_id_uuid()? {
+        return Err(AppError::IdentityNotFound);
+    }
+    if identity.id != caller_id {
+        ensure_admin(&state.db_pool, caller_id).await?;
+    }
+
+    let event = AuditEvent::new(
+        identity.tenant_id,
+        AuditEventType::IdentityExported,
+        "export_identity".to_string(),
+        "identity".to_string(),
+    )
+    .with_actor(caller_id)
+    .with_resource_id(identity.id.to_string());
+    state.audit_logger.log(event).await?;
+
+    let export = load_identity_export(&state.db_pool, identity).await?;
+    let body = Body::from_stream(export.into_stream(state.db_pool.clone())?);
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/json".to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"identity-{}.json\"", identity_id),
+            ),
+        ],
+        body,
+    )
+        .into_response())
+}
+
+/// Database query to get the delegation chain for an identity
+/// Uses a recursive CTE to traverse from the given identity up to the root,
+/// following at most `max_depth` parent links
+pub(crate) async fn get_delegation_chain_query(
     pool: &PgPool,
     identity_id: Uuid,
     tenant_id: Uuid,
+    max_depth: i32,
 ) -> Result<Vec<DelegationChainNode>> {
     let nodes = sqlx::query_as!(
         DelegationChainNode,
@@ -111,7 +192,7 @@ async fn get_delegation_chain_query(
                 c.depth + 1 as depth
             FROM identities i
             INNER JOIN chain c ON i.id = c.parent_identity_id
-            WHERE i.tenant_id = $2
+            WHERE i.tenant_id = $2 AND c.depth < $3
         )
         SELECT
             id,
@@ -129,10 +210,19 @@ async fn get_delegation_chain_query(
         ORDER BY depth ASC
         "#,
         identity_id,
-        tenant_id
+        tenant_id,
+        max_depth
     )
     .fetch_all(pool)
     .await?;
 
     Ok(nodes)
 }
+
+/// Whether a chain read with `max_depth` was cut off: its deepest node is at
+/// the guard and still has a parent
+pub(crate) fn chain_truncated(nodes: &[DelegationChainNode], max_depth: i32) -> bool {
+    nodes
+        .last()
+        .is_some_and(|node| node.depth >= max_depth && node.parent_identity_id.is_some())
+}
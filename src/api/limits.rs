@@ -0,0 +1,118 @@
+// Request size limits
+//
+// Bodies are capped before they are buffered, and JSON documents that are
+// stored or evaluated (task scopes, authorization contexts) are capped in
+// nesting depth so they cannot make later processing arbitrarily expensive.
+
+use crate::errors::{AppError, Result};
+use serde_json::Value;
+use tower_http::limit::RequestBodyLimitLayer;
+
+/// Maximum nesting depth of task scope and authorization context JSON
+pub const MAX_JSON_DEPTH: usize = 16;
+
+/// Reject request bodies larger than `max_bytes` with 413 Payload Too Large
+///
+/// Oversize bodies with a `Content-Length` are rejected before the handler
+/// runs; streamed bodies fail as soon as they pass the limit.
+pub fn body_limit_layer(max_bytes: usize) -> RequestBodyLimitLayer {
+    RequestBodyLimitLayer::new(max_bytes)
+}
+
+/// Nesting depth of a JSON value; scalars are 0 and `{}`/`[]` are 1
+pub fn json_depth(value: &Value) -> usize {
+    let mut max_depth = 0;
+    let mut pending = vec![(value, 0)];
+
+    while let Some((value, depth)) = pending.pop() {
+        let children: Box<dyn Iterator<Item = &Value>> = match value {
+            Value::Array(items) => Box::new(items.iter()),
+            Value::Object(map) => Box::new(map.values()),
+            _ => continue,
+        };
+        max_depth = max_depth.max(depth + 1);
+        pending.extend(children.map(|child| (child, depth + 1)));
+    }
+
+    max_depth
+}
+
+/// Reject JSON nested more than `MAX_JSON_DEPTH` levels deep
+pub fn ensure_json_depth(value: &Value, what: &str) -> Result<()> {
+    check_depth(json_depth(value), what)
+}
+
+pub(crate) fn check_depth(depth: usize, what: &str) -> Result<()> {
+    if depth > MAX_JSON_DEPTH {
+        return Err(AppError::ValidationError(format!(
+            "{} is nested {} levels deep; maximum is {}",
+            what, depth, MAX_JSON_DEPTH
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, http::Request, http::StatusCode, routing::post, Router};
+    use serde_json::json;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+    use tower::ServiceExt;
+
+    fn nested(depth: usize) -> Value {
+        (0..depth).fold(json!(1), |inner, _| json!({ "a": inner }))
+    }
+
+    #[test]
+    fn test_json_depth() {
+        assert_eq!(json_depth(&json!("scalar")), 0);
+        assert_eq!(json_depth(&json!({})), 1);
+        assert_eq!(json_depth(&json!({"a": [1, {"b": 2}], "c": 3})), 3);
+        assert_eq!(json_depth(&nested(40)), 40);
+    }
+
+    #[test]
+    fn test_depth_limit() {
+        assert!(ensure_json_depth(&nested(MAX_JSON_DEPTH), "Context").is_ok());
+        assert!(matches!(
+            ensure_json_depth(&nested(MAX_JSON_DEPTH + 1), "Context"),
+            Err(AppError::ValidationError(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_oversize_body_rejected_before_handler() {
+        let called = Arc::new(AtomicBool::new(false));
+        let handler_called = called.clone();
+        let app = Router::new()
+            .route(
+                "/",
+                post(move |body: String| async move {
+                    handler_called.store(true, Ordering::SeqCst);
+                    body.len().to_string()
+                }),
+            )
+            .layer(body_limit_layer(64));
+
+        let response = app
+            .clone()
+            .oneshot(
+                Request::post("/")
+                    .body(Body::from("x".repeat(1024)))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+        assert!(!called.load(Ordering::SeqCst));
+
+        let response = app
+            .oneshot(Request::post("/").body(Body::from("small")).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(called.load(Ordering::SeqCst));
+    }
+}
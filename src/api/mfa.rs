@@ -0,0 +1,190 @@
+// MFA enrollment and login verification endpoints
+
+use crate::api::auth::{issue_tokens, LoginResponse};
+use crate::api::routes::AppState;
+use crate::auth::{middleware::authenticate, totp};
+use crate::crypto::encryption::SecretCipher;
+use crate::db;
+use crate::errors::{AppError, Result};
+use crate::redis::mfa_challenge;
+use axum::{extract::State, http::HeaderMap, Json};
+use serde::{Deserialize, Serialize};
+
+// ============================================================================
+// Request/Response Types
+// ============================================================================
+
+#[derive(Debug, Serialize)]
+pub struct MfaEnrollResponse {
+    /// Base32-encoded secret, for manual entry
+    pub secret: String,
+    /// otpauth:// URI, for QR code enrollment
+    pub otpauth_uri: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MfaConfirmRequest {
+    pub code: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MfaConfirmResponse {
+    pub enabled: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MfaVerifyRequest {
+    pub challenge_id: String,
+    pub code: String,
+}
+
+// ============================================================================
+// Handlers
+// ============================================================================
+
+/// POST /v1/auth/mfa/enroll
+///
+/// Start TOTP enrollment for the calling user. The secret is stored encrypted
+/// and only takes effect once confirmed with a valid code.
+pub async fn enroll(State(state): State<AppState>, headers: HeaderMap) -> Result<Json<MfaEnrollResponse>> {
+    let claims = authenticate(&state, &headers).await?;
+    let identity_id = claims.identity_id()?;
+
+    if claims.identity_type != "user" {
+        return Err(AppError::ValidationError(
+            "MFA is only available for user identities".to_string(),
+        ));
+    }
+
+    let identity = db::identities::get_by_id(&state.db_pool, identity_id)
+        .await?
+        .ok_or(AppError::IdentityNotFound)?;
+
+    let secret = totp::generate_secret();
+    let encrypted = cipher(&state)?.encrypt(&secret)?;
+
+    if !db::mfa::store_pending_totp(&state.db_pool, identity_id, &encrypted).await? {
+        return Err(AppError::ValidationError("MFA is already enabled".to_string()));
+    }
+
+    tracing::info!("MFA enrollment started for identity: {}", identity_id);
+
+    let account = identity.email.unwrap_or(identity.name);
+    Ok(Json(MfaEnrollResponse {
+        secret: totp::encode_secret(&secret),
+        otpauth_uri: totp::otpauth_uri(&account, &secret),
+    }))
+}
+
+/// POST /v1/auth/mfa/confirm
+///
+/// Confirm a pending enrollment with a code from the authenticator app
+pub async fn confirm(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(req): Json<MfaConfirmRequest>,
+) -> Result<Json<MfaConfirmResponse>> {
+    let claims = authenticate(&state, &headers).await?;
+    let identity_id = claims.identity_id()?;
+
+    let enrollment = db::mfa::get_totp(&state.db_pool, identity_id)
+        .await?
+        .ok_or_else(|| AppError::ValidationError("No pending MFA enrollment".to_string()))?;
+
+    if enrollment.enabled {
+        return Err(AppError::ValidationError("MFA is already enabled".to_string()));
+    }
+
+    if !check_code(&state, &enrollment, &req.code).await? {
+        return Err(AppError::ValidationError("Invalid MFA code".to_string()));
+    }
+
+    db::mfa::enable_totp(&state.db_pool, identity_id).await?;
+
+    tracing::info!("MFA enabled for identity: {}", identity_id);
+
+    Ok(Json(MfaConfirmResponse { enabled: true }))
+}
+
+/// POST /v1/auth/mfa/verify
+///
+/// Second login step: exchange a challenge and a valid code for tokens
+pub async fn verify(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(req): Json<MfaVerifyRequest>,
+) -> Result<Json<LoginResponse>> {
+    let mut redis_conn = state.redis_manager.clone();
+    let identity_id = mfa_challenge::take_challenge(&mut redis_conn, &req.challenge_id)
+        .await?
+        .ok_or(AppError::InvalidCredentials)?;
+
+    let identity = db::identities::get_by_id(&state.db_pool, identity_id)
+        .await?
+        .ok_or(AppError::InvalidCredentials)?;
+
+    let enrollment = db::mfa::get_totp(&state.db_pool, identity_id)
+        .await?
+        .filter(|e| e.enabled)
+        .ok_or(AppError::InvalidCredentials)?;
+
+    if !check_code(&state, &enrollment, &req.code).await? {
+        tracing::warn!("Invalid MFA code for identity: {}", identity_id);
+        return Err(AppError::InvalidCredentials);
+    }
+
+    let token_pair = issue_tokens(
+        &state,
+        &headers,
+        identity.id,
+        identity.tenant_id,
+        &identity.identity_type,
+        true,
+    )
+    .await?;
+
+    tracing::info!("Successful MFA login for identity: {}", identity_id);
+
+    Ok(Json(token_pair.into()))
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+fn cipher(state: &AppState) -> Result<&SecretCipher> {
+    state
+        .mfa_cipher
+        .as_deref()
+        .ok_or_else(|| AppError::Configuration("MFA encryption key is not configured".to_string()))
+}
+
+/// Verify a code against an enrollment and record its time step
+///
+/// Returns false for a wrong code or a code whose step was already used.
+pub(crate) async fn check_code(state: &AppState, enrollment: &db::mfa::TotpEnrollment, code: &str) -> Result<bool> {
+    let secret = cipher(state)?.decrypt(&enrollment.secret_encrypted)?;
+    let now = u64::try_from(chrono::Utc::now().timestamp()).unwrap_or(0);
+
+    let Some(step) = totp::verify_code(&secret, code.trim(), now, enrollment.last_used_step()) else {
+        return Ok(false);
+    };
+
+    // Recording the step is conditional, so two concurrent uses of one code
+    // cannot both succeed
+    db::mfa::record_used_step(&state.db_pool, enrollment.identity_id, step).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mfa_verify_request_deserialize() {
+        let json = r#"{"challenge_id": "abc", "code": "123456"}"#;
+
+        let req: MfaVerifyRequest = serde_json::from_str(json).unwrap();
+        assert_eq!(req.challenge_id, "abc");
+        assert_eq!(req.code, "123456");
+    }
+}
@@ -1,8 +1,21 @@
+pub mod admin;
+pub mod agents;
+pub mod audit;
 pub mod auth;
 pub mod authz;
+pub mod entities;
 pub mod health;
+pub mod idempotency;
 pub mod identities;
+pub mod limits;
+pub mod mfa;
+pub mod password_reset;
 pub mod policies;
+pub mod roles;
+pub mod route_access;
 pub mod routes;
+pub mod security_headers;
+pub mod tenants;
+pub mod webauthn;
 
-pub use routes::create_router;
+pub use routes::{create_router, AppState};
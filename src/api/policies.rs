@@ -1,2 +1,71 @@
 // Policy management endpoints
-// To be implemented in Task #10
+
+use crate::api::routes::AppState;
+use crate::auth::middleware::require_admin;
+use crate::authz::cache::notify_policy_change;
+use crate::domain::policy_bundle::{
+    export_bundle, import_bundle, sign_bundle, verify_bundle, PolicyImport, SignedPolicyBundle,
+};
+use crate::errors::{AppError, Result};
+use axum::{extract::State, http::HeaderMap, Json};
+
+/// GET /v1/policies/export
+///
+/// Export the caller's tenant's active policies as a signed bundle
+pub async fn export_policies(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<SignedPolicyBundle>> {
+    let claims = require_admin(&state, &headers).await?;
+
+    let bundle = export_bundle(&state.db_pool, claims.tenant_id_uuid()?).await?;
+
+    Ok(Json(sign_bundle(&state.policy_signer, bundle)?))
+}
+
+/// POST /v1/policies/import
+///
+/// Replace the caller's tenant's policy set with an exported bundle
+pub async fn import_policies(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(signed): Json<SignedPolicyBundle>,
+) -> Result<Json<PolicyImport>> {
+    let claims = require_admin(&state, &headers).await?;
+    let tenant_id = claims.tenant_id_uuid()?;
+
+    if !verify_bundle(&state.policy_signer.verifying_key(), &signed)? {
+        return Err(AppError::ValidationError(
+            "Policy bundle signature is invalid".to_string(),
+        ));
+    }
+    if signed.bundle.tenant_id != tenant_id {
+        return Err(AppError::ValidationError(
+            "Policy bundle was exported from another tenant".to_string(),
+        ));
+    }
+
+    let import = import_bundle(
+        &state.db_pool,
+        state.audit_logger.as_ref(),
+        claims.identity_id()?,
+        tenant_id,
+        &signed.bundle.policies,
+    )
+    .await?;
+
+    // The import is committed, so a failed announcement is only logged
+    if let Err(e) = notify_policy_change(tenant_id).await {
+        tracing::error!(tenant_id = %tenant_id, "Failed to announce policy change: {}", e);
+    }
+
+    tracing::info!(
+        tenant_id = %tenant_id,
+        bundle_version = import.bundle_version,
+        imported = import.imported,
+        "Policy bundle imported by identity: {}",
+        claims.sub
+    );
+
+    Ok(Json(import))
+}
@@ -0,0 +1,251 @@
+// Role management endpoints
+
+use crate::api::routes::AppState;
+use crate::auth::middleware::require_admin;
+use crate::db::schema::{Permission, Role};
+use crate::domain::identity::get_identity_by_id;
+use crate::domain::role::{self, RoleAssignment};
+use crate::errors::{AppError, Result};
+use axum::{
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    Json,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+// ============================================================================
+// Request/Response Types
+// ============================================================================
+
+#[derive(Debug, Deserialize)]
+pub struct CreateRoleRequest {
+    pub name: String,
+    pub description: Option<String>,
+    /// Role whose grants this role inherits
+    pub parent_role_id: Option<Uuid>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AssignRoleRequest {
+    pub role_id: Uuid,
+    /// Assignment lapses at this time; permanent if omitted
+    pub valid_until: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RoleListResponse {
+    pub roles: Vec<Role>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct IdentityRolesResponse {
+    pub identity_id: Uuid,
+    /// Directly assigned roles and every role they inherit from
+    pub roles: Vec<Role>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RevokeRoleResponse {
+    pub revoked: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PermissionListResponse {
+    pub permissions: Vec<Permission>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RevokePermissionResponse {
+    pub revoked: bool,
+}
+
+// ============================================================================
+// Handlers
+// ============================================================================
+
+/// GET /v1/roles
+///
+/// List the caller's tenant roles and global roles
+pub async fn list_roles(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<RoleListResponse>> {
+    let claims = require_admin(&state, &headers).await?;
+
+    let roles = role::list_roles(&state.db_pool, claims.tenant_id_uuid()?).await?;
+
+    Ok(Json(RoleListResponse { roles }))
+}
+
+/// POST /v1/roles
+///
+/// Create a role in the caller's tenant
+pub async fn create_role(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(req): Json<CreateRoleRequest>,
+) -> Result<(StatusCode, Json<Role>)> {
+    let claims = require_admin(&state, &headers).await?;
+
+    let role = role::create_role(
+        &state.db_pool,
+        claims.tenant_id_uuid()?,
+        &req.name,
+        req.description.as_deref(),
+        req.parent_role_id,
+    )
+    .await?;
+
+    tracing::info!("Role {} created by identity: {}", role.id, claims.sub);
+
+    Ok((StatusCode::CREATED, Json(role)))
+}
+
+/// GET /v1/identities/:id/roles
+///
+/// List the roles an identity currently holds, including inherited ones
+pub async fn list_identity_roles(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(identity_id): Path<Uuid>,
+) -> Result<Json<IdentityRolesResponse>> {
+    let claims = require_admin(&state, &headers).await?;
+
+    let identity = get_identity_by_id(&state.db_pool, identity_id).await?;
+    if identity.tenant_id != claims.tenant_id_uuid()? {
+        return Err(AppError::IdentityNotFound);
+    }
+
+    let roles = role::list_roles_for_identity(&state.db_pool, identity.id).await?;
+
+    Ok(Json(IdentityRolesResponse {
+        identity_id: identity.id,
+        roles,
+    }))
+}
+
+/// POST /v1/identities/:id/roles
+///
+/// Assign a role to an identity in the caller's tenant
+pub async fn assign_role(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(identity_id): Path<Uuid>,
+    Json(req): Json<AssignRoleRequest>,
+) -> Result<Json<RoleAssignment>> {
+    let claims = require_admin(&state, &headers).await?;
+
+    let assignment = role::assign_role(
+        &state.db_pool,
+        state.audit_logger.as_ref(),
+        claims.tenant_id_uuid()?,
+        identity_id,
+        req.role_id,
+        Some(claims.identity_id()?),
+        req.valid_until,
+    )
+    .await?;
+
+    Ok(Json(assignment))
+}
+
+/// DELETE /v1/identities/:id/roles/:role_id
+///
+/// Remove a role from an identity
+pub async fn revoke_role(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path((identity_id, role_id)): Path<(Uuid, Uuid)>,
+) -> Result<Json<RevokeRoleResponse>> {
+    let claims = require_admin(&state, &headers).await?;
+
+    let revoked = role::revoke_role(
+        &state.db_pool,
+        state.audit_logger.as_ref(),
+        claims.tenant_id_uuid()?,
+        identity_id,
+        role_id,
+        Some(claims.identity_id()?),
+    )
+    .await?;
+
+    Ok(Json(RevokeRoleResponse { revoked }))
+}
+
+/// GET /v1/permissions
+///
+/// List the permissions that can be granted to roles
+pub async fn list_permissions(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<PermissionListResponse>> {
+    require_admin(&state, &headers).await?;
+
+    let permissions = role::list_permissions(&state.db_pool).await?;
+
+    Ok(Json(PermissionListResponse { permissions }))
+}
+
+/// GET /v1/roles/:id/permissions
+///
+/// List the permissions granted directly to a role
+pub async fn list_role_permissions(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(role_id): Path<Uuid>,
+) -> Result<Json<PermissionListResponse>> {
+    let claims = require_admin(&state, &headers).await?;
+
+    let role = role::get_role(&state.db_pool, claims.tenant_id_uuid()?, role_id).await?;
+    let permissions = role::list_role_permissions(&state.db_pool, role.id).await?;
+
+    Ok(Json(PermissionListResponse { permissions }))
+}
+
+/// PUT /v1/roles/:id/permissions/:permission
+///
+/// Grant a permission (e.g. `agent:create`) to one of the tenant's roles
+pub async fn grant_permission(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path((role_id, permission)): Path<(Uuid, String)>,
+) -> Result<Json<Permission>> {
+    let claims = require_admin(&state, &headers).await?;
+
+    let permission = role::grant_permission(
+        &state.db_pool,
+        state.audit_logger.as_ref(),
+        claims.tenant_id_uuid()?,
+        role_id,
+        &permission,
+        Some(claims.identity_id()?),
+    )
+    .await?;
+
+    Ok(Json(permission))
+}
+
+/// DELETE /v1/roles/:id/permissions/:permission
+///
+/// Remove a permission from one of the tenant's roles
+pub async fn revoke_permission(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path((role_id, permission)): Path<(Uuid, String)>,
+) -> Result<Json<RevokePermissionResponse>> {
+    let claims = require_admin(&state, &headers).await?;
+
+    let revoked = role::revoke_permission(
+        &state.db_pool,
+        state.audit_logger.as_ref(),
+        claims.tenant_id_uuid()?,
+        role_id,
+        &permission,
+        Some(claims.identity_id()?),
+    )
+    .await?;
+
+    Ok(Json(RevokePermissionResponse { revoked }))
+}
@@ -0,0 +1,213 @@
+// Route access classification
+//
+// Every route is listed here as public, authenticated or authorized. Public
+// routes take no credentials, or check their own (a login form, a signed
+// download token); authenticated routes authenticate the caller in the
+// handler; authorized routes are authenticated by the router and checked by
+// `authorize_middleware` before the handler runs. Requests to a route missing
+// from the list are refused, so a new route is denied until it is classified.
+
+use crate::api::routes::AppState;
+use crate::auth::middleware::authenticate;
+use crate::authz::middleware::{authorize_middleware, Principal};
+use crate::errors::{AppError, Result};
+use axum::{
+    extract::{MatchedPath, Request, State},
+    middleware::Next,
+    response::Response,
+};
+use RouteAccess::{Authenticated, Authorized, Public};
+
+/// How a route is protected
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RouteAccess {
+    /// No credentials required by the router
+    Public,
+    /// The handler authenticates the caller (and checks roles) itself
+    Authenticated,
+    /// The router authenticates the caller and evaluates the authorization policies
+    Authorized,
+}
+
+/// Access of every route, by its path template
+const ROUTE_ACCESS: &[(&str, RouteAccess)] = &[
+    // Probes, metrics and OpenID Provider endpoints
+    ("/health/live", Public),
+    ("/health/ready", Public),
+    ("/health/startup", Public),
+    ("/health/detailed", Public),
+    ("/metrics", Public),
+    ("/.well-known/openid-configuration", Public),
+    ("/.well-known/jwks.json", Public),
+    ("/oauth2/authorize", Public),
+    ("/oauth2/token", Public),
+    ("/graphql", Authenticated),
+    ("/scim/v2/Users", Authenticated),
+    ("/scim/v2/Users/:id", Authenticated),
+    // Sign-in and account recovery
+    ("/v1/auth/login", Public),
+    ("/v1/auth/logout", Authenticated),
+    ("/v1/auth/introspect", Authenticated),
+    ("/v1/auth/token", Public),
+    ("/v1/auth/verify-email", Public),
+    ("/v1/auth/forgot-password", Public),
+    ("/v1/auth/reset-password", Public),
+    ("/v1/auth/refresh", Public),
+    ("/v1/auth/mfa/enroll", Authenticated),
+    ("/v1/auth/mfa/confirm", Authenticated),
+    ("/v1/auth/mfa/verify", Public),
+    ("/v1/auth/webauthn/register/begin", Authenticated),
+    ("/v1/auth/webauthn/register/finish", Authenticated),
+    ("/v1/auth/webauthn/login/begin", Public),
+    ("/v1/auth/webauthn/login/finish", Public),
+    ("/v1/auth/oidc/:tenant/:provider/login", Public),
+    ("/v1/auth/oidc/callback", Public),
+    // Identities, roles and agents
+    ("/v1/identities", Authorized),
+    ("/v1/identities/:id", Authorized),
+    ("/v1/identities/:id/delegation-chain", Authorized),
+    ("/v1/identities/:id/effective-access", Authenticated),
+    ("/v1/identities/:id/export", Authenticated),
+    ("/v1/identities/:id/roles", Authenticated),
+    ("/v1/identities/:id/roles/:role_id", Authenticated),
+    ("/v1/roles", Authenticated),
+    ("/v1/roles/:id/permissions", Authenticated),
+    ("/v1/roles/:id/permissions/:permission", Authenticated),
+    ("/v1/permissions", Authenticated),
+    ("/v1/agents", Authenticated),
+    ("/v1/agents/batch-provision", Authenticated),
+    ("/v1/agents/provision/validate", Authenticated),
+    ("/v1/authz/check", Authenticated),
+    ("/v1/authz/bulk-check", Authenticated),
+    ("/v1/authz/check-identity", Authenticated),
+    ("/v1/authz/simulate", Authenticated),
+    ("/v1/policies", Authorized),
+    ("/v1/policies/export", Authenticated),
+    ("/v1/policies/import", Authenticated),
+    ("/v1/entities/:uid/attributes", Authenticated),
+    ("/v1/entities/:uid/parents", Authenticated),
+    // Audit
+    ("/v1/audit/events/:id/proof", Authenticated),
+    ("/v1/audit/stream", Authenticated),
+    ("/v1/audit/exports", Authenticated),
+    ("/v1/audit/downloads/:token", Public),
+    // Administration
+    ("/v1/admin/tenants", Authenticated),
+    ("/v1/admin/tenants/:id", Authenticated),
+    ("/v1/admin/tenants/:id/status", Authenticated),
+    ("/v1/admin/jwt/rotate", Authenticated),
+    ("/v1/admin/jwt/secondary", Authenticated),
+    ("/v1/admin/revoke", Authenticated),
+    ("/v1/admin/audit/dead-letter/replay", Authenticated),
+    ("/v1/admin/rate-limits/:identifier", Authenticated),
+    ("/v1/admin/identities/:id/verification-token", Authenticated),
+    ("/v1/admin/oidc/providers", Authenticated),
+    ("/v1/admin/oidc/providers/:slug", Authenticated),
+];
+
+/// Access of the route with the given path template, if it is classified
+pub fn route_access(route: &str) -> Option<RouteAccess> {
+    ROUTE_ACCESS
+        .iter()
+        .find(|(path, _)| *path == route)
+        .map(|(_, access)| *access)
+}
+
+/// Refuse requests to unclassified routes; tag the others with their access
+///
+/// Requests that match no route are passed on to get their 404.
+pub async fn classify_route(mut request: Request, next: Next) -> Result<Response> {
+    let Some(route) = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_string())
+    else {
+        return Ok(next.run(request).await);
+    };
+
+    let Some(access) = route_access(&route) else {
+        tracing::warn!(route = %route, "Refusing request to a route with no access classification");
+        return Err(AppError::Forbidden);
+    };
+
+    request.extensions_mut().insert(access);
+    Ok(next.run(request).await)
+}
+
+/// Authenticate callers of authorized routes and evaluate the policies for them
+///
+/// Runs inside `classify_route`; other routes are passed through.
+pub async fn enforce_route_authorization(
+    State(state): State<AppState>,
+    mut request: Request,
+    next: Next,
+) -> Result<Response> {
+    if request.extensions().get::<RouteAccess>() != Some(&RouteAccess::Authorized) {
+        return Ok(next.run(request).await);
+    }
+
+    let claims = authenticate(&state, request.headers()).await?;
+    request
+        .extensions_mut()
+        .insert(Principal::from_claims(&claims)?);
+
+    authorize_middleware(State(state), request, next).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, http::StatusCode, routing::get, Router};
+    use std::collections::HashSet;
+    use tower::ServiceExt;
+
+    async fn status(app: Router, uri: &str) -> StatusCode {
+        app.oneshot(axum::http::Request::get(uri).body(Body::empty()).unwrap())
+            .await
+            .unwrap()
+            .status()
+    }
+
+    #[test]
+    fn test_routes_classified_once() {
+        let mut seen = HashSet::new();
+        for (route, _) in ROUTE_ACCESS {
+            assert!(seen.insert(*route), "{} is classified twice", route);
+        }
+
+        assert_eq!(route_access("/health/live"), Some(RouteAccess::Public));
+        assert_eq!(route_access("/v1/roles"), Some(RouteAccess::Authenticated));
+        assert_eq!(route_access("/v1/policies"), Some(RouteAccess::Authorized));
+        assert_eq!(
+            route_access("/v1/audit/events/:id/proof"),
+            Some(RouteAccess::Authenticated)
+        );
+        assert_eq!(
+            route_access("/v1/authz/check-identity"),
+            Some(RouteAccess::Authenticated)
+        );
+        assert_eq!(
+            route_access("/v1/authz/check"),
+            Some(RouteAccess::Authenticated)
+        );
+        assert_eq!(route_access("/v1/reports"), None);
+    }
+
+    #[tokio::test]
+    async fn test_unclassified_route_denied_by_default() {
+        let app = Router::new()
+            .route("/health/live", get(|| async { "ok" }))
+            .nest(
+                "/v1",
+                Router::new().route("/reports/:id", get(|| async { "report" })),
+            )
+            .layer(axum::middleware::from_fn(classify_route));
+
+        assert_eq!(
+            status(app.clone(), "/v1/reports/42").await,
+            StatusCode::FORBIDDEN
+        );
+        assert_eq!(status(app.clone(), "/health/live").await, StatusCode::OK);
+        assert_eq!(status(app, "/v1/unknown").await, StatusCode::NOT_FOUND);
+    }
+}
@@ -1,14 +1,32 @@
 use crate::{
-    api::{auth, authz, health, identities, policies},
+    api::{
+        admin, agents, audit, auth, authz, entities, health, identities, limits, mfa,
+        password_reset, policies, roles,
+        route_access::{classify_route, enforce_route_authorization},
+        security_headers::{self, SecurityHeaders},
+        tenants, webauthn,
+    },
+    audit::{export::AuditExports, logger::AuditLogger},
+    auth::{
+        biscuit::BiscuitManager, jwt::JwtManager, password::PasswordPolicy,
+        webauthn::WebauthnService,
+    },
+    config::SecurityConfig,
+    crypto::{encryption::SecretCipher, signing::AuditSigner},
     observability::HealthChecker,
+    oidc::{self, upstream::UpstreamClient, OidcProvider},
+    rate_limit::RateLimiter,
+    scim,
+    webhooks::AccountNotifier,
 };
 use axum::{
-    routing::{get, post},
+    routing::{delete, get, post, put},
     Router,
 };
 use redis::aio::ConnectionManager;
 use sqlx::PgPool;
 use std::sync::Arc;
+use tokio::sync::Mutex;
 use tower_http::{
     cors::{Any, CorsLayer},
     trace::TraceLayer,
@@ -19,47 +37,179 @@ pub struct AppState {
     pub db_pool: PgPool,
     pub redis_manager: ConnectionManager,
     pub health_checker: Arc<HealthChecker>,
+    pub jwt_manager: Arc<JwtManager>,
+    pub audit_logger: Arc<AuditLogger>,
+    pub rate_limiter: Arc<Mutex<RateLimiter>>,
+    pub mfa_cipher: Option<Arc<SecretCipher>>,
+    pub webauthn: Arc<WebauthnService>,
+    pub oidc: Arc<OidcProvider>,
+    pub upstream_oidc: Arc<UpstreamClient>,
+    pub password_policy: Arc<PasswordPolicy>,
+    pub account_notifier: Arc<AccountNotifier>,
+    pub biscuit: Arc<BiscuitManager>,
+    /// Signs policy bundle exports
+    pub policy_signer: Arc<AuditSigner>,
+    pub audit_exports: Arc<AuditExports>,
 }
 
-pub fn create_router(db_pool: PgPool, redis_manager: ConnectionManager) -> Router {
-    let health_checker = Arc::new(HealthChecker::new(db_pool.clone(), redis_manager.clone()));
-
-    let state = AppState {
-        db_pool,
-        redis_manager,
-        health_checker: health_checker.clone(),
-    };
-
+/// Builds the HTTP router around an already assembled [`AppState`]
+pub fn create_router(state: AppState, security: &SecurityConfig) -> Router {
     // Configure CORS
     let cors = CorsLayer::new()
         .allow_origin(Any)
         .allow_methods(Any)
         .allow_headers(Any);
 
-    Router::new()
+    let router = Router::new()
         // Health endpoints
         .route("/health/live", get(health::liveness))
         .route("/health/ready", get(health::readiness))
         .route("/health/startup", get(health::startup))
+        .route("/health/detailed", get(health::detailed))
         .route("/metrics", get(health::metrics))
         // API v1 routes
         .nest("/v1", v1_routes())
+        // SCIM 2.0 provisioning (versioned by the SCIM spec, not /v1)
+        .nest("/scim/v2", scim_routes())
+        // OpenID Provider (paths fixed by discovery, not versioned)
+        .route(
+            "/.well-known/openid-configuration",
+            get(oidc::discovery::openid_configuration),
+        )
+        .route("/.well-known/jwks.json", get(oidc::discovery::jwks))
+        .route(
+            "/oauth2/authorize",
+            get(oidc::authorize::authorize).post(oidc::authorize::submit_login),
+        )
+        .route("/oauth2/token", post(oidc::token::token));
+
+    // GraphQL endpoint (optional)
+    #[cfg(feature = "graphql")]
+    let router = router.route("/graphql", post(crate::graphql::graphql_handler));
+
+    // Deny routes without an access classification; authorize the ones that need it
+    let router = router
+        .layer(axum::middleware::from_fn_with_state(
+            state.clone(),
+            enforce_route_authorization,
+        ))
+        .layer(axum::middleware::from_fn(classify_route));
+
+    let router = if security.headers.enabled {
+        let headers = Arc::new(SecurityHeaders::from_config(&security.headers));
+        router.layer(axum::middleware::from_fn(move |request, next| {
+            security_headers::add_security_headers(headers.clone(), request, next)
+        }))
+    } else {
+        router
+    };
+
+    router
         // Add middleware
+        .layer(limits::body_limit_layer(security.max_request_body_bytes))
         .layer(TraceLayer::new_for_http())
         .layer(cors)
         // Add state
         .with_state(state)
 }
 
+fn scim_routes() -> Router<AppState> {
+    Router::new()
+        .route(
+            "/Users",
+            get(scim::users::list_users).post(scim::users::create_user),
+        )
+        .route(
+            "/Users/:id",
+            get(scim::users::get_user)
+                .patch(scim::users::patch_user)
+                .delete(scim::users::delete_user),
+        )
+}
+
 fn v1_routes() -> Router<AppState> {
     Router::new()
         // Placeholder routes (will be implemented in subsequent tasks)
-        .route("/auth/login", post(|| async { "Auth login endpoint" }))
-        .route("/auth/logout", post(|| async { "Auth logout endpoint" }))
+        .route("/auth/login", post(auth::login))
+        .route("/auth/logout", post(auth::logout))
+        .route("/auth/introspect", post(auth::introspect))
+        .route("/auth/token", post(auth::token))
+        .route("/auth/verify-email", post(auth::verify_email))
+        .route("/auth/forgot-password", post(password_reset::forgot_password))
+        .route("/auth/reset-password", post(password_reset::reset_password))
         .route("/auth/refresh", post(|| async { "Auth refresh endpoint" }))
+        .route("/auth/mfa/enroll", post(mfa::enroll))
+        .route("/auth/mfa/confirm", post(mfa::confirm))
+        .route("/auth/mfa/verify", post(mfa::verify))
+        .route("/auth/webauthn/register/begin", post(webauthn::begin_registration))
+        .route("/auth/webauthn/register/finish", post(webauthn::finish_registration))
+        .route("/auth/webauthn/login/begin", post(webauthn::begin_authentication))
+        .route("/auth/webauthn/login/finish", post(webauthn::finish_authentication))
+        .route(
+            "/auth/oidc/:tenant/:provider/login",
+            get(oidc::federation::begin_login),
+        )
+        .route("/auth/oidc/callback", get(oidc::federation::callback))
         .route("/identities", post(|| async { "Create identity endpoint" }))
         .route("/identities/:id", get(|| async { "Get identity endpoint" }))
         .route("/identities/:id/delegation-chain", get(identities::get_delegation_chain))
-        .route("/authz/check", post(|| async { "Check authorization endpoint" }))
+        .route("/identities/:id/effective-access", get(identities::get_effective_access))
+        .route("/identities/:id/export", get(identities::export_identity))
+        .route(
+            "/identities/:id/roles",
+            get(roles::list_identity_roles).post(roles::assign_role),
+        )
+        .route("/identities/:id/roles/:role_id", delete(roles::revoke_role))
+        .route("/roles", get(roles::list_roles).post(roles::create_role))
+        .route("/roles/:id/permissions", get(roles::list_role_permissions))
+        .route(
+            "/roles/:id/permissions/:permission",
+            put(roles::grant_permission).delete(roles::revoke_permission),
+        )
+        .route("/permissions", get(roles::list_permissions))
+        .route("/agents", get(agents::list_agents))
+        .route("/agents/batch-provision", post(agents::batch_provision))
+        .route("/agents/provision/validate", post(agents::validate_provision))
+        .route("/authz/check", post(authz::check_authorization))
+        .route("/authz/check-identity", post(authz::check_identity_authorization))
+        .route("/authz/bulk-check", post(authz::bulk_check_authorization))
+        .route("/authz/simulate", post(authz::simulate_authorization))
         .route("/policies", get(|| async { "List policies endpoint" }))
+        .route("/policies/export", get(policies::export_policies))
+        .route("/policies/import", post(policies::import_policies))
+        .route("/entities/:uid/attributes", put(entities::set_entity_attributes))
+        .route("/entities/:uid/parents", put(entities::set_entity_parents))
+        .route("/audit/events/:id/proof", get(audit::get_event_proof))
+        .route("/audit/stream", get(audit::stream_events))
+        .route("/audit/exports", post(audit::start_export))
+        .route("/audit/downloads/:token", get(audit::download_export))
+        .route(
+            "/admin/tenants",
+            get(tenants::list_tenants).post(tenants::create_tenant),
+        )
+        .route(
+            "/admin/tenants/:id",
+            get(tenants::get_tenant).delete(tenants::delete_tenant),
+        )
+        .route("/admin/tenants/:id/status", put(tenants::update_tenant_status))
+        .route("/admin/jwt/rotate", post(admin::rotate_jwt_secret))
+        .route("/admin/jwt/secondary", delete(admin::clear_jwt_secondary))
+        .route("/admin/revoke", post(admin::revoke_sessions))
+        .route(
+            "/admin/audit/dead-letter/replay",
+            post(audit::replay_dead_letters),
+        )
+        .route(
+            "/admin/rate-limits/:identifier",
+            get(admin::get_rate_limits).delete(admin::reset_rate_limits),
+        )
+        .route(
+            "/admin/identities/:id/verification-token",
+            post(admin::issue_verification_token),
+        )
+        .route("/admin/oidc/providers", get(oidc::federation::list_providers))
+        .route(
+            "/admin/oidc/providers/:slug",
+            put(oidc::federation::upsert_provider).delete(oidc::federation::delete_provider),
+        )
 }
@@ -0,0 +1,156 @@
+// Security response headers
+//
+// Every response gets `X-Content-Type-Options: nosniff` and the configured
+// `X-Frame-Options` and `Referrer-Policy`. Responses from auth and identity
+// endpoints carry tokens and personal data, so they also get
+// `Cache-Control: no-store` to keep them out of browser and proxy caches.
+// Headers a handler has set itself are left alone.
+
+use crate::config::SecurityHeadersConfig;
+use axum::{
+    extract::Request,
+    http::{header, HeaderMap, HeaderName, HeaderValue},
+    middleware::Next,
+    response::Response,
+};
+use std::sync::Arc;
+
+/// Headers to add, parsed once from the configuration
+#[derive(Debug, Clone)]
+pub struct SecurityHeaders {
+    baseline: Vec<(HeaderName, HeaderValue)>,
+    no_store_paths: Vec<String>,
+}
+
+impl SecurityHeaders {
+    /// Build from configuration; header values that are not valid are left out
+    /// (`Config::validate` rejects them at startup)
+    pub fn from_config(config: &SecurityHeadersConfig) -> Self {
+        let mut baseline = vec![(
+            header::X_CONTENT_TYPE_OPTIONS,
+            HeaderValue::from_static("nosniff"),
+        )];
+        for (name, value) in [
+            (header::X_FRAME_OPTIONS, &config.frame_options),
+            (header::REFERRER_POLICY, &config.referrer_policy),
+        ] {
+            if value.is_empty() {
+                continue;
+            }
+            if let Ok(value) = HeaderValue::from_str(value) {
+                baseline.push((name, value));
+            }
+        }
+
+        Self {
+            baseline,
+            no_store_paths: config.no_store_paths.clone(),
+        }
+    }
+
+    fn is_no_store(&self, path: &str) -> bool {
+        self.no_store_paths.iter().any(|prefix| {
+            path.strip_prefix(prefix.as_str())
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+        })
+    }
+
+    fn apply(&self, no_store: bool, headers: &mut HeaderMap) {
+        for (name, value) in &self.baseline {
+            if !headers.contains_key(name) {
+                headers.insert(name.clone(), value.clone());
+            }
+        }
+        if no_store {
+            headers.insert(header::CACHE_CONTROL, HeaderValue::from_static("no-store"));
+            headers.insert(header::PRAGMA, HeaderValue::from_static("no-cache"));
+        }
+    }
+}
+
+/// Middleware adding the security headers to every response
+pub async fn add_security_headers(
+    headers: Arc<SecurityHeaders>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let no_store = headers.is_no_store(request.uri().path());
+    let mut response = next.run(request).await;
+    headers.apply(no_store, response.headers_mut());
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, http::StatusCode, routing::get, routing::post, Router};
+    use tower::ServiceExt;
+
+    fn app(config: &SecurityHeadersConfig) -> Router {
+        let headers = Arc::new(SecurityHeaders::from_config(config));
+        Router::new()
+            .route("/v1/auth/login", post(|| async { "token" }))
+            .route("/v1/authz/check", post(|| async { "allow" }))
+            .route("/health/live", get(|| async { "ok" }))
+            .layer(axum::middleware::from_fn(move |request, next| {
+                add_security_headers(headers.clone(), request, next)
+            }))
+    }
+
+    async fn send(app: Router, method: &str, path: &str) -> Response {
+        app.oneshot(
+            Request::builder()
+                .method(method)
+                .uri(path)
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_login_response_is_not_stored() {
+        let response = send(
+            app(&SecurityHeadersConfig::default()),
+            "POST",
+            "/v1/auth/login",
+        )
+        .await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let headers = response.headers();
+        assert_eq!(headers[header::CACHE_CONTROL], "no-store");
+        assert_eq!(headers[header::PRAGMA], "no-cache");
+        assert_eq!(headers[header::X_CONTENT_TYPE_OPTIONS], "nosniff");
+        assert_eq!(headers[header::X_FRAME_OPTIONS], "DENY");
+        assert_eq!(headers[header::REFERRER_POLICY], "no-referrer");
+    }
+
+    #[tokio::test]
+    async fn test_other_responses_get_baseline_headers_only() {
+        let config = SecurityHeadersConfig::default();
+        for (method, path) in [("GET", "/health/live"), ("POST", "/v1/authz/check")] {
+            let response = send(app(&config), method, path).await;
+
+            assert!(response.headers().get(header::CACHE_CONTROL).is_none());
+            assert_eq!(
+                response.headers()[header::X_CONTENT_TYPE_OPTIONS],
+                "nosniff"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_empty_values_are_left_out() {
+        let config = SecurityHeadersConfig {
+            frame_options: String::new(),
+            referrer_policy: "strict-origin".to_string(),
+            ..SecurityHeadersConfig::default()
+        };
+        let response = send(app(&config), "GET", "/health/live").await;
+
+        assert!(response.headers().get(header::X_FRAME_OPTIONS).is_none());
+        assert_eq!(response.headers()[header::REFERRER_POLICY], "strict-origin");
+    }
+}
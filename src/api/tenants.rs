@@ -0,0 +1,181 @@
+// Tenant management endpoints
+//
+// Tenants span the whole service, so these endpoints require the global
+// admin role rather than a tenant's own admin role.
+
+use crate::api::routes::AppState;
+use crate::auth::middleware::require_platform_admin;
+use crate::db::schema::Tenant;
+use crate::domain::tenant;
+use crate::errors::Result;
+use axum::{
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use uuid::Uuid;
+
+// ============================================================================
+// Request/Response Types
+// ============================================================================
+
+#[derive(Debug, Deserialize)]
+pub struct CreateTenantRequest {
+    pub name: String,
+    /// Unique identifier used in URLs, e.g. `acme-corp`
+    pub slug: String,
+    /// Tenant settings, such as `agent_max_ttl_seconds`
+    pub metadata: Option<Value>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ListTenantsQuery {
+    pub status: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateTenantStatusRequest {
+    pub status: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TenantListResponse {
+    pub tenants: Vec<Tenant>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TenantStatusResponse {
+    pub tenant: Tenant,
+    /// Identities suspended along with the tenant
+    pub identities_suspended: u64,
+    /// Sessions of the suspended identities that were revoked
+    pub sessions_revoked: u64,
+}
+
+// ============================================================================
+// Handlers
+// ============================================================================
+
+/// POST /v1/admin/tenants
+///
+/// Create a tenant
+pub async fn create_tenant(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(req): Json<CreateTenantRequest>,
+) -> Result<(StatusCode, Json<Tenant>)> {
+    let claims = require_platform_admin(&state, &headers).await?;
+
+    let tenant = tenant::create_tenant(
+        &state.db_pool,
+        state.audit_logger.as_ref(),
+        claims.identity_id()?,
+        &req.name,
+        &req.slug,
+        req.metadata,
+    )
+    .await?;
+
+    tracing::info!("Tenant {} created by identity: {}", tenant.id, claims.sub);
+
+    Ok((StatusCode::CREATED, Json(tenant)))
+}
+
+/// GET /v1/admin/tenants
+///
+/// List tenants, optionally filtered by `?status=`
+pub async fn list_tenants(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<ListTenantsQuery>,
+) -> Result<Json<TenantListResponse>> {
+    require_platform_admin(&state, &headers).await?;
+
+    let tenants = tenant::list_tenants(&state.db_pool, query.status.as_deref()).await?;
+
+    Ok(Json(TenantListResponse { tenants }))
+}
+
+/// GET /v1/admin/tenants/:id
+pub async fn get_tenant(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(tenant_id): Path<Uuid>,
+) -> Result<Json<Tenant>> {
+    require_platform_admin(&state, &headers).await?;
+
+    let tenant = tenant::get_tenant(&state.db_pool, tenant_id).await?;
+
+    Ok(Json(tenant))
+}
+
+/// PUT /v1/admin/tenants/:id/status
+///
+/// Activate, suspend or delete a tenant; suspending or deleting also suspends
+/// its identities and revokes their sessions
+pub async fn update_tenant_status(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(tenant_id): Path<Uuid>,
+    Json(req): Json<UpdateTenantStatusRequest>,
+) -> Result<Json<TenantStatusResponse>> {
+    let claims = require_platform_admin(&state, &headers).await?;
+
+    let change = set_status(&state, claims.identity_id()?, tenant_id, &req.status).await?;
+
+    tracing::info!(
+        tenant_id = %tenant_id,
+        status = %req.status,
+        "Tenant status changed by identity: {}",
+        claims.sub
+    );
+
+    Ok(Json(change))
+}
+
+/// DELETE /v1/admin/tenants/:id
+///
+/// Soft-delete a tenant, suspend its identities and revoke their sessions
+pub async fn delete_tenant(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(tenant_id): Path<Uuid>,
+) -> Result<Json<TenantStatusResponse>> {
+    let claims = require_platform_admin(&state, &headers).await?;
+
+    let change = set_status(&state, claims.identity_id()?, tenant_id, "deleted").await?;
+
+    tracing::info!("Tenant {} deleted by identity: {}", tenant_id, claims.sub);
+
+    Ok(Json(change))
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+async fn set_status(
+    state: &AppState,
+    actor_id: Uuid,
+    tenant_id: Uuid,
+    status: &str,
+) -> Result<TenantStatusResponse> {
+    let mut redis_conn = state.redis_manager.clone();
+    let change = tenant::update_tenant_status(
+        &state.db_pool,
+        &mut redis_conn,
+        state.audit_logger.as_ref(),
+        actor_id,
+        tenant_id,
+        status,
+    )
+    .await?;
+
+    Ok(TenantStatusResponse {
+        tenant: change.tenant,
+        identities_suspended: change.identities_suspended,
+        sessions_revoked: change.sessions_revoked,
+    })
+}
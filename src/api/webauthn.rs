@@ -0,0 +1,223 @@
+// WebAuthn / passkey endpoints
+
+use crate::api::auth::{issue_tokens, LoginResponse};
+use crate::api::routes::AppState;
+use crate::auth::middleware::authenticate;
+use crate::db;
+use crate::errors::{AppError, Result};
+use crate::redis::webauthn_state::{self, Ceremony};
+use axum::{extract::State, http::HeaderMap, Json};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use webauthn_rs::prelude::{
+    CreationChallengeResponse, PasskeyAuthentication, PasskeyRegistration, PublicKeyCredential,
+    RegisterPublicKeyCredential, RequestChallengeResponse,
+};
+
+// ============================================================================
+// Request/Response Types
+// ============================================================================
+
+#[derive(Debug, Serialize)]
+pub struct BeginRegistrationResponse {
+    pub ceremony_id: String,
+    /// Options to pass to `navigator.credentials.create()`
+    pub options: CreationChallengeResponse,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct FinishRegistrationRequest {
+    pub ceremony_id: String,
+    pub credential: RegisterPublicKeyCredential,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FinishRegistrationResponse {
+    pub credential_id: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BeginAuthenticationRequest {
+    pub email: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BeginAuthenticationResponse {
+    pub ceremony_id: String,
+    /// Options to pass to `navigator.credentials.get()`
+    pub options: RequestChallengeResponse,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct FinishAuthenticationRequest {
+    pub ceremony_id: String,
+    pub credential: PublicKeyCredential,
+}
+
+/// Ceremony state kept in Redis, bound to the identity it was started for
+#[derive(Serialize, Deserialize)]
+struct PendingCeremony<S> {
+    identity_id: Uuid,
+    state: S,
+}
+
+// ============================================================================
+// Handlers
+// ============================================================================
+
+/// POST /v1/auth/webauthn/register/begin
+///
+/// Start registering a passkey for the calling user
+pub async fn begin_registration(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<BeginRegistrationResponse>> {
+    let claims = authenticate(&state, &headers).await?;
+    let identity_id = claims.identity_id()?;
+
+    if claims.identity_type != "user" {
+        return Err(AppError::ValidationError(
+            "Passkeys are only available for user identities".to_string(),
+        ));
+    }
+
+    let identity = db::identities::get_by_id(&state.db_pool, identity_id)
+        .await?
+        .ok_or(AppError::IdentityNotFound)?;
+    let existing = db::webauthn::list_passkeys(&state.db_pool, identity_id).await?;
+
+    let user_name = identity.email.unwrap_or(identity.name);
+    let (options, registration) = state
+        .webauthn
+        .begin_registration(identity_id, &user_name, &existing)?;
+
+    let mut redis_conn = state.redis_manager.clone();
+    let ceremony_id = webauthn_state::store_state(
+        &mut redis_conn,
+        Ceremony::Registration,
+        &PendingCeremony {
+            identity_id,
+            state: registration,
+        },
+    )
+    .await?;
+
+    Ok(Json(BeginRegistrationResponse { ceremony_id, options }))
+}
+
+/// POST /v1/auth/webauthn/register/finish
+///
+/// Verify the authenticator's response and store the new passkey
+pub async fn finish_registration(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(req): Json<FinishRegistrationRequest>,
+) -> Result<Json<FinishRegistrationResponse>> {
+    let claims = authenticate(&state, &headers).await?;
+    let identity_id = claims.identity_id()?;
+
+    let mut redis_conn = state.redis_manager.clone();
+    let pending: PendingCeremony<PasskeyRegistration> =
+        webauthn_state::take_state(&mut redis_conn, Ceremony::Registration, &req.ceremony_id)
+            .await?
+            .filter(|p| p.identity_id == identity_id)
+            .ok_or_else(|| AppError::ValidationError("Unknown or expired registration".to_string()))?;
+
+    let passkey = state
+        .webauthn
+        .finish_registration(&req.credential, &pending.state)?;
+    let credential_id = db::webauthn::insert_passkey(&state.db_pool, identity_id, &passkey).await?;
+
+    tracing::info!("Passkey registered for identity: {}", identity_id);
+
+    Ok(Json(FinishRegistrationResponse { credential_id }))
+}
+
+/// POST /v1/auth/webauthn/login/begin
+///
+/// Start a passkey login for the user with the given email
+pub async fn begin_authentication(
+    State(state): State<AppState>,
+    Json(req): Json<BeginAuthenticationRequest>,
+) -> Result<Json<BeginAuthenticationResponse>> {
+    let identity = db::identities::get_by_email(&state.db_pool, &req.email)
+        .await?
+        .filter(|i| i.identity_type == "user")
+        .ok_or(AppError::InvalidCredentials)?;
+
+    let passkeys = db::webauthn::list_passkeys(&state.db_pool, identity.id).await?;
+    let (options, authentication) = state.webauthn.begin_authentication(&passkeys)?;
+
+    let mut redis_conn = state.redis_manager.clone();
+    let ceremony_id = webauthn_state::store_state(
+        &mut redis_conn,
+        Ceremony::Authentication,
+        &PendingCeremony {
+            identity_id: identity.id,
+            state: authentication,
+        },
+    )
+    .await?;
+
+    Ok(Json(BeginAuthenticationResponse { ceremony_id, options }))
+}
+
+/// POST /v1/auth/webauthn/login/finish
+///
+/// Verify the assertion and issue the normal token pair
+pub async fn finish_authentication(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(req): Json<FinishAuthenticationRequest>,
+) -> Result<Json<LoginResponse>> {
+    let mut redis_conn = state.redis_manager.clone();
+    let pending: PendingCeremony<PasskeyAuthentication> =
+        webauthn_state::take_state(&mut redis_conn, Ceremony::Authentication, &req.ceremony_id)
+            .await?
+            .ok_or(AppError::InvalidCredentials)?;
+
+    let result = state
+        .webauthn
+        .finish_authentication(&req.credential, &pending.state)?;
+
+    let identity = db::identities::get_by_id(&state.db_pool, pending.identity_id)
+        .await?
+        .ok_or(AppError::InvalidCredentials)?;
+
+    // Persist the updated signature counter so cloned authenticators are detectable
+    if result.needs_update() {
+        let passkeys = db::webauthn::list_passkeys(&state.db_pool, identity.id).await?;
+        for mut passkey in passkeys {
+            if passkey.update_credential(&result) == Some(true) {
+                db::webauthn::update_passkey(&state.db_pool, identity.id, &passkey).await?;
+            }
+        }
+    }
+
+    // A user-verified passkey covers both possession and a local factor
+    let token_pair = issue_tokens(
+        &state,
+        &headers,
+        identity.id,
+        identity.tenant_id,
+        &identity.identity_type,
+        result.user_verified(),
+    )
+    .await?;
+
+    tracing::info!("Successful passkey login for identity: {}", identity.id);
+
+    Ok(Json(token_pair.into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_begin_authentication_request_deserialize() {
+        let req: BeginAuthenticationRequest =
+            serde_json::from_str(r#"{"email": "alice@example.com"}"#).unwrap();
+        assert_eq!(req.email, "alice@example.com");
+    }
+}
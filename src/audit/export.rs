@@ -0,0 +1,409 @@
+// Asynchronous audit exports
+//
+// Large exports are not streamed through the request that asks for them.
+// The export is written in the background to a file under the configured
+// directory, one JSON event per line, and the caller gets a signed, expiring
+// download URL straight away. Until the file is complete the download
+// answers 404; it is written under a temporary name and renamed when done.
+
+use crate::config::Config;
+use crate::crypto::download::DownloadSigner;
+use crate::domain::audit::AuditEventType;
+use crate::domain::export::ExportedAuditEvent;
+use crate::errors::{AppError, Result};
+use bytes::Bytes;
+use chrono::{DateTime, Duration, Utc};
+use futures::stream::{self, Stream};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::fs::File;
+use tokio::io::{AsyncReadExt, AsyncWriteExt, BufWriter};
+use uuid::Uuid;
+
+/// Audit events read per query while writing an export
+const EXPORT_PAGE_SIZE: i64 = 500;
+
+/// Bytes read per chunk while serving a download
+const DOWNLOAD_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Extension of export files
+const EXPORT_EXTENSION: &str = "ndjson";
+
+/// Which of a tenant's audit events to export
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct AuditExportFilter {
+    /// Only events at or after this time
+    pub from: Option<DateTime<Utc>>,
+    /// Only events before this time
+    pub to: Option<DateTime<Utc>>,
+    /// Only events of this type
+    pub event_type: Option<AuditEventType>,
+}
+
+/// Returned when an export is started
+#[derive(Debug, Clone, Serialize)]
+pub struct AuditExportTicket {
+    pub export_id: Uuid,
+    pub download_url: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Writes audit exports and serves them through signed download URLs
+pub struct AuditExports {
+    directory: PathBuf,
+    signer: DownloadSigner,
+    url_ttl: Duration,
+}
+
+impl AuditExports {
+    pub fn new(directory: impl Into<PathBuf>, signer: DownloadSigner, url_ttl: Duration) -> Self {
+        Self {
+            directory: directory.into(),
+            signer,
+            url_ttl,
+        }
+    }
+
+    pub fn from_config(config: &Config) -> Result<Self> {
+        Ok(Self::new(
+            &config.audit.exports.directory,
+            DownloadSigner::from_config(&config.crypto)?,
+            Duration::seconds(config.audit.exports.url_ttl_seconds as i64),
+        ))
+    }
+
+    /// Start exporting a tenant's audit events in the background
+    ///
+    /// The returned URL is valid for the configured TTL from now, whether or
+    /// not the export has finished by then.
+    pub fn start(
+        self: &Arc<Self>,
+        pool: PgPool,
+        tenant_id: Uuid,
+        filter: AuditExportFilter,
+    ) -> Result<AuditExportTicket> {
+        let ticket = self.ticket(tenant_id, Uuid::new_v4())?;
+
+        let exports = self.clone();
+        let export_id = ticket.export_id;
+        tokio::spawn(async move {
+            match exports
+                .write_export(&pool, tenant_id, export_id, &filter)
+                .await
+            {
+                Ok(count) => tracing::info!(
+                    tenant_id = %tenant_id,
+                    export_id = %export_id,
+                    events = count,
+                    "Audit export written"
+                ),
+                Err(e) => tracing::error!(
+                    tenant_id = %tenant_id,
+                    export_id = %export_id,
+                    error = %e,
+                    "Audit export failed"
+                ),
+            }
+        });
+
+        Ok(ticket)
+    }
+
+    /// Sign the download URL for an export
+    fn ticket(&self, tenant_id: Uuid, export_id: Uuid) -> Result<AuditExportTicket> {
+        let expires_at = Utc::now() + self.url_ttl;
+        let token = self
+            .signer
+            .sign(&relative_path(tenant_id, export_id), expires_at)?;
+
+        Ok(AuditExportTicket {
+            export_id,
+            download_url: format!("/v1/audit/downloads/{}", token),
+            expires_at,
+        })
+    }
+
+    /// Write every matching event, returning how many were written
+    async fn write_export(
+        &self,
+        pool: &PgPool,
+        tenant_id: Uuid,
+        export_id: Uuid,
+        filter: &AuditExportFilter,
+    ) -> Result<usize> {
+        let mut writer = self.create(tenant_id, export_id).await?;
+        let mut after = None;
+        let mut count = 0;
+
+        loop {
+            let events = match export_page(pool, tenant_id, filter, after).await {
+                Ok(events) => events,
+                Err(e) => {
+                    writer.discard().await;
+                    return Err(e);
+                }
+            };
+            for event in &events {
+                if let Err(e) = writer.write_line(event).await {
+                    writer.discard().await;
+                    return Err(e);
+                }
+            }
+            count += events.len();
+
+            match events.last() {
+                Some(last) if events.len() as i64 == EXPORT_PAGE_SIZE => {
+                    after = Some((last.timestamp, last.id));
+                }
+                _ => break,
+            }
+        }
+
+        writer.finish().await?;
+        Ok(count)
+    }
+
+    /// Create the file for an export
+    pub async fn create(&self, tenant_id: Uuid, export_id: Uuid) -> Result<ExportWriter> {
+        let path = self.directory.join(relative_path(tenant_id, export_id));
+        let partial = path.with_extension(format!("{}.partial", EXPORT_EXTENSION));
+
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await.map_err(io_error)?;
+        }
+        let file = File::create(&partial).await.map_err(io_error)?;
+
+        Ok(ExportWriter {
+            file: BufWriter::new(file),
+            partial,
+            path,
+        })
+    }
+
+    /// Open the export a download token was signed for
+    ///
+    /// Tampered and expired tokens are rejected as forbidden; exports that are
+    /// still being written, failed or were removed are not found.
+    pub async fn open_download(&self, token: &str) -> Result<File> {
+        let relative = self.signer.verify(token, Utc::now())?;
+        let path = self.resolve(&relative)?;
+
+        File::open(&path).await.map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => {
+                AppError::NotFound("Export is not ready or no longer available".to_string())
+            }
+            _ => io_error(e),
+        })
+    }
+
+    /// Map a signed relative path back to a file under the export directory
+    ///
+    /// Only `<tenant uuid>/<export uuid>.ndjson` is accepted, so even a validly
+    /// signed path cannot leave the directory.
+    fn resolve(&self, relative: &str) -> Result<PathBuf> {
+        let (tenant, file) = relative.split_once('/').ok_or(AppError::Forbidden)?;
+        let export = Path::new(file)
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .ok_or(AppError::Forbidden)?;
+        let tenant_id = Uuid::parse_str(tenant).map_err(|_| AppError::Forbidden)?;
+        let export_id = Uuid::parse_str(export).map_err(|_| AppError::Forbidden)?;
+        if relative_path(tenant_id, export_id) != relative {
+            return Err(AppError::Forbidden);
+        }
+
+        Ok(self.directory.join(relative))
+    }
+}
+
+/// An export being written; it only becomes downloadable once finished
+pub struct ExportWriter {
+    file: BufWriter<File>,
+    partial: PathBuf,
+    path: PathBuf,
+}
+
+impl ExportWriter {
+    /// Append one record as a line of JSON
+    pub async fn write_line<T: Serialize>(&mut self, record: &T) -> Result<()> {
+        let mut line = serde_json::to_vec(record)
+            .map_err(|e| AppError::Internal(format!("Failed to serialize export: {}", e)))?;
+        line.push(b'\n');
+        self.file.write_all(&line).await.map_err(io_error)
+    }
+
+    /// Flush the file and make it available for download
+    pub async fn finish(mut self) -> Result<()> {
+        self.file.flush().await.map_err(io_error)?;
+        tokio::fs::rename(&self.partial, &self.path)
+            .await
+            .map_err(io_error)
+    }
+
+    /// Remove a failed export
+    async fn discard(self) {
+        drop(self.file);
+        if let Err(e) = tokio::fs::remove_file(&self.partial).await {
+            tracing::warn!(error = %e, "Failed to remove partial audit export");
+        }
+    }
+}
+
+/// Stream a downloaded export in chunks
+pub fn file_stream(file: File) -> impl Stream<Item = std::io::Result<Bytes>> + Send {
+    stream::try_unfold(file, |mut file| async move {
+        let mut buffer = vec![0u8; DOWNLOAD_CHUNK_SIZE];
+        let read = file.read(&mut buffer).await?;
+        if read == 0 {
+            return Ok(None);
+        }
+        buffer.truncate(read);
+        Ok(Some((Bytes::from(buffer), file)))
+    })
+}
+
+fn relative_path(tenant_id: Uuid, export_id: Uuid) -> String {
+    format!("{}/{}.{}", tenant_id, export_id, EXPORT_EXTENSION)
+}
+
+fn io_error(e: std::io::Error) -> AppError {
+    AppError::Internal(format!("Audit export I/O error: {}", e))
+}
+
+/// A page of a tenant's audit events matching the filter, oldest first,
+/// after the given `(timestamp, id)` position
+async fn export_page(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    filter: &AuditExportFilter,
+    after: Option<(DateTime<Utc>, Uuid)>,
+) -> Result<Vec<ExportedAuditEvent>> {
+    let events = sqlx::query_as!(
+        ExportedAuditEvent,
+        r#"
+        SELECT id, actor_identity_id, event_type, action, resource_type, resource_id,
+               decision, decision_reason, request_id,
+               COALESCE(metadata, '{}'::jsonb) as "metadata!", timestamp
+        FROM audit_logs
+        WHERE tenant_id = $1
+          AND ($2::timestamptz IS NULL OR timestamp >= $2)
+          AND ($3::timestamptz IS NULL OR timestamp < $3)
+          AND ($4::text IS NULL OR event_type = $4)
+          AND ($5::timestamptz IS NULL OR (timestamp, id) > ($5, $6))
+        ORDER BY timestamp ASC, id ASC
+        LIMIT $7
+        "#,
+        tenant_id,
+        filter.from,
+        filter.to,
+        filter.event_type.map(|t| t.as_str().to_string()),
+        after.map(|(timestamp, _)| timestamp),
+        after.map(|(_, id)| id),
+        EXPORT_PAGE_SIZE
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(events)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::TryStreamExt;
+    use serde_json::json;
+
+    fn exports(directory: &Path, url_ttl: Duration) -> AuditExports {
+        AuditExports::new(directory, DownloadSigner::generate(), url_ttl)
+    }
+
+    fn token(ticket: &AuditExportTicket) -> &str {
+        ticket
+            .download_url
+            .strip_prefix("/v1/audit/downloads/")
+            .unwrap()
+    }
+
+    async fn read_all(file: File) -> String {
+        let chunks: Vec<Bytes> = file_stream(file).try_collect().await.unwrap();
+        String::from_utf8(chunks.concat()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_signed_url_serves_finished_export() {
+        let dir = tempfile::tempdir().unwrap();
+        let exports = exports(dir.path(), Duration::minutes(5));
+        let tenant_id = Uuid::new_v4();
+        let ticket = exports.ticket(tenant_id, Uuid::new_v4()).unwrap();
+
+        // Not downloadable while it is still being written
+        let mut writer = exports.create(tenant_id, ticket.export_id).await.unwrap();
+        writer
+            .write_line(&json!({ "action": "login" }))
+            .await
+            .unwrap();
+        assert!(matches!(
+            exports.open_download(token(&ticket)).await,
+            Err(AppError::NotFound(_))
+        ));
+
+        writer.finish().await.unwrap();
+        let file = exports.open_download(token(&ticket)).await.unwrap();
+        assert_eq!(read_all(file).await, "{\"action\":\"login\"}\n");
+    }
+
+    #[tokio::test]
+    async fn test_tampered_or_expired_url_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let exports = exports(dir.path(), Duration::minutes(5));
+        let tenant_id = Uuid::new_v4();
+        let ticket = exports.ticket(tenant_id, Uuid::new_v4()).unwrap();
+        exports
+            .create(tenant_id, ticket.export_id)
+            .await
+            .unwrap()
+            .finish()
+            .await
+            .unwrap();
+
+        let mut tampered = token(&ticket).to_string();
+        tampered.insert(3, 'x');
+        assert!(matches!(
+            exports.open_download(&tampered).await,
+            Err(AppError::Forbidden)
+        ));
+
+        let expired = AuditExports {
+            url_ttl: Duration::seconds(-1),
+            ..exports
+        };
+        let ticket = expired.ticket(tenant_id, ticket.export_id).unwrap();
+        assert!(matches!(
+            expired.open_download(token(&ticket)).await,
+            Err(AppError::Forbidden)
+        ));
+    }
+
+    #[test]
+    fn test_signed_paths_cannot_leave_the_directory() {
+        let exports = exports(Path::new("/exports"), Duration::minutes(5));
+        let tenant_id = Uuid::new_v4();
+        let export_id = Uuid::new_v4();
+
+        assert_eq!(
+            exports
+                .resolve(&relative_path(tenant_id, export_id))
+                .unwrap(),
+            Path::new("/exports").join(relative_path(tenant_id, export_id))
+        );
+        for relative in [
+            "../etc/passwd".to_string(),
+            format!("{}/../{}.ndjson", tenant_id, export_id),
+            format!("{}/{}.ndjson.partial", tenant_id, export_id),
+        ] {
+            assert!(exports.resolve(&relative).is_err());
+        }
+    }
+}
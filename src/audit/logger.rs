@@ -1,8 +1,16 @@
-use crate::domain::audit::{AuditEvent, PersistedAuditEvent};
+use crate::domain::audit::{AuditBatchAnchor, AuditEvent, PersistedAuditEvent};
 use crate::errors::Result;
-use crate::audit::storage::AuditStorage;
+use crate::audit::storage::{AuditStorage, FileAuditStorage, ReplayOutcome};
+use crate::audit::tamper_proof::{HashChain, HashableEvent};
+use crate::crypto::keys::KeyManager;
+use crate::crypto::merkle;
+use crate::crypto::pseudonym::AuditPseudonymizer;
+use crate::crypto::signing::AuditSigner;
+use crate::observability::MetricsRecorder;
+use crate::webhooks::WebhookDispatcher;
+use async_trait::async_trait;
 use std::sync::Arc;
-use tokio::sync::mpsc;
+use tokio::sync::{broadcast, mpsc};
 use tokio::time::{Duration, interval};
 use tracing::{error, info, warn};
 use uuid::Uuid;
@@ -13,6 +21,13 @@ pub struct AuditLoggerConfig {
     pub batch_size: usize,
     pub batch_timeout_ms: u64,
     pub channel_buffer_size: usize,
+    /// Retries of a failed batch write before it goes to the dead-letter queue
+    pub max_flush_retries: u32,
+    /// Wait before the first retry, doubled for each further one
+    pub retry_backoff_ms: u64,
+    /// Unwritten events kept for the next write attempt; the oldest are
+    /// dropped beyond this
+    pub max_retained_events: usize,
 }
 
 impl Default for AuditLoggerConfig {
@@ -21,29 +36,136 @@ impl Default for AuditLoggerConfig {
             batch_size: 100,
             batch_timeout_ms: 1000,
             channel_buffer_size: 10000,
+            max_flush_retries: 3,
+            retry_backoff_ms: 200,
+            max_retained_events: 10000,
         }
     }
 }
 
+/// Number of events buffered for live subscribers before slow ones start lagging
+const STREAM_BUFFER_SIZE: usize = 1024;
+
+/// Batches handed to the writer task while it is still busy with an earlier one
+const WRITER_QUEUE_BATCHES: usize = 1;
+
 /// Async audit logger with batching for high-performance event logging
 pub struct AuditLogger {
     sender: mpsc::Sender<AuditEvent>,
+    stream: broadcast::Sender<AuditEvent>,
+    webhooks: Option<Arc<WebhookDispatcher>>,
+    pseudonymizer: Option<Arc<AuditPseudonymizer>>,
+    storage: Arc<dyn AuditStorage>,
+    dead_letter: Option<Arc<FileAuditStorage>>,
+}
+
+/// Builder for audit loggers that sign events or dead-letter failed batches
+pub struct AuditLoggerBuilder {
+    storage: Arc<dyn AuditStorage>,
+    config: AuditLoggerConfig,
+    signer: Option<Arc<AuditSigner>>,
+    signing_keys: Option<Arc<KeyManager>>,
+    dead_letter: Option<Arc<FileAuditStorage>>,
+}
+
+impl AuditLoggerBuilder {
+    pub fn new(storage: Arc<dyn AuditStorage>, config: AuditLoggerConfig) -> Self {
+        Self {
+            storage,
+            config,
+            signer: None,
+            signing_keys: None,
+            dead_letter: None,
+        }
+    }
+
+    /// Sign every event and batch root with the given signer
+    pub fn signer(mut self, signer: Arc<AuditSigner>) -> Self {
+        self.signer = Some(signer);
+        self
+    }
+
+    /// Sign every event and batch root with the key manager's active key
+    ///
+    /// Takes precedence over a fixed `signer`.
+    pub fn signing_keys(mut self, signing_keys: Arc<KeyManager>) -> Self {
+        self.signing_keys = Some(signing_keys);
+        self
+    }
+
+    /// Move batches still failing after their retries to the given dead-letter file
+    pub fn dead_letter(mut self, dead_letter: Arc<FileAuditStorage>) -> Self {
+        self.dead_letter = Some(dead_letter);
+        self
+    }
+
+    /// Start the logger's background batch processor
+    pub fn build(self) -> AuditLogger {
+        AuditLogger::start(self)
+    }
 }
 
 impl AuditLogger {
     /// Create a new audit logger with the given storage backend and configuration
     pub fn new(storage: Arc<dyn AuditStorage>, config: AuditLoggerConfig) -> Self {
+        AuditLoggerBuilder::new(storage, config).build()
+    }
+
+    fn start(builder: AuditLoggerBuilder) -> Self {
+        let AuditLoggerBuilder {
+            storage,
+            config,
+            signer,
+            signing_keys,
+            dead_letter,
+        } = builder;
         let (sender, receiver) = mpsc::channel(config.channel_buffer_size);
+        let (stream, _) = broadcast::channel(STREAM_BUFFER_SIZE);
 
         // Spawn the background batch processor
-        tokio::spawn(batch_processor(receiver, storage, config));
+        tokio::spawn(batch_processor(
+            receiver,
+            BatchWriter {
+                storage: storage.clone(),
+                dead_letter: dead_letter.clone(),
+                signer,
+                signing_keys,
+                config,
+            },
+            stream.clone(),
+        ));
 
-        Self { sender }
+        Self {
+            sender,
+            stream,
+            webhooks: None,
+            pseudonymizer: None,
+            storage,
+            dead_letter,
+        }
+    }
+
+    /// Fan events of subscribed types out to the given webhook dispatcher
+    pub fn with_webhooks(mut self, dispatcher: Arc<WebhookDispatcher>) -> Self {
+        self.webhooks = Some(dispatcher);
+        self
+    }
+
+    /// Pseudonymize actor and resource IDs of the given tenants' events before
+    /// they leave the logger
+    ///
+    /// Webhooks, live subscribers and the storage all receive the
+    /// pseudonymized form.
+    pub fn with_pseudonymizer(mut self, pseudonymizer: Arc<AuditPseudonymizer>) -> Self {
+        self.pseudonymizer = Some(pseudonymizer);
+        self
     }
 
     /// Log an audit event asynchronously
     /// Returns immediately after queuing the event
     pub async fn log(&self, event: AuditEvent) -> Result<()> {
+        let event = self.pseudonymize(event)?;
+        self.notify_webhooks(&event);
         self.sender
             .send(event)
             .await
@@ -51,64 +173,230 @@ impl AuditLogger {
         Ok(())
     }
 
+    /// Queue an audit event without waiting for room in the queue
+    ///
+    /// For hot paths: an event that does not fit is dropped and counted in
+    /// `audit_events_dropped_total` rather than holding up the caller.
+    pub fn try_log(&self, event: AuditEvent) {
+        let queued = self.pseudonymize(event).and_then(|event| {
+            self.notify_webhooks(&event);
+            self.sender.try_send(event).map_err(|e| {
+                crate::errors::AppError::Internal(format!("Failed to queue audit event: {}", e))
+            })
+        });
+        if let Err(e) = queued {
+            MetricsRecorder::record_audit_event_dropped();
+            warn!("Dropped audit event: {}", e);
+        }
+    }
+
     /// Log an audit event with a blocking call (for tests or critical operations)
     pub fn log_blocking(&self, event: AuditEvent) -> Result<()> {
+        let event = self.pseudonymize(event)?;
+        self.notify_webhooks(&event);
         self.sender
             .try_send(event)
             .map_err(|e| crate::errors::AppError::Internal(format!("Failed to queue audit event: {}", e)))?;
         Ok(())
     }
 
+    /// Subscribe to the live feed of events accepted by the batch processor
+    ///
+    /// Subscribers that fall more than the buffer size behind receive
+    /// `RecvError::Lagged` and skip ahead; the writer never waits on them.
+    pub fn subscribe(&self) -> broadcast::Receiver<AuditEvent> {
+        self.stream.subscribe()
+    }
+
+    fn notify_webhooks(&self, event: &AuditEvent) {
+        if let Some(dispatcher) = &self.webhooks {
+            dispatcher.dispatch(event);
+        }
+    }
+
+    fn pseudonymize(&self, event: AuditEvent) -> Result<AuditEvent> {
+        match &self.pseudonymizer {
+            Some(pseudonymizer) => pseudonymizer.pseudonymize(event),
+            None => Ok(event),
+        }
+    }
+
+    /// Write the dead-lettered batches back to the primary storage
+    ///
+    /// Batches that replayed are removed from the dead-letter file; replay
+    /// stops at the first batch that fails again.
+    pub async fn replay_dead_letters(&self) -> Result<ReplayOutcome> {
+        let dead_letter = self.dead_letter.as_ref().ok_or_else(|| {
+            crate::errors::AppError::NotFound("No audit dead-letter queue configured".to_string())
+        })?;
+        let outcome = dead_letter.replay(self.storage.as_ref()).await?;
+
+        info!(
+            batches = outcome.batches,
+            events = outcome.events,
+            "Replayed dead-lettered audit batches"
+        );
+        Ok(outcome)
+    }
+
     /// Get the current queue size (for monitoring)
     pub fn queue_size(&self) -> usize {
         self.sender.capacity() - self.sender.max_capacity()
     }
 }
 
+/// Destination for audit events emitted by domain operations
+///
+/// Lets domain code record events without depending on the batching logger,
+/// so tests can substitute a sink that keeps events in memory.
+#[async_trait]
+pub trait AuditSink: Send + Sync {
+    async fn log(&self, event: AuditEvent) -> Result<()>;
+}
+
+#[async_trait]
+impl AuditSink for AuditLogger {
+    async fn log(&self, event: AuditEvent) -> Result<()> {
+        AuditLogger::log(self, event).await
+    }
+}
+
+/// Sink that holds events until the work that produced them is committed
+///
+/// Used with `db::with_tx` so that a rolled-back transaction leaves nothing in
+/// the audit log; call `flush` once the transaction has committed.
+#[derive(Default)]
+pub struct DeferredAuditSink {
+    events: std::sync::Mutex<Vec<AuditEvent>>,
+}
+
+impl DeferredAuditSink {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Forward the held events to `sink`, leaving the buffer empty
+    pub async fn flush(&self, sink: &dyn AuditSink) -> Result<()> {
+        let events = std::mem::take(
+            &mut *self.events.lock().map_err(|_| {
+                crate::errors::AppError::Internal("Audit buffer poisoned".to_string())
+            })?,
+        );
+        for event in events {
+            sink.log(event).await?;
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl AuditSink for DeferredAuditSink {
+    async fn log(&self, event: AuditEvent) -> Result<()> {
+        self.events
+            .lock()
+            .map_err(|_| crate::errors::AppError::Internal("Audit buffer poisoned".to_string()))?
+            .push(event);
+        Ok(())
+    }
+}
+
+/// Sink that records events in memory (for testing)
+#[cfg(test)]
+#[derive(Default)]
+pub struct RecordingAuditSink {
+    events: std::sync::Mutex<Vec<AuditEvent>>,
+}
+
+#[cfg(test)]
+impl RecordingAuditSink {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn events(&self) -> Vec<AuditEvent> {
+        self.events.lock().unwrap().clone()
+    }
+}
+
+#[cfg(test)]
+#[async_trait]
+impl AuditSink for RecordingAuditSink {
+    async fn log(&self, event: AuditEvent) -> Result<()> {
+        self.events.lock().unwrap().push(event);
+        Ok(())
+    }
+}
+
+/// Where the batch processor writes batches, and how
+struct BatchWriter {
+    storage: Arc<dyn AuditStorage>,
+    dead_letter: Option<Arc<FileAuditStorage>>,
+    signer: Option<Arc<AuditSigner>>,
+    signing_keys: Option<Arc<KeyManager>>,
+    config: AuditLoggerConfig,
+}
+
+impl BatchWriter {
+    /// Signer for the next batch: the active rotating key, else the fixed signer
+    fn current_signer(&self) -> Result<Option<Arc<AuditSigner>>> {
+        match &self.signing_keys {
+            Some(keys) => Ok(Some(Arc::new(keys.current_signing_key()?.audit_signer()?))),
+            None => Ok(self.signer.clone()),
+        }
+    }
+}
+
 /// Background batch processor that accumulates events and writes them in batches
 async fn batch_processor(
     mut receiver: mpsc::Receiver<AuditEvent>,
-    storage: Arc<dyn AuditStorage>,
-    config: AuditLoggerConfig,
+    writer: BatchWriter,
+    stream: broadcast::Sender<AuditEvent>,
 ) {
-    let mut batch: Vec<AuditEvent> = Vec::with_capacity(config.batch_size);
-    let mut flush_interval = interval(Duration::from_millis(config.batch_timeout_ms));
+    let batch_size = writer.config.batch_size;
+    let batch_timeout_ms = writer.config.batch_timeout_ms;
+    let max_retained = writer.config.max_retained_events;
+    let mut batch: Vec<AuditEvent> = Vec::with_capacity(batch_size);
+    let mut flush_interval = interval(Duration::from_millis(batch_timeout_ms));
+
+    // Writes and their retries run in their own task so intake never waits on them
+    let (batches, pending) = mpsc::channel(WRITER_QUEUE_BATCHES);
+    let write_task = tokio::spawn(batch_writer(pending, writer));
 
     info!(
         "Audit logger batch processor started (batch_size={}, timeout_ms={})",
-        config.batch_size, config.batch_timeout_ms
+        batch_size, batch_timeout_ms
     );
 
     loop {
         tokio::select! {
             // Receive events from the channel
             Some(event) = receiver.recv() => {
+                // Tap for live subscribers; an error only means nobody is listening
+                let _ = stream.send(event.clone());
                 batch.push(event);
 
-                // Flush if batch is full
-                if batch.len() >= config.batch_size {
-                    if let Err(e) = flush_batch(&mut batch, &storage).await {
-                        error!("Failed to flush audit batch: {:?}", e);
-                    }
+                // Hand the batch to the writer if it is full
+                if batch.len() >= batch_size {
+                    hand_over(&mut batch, &batches, max_retained);
                 }
             }
 
-            // Flush on timeout even if batch is not full
+            // Hand it over on timeout even if batch is not full
             _ = flush_interval.tick() => {
                 if !batch.is_empty() {
-                    if let Err(e) = flush_batch(&mut batch, &storage).await {
-                        error!("Failed to flush audit batch on timeout: {:?}", e);
-                    }
+                    hand_over(&mut batch, &batches, max_retained);
                 }
             }
 
             // Channel closed, flush remaining events and exit
             else => {
                 warn!("Audit logger channel closed, flushing remaining events");
-                if !batch.is_empty() {
-                    if let Err(e) = flush_batch(&mut batch, &storage).await {
-                        error!("Failed to flush final audit batch: {:?}", e);
-                    }
+                if !batch.is_empty() && batches.send(batch).await.is_err() {
+                    error!("Audit batch writer stopped; final audit batch lost");
+                }
+                drop(batches);
+                if let Err(e) = write_task.await {
+                    error!("Audit batch writer failed: {}", e);
                 }
                 break;
             }
@@ -118,31 +406,139 @@ async fn batch_processor(
     info!("Audit logger batch processor stopped");
 }
 
-/// Flush a batch of events to storage
-async fn flush_batch(
+/// Pass a batch to the writer task, or keep it while the writer is still busy
+fn hand_over(
     batch: &mut Vec<AuditEvent>,
-    storage: &Arc<dyn AuditStorage>,
-) -> Result<()> {
+    batches: &mpsc::Sender<Vec<AuditEvent>>,
+    max_retained: usize,
+) {
+    if let Err(mpsc::error::TrySendError::Full(kept) | mpsc::error::TrySendError::Closed(kept)) =
+        batches.try_send(std::mem::take(batch))
+    {
+        *batch = kept;
+        retain_newest(batch, max_retained);
+    }
+}
+
+/// Write the batches handed over by the processor
+///
+/// Events of a batch that could not be written are kept and go out in front
+/// of the next batch.
+async fn batch_writer(mut batches: mpsc::Receiver<Vec<AuditEvent>>, writer: BatchWriter) {
+    let mut unwritten: Vec<AuditEvent> = Vec::new();
+
+    while let Some(batch) = batches.recv().await {
+        unwritten.extend(batch);
+        retain_newest(&mut unwritten, writer.config.max_retained_events);
+        if let Err(e) = flush_batch(&mut unwritten, &writer).await {
+            error!("Failed to flush audit batch: {:?}", e);
+        }
+    }
+
+    if !unwritten.is_empty() {
+        error!(
+            events = unwritten.len(),
+            "Audit logger stopped with unwritten events"
+        );
+    }
+}
+
+/// Drop the oldest events beyond `max`, counting them in
+/// `audit_retained_events_dropped_total`
+fn retain_newest(events: &mut Vec<AuditEvent>, max: usize) {
+    let excess = events.len().saturating_sub(max);
+    if excess > 0 {
+        events.drain(..excess);
+        warn!(
+            events = excess,
+            "Dropped the oldest unwritten audit events past the retention limit"
+        );
+        MetricsRecorder::record_audit_retained_events_dropped(excess as u64);
+    }
+}
+
+/// Flush a batch of events to storage
+///
+/// A failed write is retried with exponential backoff; once the retries are
+/// used up the batch goes to the dead-letter queue. Without one, or if that
+/// write fails too, the events are left in `batch` for the next flush.
+async fn flush_batch(batch: &mut Vec<AuditEvent>, writer: &BatchWriter) -> Result<()> {
     if batch.is_empty() {
         return Ok(());
     }
+    let signer = writer.current_signer()?;
+    let signer = signer.as_deref();
 
     let count = batch.len();
     let start = std::time::Instant::now();
 
-    // Convert events to persisted events (without tamper-proofing for now)
-    let persisted_events: Vec<PersistedAuditEvent> = batch
-        .iter()
-        .map(|event| PersistedAuditEvent {
-            id: Uuid::new_v4(),
+    // Convert events to persisted events, hashing each one for the batch's Merkle tree
+    // and signing it if a signer is configured
+    let hash_chain = HashChain::new();
+    let batch_id = Uuid::new_v4();
+    let mut persisted_events: Vec<PersistedAuditEvent> = Vec::with_capacity(count);
+    let mut leaves: Vec<String> = Vec::with_capacity(count);
+
+    for (index, event) in batch.iter().enumerate() {
+        let id = Uuid::new_v4();
+        let hashable = HashableEvent::from_audit_event(id, event, None);
+        let event_hash = hash_chain.compute_hash(&hashable)?;
+        let signature = match signer {
+            Some(signer) => Some(signer.sign_event(&hashable)?),
+            None => None,
+        };
+
+        leaves.push(event_hash.clone());
+        persisted_events.push(PersistedAuditEvent {
+            id,
             event: event.clone(),
-            signature: None,
+            signature,
             previous_event_hash: None,
-        })
-        .collect();
+            event_hash: Some(event_hash),
+            batch_id: Some(batch_id),
+            batch_index: Some(index as i32),
+        });
+    }
+
+    // Anchor the batch with its Merkle root so single events can be proven later
+    let merkle_root = merkle::build_tree(&leaves)?.root();
+    let anchor = AuditBatchAnchor {
+        id: batch_id,
+        root_signature: signer.map(|s| s.sign_bytes(merkle_root.as_bytes())),
+        signing_key_id: signer.map(|s| s.key_id().to_string()),
+        merkle_root,
+        event_count: count as i32,
+        created_at: chrono::Utc::now(),
+    };
 
     // Write batch to storage
-    storage.write_batch(persisted_events).await?;
+    if let Err(e) = write_with_retry(writer, &anchor, &persisted_events).await {
+        let Some(dead_letter) = &writer.dead_letter else {
+            return Err(e);
+        };
+        let result = dead_letter
+            .write_anchored_batch(anchor, persisted_events)
+            .await;
+        return match result {
+            Ok(()) => {
+                warn!(
+                    events = count,
+                    "Audit batch write failed after retries ({}); moved to dead-letter queue", e
+                );
+                MetricsRecorder::record_audit_dead_letter("written", count as u64);
+                batch.clear();
+                Ok(())
+            }
+            Err(dead_letter_error) => {
+                error!(
+                    events = count,
+                    "Audit batch could not be dead-lettered either: {}", dead_letter_error
+                );
+                MetricsRecorder::record_audit_dead_letter("failed", count as u64);
+                Err(e)
+            }
+        };
+    }
 
     let duration = start.elapsed();
     info!(
@@ -160,11 +556,44 @@ async fn flush_batch(
     Ok(())
 }
 
+/// Write a batch to storage, retrying failures with exponential backoff
+async fn write_with_retry(
+    writer: &BatchWriter,
+    anchor: &AuditBatchAnchor,
+    events: &[PersistedAuditEvent],
+) -> Result<()> {
+    let mut attempt = 0;
+    loop {
+        let result = writer
+            .storage
+            .write_anchored_batch(anchor.clone(), events.to_vec())
+            .await;
+        match result {
+            Ok(()) => return Ok(()),
+            Err(e) if attempt < writer.config.max_flush_retries => {
+                let backoff = writer
+                    .config
+                    .retry_backoff_ms
+                    .saturating_mul(1 << attempt.min(16));
+                attempt += 1;
+                warn!(
+                    attempt,
+                    "Audit batch write failed ({}); retrying in {}ms", e, backoff
+                );
+                MetricsRecorder::record_audit_flush_retry();
+                tokio::time::sleep(Duration::from_millis(backoff)).await;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::audit::storage::FallbackAuditStorage;
     use crate::domain::audit::AuditEventType;
-    use async_trait::async_trait;
+    use std::sync::atomic::{AtomicUsize, Ordering};
     use std::sync::Mutex;
 
     struct MockStorage {
@@ -191,6 +620,239 @@ mod tests {
         }
     }
 
+    /// Storage that fails its first `failures` writes, then recovers
+    struct RecoveringStorage {
+        failures: AtomicUsize,
+        inner: MockStorage,
+    }
+
+    #[async_trait]
+    impl AuditStorage for RecoveringStorage {
+        async fn write_batch(&self, events: Vec<PersistedAuditEvent>) -> Result<()> {
+            let failing = self
+                .failures
+                .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1))
+                .is_ok();
+            if failing {
+                return Err(crate::errors::AppError::Internal(
+                    "storage unavailable".to_string(),
+                ));
+            }
+            self.inner.write_batch(events).await
+        }
+    }
+
+    /// Storage whose backend is down
+    struct FailingStorage;
+
+    #[async_trait]
+    impl AuditStorage for FailingStorage {
+        async fn write_batch(&self, _events: Vec<PersistedAuditEvent>) -> Result<()> {
+            Err(crate::errors::AppError::Internal(
+                "storage unavailable".to_string(),
+            ))
+        }
+    }
+
+    fn retrying_config() -> AuditLoggerConfig {
+        AuditLoggerConfig {
+            batch_size: 2,
+            batch_timeout_ms: 1000,
+            channel_buffer_size: 100,
+            max_flush_retries: 2,
+            retry_backoff_ms: 5,
+            max_retained_events: 100,
+        }
+    }
+
+    async fn log_actions(logger: &AuditLogger, actions: &[&str]) {
+        for action in actions {
+            let event = AuditEvent::new(
+                Uuid::new_v4(),
+                AuditEventType::SystemEvent,
+                action.to_string(),
+                "test_resource".to_string(),
+            );
+            logger.log(event).await.unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_failed_write_retried_until_it_succeeds() {
+        let storage = Arc::new(RecoveringStorage {
+            failures: AtomicUsize::new(2),
+            inner: MockStorage::new(),
+        });
+        let logger = AuditLogger::new(storage.clone(), retrying_config());
+
+        log_actions(&logger, &["first", "second"]).await;
+
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        let events = storage.inner.get_events();
+        let actions: Vec<&str> = events.iter().map(|e| e.event.action.as_str()).collect();
+        assert_eq!(actions, vec!["first", "second"]);
+    }
+
+    #[tokio::test]
+    async fn test_exhausted_retries_dead_letter_the_batch_for_replay() {
+        let path = std::env::temp_dir()
+            .join(format!("audit-dead-letter-{}", Uuid::new_v4()))
+            .join("audit.jsonl");
+        let dead_letter = Arc::new(FileAuditStorage::new(&path));
+        let storage = Arc::new(RecoveringStorage {
+            failures: AtomicUsize::new(3),
+            inner: MockStorage::new(),
+        });
+        let logger = AuditLoggerBuilder::new(storage.clone(), retrying_config())
+            .dead_letter(dead_letter)
+            .build();
+
+        log_actions(&logger, &["first", "second"]).await;
+
+        // One attempt plus two retries all fail, so the batch is dead-lettered
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(storage.inner.get_events().is_empty());
+        let contents = tokio::fs::read_to_string(&path).await.unwrap();
+        assert_eq!(contents.lines().count(), 1);
+
+        // The storage has recovered, so replay moves the batch over
+        let outcome = logger.replay_dead_letters().await.unwrap();
+        assert_eq!(
+            outcome,
+            ReplayOutcome {
+                batches: 1,
+                events: 2,
+                remaining: 0,
+            }
+        );
+        let events = storage.inner.get_events();
+        let actions: Vec<&str> = events.iter().map(|e| e.event.action.as_str()).collect();
+        assert_eq!(actions, vec!["first", "second"]);
+        assert!(events.iter().all(|e| e.batch_id.is_some()));
+        assert!(tokio::fs::read_to_string(&path).await.unwrap().is_empty());
+
+        let _ = tokio::fs::remove_dir_all(path.parent().unwrap()).await;
+    }
+
+    #[tokio::test]
+    async fn test_failed_batches_land_in_fallback_storage() {
+        let fallback = Arc::new(Mutex::new(Vec::new()));
+        let storage = Arc::new(FallbackAuditStorage::new(
+            Box::new(FailingStorage),
+            Box::new(MockStorage {
+                events: fallback.clone(),
+            }),
+        ));
+        let config = AuditLoggerConfig {
+            batch_size: 2,
+            batch_timeout_ms: 100,
+            channel_buffer_size: 100,
+            ..AuditLoggerConfig::default()
+        };
+        let logger = AuditLogger::new(storage, config);
+
+        log_actions(&logger, &["first", "second"]).await;
+
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        let events = fallback.lock().unwrap().clone();
+        let actions: Vec<&str> = events.iter().map(|e| e.event.action.as_str()).collect();
+        assert_eq!(actions, vec!["first", "second"]);
+        assert!(events.iter().all(|e| e.batch_id.is_some()));
+    }
+
+    #[tokio::test]
+    async fn test_unwritten_events_past_retention_limit_drop_oldest_first() {
+        let storage = Arc::new(RecoveringStorage {
+            failures: AtomicUsize::new(2),
+            inner: MockStorage::new(),
+        });
+        let config = AuditLoggerConfig {
+            max_flush_retries: 0,
+            max_retained_events: 3,
+            ..retrying_config()
+        };
+        let logger = AuditLogger::new(storage.clone(), config);
+
+        // The first two batches fail and are kept, trimmed to the newest three
+        // events, then go out in front of the third batch
+        for actions in [["a", "b"], ["c", "d"], ["e", "f"]] {
+            log_actions(&logger, &actions).await;
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        let events = storage.inner.get_events();
+        let actions: Vec<&str> = events.iter().map(|e| e.event.action.as_str()).collect();
+        assert_eq!(actions, vec!["d", "e", "f"]);
+    }
+
+    #[tokio::test]
+    async fn test_retry_backoff_does_not_block_event_intake() {
+        let config = AuditLoggerConfig {
+            batch_size: 1,
+            channel_buffer_size: 2,
+            max_flush_retries: 1,
+            retry_backoff_ms: 60_000,
+            ..retrying_config()
+        };
+        let logger = AuditLogger::new(Arc::new(FailingStorage), config);
+
+        // The writer waits a minute before its retry; events keep being accepted
+        let actions: Vec<String> = (0..20).map(|i| format!("action_{}", i)).collect();
+        let actions: Vec<&str> = actions.iter().map(String::as_str).collect();
+        let intake = tokio::time::timeout(Duration::from_secs(1), log_actions(&logger, &actions));
+        assert!(
+            intake.await.is_ok(),
+            "Logging stalled behind the retry backoff"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_listed_tenant_stored_pseudonymized_and_verifiable() {
+        let tenant_id = Uuid::new_v4();
+        let actor = Uuid::new_v4();
+        let storage = Arc::new(MockStorage::new());
+        let pseudonymizer = AuditPseudonymizer::new(&[7u8; 32], [tenant_id]).unwrap();
+        let logger = AuditLogger::new(storage.clone(), retrying_config())
+            .with_pseudonymizer(Arc::new(pseudonymizer));
+        let mut subscriber = logger.subscribe();
+
+        for _ in 0..2 {
+            let event = AuditEvent::new(
+                tenant_id,
+                AuditEventType::IdentityUpdated,
+                "update".to_string(),
+                "identity".to_string(),
+            )
+            .with_actor(actor)
+            .with_resource_id(actor.to_string());
+            logger.log(event).await.unwrap();
+        }
+
+        // Live subscribers never see the original IDs
+        let streamed = tokio::time::timeout(Duration::from_secs(1), subscriber.recv())
+            .await
+            .expect("Subscriber should receive the event")
+            .unwrap();
+        assert_ne!(streamed.actor_identity_id, Some(actor));
+        assert_ne!(streamed.resource_id, Some(actor.to_string()));
+
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        let events = storage.get_events();
+        assert_eq!(events.len(), 2);
+        assert_ne!(events[0].event.actor_identity_id, Some(actor));
+        assert_eq!(events[0].event.actor_identity_id, events[1].event.actor_identity_id);
+        assert_eq!(events[0].event.resource_id, events[1].event.resource_id);
+
+        // Stored hashes cover the pseudonymized form
+        let chain = HashChain::new();
+        for stored in &events {
+            let hashable = HashableEvent::from_audit_event(stored.id, &stored.event, None);
+            let hash = stored.event_hash.as_deref().unwrap();
+            assert!(chain.verify_hash(&hashable, hash).unwrap());
+        }
+    }
+
     #[tokio::test]
     async fn test_audit_logger_batching() {
         let storage = Arc::new(MockStorage::new());
@@ -198,6 +860,7 @@ mod tests {
             batch_size: 5,
             batch_timeout_ms: 100,
             channel_buffer_size: 100,
+            ..AuditLoggerConfig::default()
         };
 
         let logger = AuditLogger::new(storage.clone(), config);
@@ -240,6 +903,7 @@ mod tests {
             batch_size: 100,
             batch_timeout_ms: 100,
             channel_buffer_size: 100,
+            ..AuditLoggerConfig::default()
         };
 
         let logger = AuditLogger::new(storage.clone(), config);
@@ -259,4 +923,125 @@ mod tests {
         tokio::time::sleep(Duration::from_millis(150)).await;
         assert_eq!(storage.get_events().len(), 2, "Events should be flushed after timeout");
     }
+
+    #[tokio::test]
+    async fn test_audit_logger_signs_events() {
+        let storage = Arc::new(MockStorage::new());
+        let signer = Arc::new(AuditSigner::generate("audit-test".to_string()));
+        let config = AuditLoggerConfig {
+            batch_size: 1,
+            batch_timeout_ms: 100,
+            channel_buffer_size: 100,
+            ..AuditLoggerConfig::default()
+        };
+
+        let logger = AuditLoggerBuilder::new(storage.clone(), config)
+            .signer(signer.clone())
+            .build();
+
+        let event = AuditEvent::new(
+            Uuid::new_v4(),
+            AuditEventType::SystemEvent,
+            "test_action".to_string(),
+            "test_resource".to_string(),
+        );
+        logger.log(event).await.unwrap();
+
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        let events = storage.get_events();
+        assert_eq!(events.len(), 1);
+
+        let persisted = &events[0];
+        assert!(persisted.batch_id.is_some());
+        assert_eq!(persisted.batch_index, Some(0));
+        let signature = persisted.signature.as_ref().expect("Event should be signed");
+        let hashable = HashableEvent::from_audit_event(persisted.id, &persisted.event, None);
+        assert!(signer.verify_event(&hashable, signature).unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_batches_signed_with_active_rotating_key() {
+        use crate::crypto::keys::{KeyPurpose, ManagedKey};
+
+        let storage = Arc::new(MockStorage::new());
+        let keys = Arc::new(KeyManager::new(
+            KeyPurpose::AuditSigning,
+            chrono::Duration::seconds(10),
+            chrono::Duration::seconds(5),
+        ));
+        let config = AuditLoggerConfig {
+            batch_size: 1,
+            ..retrying_config()
+        };
+        let logger = AuditLoggerBuilder::new(storage.clone(), config)
+            .signing_keys(keys.clone())
+            .build();
+
+        let original = keys.current_signing_key().unwrap();
+        log_actions(&logger, &["before"]).await;
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        let rotated_at = original.created_at + chrono::Duration::seconds(11);
+        let rotated = ManagedKey::generate(KeyPurpose::AuditSigning, rotated_at);
+        keys.install(vec![rotated.clone()], rotated_at).unwrap();
+        log_actions(&logger, &["after"]).await;
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        let events = storage.get_events();
+        assert_eq!(events.len(), 2);
+        for (stored, key) in events.iter().zip([&original, &rotated]) {
+            let hashable = HashableEvent::from_audit_event(stored.id, &stored.event, None);
+            let signature = stored.signature.as_ref().unwrap();
+            let signer = key.audit_signer().unwrap();
+            assert!(signer.verify_event(&hashable, signature).unwrap());
+        }
+        let hashable = HashableEvent::from_audit_event(events[1].id, &events[1].event, None);
+        let signature = events[1].signature.as_ref().unwrap();
+        let original_signer = original.audit_signer().unwrap();
+        assert!(!original_signer.verify_event(&hashable, signature).unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_audit_logger_streams_events_to_subscribers() {
+        let storage = Arc::new(MockStorage::new());
+        let logger = AuditLogger::new(storage.clone(), AuditLoggerConfig::default());
+        let mut subscriber = logger.subscribe();
+
+        let tenant_id = Uuid::new_v4();
+        let event = AuditEvent::new(
+            tenant_id,
+            AuditEventType::PolicyUpdated,
+            "update_policy".to_string(),
+            "policy".to_string(),
+        );
+        logger.log(event).await.unwrap();
+
+        let received = tokio::time::timeout(Duration::from_secs(1), subscriber.recv())
+            .await
+            .expect("Subscriber should receive the event")
+            .unwrap();
+        assert_eq!(received.tenant_id, tenant_id);
+        assert_eq!(received.event_type, AuditEventType::PolicyUpdated);
+        assert_eq!(received.action, "update_policy");
+    }
+
+    #[tokio::test]
+    async fn test_deferred_sink_holds_events_until_flushed() {
+        let deferred = DeferredAuditSink::new();
+        let recorder = RecordingAuditSink::new();
+
+        for action in ["first", "second"] {
+            let event = AuditEvent::new(
+                Uuid::new_v4(),
+                AuditEventType::IdentityCreated,
+                action.to_string(),
+                "identity".to_string(),
+            );
+            AuditSink::log(&deferred, event).await.unwrap();
+        }
+        assert!(recorder.events().is_empty());
+
+        deferred.flush(&recorder).await.unwrap();
+        let actions: Vec<String> = recorder.events().into_iter().map(|e| e.action).collect();
+        assert_eq!(actions, vec!["first", "second"]);
+    }
 }
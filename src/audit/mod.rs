@@ -1,5 +1,8 @@
 // Audit logging module
+pub mod archive;
+pub mod export;
 pub mod logger;
+pub mod redaction;
 pub mod storage;
 pub mod tamper_proof;
 pub mod query;
@@ -1,2 +1,71 @@
 // Audit log query interface
-// To be implemented in Task #13
+
+use crate::db::schema::AuditBatch;
+use crate::errors::Result;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+/// Where an audit event sits within its Merkle batch
+#[derive(Debug, Clone)]
+pub struct EventBatchPosition {
+    pub event_id: Uuid,
+    pub event_hash: Option<String>,
+    pub batch_id: Option<Uuid>,
+    pub batch_index: Option<i32>,
+}
+
+/// Get the batch position of one of a tenant's audit events
+pub async fn get_event_batch_position(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    event_id: Uuid,
+) -> Result<Option<EventBatchPosition>> {
+    let position = sqlx::query_as!(
+        EventBatchPosition,
+        r#"
+        SELECT id as event_id, event_hash, batch_id, batch_index
+        FROM audit_logs
+        WHERE id = $1 AND tenant_id = $2
+        "#,
+        event_id,
+        tenant_id
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(position)
+}
+
+/// Get a batch anchor by ID
+pub async fn get_batch(pool: &PgPool, batch_id: Uuid) -> Result<Option<AuditBatch>> {
+    let batch = sqlx::query_as!(
+        AuditBatch,
+        r#"
+        SELECT id, merkle_root, event_count, root_signature, signing_key_id, created_at
+        FROM audit_batches
+        WHERE id = $1
+        "#,
+        batch_id
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(batch)
+}
+
+/// Get the ordered leaf hashes of a batch
+pub async fn get_batch_leaves(pool: &PgPool, batch_id: Uuid) -> Result<Vec<String>> {
+    let rows = sqlx::query!(
+        r#"
+        SELECT event_hash as "event_hash!"
+        FROM audit_logs
+        WHERE batch_id = $1 AND event_hash IS NOT NULL
+        ORDER BY batch_index ASC
+        "#,
+        batch_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows.into_iter().map(|r| r.event_hash).collect())
+}
@@ -0,0 +1,327 @@
+// Audit event redaction
+//
+// Erasing an identity removes it as the actor of its audit events. An event's
+// stored hash covers the actor and is also its Merkle leaf, so the hash is
+// left as it was; instead a signed redaction records the original hash
+// together with the hash of the redacted event. A redacted event verifies when
+// its current content hashes to the signed redacted hash and its stored hash
+// is the signed original.
+
+use crate::audit::tamper_proof::{
+    canonical_timestamp, constant_time_compare, HashChain, HashableEvent,
+};
+use crate::crypto::signing::{verify_signature, AuditSigner};
+use crate::domain::audit::EventRedaction;
+use crate::errors::{AppError, Result};
+use chrono::{DateTime, Utc};
+use ed25519_dalek::VerifyingKey;
+use sqlx::{PgConnection, PgPool};
+use std::collections::HashMap;
+use tracing::warn;
+use uuid::Uuid;
+
+/// An audit event as stored, with the hash it was logged with
+#[derive(Debug, Clone)]
+pub struct StoredEvent {
+    pub event: HashableEvent,
+    pub event_hash: Option<String>,
+}
+
+/// Hashed columns of an `audit_logs` row
+struct EventRow {
+    id: Uuid,
+    tenant_id: Uuid,
+    actor_identity_id: Option<Uuid>,
+    event_type: String,
+    action: String,
+    resource_type: String,
+    resource_id: Option<String>,
+    decision: Option<String>,
+    timestamp: DateTime<Utc>,
+    previous_event_hash: Option<String>,
+    metadata: serde_json::Value,
+    event_hash: Option<String>,
+}
+
+impl From<EventRow> for StoredEvent {
+    fn from(row: EventRow) -> Self {
+        Self {
+            event: HashableEvent {
+                id: row.id,
+                tenant_id: row.tenant_id,
+                actor_identity_id: row.actor_identity_id,
+                event_type: row.event_type,
+                action: row.action,
+                resource_type: row.resource_type,
+                resource_id: row.resource_id,
+                decision: row.decision,
+                timestamp: canonical_timestamp(&row.timestamp),
+                previous_hash: row.previous_event_hash,
+                metadata: row.metadata,
+            },
+            event_hash: row.event_hash,
+        }
+    }
+}
+
+/// Build and sign the redaction of one event
+///
+/// `redacted` must be `original` with only unhashed or erased fields changed;
+/// its hash is what the event is verified against from now on.
+pub fn create_redaction(
+    signer: &AuditSigner,
+    original: &StoredEvent,
+    redacted: &HashableEvent,
+) -> Result<EventRedaction> {
+    if original.event.id != redacted.id || original.event.tenant_id != redacted.tenant_id {
+        return Err(AppError::ValidationError(
+            "Redacted event does not match the original".to_string(),
+        ));
+    }
+
+    let chain = HashChain::new();
+    let original_event_hash = match &original.event_hash {
+        Some(hash) => hash.clone(),
+        None => chain.compute_hash(&original.event)?,
+    };
+
+    let mut redaction = EventRedaction {
+        event_id: redacted.id,
+        tenant_id: redacted.tenant_id,
+        original_event_hash,
+        redacted_event_hash: chain.compute_hash(redacted)?,
+        signature: String::new(),
+        signing_key_id: signer.key_id().to_string(),
+        created_at: Utc::now(),
+    };
+    redaction.signature = signer.sign_bytes(redaction_payload(&redaction).as_bytes());
+
+    Ok(redaction)
+}
+
+/// Remove an identity as the actor of every audit event, recording a signed
+/// redaction for each
+///
+/// The events' IP address and user agent are cleared as well; they are not
+/// hashed. Run inside a transaction so events and redactions change together.
+/// Returns the number of events redacted.
+pub async fn redact_actor(
+    conn: &mut PgConnection,
+    signer: &AuditSigner,
+    actor_id: Uuid,
+) -> Result<u64> {
+    let rows = sqlx::query_as!(
+        EventRow,
+        r#"
+        SELECT id, tenant_id, actor_identity_id, event_type, action, resource_type,
+               resource_id, decision, timestamp, previous_event_hash,
+               COALESCE(metadata, '{}'::jsonb) as "metadata!", event_hash
+        FROM audit_logs
+        WHERE actor_identity_id = $1
+        "#,
+        actor_id
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    for row in rows {
+        let original = StoredEvent::from(row);
+        let mut redacted = original.event.clone();
+        redacted.actor_identity_id = None;
+
+        let redaction = create_redaction(signer, &original, &redacted)?;
+        sqlx::query!(
+            r#"
+            INSERT INTO audit_event_redactions (
+                event_id, tenant_id, original_event_hash, redacted_event_hash,
+                signature, signing_key_id, created_at
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7)
+            "#,
+            redaction.event_id,
+            redaction.tenant_id,
+            redaction.original_event_hash,
+            redaction.redacted_event_hash,
+            redaction.signature,
+            redaction.signing_key_id,
+            redaction.created_at,
+        )
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    let result = sqlx::query!(
+        r#"
+        UPDATE audit_logs
+        SET actor_identity_id = NULL, ip_address = NULL, user_agent = NULL
+        WHERE actor_identity_id = $1
+        "#,
+        actor_id
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
+/// Verify one stored event, using its redaction if it has one
+///
+/// Returns Ok(false) if the event was altered other than by a valid redaction.
+/// Events stored without a hash have nothing to verify against.
+pub fn verify_event(
+    verifying_key: &VerifyingKey,
+    stored: &StoredEvent,
+    redaction: Option<&EventRedaction>,
+) -> Result<bool> {
+    let chain = HashChain::new();
+    let Some(redaction) = redaction else {
+        return match &stored.event_hash {
+            Some(hash) => chain.verify_hash(&stored.event, hash),
+            None => Ok(true),
+        };
+    };
+
+    if redaction.event_id != stored.event.id || redaction.tenant_id != stored.event.tenant_id {
+        return Ok(false);
+    }
+    if !verify_signature(
+        verifying_key,
+        redaction_payload(redaction).as_bytes(),
+        &redaction.signature,
+    )? {
+        warn!(event_id = %redaction.event_id, "Audit redaction signature is invalid");
+        return Ok(false);
+    }
+    if let Some(hash) = &stored.event_hash {
+        if !constant_time_compare(hash, &redaction.original_event_hash) {
+            return Ok(false);
+        }
+    }
+
+    chain.verify_hash(&stored.event, &redaction.redacted_event_hash)
+}
+
+/// Verify every stored audit event of a tenant
+///
+/// Returns Ok(false) at the first event that fails verification.
+pub async fn verify_tenant_events(
+    pool: &PgPool,
+    verifying_key: &VerifyingKey,
+    tenant_id: Uuid,
+) -> Result<bool> {
+    let redactions: HashMap<Uuid, EventRedaction> = sqlx::query_as!(
+        EventRedaction,
+        r#"
+        SELECT event_id, tenant_id, original_event_hash, redacted_event_hash,
+               signature, signing_key_id, created_at
+        FROM audit_event_redactions
+        WHERE tenant_id = $1
+        "#,
+        tenant_id
+    )
+    .fetch_all(pool)
+    .await?
+    .into_iter()
+    .map(|redaction| (redaction.event_id, redaction))
+    .collect();
+
+    let rows = sqlx::query_as!(
+        EventRow,
+        r#"
+        SELECT id, tenant_id, actor_identity_id, event_type, action, resource_type,
+               resource_id, decision, timestamp, previous_event_hash,
+               COALESCE(metadata, '{}'::jsonb) as "metadata!", event_hash
+        FROM audit_logs
+        WHERE tenant_id = $1
+        ORDER BY timestamp ASC, id ASC
+        "#,
+        tenant_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    for row in rows {
+        let stored = StoredEvent::from(row);
+        if !verify_event(verifying_key, &stored, redactions.get(&stored.event.id))? {
+            warn!(event_id = %stored.event.id, "Audit event failed verification");
+            return Ok(false);
+        }
+    }
+
+    Ok(true)
+}
+
+/// Canonical form of a redaction, as signed
+fn redaction_payload(redaction: &EventRedaction) -> String {
+    format!(
+        "event_id={}|tenant_id={}|original_event_hash={}|redacted_event_hash={}|signing_key_id={}",
+        redaction.event_id,
+        redaction.tenant_id,
+        redaction.original_event_hash,
+        redaction.redacted_event_hash,
+        redaction.signing_key_id
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stored_event(actor_identity_id: Option<Uuid>) -> StoredEvent {
+        let event = HashableEvent {
+            id: Uuid::new_v4(),
+            tenant_id: Uuid::new_v4(),
+            actor_identity_id,
+            event_type: "authentication".to_string(),
+            action: "login".to_string(),
+            resource_type: "session".to_string(),
+            resource_id: None,
+            decision: None,
+            timestamp: "2026-02-12T10:00:00.000000Z".to_string(),
+            previous_hash: None,
+            metadata: serde_json::json!({ "method": "password" }),
+        };
+        let event_hash = Some(HashChain::new().compute_hash(&event).unwrap());
+        StoredEvent { event, event_hash }
+    }
+
+    fn erase_actor(stored: &StoredEvent) -> StoredEvent {
+        let mut erased = stored.clone();
+        erased.event.actor_identity_id = None;
+        erased
+    }
+
+    #[test]
+    fn test_redacted_event_verifies_only_with_redaction() {
+        let signer = AuditSigner::generate("audit-test".to_string());
+        let original = stored_event(Some(Uuid::new_v4()));
+        let erased = erase_actor(&original);
+        let redaction = create_redaction(&signer, &original, &erased.event).unwrap();
+
+        let key = signer.verifying_key();
+        assert!(verify_event(&key, &original, None).unwrap());
+        assert!(!verify_event(&key, &erased, None).unwrap());
+        assert!(verify_event(&key, &erased, Some(&redaction)).unwrap());
+        // The stored hash, and so the Merkle leaf, is unchanged
+        assert_eq!(Some(redaction.original_event_hash), erased.event_hash);
+    }
+
+    #[test]
+    fn test_redaction_does_not_cover_other_changes() {
+        let signer = AuditSigner::generate("audit-test".to_string());
+        let original = stored_event(Some(Uuid::new_v4()));
+        let erased = erase_actor(&original);
+        let redaction = create_redaction(&signer, &original, &erased.event).unwrap();
+        let key = signer.verifying_key();
+
+        let mut tampered = erased.clone();
+        tampered.event.action = "logout".to_string();
+        assert!(!verify_event(&key, &tampered, Some(&redaction)).unwrap());
+
+        let mut forged = redaction.clone();
+        forged.redacted_event_hash = HashChain::new().compute_hash(&tampered.event).unwrap();
+        assert!(!verify_event(&key, &tampered, Some(&forged)).unwrap());
+
+        let other = AuditSigner::generate("other".to_string());
+        assert!(!verify_event(&other.verifying_key(), &erased, Some(&redaction)).unwrap());
+    }
+}
@@ -1,14 +1,30 @@
-use crate::domain::audit::PersistedAuditEvent;
+use crate::domain::audit::{AuditBatchAnchor, PersistedAuditEvent};
 use crate::errors::{AppError, Result};
+use crate::observability::MetricsRecorder;
 use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
 use sqlx::PgPool;
-use tracing::{error, info};
+use std::path::PathBuf;
+use tokio::io::AsyncWriteExt;
+use tracing::{error, info, warn};
 
 /// Trait for audit event storage backends
 #[async_trait]
 pub trait AuditStorage: Send + Sync {
     /// Write a batch of audit events to storage
     async fn write_batch(&self, events: Vec<PersistedAuditEvent>) -> Result<()>;
+
+    /// Write a batch of audit events together with its Merkle anchor
+    ///
+    /// Backends that cannot store anchors fall back to writing the events only.
+    async fn write_anchored_batch(
+        &self,
+        anchor: AuditBatchAnchor,
+        events: Vec<PersistedAuditEvent>,
+    ) -> Result<()> {
+        let _ = anchor;
+        self.write_batch(events).await
+    }
 }
 
 /// PostgreSQL storage backend for audit logs
@@ -22,15 +38,12 @@ impl PostgresAuditStorage {
     }
 }
 
-#[async_trait]
-impl AuditStorage for PostgresAuditStorage {
-    async fn write_batch(&self, events: Vec<PersistedAuditEvent>) -> Result<()> {
-        if events.is_empty() {
-            return Ok(());
-        }
-
-        let mut tx = self.pool.begin().await?;
-
+impl PostgresAuditStorage {
+    /// Insert events within an open transaction
+    async fn insert_events(
+        tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+        events: Vec<PersistedAuditEvent>,
+    ) -> Result<()> {
         for event in events {
             let e = &event.event;
 
@@ -47,8 +60,9 @@ impl AuditStorage for PostgresAuditStorage {
                     event_type, action, resource_type, resource_id,
                     decision, decision_reason,
                     request_id, ip_address, user_agent, metadata, timestamp,
-                    signature, previous_event_hash
-                ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17)
+                    signature, previous_event_hash,
+                    event_hash, batch_id, batch_index
+                ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19, $20)
                 "#,
                 event.id,
                 e.tenant_id,
@@ -67,15 +81,65 @@ impl AuditStorage for PostgresAuditStorage {
                 e.timestamp,
                 event.signature,
                 event.previous_event_hash,
+                event.event_hash,
+                event.batch_id,
+                event.batch_index,
             )
-            .execute(&mut *tx)
+            .execute(&mut **tx)
             .await
             .map_err(|e| {
                 error!("Failed to insert audit log: {:?}", e);
-                AppError::Database(e)
+                AppError::from(e)
             })?;
         }
 
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl AuditStorage for PostgresAuditStorage {
+    async fn write_batch(&self, events: Vec<PersistedAuditEvent>) -> Result<()> {
+        if events.is_empty() {
+            return Ok(());
+        }
+
+        let mut tx = self.pool.begin().await?;
+        Self::insert_events(&mut tx, events).await?;
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    async fn write_anchored_batch(
+        &self,
+        anchor: AuditBatchAnchor,
+        events: Vec<PersistedAuditEvent>,
+    ) -> Result<()> {
+        if events.is_empty() {
+            return Ok(());
+        }
+
+        let mut tx = self.pool.begin().await?;
+
+        // The anchor must exist before events can reference it
+        sqlx::query!(
+            r#"
+            INSERT INTO audit_batches (
+                id, merkle_root, event_count, root_signature, signing_key_id, created_at
+            ) VALUES ($1, $2, $3, $4, $5, $6)
+            "#,
+            anchor.id,
+            anchor.merkle_root,
+            anchor.event_count,
+            anchor.root_signature,
+            anchor.signing_key_id,
+            anchor.created_at,
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        Self::insert_events(&mut tx, events).await?;
         tx.commit().await?;
 
         Ok(())
@@ -143,6 +207,258 @@ impl AuditStorage for MultiBackendStorage {
 
         Ok(())
     }
+
+    async fn write_anchored_batch(
+        &self,
+        anchor: AuditBatchAnchor,
+        events: Vec<PersistedAuditEvent>,
+    ) -> Result<()> {
+        if self.backends.is_empty() {
+            return Err(AppError::Internal(
+                "No storage backends configured".to_string(),
+            ));
+        }
+
+        let mut errors = Vec::new();
+        for (idx, backend) in self.backends.iter().enumerate() {
+            if let Err(e) = backend
+                .write_anchored_batch(anchor.clone(), events.clone())
+                .await
+            {
+                error!("Backend {} failed to write anchored audit batch: {:?}", idx, e);
+                errors.push(e);
+            }
+        }
+
+        if !errors.is_empty() && errors.len() == self.backends.len() {
+            return Err(AppError::Internal(
+                "All storage backends failed to write audit logs".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+/// Local file storage backend, one JSON line per batch
+///
+/// Used as the fallback while the primary backend is down and as the
+/// dead-letter queue for batches it could not take: each batch is appended
+/// together with its anchor and synced to disk before the write counts as
+/// done, so the file can be replayed into the primary later.
+pub struct FileAuditStorage {
+    path: PathBuf,
+    /// Keeps concurrent batches from interleaving their lines
+    write_lock: tokio::sync::Mutex<()>,
+}
+
+#[derive(Serialize)]
+struct FileBatch<'a> {
+    anchor: Option<&'a AuditBatchAnchor>,
+    events: &'a [PersistedAuditEvent],
+}
+
+#[derive(Deserialize)]
+struct StoredBatch {
+    anchor: Option<AuditBatchAnchor>,
+    events: Vec<PersistedAuditEvent>,
+}
+
+/// Outcome of replaying a file's batches into another backend
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ReplayOutcome {
+    pub batches: usize,
+    pub events: usize,
+    /// Batches left in the file because replay stopped at a failure
+    pub remaining: usize,
+}
+
+impl FileAuditStorage {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            write_lock: tokio::sync::Mutex::new(()),
+        }
+    }
+
+    async fn append(
+        &self,
+        anchor: Option<&AuditBatchAnchor>,
+        events: &[PersistedAuditEvent],
+    ) -> Result<()> {
+        let mut line = serde_json::to_vec(&FileBatch { anchor, events })
+            .map_err(|e| AppError::Internal(format!("Failed to serialize audit batch: {}", e)))?;
+        line.push(b'\n');
+
+        let _guard = self.write_lock.lock().await;
+        if let Some(parent) = self.path.parent() {
+            tokio::fs::create_dir_all(parent).await.map_err(io_error)?;
+        }
+        let mut file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .await
+            .map_err(io_error)?;
+        file.write_all(&line).await.map_err(io_error)?;
+        file.sync_data().await.map_err(io_error)?;
+
+        Ok(())
+    }
+
+    /// Write the stored batches to `target` in order, removing them from the file
+    ///
+    /// Stops at the first batch that cannot be read or written; it and every
+    /// later batch stay in the file for the next replay, and the error is
+    /// returned once the file has been rewritten.
+    pub async fn replay(&self, target: &dyn AuditStorage) -> Result<ReplayOutcome> {
+        let _guard = self.write_lock.lock().await;
+        let contents = match tokio::fs::read_to_string(&self.path).await {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                return Ok(ReplayOutcome::default())
+            }
+            Err(e) => return Err(io_error(e)),
+        };
+
+        let lines: Vec<&str> = contents.lines().filter(|l| !l.trim().is_empty()).collect();
+        let mut outcome = ReplayOutcome::default();
+        let mut failure = None;
+        for line in &lines {
+            if let Err(e) = replay_line(line, target, &mut outcome).await {
+                failure = Some(e);
+                break;
+            }
+        }
+
+        let remaining = &lines[outcome.batches..];
+        outcome.remaining = remaining.len();
+        let mut rest = remaining.join("\n");
+        if !rest.is_empty() {
+            rest.push('\n');
+        }
+        tokio::fs::write(&self.path, rest).await.map_err(io_error)?;
+
+        match failure {
+            Some(e) => Err(e),
+            None => Ok(outcome),
+        }
+    }
+}
+
+async fn replay_line(
+    line: &str,
+    target: &dyn AuditStorage,
+    outcome: &mut ReplayOutcome,
+) -> Result<()> {
+    let batch: StoredBatch = serde_json::from_str(line)
+        .map_err(|e| AppError::Internal(format!("Stored audit batch is unreadable: {}", e)))?;
+    let count = batch.events.len();
+
+    match batch.anchor {
+        Some(anchor) => target.write_anchored_batch(anchor, batch.events).await?,
+        None => target.write_batch(batch.events).await?,
+    }
+
+    outcome.batches += 1;
+    outcome.events += count;
+    Ok(())
+}
+
+fn io_error(e: std::io::Error) -> AppError {
+    AppError::Internal(format!("Audit file storage I/O error: {}", e))
+}
+
+#[async_trait]
+impl AuditStorage for FileAuditStorage {
+    async fn write_batch(&self, events: Vec<PersistedAuditEvent>) -> Result<()> {
+        if events.is_empty() {
+            return Ok(());
+        }
+        self.append(None, &events).await
+    }
+
+    async fn write_anchored_batch(
+        &self,
+        anchor: AuditBatchAnchor,
+        events: Vec<PersistedAuditEvent>,
+    ) -> Result<()> {
+        if events.is_empty() {
+            return Ok(());
+        }
+        self.append(Some(&anchor), &events).await
+    }
+}
+
+/// Storage that writes to a fallback backend when the primary one fails
+///
+/// A batch only errors if both backends fail. Every batch diverted to the
+/// fallback is counted in `audit_fallback_events_total`.
+pub struct FallbackAuditStorage {
+    primary: Box<dyn AuditStorage>,
+    fallback: Box<dyn AuditStorage>,
+}
+
+impl FallbackAuditStorage {
+    pub fn new(primary: Box<dyn AuditStorage>, fallback: Box<dyn AuditStorage>) -> Self {
+        Self { primary, fallback }
+    }
+
+    fn record_fallback(&self, count: usize, primary_error: &AppError, result: &Result<()>) {
+        match result {
+            Ok(()) => {
+                warn!(
+                    events = count,
+                    "Primary audit storage failed ({}); batch written to fallback", primary_error
+                );
+                MetricsRecorder::record_audit_fallback("written", count as u64);
+            }
+            Err(e) => {
+                error!(
+                    events = count,
+                    "Primary audit storage failed ({}) and so did the fallback: {}",
+                    primary_error,
+                    e
+                );
+                MetricsRecorder::record_audit_fallback("failed", count as u64);
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl AuditStorage for FallbackAuditStorage {
+    async fn write_batch(&self, events: Vec<PersistedAuditEvent>) -> Result<()> {
+        let primary_error = match self.primary.write_batch(events.clone()).await {
+            Ok(()) => return Ok(()),
+            Err(e) => e,
+        };
+
+        let count = events.len();
+        let result = self.fallback.write_batch(events).await;
+        self.record_fallback(count, &primary_error, &result);
+        result
+    }
+
+    async fn write_anchored_batch(
+        &self,
+        anchor: AuditBatchAnchor,
+        events: Vec<PersistedAuditEvent>,
+    ) -> Result<()> {
+        let primary_error = match self
+            .primary
+            .write_anchored_batch(anchor.clone(), events.clone())
+            .await
+        {
+            Ok(()) => return Ok(()),
+            Err(e) => e,
+        };
+
+        let count = events.len();
+        let result = self.fallback.write_anchored_batch(anchor, events).await;
+        self.record_fallback(count, &primary_error, &result);
+        result
+    }
 }
 
 /// In-memory storage backend (for testing)
@@ -193,6 +509,9 @@ mod tests {
             ),
             signature: None,
             previous_event_hash: None,
+            event_hash: None,
+            batch_id: None,
+            batch_index: None,
         };
 
         storage.write_batch(vec![event.clone()]).await.unwrap();
@@ -202,6 +521,45 @@ mod tests {
         assert_eq!(stored[0].id, event.id);
     }
 
+    #[tokio::test]
+    async fn test_file_storage_appends_one_line_per_batch() {
+        let path = std::env::temp_dir()
+            .join(format!("audit-fallback-{}", Uuid::new_v4()))
+            .join("audit.jsonl");
+        let storage = FileAuditStorage::new(&path);
+        let event = |action: &str| PersistedAuditEvent {
+            id: Uuid::new_v4(),
+            event: AuditEvent::new(
+                Uuid::new_v4(),
+                AuditEventType::SystemEvent,
+                action.to_string(),
+                "test_resource".to_string(),
+            ),
+            signature: None,
+            previous_event_hash: None,
+            event_hash: None,
+            batch_id: None,
+            batch_index: None,
+        };
+
+        storage
+            .write_batch(vec![event("first"), event("second")])
+            .await
+            .unwrap();
+        storage.write_batch(vec![event("third")]).await.unwrap();
+
+        let contents = tokio::fs::read_to_string(&path).await.unwrap();
+        let batches: Vec<serde_json::Value> = contents
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(batches.len(), 2);
+        assert_eq!(batches[0]["events"].as_array().unwrap().len(), 2);
+        assert_eq!(batches[1]["events"][0]["event"]["action"], "third");
+
+        let _ = tokio::fs::remove_dir_all(path.parent().unwrap()).await;
+    }
+
     #[tokio::test]
     async fn test_multi_backend_storage() {
         let storage1 = InMemoryAuditStorage::new();
@@ -221,6 +579,9 @@ mod tests {
             ),
             signature: None,
             previous_event_hash: None,
+            event_hash: None,
+            batch_id: None,
+            batch_index: None,
         };
 
         multi.write_batch(vec![event.clone()]).await.unwrap();
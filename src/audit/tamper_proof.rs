@@ -1,4 +1,6 @@
+use crate::domain::audit::AuditEvent;
 use crate::errors::{AppError, Result};
+use chrono::{DateTime, SecondsFormat, Utc};
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 use std::fmt;
@@ -51,7 +53,7 @@ pub struct HashableEvent {
     pub resource_id: Option<String>,
     /// Decision for authorization events (nullable)
     pub decision: Option<String>,
-    /// Timestamp in RFC3339 format
+    /// Timestamp in RFC3339 format; normalized by `canonical_timestamp` when hashed
     pub timestamp: String,
     /// Previous event hash (nullable for first event in chain)
     pub previous_hash: Option<String>,
@@ -59,6 +61,29 @@ pub struct HashableEvent {
     pub metadata: serde_json::Value,
 }
 
+impl HashableEvent {
+    /// Build a hashable event from a domain audit event
+    pub fn from_audit_event(
+        id: uuid::Uuid,
+        event: &AuditEvent,
+        previous_hash: Option<String>,
+    ) -> Self {
+        Self {
+            id,
+            tenant_id: event.tenant_id,
+            actor_identity_id: event.actor_identity_id,
+            event_type: event.event_type.as_str().to_string(),
+            action: event.action.clone(),
+            resource_type: event.resource_type.clone(),
+            resource_id: event.resource_id.clone(),
+            decision: event.decision.map(|d| d.as_str().to_string()),
+            timestamp: canonical_timestamp(&event.timestamp),
+            previous_hash,
+            metadata: event.metadata.clone(),
+        }
+    }
+}
+
 impl HashChain {
     /// Create a new hash chain with SHA-256
     pub fn new() -> Self {
@@ -107,19 +132,33 @@ impl HashChain {
     ///
     /// Returns Ok(false) if any verification fails
     pub fn verify_chain(&self, events: &[HashableEvent]) -> Result<bool> {
+        self.verify_chain_from(events, None)
+    }
+
+    /// Verify a chain segment whose first event links to `expected_initial_hash`
+    ///
+    /// Used for the events that remain after older ones were archived: the
+    /// first retained event points at the hash of the last archived event,
+    /// which is recorded in the segment's chain anchor.
+    pub fn verify_chain_from(
+        &self,
+        events: &[HashableEvent],
+        expected_initial_hash: Option<String>,
+    ) -> Result<bool> {
         if events.is_empty() {
             return Ok(true);
         }
 
-        // First event should have no previous hash
-        if events[0].previous_hash.is_some() {
+        if events[0].previous_hash != expected_initial_hash {
             tracing::warn!(
-                "First event in chain has a previous_hash, expected None"
+                expected = ?expected_initial_hash,
+                actual = ?events[0].previous_hash,
+                "First event in chain does not link to the expected initial hash"
             );
             return Ok(false);
         }
 
-        let mut previous_hash: Option<String> = None;
+        let mut previous_hash = expected_initial_hash;
 
         for (idx, event) in events.iter().enumerate() {
             // Verify previous hash linkage
@@ -170,7 +209,7 @@ impl HashChain {
     ///
     /// This ensures that the same event data always produces the same hash,
     /// regardless of field ordering or formatting.
-    fn canonicalize(&self, event: &HashableEvent) -> Result<String> {
+    pub(crate) fn canonicalize(&self, event: &HashableEvent) -> Result<String> {
         // Create a canonical representation using pipe-separated fields
         // Format: field_name=value|field_name=value|...
         let mut parts = Vec::new();
@@ -200,7 +239,10 @@ impl HashChain {
             parts.push("decision=null".to_string());
         }
 
-        parts.push(format!("timestamp={}", event.timestamp));
+        parts.push(format!(
+            "timestamp={}",
+            normalize_timestamp(&event.timestamp)?
+        ));
 
         if let Some(prev_hash) = &event.previous_hash {
             parts.push(format!("previous_hash={}", prev_hash));
@@ -208,10 +250,7 @@ impl HashChain {
             parts.push("previous_hash=null".to_string());
         }
 
-        // Serialize metadata to canonical JSON (sorted keys)
-        let metadata_canonical = serde_json::to_string(&event.metadata)
-            .map_err(|e| AppError::Internal(format!("Failed to serialize metadata: {}", e)))?;
-        parts.push(format!("metadata={}", metadata_canonical));
+        parts.push(format!("metadata={}", canonical_json(&event.metadata)?));
 
         Ok(parts.join("|"))
     }
@@ -231,10 +270,95 @@ impl Default for HashChain {
     }
 }
 
+/// Canonical form of an event timestamp: RFC3339 in UTC with microseconds
+///
+/// Microseconds match what Postgres stores, so an event read back from the
+/// database hashes the same as when it was written.
+pub fn canonical_timestamp(timestamp: &DateTime<Utc>) -> String {
+    timestamp.to_rfc3339_opts(SecondsFormat::Micros, true)
+}
+
+/// Re-serialize an RFC3339 timestamp in canonical form
+fn normalize_timestamp(timestamp: &str) -> Result<String> {
+    let parsed = DateTime::parse_from_rfc3339(timestamp).map_err(|e| {
+        AppError::Internal(format!("Invalid event timestamp '{}': {}", timestamp, e))
+    })?;
+    Ok(canonical_timestamp(&parsed.with_timezone(&Utc)))
+}
+
+/// Serialize JSON deterministically for hashing
+///
+/// Object keys are sorted (serde_json keeps insertion order when its
+/// `preserve_order` feature is enabled anywhere in the build), integral floats
+/// are written as integers so `1.0` and `1` agree, and strings use
+/// serde_json's escaping.
+pub(crate) fn canonical_json(value: &serde_json::Value) -> Result<String> {
+    let mut out = String::new();
+    write_canonical(value, &mut out)?;
+    Ok(out)
+}
+
+fn write_canonical(value: &serde_json::Value, out: &mut String) -> Result<()> {
+    use serde_json::Value;
+
+    match value {
+        Value::Null => out.push_str("null"),
+        Value::Bool(b) => out.push_str(if *b { "true" } else { "false" }),
+        Value::Number(n) => out.push_str(&canonical_number(n)?),
+        Value::String(s) => out.push_str(&encode_json(s)?),
+        Value::Array(items) => {
+            out.push('[');
+            for (idx, item) in items.iter().enumerate() {
+                if idx > 0 {
+                    out.push(',');
+                }
+                write_canonical(item, out)?;
+            }
+            out.push(']');
+        }
+        Value::Object(map) => {
+            let mut entries: Vec<_> = map.iter().collect();
+            entries.sort_by(|a, b| a.0.cmp(b.0));
+
+            out.push('{');
+            for (idx, (key, item)) in entries.into_iter().enumerate() {
+                if idx > 0 {
+                    out.push(',');
+                }
+                out.push_str(&encode_json(key)?);
+                out.push(':');
+                write_canonical(item, out)?;
+            }
+            out.push('}');
+        }
+    }
+
+    Ok(())
+}
+
+fn canonical_number(n: &serde_json::Number) -> Result<String> {
+    /// Largest magnitude at which every integer is exactly representable in an f64
+    const MAX_SAFE_INTEGER: f64 = 9_007_199_254_740_992.0;
+
+    if n.is_i64() || n.is_u64() {
+        return Ok(n.to_string());
+    }
+
+    match n.as_f64() {
+        Some(f) if f.fract() == 0.0 && f.abs() <= MAX_SAFE_INTEGER => Ok(format!("{}", f as i64)),
+        _ => encode_json(n),
+    }
+}
+
+fn encode_json<T: Serialize + ?Sized>(value: &T) -> Result<String> {
+    serde_json::to_string(value)
+        .map_err(|e| AppError::Internal(format!("Failed to serialize metadata: {}", e)))
+}
+
 /// Constant-time string comparison to prevent timing attacks
 ///
 /// This is important for security-sensitive comparisons like hash verification
-fn constant_time_compare(a: &str, b: &str) -> bool {
+pub(crate) fn constant_time_compare(a: &str, b: &str) -> bool {
     if a.len() != b.len() {
         return false;
     }
@@ -501,6 +625,52 @@ mod tests {
         assert!(!chain.verify_chain(&events).unwrap());
     }
 
+    fn create_linked_chain(len: usize) -> Vec<HashableEvent> {
+        let chain = HashChain::new();
+        let tenant_id = uuid::Uuid::new_v4();
+        let mut events: Vec<HashableEvent> = Vec::with_capacity(len);
+
+        for i in 0..len {
+            let previous_hash = events.last().map(|e| chain.compute_hash(e).unwrap());
+            events.push(create_test_event(
+                uuid::Uuid::new_v4(),
+                tenant_id,
+                &format!("test.event{}", i),
+                previous_hash,
+            ));
+        }
+
+        events
+    }
+
+    #[test]
+    fn test_truncated_chain_verifies_from_anchor() {
+        let chain = HashChain::new();
+        let events = create_linked_chain(5);
+        let (archived, retained) = events.split_at(2);
+        let anchor_hash = chain.compute_hash(&archived[1]).unwrap();
+
+        // The first retained event points at an archived one
+        assert!(!chain.verify_chain(retained).unwrap());
+        assert!(chain
+            .verify_chain_from(retained, Some(anchor_hash))
+            .unwrap());
+
+        let wrong_anchor = chain.compute_hash(&archived[0]).unwrap();
+        assert!(!chain
+            .verify_chain_from(retained, Some(wrong_anchor))
+            .unwrap());
+    }
+
+    #[test]
+    fn test_verify_chain_from_none_matches_verify_chain() {
+        let chain = HashChain::new();
+        let events = create_linked_chain(3);
+
+        assert!(chain.verify_chain(&events).unwrap());
+        assert!(chain.verify_chain_from(&events, None).unwrap());
+    }
+
     #[test]
     fn test_canonicalize_is_deterministic() {
         let chain = HashChain::new();
@@ -517,6 +687,95 @@ mod tests {
         assert_eq!(canonical1, canonical2);
     }
 
+    #[test]
+    fn test_metadata_key_order_does_not_affect_hash() {
+        let chain = HashChain::new();
+
+        let mut first = serde_json::Map::new();
+        first.insert("zeta".to_string(), serde_json::json!(1));
+        first.insert("alpha".to_string(), serde_json::json!({"b": 2.0, "a": [1, "x"]}));
+        let mut nested = serde_json::Map::new();
+        nested.insert("a".to_string(), serde_json::json!([1, "x"]));
+        nested.insert("b".to_string(), serde_json::json!(2.0));
+        let mut second = serde_json::Map::new();
+        second.insert("alpha".to_string(), serde_json::Value::Object(nested));
+        second.insert("zeta".to_string(), serde_json::json!(1));
+
+        let first = serde_json::Value::Object(first);
+        let second = serde_json::Value::Object(second);
+        assert_eq!(first, second);
+
+        let canonical = canonical_json(&first).unwrap();
+        assert_eq!(canonical, canonical_json(&second).unwrap());
+        assert_eq!(canonical, r#"{"alpha":{"a":[1,"x"],"b":2},"zeta":1}"#);
+
+        let mut event = create_test_event(
+            uuid::Uuid::new_v4(),
+            uuid::Uuid::new_v4(),
+            "test.event",
+            None,
+        );
+        event.metadata = first;
+        let hash = chain.compute_hash(&event).unwrap();
+        event.metadata = second;
+        assert_eq!(hash, chain.compute_hash(&event).unwrap());
+    }
+
+    #[test]
+    fn test_canonical_json_numbers_and_strings() {
+        assert_eq!(
+            canonical_json(&serde_json::json!({"n": 1.0})).unwrap(),
+            canonical_json(&serde_json::json!({"n": 1})).unwrap()
+        );
+        assert_eq!(canonical_json(&serde_json::json!(-0.0)).unwrap(), "0");
+        assert_eq!(canonical_json(&serde_json::json!(1.5)).unwrap(), "1.5");
+        assert_eq!(canonical_json(&serde_json::json!(u64::MAX)).unwrap(), u64::MAX.to_string());
+        assert_eq!(
+            canonical_json(&serde_json::json!("quote\"\n")).unwrap(),
+            r#""quote\"\n""#
+        );
+    }
+
+    #[test]
+    fn test_equivalent_timestamps_hash_the_same() {
+        let chain = HashChain::new();
+        let mut event = create_test_event(
+            uuid::Uuid::new_v4(),
+            uuid::Uuid::new_v4(),
+            "test.event",
+            None,
+        );
+
+        let mut hashes = Vec::new();
+        for timestamp in [
+            "2026-02-12T10:00:00Z",
+            "2026-02-12T10:00:00+00:00",
+            "2026-02-12T12:00:00.000000+02:00",
+            "2026-02-12T10:00:00.000000400Z",
+        ] {
+            event.timestamp = timestamp.to_string();
+            hashes.push(chain.compute_hash(&event).unwrap());
+        }
+        assert!(hashes.iter().all(|h| h == &hashes[0]));
+
+        event.timestamp = "2026-02-12T10:00:00.000001Z".to_string();
+        assert_ne!(chain.compute_hash(&event).unwrap(), hashes[0]);
+    }
+
+    #[test]
+    fn test_canonical_timestamp_format() {
+        let timestamp = DateTime::parse_from_rfc3339("2026-02-12T12:00:00.123456789+02:00")
+            .unwrap()
+            .with_timezone(&Utc);
+
+        assert_eq!(canonical_timestamp(&timestamp), "2026-02-12T10:00:00.123456Z");
+        assert_eq!(
+            normalize_timestamp("2026-02-12T10:00:00Z").unwrap(),
+            "2026-02-12T10:00:00.000000Z"
+        );
+        assert!(normalize_timestamp("yesterday").is_err());
+    }
+
     #[test]
     fn test_null_fields_handled() {
         let chain = HashChain::new();
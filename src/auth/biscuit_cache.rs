@@ -0,0 +1,182 @@
+// Cache of validated Biscuit claims
+//
+// Parsing a Biscuit and running its authorizer is pure CPU, and integrations
+// that validate the agent's token on every request see the same token over
+// and over. Validated claims are kept, keyed by a SHA-256 of the token, until
+// the token itself expires. The cache is bounded and evicts the least
+// recently used entry when full.
+
+use crate::auth::biscuit::BiscuitClaims;
+use crate::errors::{AppError, Result};
+use crate::observability::MetricsRecorder;
+use chrono::{DateTime, Utc};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Mutex, MutexGuard};
+
+type TokenHash = [u8; 32];
+
+struct CacheEntry {
+    claims: BiscuitClaims,
+    /// Position in the recency order
+    last_used: u64,
+}
+
+#[derive(Default)]
+struct CacheInner {
+    entries: HashMap<TokenHash, CacheEntry>,
+    /// Entries by last use, oldest first
+    recency: BTreeMap<u64, TokenHash>,
+    tick: u64,
+}
+
+impl CacheInner {
+    fn touch(&mut self, hash: TokenHash) {
+        self.tick += 1;
+        let tick = self.tick;
+        if let Some(entry) = self.entries.get_mut(&hash) {
+            self.recency.remove(&entry.last_used);
+            entry.last_used = tick;
+            self.recency.insert(tick, hash);
+        }
+    }
+
+    fn remove(&mut self, hash: &TokenHash) {
+        if let Some(entry) = self.entries.remove(hash) {
+            self.recency.remove(&entry.last_used);
+        }
+    }
+}
+
+/// Bounded LRU cache of validated Biscuit claims
+pub struct BiscuitClaimsCache {
+    capacity: usize,
+    inner: Mutex<CacheInner>,
+}
+
+impl BiscuitClaimsCache {
+    /// Create a cache holding at most `capacity` tokens
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            inner: Mutex::new(CacheInner::default()),
+        }
+    }
+
+    /// Claims of a token validated earlier, if it has not expired since
+    pub fn get(&self, token: &str, now: DateTime<Utc>) -> Result<Option<BiscuitClaims>> {
+        let hash = hash_token(token);
+        let mut inner = self.lock()?;
+
+        let claims = match inner.entries.get(&hash) {
+            Some(entry) if entry.claims.expires_at > now => Some(entry.claims.clone()),
+            Some(_) => {
+                inner.remove(&hash);
+                None
+            }
+            None => None,
+        };
+
+        match claims {
+            Some(claims) => {
+                inner.touch(hash);
+                MetricsRecorder::record_biscuit_cache("hit");
+                Ok(Some(claims))
+            }
+            None => {
+                MetricsRecorder::record_biscuit_cache("miss");
+                Ok(None)
+            }
+        }
+    }
+
+    /// Remember the claims of a token that just validated
+    pub fn insert(&self, token: &str, claims: &BiscuitClaims) -> Result<()> {
+        let hash = hash_token(token);
+        let mut inner = self.lock()?;
+
+        inner.remove(&hash);
+        while inner.entries.len() >= self.capacity {
+            let Some((_, oldest)) = inner.recency.pop_first() else {
+                break;
+            };
+            inner.entries.remove(&oldest);
+        }
+
+        inner.tick += 1;
+        let tick = inner.tick;
+        inner.entries.insert(
+            hash,
+            CacheEntry {
+                claims: claims.clone(),
+                last_used: tick,
+            },
+        );
+        inner.recency.insert(tick, hash);
+        Ok(())
+    }
+
+    /// Drop a token, e.g. once it has been revoked
+    pub fn invalidate(&self, token: &str) -> Result<()> {
+        self.lock()?.remove(&hash_token(token));
+        Ok(())
+    }
+
+    fn lock(&self) -> Result<MutexGuard<'_, CacheInner>> {
+        self.inner
+            .lock()
+            .map_err(|_| AppError::Internal("Biscuit claims cache lock poisoned".to_string()))
+    }
+}
+
+fn hash_token(token: &str) -> TokenHash {
+    Sha256::digest(token.as_bytes()).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use uuid::Uuid;
+
+    fn claims(expires_at: DateTime<Utc>) -> BiscuitClaims {
+        BiscuitClaims {
+            agent_id: Uuid::new_v4(),
+            tenant_id: Uuid::new_v4(),
+            parent_id: Uuid::new_v4(),
+            task_id: "task-123".to_string(),
+            task_scope: HashMap::new(),
+            expires_at,
+            issued_at: Utc::now(),
+            key_id: "test-key-id".to_string(),
+            revocation_id: "00".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_least_recently_used_token_evicted() {
+        let cache = BiscuitClaimsCache::new(2);
+        let expires_at = Utc::now() + chrono::Duration::hours(1);
+        cache.insert("token-a", &claims(expires_at)).unwrap();
+        cache.insert("token-b", &claims(expires_at)).unwrap();
+
+        // Using token-a makes token-b the oldest
+        assert!(cache.get("token-a", Utc::now()).unwrap().is_some());
+        cache.insert("token-c", &claims(expires_at)).unwrap();
+
+        assert!(cache.get("token-a", Utc::now()).unwrap().is_some());
+        assert!(cache.get("token-b", Utc::now()).unwrap().is_none());
+        assert!(cache.get("token-c", Utc::now()).unwrap().is_some());
+    }
+
+    #[test]
+    fn test_expired_entry_removed_on_lookup() {
+        let cache = BiscuitClaimsCache::new(4);
+        let expires_at = Utc::now() + chrono::Duration::minutes(1);
+        cache.insert("token-a", &claims(expires_at)).unwrap();
+
+        assert!(cache.get("token-a", expires_at).unwrap().is_none());
+        assert!(cache.lock().unwrap().entries.is_empty());
+        assert!(cache.lock().unwrap().recency.is_empty());
+    }
+}
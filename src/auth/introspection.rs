@@ -0,0 +1,269 @@
+// Token introspection (RFC 7662)
+//
+// Resource servers hand us a token and get back whether it is currently
+// active, plus its claims if it is. Both token formats are accepted: a JWT
+// has three dot-separated parts, anything else is treated as a Biscuit.
+// Invalid, expired and revoked tokens all report `{"active": false}` without
+// saying why. Introspecting a single-use Biscuit uses it up.
+
+use crate::auth::{
+    biscuit::{BiscuitClaims, BiscuitManager},
+    jwt::JwtManager,
+};
+use crate::errors::{AppError, Result};
+use crate::redis::revocation;
+use redis::aio::ConnectionLike;
+use serde::Serialize;
+use uuid::Uuid;
+
+/// Introspection response; only `active` is set for inactive tokens
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct IntrospectionResponse {
+    pub active: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sub: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tenant_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub identity_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exp: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub iat: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub jti: Option<String>,
+    /// Space-separated actions, as in OAuth 2.0 scopes
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scope: Option<String>,
+}
+
+impl IntrospectionResponse {
+    pub fn inactive() -> Self {
+        Self::default()
+    }
+}
+
+/// Introspect a token on behalf of a resource server in `caller_tenant`
+///
+/// Tokens of other tenants are reported inactive. Only Redis errors are
+/// returned as errors; a token that fails validation is simply inactive.
+pub async fn introspect_token<C>(
+    jwt: &JwtManager,
+    biscuit: &BiscuitManager,
+    redis_conn: &mut C,
+    caller_tenant: Uuid,
+    token: &str,
+) -> Result<IntrospectionResponse>
+where
+    C: ConnectionLike + Clone + Send + Sync,
+{
+    let (response, decoded) = match decode(jwt, biscuit, token) {
+        Some(decoded) => decoded,
+        None => return Ok(IntrospectionResponse::inactive()),
+    };
+
+    if response.tenant_id.as_deref() != Some(caller_tenant.to_string().as_str()) {
+        return Ok(IntrospectionResponse::inactive());
+    }
+
+    let revoked = match decoded {
+        Decoded::Jwt { token_id } => revocation::is_token_revoked(redis_conn, &token_id).await?,
+        // Consumes single-use Biscuits, so only the first introspection is active
+        Decoded::Biscuit(claims) => {
+            match biscuit.check_revocation(redis_conn, token, &claims).await {
+                Ok(()) => false,
+                Err(AppError::TokenRevoked) => true,
+                Err(e) => return Err(e),
+            }
+        }
+    };
+    if revoked {
+        return Ok(IntrospectionResponse::inactive());
+    }
+
+    Ok(response)
+}
+
+/// What revocation is checked against for a decoded token
+enum Decoded {
+    Jwt { token_id: String },
+    Biscuit(BiscuitClaims),
+}
+
+/// Validate the token in whichever format it is in, returning the active
+/// response
+fn decode(
+    jwt: &JwtManager,
+    biscuit: &BiscuitManager,
+    token: &str,
+) -> Option<(IntrospectionResponse, Decoded)> {
+    let result = if token.split('.').count() == 3 {
+        decode_jwt(jwt, token)
+    } else {
+        decode_biscuit(biscuit, token)
+    };
+
+    match result {
+        Ok(decoded) => Some(decoded),
+        Err(e) => {
+            tracing::debug!(error = %e, "Introspected token is not active");
+            None
+        }
+    }
+}
+
+fn decode_jwt(jwt: &JwtManager, token: &str) -> Result<(IntrospectionResponse, Decoded)> {
+    let claims = jwt.validate_access_token(token)?;
+    let token_id = claims.token_id().to_string();
+
+    Ok((
+        IntrospectionResponse {
+            active: true,
+            sub: Some(claims.sub),
+            tenant_id: Some(claims.tenant_id),
+            identity_type: Some(claims.identity_type),
+            exp: Some(claims.exp),
+            iat: Some(claims.iat),
+            jti: Some(claims.jti),
+            scope: claims.scope,
+        },
+        Decoded::Jwt { token_id },
+    ))
+}
+
+fn decode_biscuit(
+    biscuit: &BiscuitManager,
+    token: &str,
+) -> Result<(IntrospectionResponse, Decoded)> {
+    let claims = biscuit.validate_token(token)?;
+    let scope = claims.scope()?;
+
+    Ok((
+        IntrospectionResponse {
+            active: true,
+            sub: Some(claims.agent_id.to_string()),
+            tenant_id: Some(claims.tenant_id.to_string()),
+            identity_type: Some("agent".to_string()),
+            exp: Some(claims.expires_at.timestamp()),
+            iat: Some(claims.issued_at.timestamp()),
+            jti: Some(claims.revocation_id.clone()),
+            scope: (!scope.allowed_actions.is_empty()).then(|| scope.allowed_actions.join(" ")),
+        },
+        Decoded::Biscuit(claims),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::redis::mock::FlakyConnection;
+    use redis::Value;
+
+    fn jwt_manager(expiration_seconds: i64) -> JwtManager {
+        std::env::set_var(
+            "AGENT_IAM__AUTH__JWT_SECRET",
+            "test-secret-key-for-jwt-signing-minimum-length-requirement",
+        );
+        let mut config = Config::default();
+        config.auth.jwt_expiration_seconds = expiration_seconds;
+        JwtManager::new(&config).unwrap()
+    }
+
+    fn biscuit_manager() -> BiscuitManager {
+        BiscuitManager::new("test-key-id".to_string()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_active_jwt() {
+        let jwt = jwt_manager(900);
+        let identity_id = Uuid::new_v4();
+        let tenant_id = Uuid::new_v4();
+        let token = jwt
+            .generate_access_token(identity_id, tenant_id, "user")
+            .unwrap();
+        let mut conn = FlakyConnection::responding(Value::Int(0));
+
+        let response = introspect_token(&jwt, &biscuit_manager(), &mut conn, tenant_id, &token)
+            .await
+            .unwrap();
+
+        assert!(response.active);
+        assert_eq!(response.sub, Some(identity_id.to_string()));
+        assert_eq!(response.tenant_id, Some(tenant_id.to_string()));
+        assert_eq!(response.identity_type.as_deref(), Some("user"));
+        assert!(response.exp > response.iat);
+        assert!(response.jti.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_expired_jwt_is_inactive() {
+        let jwt = jwt_manager(-300);
+        let tenant_id = Uuid::new_v4();
+        let token = jwt
+            .generate_access_token(Uuid::new_v4(), tenant_id, "user")
+            .unwrap();
+        let mut conn = FlakyConnection::responding(Value::Int(0));
+
+        let response = introspect_token(&jwt, &biscuit_manager(), &mut conn, tenant_id, &token)
+            .await
+            .unwrap();
+
+        assert_eq!(response, IntrospectionResponse::inactive());
+        assert_eq!(
+            serde_json::to_value(&response).unwrap(),
+            serde_json::json!({ "active": false })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_revoked_jwt_is_inactive() {
+        let jwt = jwt_manager(900);
+        let tenant_id = Uuid::new_v4();
+        let token = jwt
+            .generate_access_token(Uuid::new_v4(), tenant_id, "user")
+            .unwrap();
+        let mut conn = FlakyConnection::responding(Value::Int(1));
+
+        let response = introspect_token(&jwt, &biscuit_manager(), &mut conn, tenant_id, &token)
+            .await
+            .unwrap();
+
+        assert!(!response.active);
+        assert_eq!(conn.calls(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_other_tenant_token_is_inactive() {
+        let jwt = jwt_manager(900);
+        let token = jwt
+            .generate_access_token(Uuid::new_v4(), Uuid::new_v4(), "user")
+            .unwrap();
+        let mut conn = FlakyConnection::responding(Value::Int(0));
+
+        let response =
+            introspect_token(&jwt, &biscuit_manager(), &mut conn, Uuid::new_v4(), &token)
+                .await
+                .unwrap();
+
+        assert!(!response.active);
+        assert_eq!(conn.calls(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_garbage_token_is_inactive() {
+        let mut conn = FlakyConnection::responding(Value::Int(0));
+
+        let response = introspect_token(
+            &jwt_manager(900),
+            &biscuit_manager(),
+            &mut conn,
+            Uuid::new_v4(),
+            "not-a-token",
+        )
+        .await
+        .unwrap();
+
+        assert!(!response.active);
+    }
+}
@@ -1,12 +1,18 @@
 // JWT token generation and validation
 
+use crate::clock::{Clock, SharedClock, SystemClock};
 use crate::config::Config;
 use crate::errors::{AppError, Result};
 use chrono::{DateTime, Duration, Utc};
 use jsonwebtoken::{decode, encode, Algorithm, DecodingKey, EncodingKey, Header, Validation};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::sync::{Arc, RwLock};
 use uuid::Uuid;
 
+/// Audience of access tokens for this service's own API
+pub const DEFAULT_AUDIENCE: &str = "agent-iam-api";
+
 // ============================================================================
 // JWT Claims
 // ============================================================================
@@ -24,12 +30,21 @@ pub struct JwtClaims {
     pub iat: i64,
     /// Expiration time (Unix timestamp)
     pub exp: i64,
+    /// Not before (Unix timestamp); the token is rejected until then
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nbf: Option<i64>,
     /// JWT ID (unique token identifier)
     pub jti: String,
     /// Issuer
     pub iss: String,
     /// Audience
     pub aud: Vec<String>,
+    /// Whether the login completed a second factor
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub mfa: bool,
+    /// Space-delimited permissions held at issuance (OAuth-style scopes)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scope: Option<String>,
     /// Optional custom claims
     #[serde(flatten)]
     pub custom: Option<serde_json::Value>,
@@ -43,7 +58,23 @@ impl JwtClaims {
         identity_type: &str,
         duration_seconds: i64,
     ) -> Self {
-        let now = Utc::now();
+        Self::new_at(
+            identity_id,
+            tenant_id,
+            identity_type,
+            duration_seconds,
+            Utc::now(),
+        )
+    }
+
+    /// Create new JWT claims issued at `now`
+    pub fn new_at(
+        identity_id: Uuid,
+        tenant_id: Uuid,
+        identity_type: &str,
+        duration_seconds: i64,
+        now: DateTime<Utc>,
+    ) -> Self {
         let exp = now + Duration::seconds(duration_seconds);
 
         Self {
@@ -52,17 +83,36 @@ impl JwtClaims {
             identity_type: identity_type.to_string(),
             iat: now.timestamp(),
             exp: exp.timestamp(),
+            nbf: None,
             jti: Uuid::new_v4().to_string(),
             iss: "agent-iam".to_string(),
-            aud: vec!["agent-iam-api".to_string()],
+            aud: vec![DEFAULT_AUDIENCE.to_string()],
+            mfa: false,
+            scope: None,
             custom: None,
         }
     }
 
     /// Check if token is expired
     pub fn is_expired(&self) -> bool {
-        let now = Utc::now().timestamp();
-        self.exp <= now
+        self.is_expired_at(Utc::now())
+    }
+
+    /// Check if token is expired as of `now`
+    pub fn is_expired_at(&self, now: DateTime<Utc>) -> bool {
+        self.exp <= now.timestamp()
+    }
+
+    /// Whether the token's `scope` claim includes `scope`
+    pub fn has_scope(&self, scope: &str) -> bool {
+        self.scope
+            .as_deref()
+            .is_some_and(|scopes| scopes.split(' ').any(|s| s == scope))
+    }
+
+    /// Check if token is not valid yet as of `now`
+    pub fn is_before_nbf_at(&self, now: DateTime<Utc>) -> bool {
+        self.nbf.is_some_and(|nbf| now.timestamp() < nbf)
     }
 
     /// Get token ID
@@ -117,7 +167,23 @@ impl RefreshTokenClaims {
         duration_seconds: i64,
         family_id: Option<String>,
     ) -> Self {
-        let now = Utc::now();
+        Self::new_at(
+            identity_id,
+            tenant_id,
+            duration_seconds,
+            family_id,
+            Utc::now(),
+        )
+    }
+
+    /// Create new refresh token claims issued at `now`
+    pub fn new_at(
+        identity_id: Uuid,
+        tenant_id: Uuid,
+        duration_seconds: i64,
+        family_id: Option<String>,
+        now: DateTime<Utc>,
+    ) -> Self {
         let exp = now + Duration::seconds(duration_seconds);
 
         Self {
@@ -133,8 +199,12 @@ impl RefreshTokenClaims {
 
     /// Check if token is expired
     pub fn is_expired(&self) -> bool {
-        let now = Utc::now().timestamp();
-        self.exp <= now
+        self.is_expired_at(Utc::now())
+    }
+
+    /// Check if token is expired as of `now`
+    pub fn is_expired_at(&self, now: DateTime<Utc>) -> bool {
+        self.exp <= now.timestamp()
     }
 
     /// Get token ID
@@ -162,12 +232,34 @@ impl RefreshTokenClaims {
 // JWT Manager
 // ============================================================================
 
-/// JWT token manager for generation and validation
-pub struct JwtManager {
+/// Signing material for the JWT manager
+///
+/// The secondary key is the previous secret, kept only for validation so that
+/// tokens (refresh tokens included) issued before a rotation remain valid until
+/// they would expire anyway.
+struct JwtKeys {
     encoding_key: EncodingKey,
     decoding_key: DecodingKey,
+    secondary: Option<SecondaryKey>,
+}
+
+struct SecondaryKey {
+    decoding_key: DecodingKey,
+    rotated_at: DateTime<Utc>,
+}
+
+/// JWT token manager for generation and validation
+pub struct JwtManager {
+    keys: RwLock<JwtKeys>,
+    /// Key for the secret from the environment, the secondary after a first rotation
+    bootstrap_decoding_key: DecodingKey,
     access_token_expiration: i64,
     refresh_token_expiration: i64,
+    /// Largest `scope` claim put in access tokens; `None` leaves it out
+    scope_claim_max_bytes: Option<usize>,
+    /// Audiences besides the default that access tokens may be issued for
+    extra_audiences: Vec<String>,
+    clock: SharedClock,
 }
 
 impl JwtManager {
@@ -179,20 +271,168 @@ impl JwtManager {
                 "JWT_SECRET must be set via AGENT_IAM__AUTH__JWT_SECRET environment variable".to_string()
             ))?;
 
-        if secret.len() < 32 {
-            return Err(AppError::Configuration(
-                "JWT secret must be at least 32 characters long".to_string()
-            ));
-        }
+        validate_secret(&secret)?;
 
         Ok(Self {
-            encoding_key: EncodingKey::from_secret(secret.as_bytes()),
-            decoding_key: DecodingKey::from_secret(secret.as_bytes()),
+            keys: RwLock::new(JwtKeys {
+                encoding_key: EncodingKey::from_secret(secret.as_bytes()),
+                decoding_key: DecodingKey::from_secret(secret.as_bytes()),
+                secondary: None,
+            }),
+            bootstrap_decoding_key: DecodingKey::from_secret(secret.as_bytes()),
             access_token_expiration: config.auth.jwt_expiration_seconds,
             refresh_token_expiration: config.auth.refresh_token_expiration_seconds,
+            scope_claim_max_bytes: Some(config.auth.access_token_scope_max_bytes)
+                .filter(|max| *max > 0),
+            extra_audiences: config.auth.jwt_extra_audiences.clone(),
+            clock: Arc::new(SystemClock),
         })
     }
 
+    /// Use `clock` for issuing tokens and checking their expiry
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Rotate the signing secret
+    ///
+    /// The current secret becomes the secondary validation key and the new one
+    /// is used for all newly issued tokens. The secondary is honoured for one
+    /// refresh-token lifetime, after which it is ignored and can be cleared.
+    pub fn rotate_secret(&self, new_secret: &str) -> Result<()> {
+        validate_secret(new_secret)?;
+
+        let mut keys = self.write_keys()?;
+        let previous = std::mem::replace(
+            &mut keys.decoding_key,
+            DecodingKey::from_secret(new_secret.as_bytes()),
+        );
+        keys.encoding_key = EncodingKey::from_secret(new_secret.as_bytes());
+        keys.secondary = Some(SecondaryKey {
+            decoding_key: previous,
+            rotated_at: self.clock.now(),
+        });
+
+        tracing::info!("JWT signing secret rotated; previous secret kept for validation");
+        Ok(())
+    }
+
+    /// Install the secrets recorded by the most recent rotation
+    ///
+    /// `previous` is the secret `current` replaced, or `None` when it replaced
+    /// the secret from the environment. The previous secret is not kept if it
+    /// was cleared.
+    pub fn install_rotated(
+        &self,
+        current: &str,
+        previous: Option<&str>,
+        rotated_at: DateTime<Utc>,
+        previous_cleared: bool,
+    ) -> Result<()> {
+        validate_secret(current)?;
+
+        let secondary = (!previous_cleared).then(|| SecondaryKey {
+            decoding_key: previous
+                .map(|secret| DecodingKey::from_secret(secret.as_bytes()))
+                .unwrap_or_else(|| self.bootstrap_decoding_key.clone()),
+            rotated_at,
+        });
+
+        let mut keys = self.write_keys()?;
+        keys.encoding_key = EncodingKey::from_secret(current.as_bytes());
+        keys.decoding_key = DecodingKey::from_secret(current.as_bytes());
+        keys.secondary = secondary.filter(|s| self.secondary_in_window(s));
+        Ok(())
+    }
+
+    /// Drop the secondary validation key immediately
+    pub fn clear_secondary(&self) -> Result<()> {
+        self.write_keys()?.secondary = None;
+        tracing::info!("JWT secondary validation key cleared");
+        Ok(())
+    }
+
+    /// Drop the secondary validation key if its window has elapsed
+    ///
+    /// Returns true if a key was dropped.
+    pub fn drop_expired_secondary(&self) -> Result<bool> {
+        let mut keys = self.write_keys()?;
+        let expired = keys
+            .secondary
+            .as_ref()
+            .map(|s| !self.secondary_in_window(s))
+            .unwrap_or(false);
+
+        if expired {
+            keys.secondary = None;
+            tracing::info!("JWT secondary validation key expired and was dropped");
+        }
+
+        Ok(expired)
+    }
+
+    /// Whether access tokens carry a `scope` claim
+    pub fn scope_claim_enabled(&self) -> bool {
+        self.scope_claim_max_bytes.is_some()
+    }
+
+    /// Access token lifetime in seconds
+    pub fn access_token_expiration(&self) -> i64 {
+        self.access_token_expiration
+    }
+
+    /// Refresh token lifetime in seconds
+    pub fn refresh_token_expiration(&self) -> i64 {
+        self.refresh_token_expiration
+    }
+
+    /// Whether a secondary validation key is currently installed
+    pub fn has_secondary(&self) -> Result<bool> {
+        Ok(self.read_keys()?.secondary.is_some())
+    }
+
+    fn secondary_in_window(&self, secondary: &SecondaryKey) -> bool {
+        self.clock.now() - secondary.rotated_at < Duration::seconds(self.refresh_token_expiration)
+    }
+
+    fn read_keys(&self) -> Result<std::sync::RwLockReadGuard<'_, JwtKeys>> {
+        self.keys
+            .read()
+            .map_err(|_| AppError::Internal("JWT key lock poisoned".to_string()))
+    }
+
+    fn write_keys(&self) -> Result<std::sync::RwLockWriteGuard<'_, JwtKeys>> {
+        self.keys
+            .write()
+            .map_err(|_| AppError::Internal("JWT key lock poisoned".to_string()))
+    }
+
+    /// Decode a token with the primary key, falling back to the secondary key
+    /// while it is within its validation window
+    fn decode_with_fallback<T: serde::de::DeserializeOwned>(
+        &self,
+        token: &str,
+        validation: &Validation,
+    ) -> std::result::Result<T, jsonwebtoken::errors::Error> {
+        let keys = self.keys.read().map_err(|_| {
+            jsonwebtoken::errors::Error::from(jsonwebtoken::errors::ErrorKind::InvalidKeyFormat)
+        })?;
+
+        match decode::<T>(token, &keys.decoding_key, validation) {
+            Ok(data) => Ok(data.claims),
+            Err(e) if matches!(e.kind(), jsonwebtoken::errors::ErrorKind::InvalidSignature) => {
+                match keys.secondary.as_ref().filter(|s| self.secondary_in_window(s)) {
+                    Some(secondary) => {
+                        decode::<T>(token, &secondary.decoding_key, validation).map(|d| d.claims)
+                    }
+                    None => Err(e),
+                }
+            }
+            Err(e) => Err(e),
+        }
+    }
+
     /// Generate access token (JWT)
     pub fn generate_access_token(
         &self,
@@ -200,16 +440,117 @@ impl JwtManager {
         tenant_id: Uuid,
         identity_type: &str,
     ) -> Result<String> {
-        let claims = JwtClaims::new(
+        self.generate_access_token_with_mfa(identity_id, tenant_id, identity_type, false)
+    }
+
+    /// Generate access token (JWT), recording whether MFA was completed
+    pub fn generate_access_token_with_mfa(
+        &self,
+        identity_id: Uuid,
+        tenant_id: Uuid,
+        identity_type: &str,
+        mfa: bool,
+    ) -> Result<String> {
+        self.generate_scoped_access_token(identity_id, tenant_id, identity_type, mfa, &[])
+    }
+
+    /// Generate access token (JWT) whose `scope` claim lists `scopes`
+    ///
+    /// Scopes are deduplicated and sorted. The claim is left out when scope
+    /// claims are disabled, or when the scopes would not fit in the configured
+    /// size; a present claim is always complete.
+    pub fn generate_scoped_access_token(
+        &self,
+        identity_id: Uuid,
+        tenant_id: Uuid,
+        identity_type: &str,
+        mfa: bool,
+        scopes: &[String],
+    ) -> Result<String> {
+        let mut claims = JwtClaims::new_at(
+            identity_id,
+            tenant_id,
+            identity_type,
+            self.access_token_expiration,
+            self.clock.now(),
+        );
+        claims.mfa = mfa;
+        if let Some(max_bytes) = self.scope_claim_max_bytes {
+            claims.scope = scope_claim(identity_id, scopes, max_bytes);
+        }
+
+        self.encode_access_token(&claims)
+    }
+
+    /// Generate an access token (JWT) for the given downstream audiences
+    ///
+    /// Each audience must be the default audience or one of
+    /// `auth.jwt_extra_audiences`. A token without the default audience is not
+    /// accepted by this service's own API.
+    pub fn generate_access_token_for_audiences(
+        &self,
+        identity_id: Uuid,
+        tenant_id: Uuid,
+        identity_type: &str,
+        audiences: &[String],
+    ) -> Result<String> {
+        if audiences.is_empty() {
+            return Err(AppError::ValidationError(
+                "At least one audience is required".to_string(),
+            ));
+        }
+        if let Some(unknown) = audiences
+            .iter()
+            .find(|aud| *aud != DEFAULT_AUDIENCE && !self.extra_audiences.contains(aud))
+        {
+            return Err(AppError::ValidationError(format!(
+                "Unknown token audience: {}",
+                unknown
+            )));
+        }
+
+        let mut claims = JwtClaims::new_at(
             identity_id,
             tenant_id,
             identity_type,
             self.access_token_expiration,
+            self.clock.now(),
         );
+        claims.aud = audiences.to_vec();
 
+        self.encode_access_token(&claims)
+    }
+
+    /// Generate an access token (JWT) that only becomes valid at `not_before`
+    ///
+    /// The token's lifetime is counted from `not_before`, or from now if that
+    /// has already passed.
+    pub fn generate_access_token_not_before(
+        &self,
+        identity_id: Uuid,
+        tenant_id: Uuid,
+        identity_type: &str,
+        not_before: DateTime<Utc>,
+    ) -> Result<String> {
+        let now = self.clock.now();
+        let mut claims = JwtClaims::new_at(
+            identity_id,
+            tenant_id,
+            identity_type,
+            self.access_token_expiration,
+            now,
+        );
+        let starts_at = not_before.max(now);
+        claims.exp = (starts_at + Duration::seconds(self.access_token_expiration)).timestamp();
+        claims.nbf = Some(not_before.timestamp());
+
+        self.encode_access_token(&claims)
+    }
+
+    fn encode_access_token(&self, claims: &JwtClaims) -> Result<String> {
         let header = Header::new(Algorithm::HS256);
 
-        encode(&header, &claims, &self.encoding_key)
+        encode(&header, claims, &self.read_keys()?.encoding_key)
             .map_err(|e| AppError::TokenGeneration(format!("Failed to encode JWT: {}", e)))
     }
 
@@ -220,34 +561,50 @@ impl JwtManager {
         tenant_id: Uuid,
         family_id: Option<String>,
     ) -> Result<String> {
-        let claims = RefreshTokenClaims::new(
+        let claims = RefreshTokenClaims::new_at(
             identity_id,
             tenant_id,
             self.refresh_token_expiration,
             family_id,
+            self.clock.now(),
         );
 
         let header = Header::new(Algorithm::HS256);
 
-        encode(&header, &claims, &self.encoding_key)
+        encode(&header, &claims, &self.read_keys()?.encoding_key)
             .map_err(|e| AppError::TokenGeneration(format!("Failed to encode refresh token: {}", e)))
     }
 
     /// Validate and decode access token
     pub fn validate_access_token(&self, token: &str) -> Result<JwtClaims> {
+        self.validate_access_token_for(token, DEFAULT_AUDIENCE)
+    }
+
+    /// Validate and decode an access token for the service known as `audience`
+    ///
+    /// The token's `aud` must contain `audience`; other audiences it lists are
+    /// ignored.
+    pub fn validate_access_token_for(&self, token: &str, audience: &str) -> Result<JwtClaims> {
         let mut validation = Validation::new(Algorithm::HS256);
         validation.set_issuer(&["agent-iam"]);
-        validation.set_audience(&["agent-iam-api"]);
+        validation.set_audience(&[audience]);
+        // Expiry is checked below against the manager's clock; the library's
+        // nbf check (on system time, with leeway) is kept as a backstop
+        validation.validate_exp = false;
+        validation.validate_nbf = true;
 
-        let token_data = decode::<JwtClaims>(token, &self.decoding_key, &validation)
+        let claims: JwtClaims = self
+            .decode_with_fallback(token, &validation)
             .map_err(|e| AppError::TokenValidation(format!("Failed to decode JWT: {}", e)))?;
 
-        let claims = token_data.claims;
-
         // Additional validation
-        if claims.is_expired() {
+        let now = self.clock.now();
+        if claims.is_expired_at(now) {
             return Err(AppError::TokenExpired);
         }
+        if claims.is_before_nbf_at(now) {
+            return Err(AppError::TokenValidation("Token is not valid yet".to_string()));
+        }
 
         Ok(claims)
     }
@@ -258,14 +615,14 @@ impl JwtManager {
         validation.set_issuer(&["agent-iam"]);
         // Refresh tokens don't have audience requirement
         validation.set_required_spec_claims(&["exp", "iat", "iss", "jti", "sub"]);
+        validation.validate_exp = false;
 
-        let token_data = decode::<RefreshTokenClaims>(token, &self.decoding_key, &validation)
+        let claims: RefreshTokenClaims = self
+            .decode_with_fallback(token, &validation)
             .map_err(|e| AppError::TokenValidation(format!("Failed to decode refresh token: {}", e)))?;
 
-        let claims = token_data.claims;
-
         // Additional validation
-        if claims.is_expired() {
+        if claims.is_expired_at(self.clock.now()) {
             return Err(AppError::TokenExpired);
         }
 
@@ -280,7 +637,7 @@ impl JwtManager {
         validation.insecure_disable_signature_validation();
         validation.validate_exp = false;
 
-        let token_data = decode::<serde_json::Value>(token, &self.decoding_key, &validation)
+        let token_data = decode::<serde_json::Value>(token, &self.read_keys()?.decoding_key, &validation)
             .map_err(|e| AppError::TokenValidation(format!("Failed to extract token ID: {}", e)))?;
 
         token_data.claims
@@ -291,6 +648,41 @@ impl JwtManager {
     }
 }
 
+/// Join scopes into a `scope` claim, or `None` if there are none or they
+/// exceed `max_bytes`
+fn scope_claim(identity_id: Uuid, scopes: &[String], max_bytes: usize) -> Option<String> {
+    let scopes: BTreeSet<&str> = scopes
+        .iter()
+        .map(String::as_str)
+        .filter(|s| !s.is_empty() && !s.contains(char::is_whitespace))
+        .collect();
+    if scopes.is_empty() {
+        return None;
+    }
+
+    let claim = scopes.into_iter().collect::<Vec<_>>().join(" ");
+    if claim.len() > max_bytes {
+        tracing::warn!(
+            identity_id = %identity_id,
+            scope_bytes = claim.len(),
+            max_bytes,
+            "Scope claim too large; issuing access token without it"
+        );
+        return None;
+    }
+    Some(claim)
+}
+
+/// Reject secrets too short to be safe for HS256
+fn validate_secret(secret: &str) -> Result<()> {
+    if secret.len() < 32 {
+        return Err(AppError::Configuration(
+            "JWT secret must be at least 32 characters long".to_string()
+        ));
+    }
+    Ok(())
+}
+
 // ============================================================================
 // Token Pair
 // ============================================================================
@@ -319,6 +711,7 @@ impl TokenPair {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::clock::MockClock;
 
     fn create_test_config() -> Config {
         // Set test JWT secret in environment
@@ -359,4 +752,247 @@ mod tests {
         assert_eq!(claims.tenant_id_uuid().unwrap(), tenant_id);
         assert_eq!(claims.identity_type, "user");
     }
+
+    #[test]
+    fn test_mfa_claim_round_trip() {
+        let config = create_test_config();
+        let manager = JwtManager::new(&config).unwrap();
+
+        let token = manager
+            .generate_access_token_with_mfa(Uuid::new_v4(), Uuid::new_v4(), "user", true)
+            .unwrap();
+        assert!(manager.validate_access_token(&token).unwrap().mfa);
+
+        let token = manager
+            .generate_access_token(Uuid::new_v4(), Uuid::new_v4(), "user")
+            .unwrap();
+        assert!(!manager.validate_access_token(&token).unwrap().mfa);
+    }
+
+    #[test]
+    fn test_token_valid_during_rotation_window() {
+        let config = create_test_config();
+        let manager = JwtManager::new(&config).unwrap();
+
+        let identity_id = Uuid::new_v4();
+        let tenant_id = Uuid::new_v4();
+        let old_token = manager.generate_access_token(identity_id, tenant_id, "user").unwrap();
+
+        manager
+            .rotate_secret("rotated-secret-key-for-jwt-signing-minimum-length")
+            .unwrap();
+        assert!(manager.has_secondary().unwrap());
+
+        // Token signed with the previous secret still validates
+        let claims = manager.validate_access_token(&old_token).unwrap();
+        assert_eq!(claims.identity_id().unwrap(), identity_id);
+
+        // New tokens are signed with the new secret
+        let new_token = manager.generate_access_token(identity_id, tenant_id, "user").unwrap();
+        assert!(manager.validate_access_token(&new_token).is_ok());
+    }
+
+    #[test]
+    fn test_token_invalid_after_secondary_cleared() {
+        let config = create_test_config();
+        let manager = JwtManager::new(&config).unwrap();
+
+        let old_token = manager
+            .generate_access_token(Uuid::new_v4(), Uuid::new_v4(), "user")
+            .unwrap();
+
+        manager
+            .rotate_secret("rotated-secret-key-for-jwt-signing-minimum-length")
+            .unwrap();
+        manager.clear_secondary().unwrap();
+
+        assert!(manager.validate_access_token(&old_token).is_err());
+    }
+
+    #[test]
+    fn test_access_token_expires_at_clock_boundary() {
+        let clock = Arc::new(MockClock::new(Utc::now()));
+        let manager = JwtManager::new(&create_test_config())
+            .unwrap()
+            .with_clock(clock.clone());
+        let token = manager
+            .generate_access_token(Uuid::new_v4(), Uuid::new_v4(), "user")
+            .unwrap();
+
+        // Valid up to the last second of its 15 minute lifetime
+        clock.advance(Duration::seconds(899));
+        assert!(manager.validate_access_token(&token).is_ok());
+
+        clock.advance(Duration::seconds(1));
+        assert!(matches!(
+            manager.validate_access_token(&token),
+            Err(AppError::TokenExpired)
+        ));
+    }
+
+    #[test]
+    fn test_token_rejected_before_nbf() {
+        let clock = Arc::new(MockClock::new(Utc::now()));
+        let manager = JwtManager::new(&create_test_config())
+            .unwrap()
+            .with_clock(clock.clone());
+        let not_before = clock.now() + Duration::seconds(30);
+        let token = manager
+            .generate_access_token_not_before(Uuid::new_v4(), Uuid::new_v4(), "agent", not_before)
+            .unwrap();
+
+        assert!(matches!(
+            manager.validate_access_token(&token),
+            Err(AppError::TokenValidation(_))
+        ));
+        clock.advance(Duration::seconds(29));
+        assert!(manager.validate_access_token(&token).is_err());
+
+        clock.advance(Duration::seconds(1));
+        let claims = manager.validate_access_token(&token).unwrap();
+        assert_eq!(claims.nbf, Some(not_before.timestamp()));
+        // The full lifetime starts at nbf
+        assert_eq!(claims.exp, not_before.timestamp() + 900);
+    }
+
+    #[test]
+    fn test_token_without_nbf_omits_claim() {
+        let manager = JwtManager::new(&create_test_config()).unwrap();
+        let token = manager
+            .generate_access_token(Uuid::new_v4(), Uuid::new_v4(), "user")
+            .unwrap();
+
+        assert_eq!(manager.validate_access_token(&token).unwrap().nbf, None);
+        let claims = JwtClaims::new(Uuid::new_v4(), Uuid::new_v4(), "user", 900);
+        assert!(serde_json::to_value(&claims).unwrap().get("nbf").is_none());
+    }
+
+    #[test]
+    fn test_scope_claim_sorted_and_deduplicated() {
+        let scopes = ["write", "read", "write", "", "bad scope"].map(String::from);
+        let claim = scope_claim(Uuid::new_v4(), &scopes, 1024);
+        assert_eq!(claim.as_deref(), Some("read write"));
+
+        // A claim that does not fit is left out rather than cut short
+        assert_eq!(scope_claim(Uuid::new_v4(), &scopes, 9), None);
+        assert_eq!(scope_claim(Uuid::new_v4(), &[], 1024), None);
+    }
+
+    #[test]
+    fn test_scope_claim_disabled_by_default() {
+        let manager = JwtManager::new(&create_test_config()).unwrap();
+        assert!(!manager.scope_claim_enabled());
+
+        let token = manager
+            .generate_scoped_access_token(
+                Uuid::new_v4(),
+                Uuid::new_v4(),
+                "user",
+                false,
+                &["read".to_string()],
+            )
+            .unwrap();
+        assert_eq!(manager.validate_access_token(&token).unwrap().scope, None);
+    }
+
+    #[test]
+    fn test_token_validates_only_for_its_audiences() {
+        let mut config = create_test_config();
+        config.auth.jwt_extra_audiences =
+            vec!["svc-billing".to_string(), "svc-reports".to_string()];
+        let manager = JwtManager::new(&config).unwrap();
+        let token = manager
+            .generate_access_token_for_audiences(
+                Uuid::new_v4(),
+                Uuid::new_v4(),
+                "service",
+                &["svc-billing".to_string()],
+            )
+            .unwrap();
+
+        let claims = manager.validate_access_token_for(&token, "svc-billing").unwrap();
+        assert_eq!(claims.aud, vec!["svc-billing"]);
+        assert!(manager.validate_access_token_for(&token, "svc-reports").is_err());
+        assert!(manager.validate_access_token(&token).is_err());
+    }
+
+    #[test]
+    fn test_token_with_several_audiences_validates_for_each() {
+        let mut config = create_test_config();
+        config.auth.jwt_extra_audiences = vec!["svc-billing".to_string()];
+        let manager = JwtManager::new(&config).unwrap();
+        let audiences = [DEFAULT_AUDIENCE.to_string(), "svc-billing".to_string()];
+        let token = manager
+            .generate_access_token_for_audiences(Uuid::new_v4(), Uuid::new_v4(), "user", &audiences)
+            .unwrap();
+
+        assert!(manager.validate_access_token(&token).is_ok());
+        assert!(manager.validate_access_token_for(&token, "svc-billing").is_ok());
+
+        // Only configured audiences can be requested
+        assert!(matches!(
+            manager.generate_access_token_for_audiences(
+                Uuid::new_v4(),
+                Uuid::new_v4(),
+                "user",
+                &["svc-unknown".to_string()],
+            ),
+            Err(AppError::ValidationError(_))
+        ));
+    }
+
+    #[test]
+    fn test_refresh_token_valid_for_its_lifetime_after_rotation() {
+        let clock = Arc::new(MockClock::new(Utc::now()));
+        let manager = JwtManager::new(&create_test_config())
+            .unwrap()
+            .with_clock(clock.clone());
+        let refresh_token = manager
+            .generate_refresh_token(Uuid::new_v4(), Uuid::new_v4(), None)
+            .unwrap();
+
+        manager
+            .rotate_secret("rotated-secret-key-for-jwt-signing-minimum-length")
+            .unwrap();
+
+        // Well past the access-token lifetime, the old refresh token still works
+        clock.advance(Duration::hours(1));
+        assert!(!manager.drop_expired_secondary().unwrap());
+        assert!(manager.validate_refresh_token(&refresh_token).is_ok());
+
+        clock.advance(Duration::days(30));
+        assert!(manager.drop_expired_secondary().unwrap());
+    }
+
+    #[test]
+    fn test_install_rotated_keeps_environment_secret_as_secondary() {
+        let config = create_test_config();
+        let manager = JwtManager::new(&config).unwrap();
+        let old_token = manager
+            .generate_access_token(Uuid::new_v4(), Uuid::new_v4(), "user")
+            .unwrap();
+
+        let current = "persisted-secret-key-for-jwt-signing-minimum-length";
+        manager.install_rotated(current, None, Utc::now(), false).unwrap();
+        assert!(manager.validate_access_token(&old_token).is_ok());
+
+        // Another instance signing with the persisted secret is accepted
+        let other = JwtManager::new(&config).unwrap();
+        other.install_rotated(current, None, Utc::now(), true).unwrap();
+        assert!(!other.has_secondary().unwrap());
+        let new_token = other
+            .generate_access_token(Uuid::new_v4(), Uuid::new_v4(), "user")
+            .unwrap();
+        assert!(manager.validate_access_token(&new_token).is_ok());
+        assert!(other.validate_access_token(&old_token).is_err());
+    }
+
+    #[test]
+    fn test_rotate_rejects_short_secret() {
+        let config = create_test_config();
+        let manager = JwtManager::new(&config).unwrap();
+
+        assert!(manager.rotate_secret("too-short").is_err());
+        assert!(!manager.has_secondary().unwrap());
+    }
 }
@@ -0,0 +1,104 @@
+// Persisted JWT secret rotation
+//
+// A rotation generates a fresh secret, stores it encrypted in
+// `jwt_signing_keys` and installs it. Every instance reloads the newest rows
+// at startup and then periodically, so all of them sign with the same secret
+// and keep accepting the previous one for one refresh-token lifetime.
+
+use crate::auth::jwt::JwtManager;
+use crate::crypto::encryption::SecretCipher;
+use crate::db::jwt_keys;
+use crate::errors::{AppError, Result};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use rand::{rngs::OsRng, RngCore};
+use sqlx::PgPool;
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// Random bytes in a generated secret (encoded to 64 characters)
+const SECRET_BYTES: usize = 48;
+
+/// Generate, persist and install a new signing secret
+pub async fn rotate(
+    pool: &PgPool,
+    jwt_manager: &JwtManager,
+    cipher: Option<&SecretCipher>,
+    rotated_by: Uuid,
+) -> Result<()> {
+    let cipher = cipher.ok_or_else(|| {
+        AppError::Configuration(
+            "JWT secret rotation requires an encryption key (crypto.mfa_encryption_key)"
+                .to_string(),
+        )
+    })?;
+
+    let mut bytes = [0u8; SECRET_BYTES];
+    OsRng.fill_bytes(&mut bytes);
+    let secret = URL_SAFE_NO_PAD.encode(bytes);
+
+    jwt_keys::insert(pool, &cipher.encrypt(secret.as_bytes())?, rotated_by).await?;
+    sync(pool, jwt_manager, Some(cipher)).await
+}
+
+/// Stop accepting the previous secret on every instance
+pub async fn clear_previous(pool: &PgPool, jwt_manager: &JwtManager) -> Result<()> {
+    jwt_keys::clear_previous(pool).await?;
+    jwt_manager.clear_secondary()
+}
+
+/// Install the persisted secrets, if any rotation has been recorded
+pub async fn sync(
+    pool: &PgPool,
+    jwt_manager: &JwtManager,
+    cipher: Option<&SecretCipher>,
+) -> Result<()> {
+    let keys = jwt_keys::latest(pool).await?;
+    let Some(current) = keys.first() else {
+        return Ok(());
+    };
+
+    let cipher = cipher.ok_or_else(|| {
+        AppError::Configuration(
+            "JWT signing secrets are persisted but no encryption key is configured".to_string(),
+        )
+    })?;
+
+    let current_secret = decrypt_secret(cipher, &current.secret_encrypted)?;
+    let previous_secret = keys
+        .get(1)
+        .map(|previous| decrypt_secret(cipher, &previous.secret_encrypted))
+        .transpose()?;
+
+    jwt_manager.install_rotated(
+        &current_secret,
+        previous_secret.as_deref(),
+        current.created_at,
+        current.previous_cleared_at.is_some(),
+    )
+}
+
+/// Spawn a background task that reloads the persisted secrets every `interval`
+pub fn spawn_key_sync(
+    pool: PgPool,
+    jwt_manager: Arc<JwtManager>,
+    cipher: Option<Arc<SecretCipher>>,
+    interval: std::time::Duration,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(interval);
+        loop {
+            interval.tick().await;
+            if let Err(e) = sync(&pool, &jwt_manager, cipher.as_deref()).await {
+                tracing::error!("Failed to reload JWT signing secrets: {}", e);
+            }
+            if let Err(e) = jwt_manager.drop_expired_secondary() {
+                tracing::error!("Failed to drop expired JWT secondary key: {}", e);
+            }
+        }
+    })
+}
+
+fn decrypt_secret(cipher: &SecretCipher, encrypted: &str) -> Result<String> {
+    String::from_utf8(cipher.decrypt(encrypted)?)
+        .map_err(|_| AppError::Cryptographic("Stored JWT secret is not valid UTF-8".to_string()))
+}
@@ -1,5 +1,11 @@
 // Authentication module
 pub mod jwt;
+pub mod jwt_rotation;
+pub mod binding;
 pub mod biscuit;
+pub mod biscuit_cache;
 pub mod password;
 pub mod middleware;
+pub mod introspection;
+pub mod totp;
+pub mod webauthn;
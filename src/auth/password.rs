@@ -1,9 +1,23 @@
 // Password hashing with Argon2id
+//
+// New hashes are always Argon2id. Identities imported from other systems may
+// carry bcrypt or Argon2i/Argon2d hashes; those are recognized by their
+// prefix and still verify, and are reported as needing a rehash so the login
+// path can replace them with Argon2id.
 use argon2::{
     password_hash::{rand_core::OsRng, PasswordHash, PasswordHasher, PasswordVerifier, SaltString},
     Argon2, Params, Version,
 };
-use crate::errors::{AppError, Result};
+use crate::config::AuthConfig;
+use crate::errors::{AppError, FieldErrors, Result};
+use once_cell::sync::Lazy;
+
+/// Hash verified when a login names no usable account
+///
+/// Created with the same parameters as real hashes, so checking it costs as
+/// much as checking a real password.
+static DUMMY_HASH: Lazy<Option<String>> =
+    Lazy::new(|| hash_password("dummy-password-for-timing-equalization").ok());
 
 /// Hash a password using Argon2id with OWASP recommended parameters
 ///
@@ -48,8 +62,65 @@ pub fn hash_password(password: &str) -> Result<String> {
     Ok(password_hash)
 }
 
+/// Algorithm of a stored password hash, read from its prefix
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PasswordHashAlgorithm {
+    Argon2id,
+    /// Argon2i or Argon2d (PHC `$argon2i$` / `$argon2d$`)
+    LegacyArgon2,
+    /// bcrypt (`$2a$`, `$2b$`, `$2x$` or `$2y$`)
+    Bcrypt,
+    Unknown,
+}
+
+impl PasswordHashAlgorithm {
+    /// Detect the algorithm of a hash from its PHC or modular crypt prefix
+    pub fn detect(hash: &str) -> Self {
+        if hash.starts_with("$argon2id$") {
+            Self::Argon2id
+        } else if hash.starts_with("$argon2i$") || hash.starts_with("$argon2d$") {
+            Self::LegacyArgon2
+        } else if ["$2a$", "$2b$", "$2x$", "$2y$"]
+            .iter()
+            .any(|prefix| hash.starts_with(prefix))
+        {
+            Self::Bcrypt
+        } else {
+            Self::Unknown
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Argon2id => "argon2id",
+            Self::LegacyArgon2 => "argon2",
+            Self::Bcrypt => "bcrypt",
+            Self::Unknown => "unknown",
+        }
+    }
+}
+
+/// Whether a hash uses a deprecated algorithm and should be replaced by an
+/// Argon2id hash once the password is next verified
+pub fn needs_rehash(hash: &str) -> bool {
+    PasswordHashAlgorithm::detect(hash) != PasswordHashAlgorithm::Argon2id
+}
+
 /// Verify a password against a hash using constant-time comparison
+///
+/// Argon2 hashes of any variant and bcrypt hashes are supported; other
+/// formats are an error rather than a mismatch.
 pub fn verify_password(password: &str, hash: &str) -> Result<bool> {
+    match PasswordHashAlgorithm::detect(hash) {
+        PasswordHashAlgorithm::Argon2id | PasswordHashAlgorithm::LegacyArgon2 => {}
+        PasswordHashAlgorithm::Bcrypt => return verify_bcrypt(password, hash),
+        PasswordHashAlgorithm::Unknown => {
+            return Err(AppError::Cryptographic(
+                "Unsupported password hash algorithm".to_string(),
+            ))
+        }
+    }
+
     let parsed_hash = PasswordHash::new(hash)
         .map_err(|e| AppError::Cryptographic(format!("Failed to parse password hash: {}", e)))?;
 
@@ -72,6 +143,112 @@ pub fn verify_password(password: &str, hash: &str) -> Result<bool> {
     }
 }
 
+/// Verify a password against a bcrypt hash
+fn verify_bcrypt(password: &str, hash: &str) -> Result<bool> {
+    let verified = bcrypt::verify(password, hash).map_err(|e| {
+        tracing::error!("Password verification error: {}", e);
+        AppError::Cryptographic(format!("Password verification error: {}", e))
+    })?;
+    tracing::debug!(verified, "Verified legacy bcrypt password hash");
+    Ok(verified)
+}
+
+/// Verify a password against an account's hash, or a dummy hash if there is none
+///
+/// Without an account hash the dummy is still verified and `false` returned,
+/// so unknown accounts take as long to reject as wrong passwords.
+pub fn verify_password_or_dummy(password: &str, hash: Option<&str>) -> Result<bool> {
+    match hash {
+        Some(hash) => verify_password(password, hash),
+        None => {
+            if let Some(dummy) = DUMMY_HASH.as_deref() {
+                verify_password(password, dummy)?;
+            }
+            Ok(false)
+        }
+    }
+}
+
+/// Reject a new password that matches any of the given previous hashes
+///
+/// Each hash is checked with `verify_password`, so the cost grows linearly
+/// with the length of the history window.
+pub fn ensure_not_reused<'a, I>(password: &str, previous_hashes: I) -> Result<()>
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    for hash in previous_hashes {
+        if verify_password(password, hash)? {
+            return Err(AppError::ValidationError(
+                "Password was used recently and cannot be reused".to_string(),
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// Rules a new password must satisfy (the `auth.password_*` settings)
+#[derive(Debug, Clone)]
+pub struct PasswordPolicy {
+    pub min_length: usize,
+    pub require_uppercase: bool,
+    pub require_lowercase: bool,
+    pub require_digit: bool,
+    pub require_special: bool,
+    /// Number of previous passwords (besides the current one) that cannot be reused
+    pub history_count: usize,
+}
+
+impl PasswordPolicy {
+    pub fn from_config(config: &AuthConfig) -> Self {
+        Self {
+            min_length: config.password_min_length,
+            require_uppercase: config.password_require_uppercase,
+            require_lowercase: config.password_require_lowercase,
+            require_digit: config.password_require_digit,
+            require_special: config.password_require_special,
+            history_count: config.password_history_count,
+        }
+    }
+
+    /// Check a new password's length and character classes
+    ///
+    /// Every unmet requirement is listed in one error on the `password` field.
+    pub fn validate(&self, password: &str) -> Result<()> {
+        let mut missing = Vec::new();
+
+        if password.chars().count() < self.min_length {
+            missing.push(format!("at least {} characters", self.min_length));
+        }
+        if self.require_uppercase && !password.chars().any(char::is_uppercase) {
+            missing.push("an uppercase letter".to_string());
+        }
+        if self.require_lowercase && !password.chars().any(char::is_lowercase) {
+            missing.push("a lowercase letter".to_string());
+        }
+        if self.require_digit && !password.chars().any(|c| c.is_ascii_digit()) {
+            missing.push("a digit".to_string());
+        }
+        if self.require_special
+            && !password
+                .chars()
+                .any(|c| !c.is_alphanumeric() && !c.is_whitespace())
+        {
+            missing.push("a special character".to_string());
+        }
+
+        let mut errors = FieldErrors::new();
+        if !missing.is_empty() {
+            errors.add(
+                "password",
+                format!("Password must contain {}", missing.join(", ")),
+            );
+        }
+        errors.into_result()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -105,6 +282,75 @@ mod tests {
         assert!(!verify_password("wrong_password", &hash).unwrap());
     }
 
+    #[test]
+    fn test_missing_account_still_verifies() {
+        assert!(DUMMY_HASH.is_some());
+        assert!(!verify_password_or_dummy("dummy-password-for-timing-equalization", None).unwrap());
+
+        let hash = hash_password("test_password_123").unwrap();
+        assert!(verify_password_or_dummy("test_password_123", Some(&hash)).unwrap());
+
+        // Tolerant timing check: the missing-account path does a full Argon2 verify
+        let start = std::time::Instant::now();
+        verify_password_or_dummy("wrong_password", Some(&hash)).unwrap();
+        let existing = start.elapsed();
+
+        let start = std::time::Instant::now();
+        verify_password_or_dummy("wrong_password", None).unwrap();
+        let missing = start.elapsed();
+
+        assert!(missing * 4 >= existing, "{:?} vs {:?}", missing, existing);
+    }
+
+    #[test]
+    fn test_bcrypt_hash_verifies_and_needs_rehash() {
+        let hash = bcrypt::hash("legacy_password_1", 4).unwrap();
+        assert_eq!(
+            PasswordHashAlgorithm::detect(&hash),
+            PasswordHashAlgorithm::Bcrypt
+        );
+
+        assert!(verify_password("legacy_password_1", &hash).unwrap());
+        assert!(!verify_password("wrong_password", &hash).unwrap());
+        assert!(needs_rehash(&hash));
+    }
+
+    #[test]
+    fn test_argon2i_hash_verifies_and_needs_rehash() {
+        let salt = SaltString::generate(&mut OsRng);
+        let hash = Argon2::new(
+            argon2::Algorithm::Argon2i,
+            Version::V0x13,
+            Params::default(),
+        )
+        .hash_password(b"legacy_password_1", &salt)
+        .unwrap()
+        .to_string();
+        assert_eq!(
+            PasswordHashAlgorithm::detect(&hash),
+            PasswordHashAlgorithm::LegacyArgon2
+        );
+
+        assert!(verify_password("legacy_password_1", &hash).unwrap());
+        assert!(needs_rehash(&hash));
+    }
+
+    #[test]
+    fn test_argon2id_hash_does_not_need_rehash() {
+        let hash = hash_password("test_password_123").unwrap();
+        assert!(!needs_rehash(&hash));
+    }
+
+    #[test]
+    fn test_unknown_hash_format_is_an_error() {
+        let result = verify_password("test_password_123", "5f4dcc3b5aa765d61d8327deb882cf99");
+        assert!(matches!(result, Err(AppError::Cryptographic(_))));
+        assert_eq!(
+            PasswordHashAlgorithm::detect("5f4dcc3b5aa765d61d8327deb882cf99"),
+            PasswordHashAlgorithm::Unknown
+        );
+    }
+
     #[test]
     fn test_empty_password() {
         let result = hash_password("");
@@ -118,4 +364,57 @@ mod tests {
         assert!(result.is_err());
         assert!(matches!(result.unwrap_err(), AppError::ValidationError(_)));
     }
+
+    #[test]
+    fn test_reused_password_rejected() {
+        let history = vec![
+            hash_password("first_password_1").unwrap(),
+            hash_password("second_password_2").unwrap(),
+        ];
+
+        let result = ensure_not_reused("first_password_1", history.iter().map(String::as_str));
+        assert!(matches!(result.unwrap_err(), AppError::ValidationError(_)));
+    }
+
+    #[test]
+    fn test_novel_password_accepted() {
+        let history = vec![
+            hash_password("first_password_1").unwrap(),
+            hash_password("second_password_2").unwrap(),
+        ];
+
+        assert!(ensure_not_reused("third_password_3", history.iter().map(String::as_str)).is_ok());
+    }
+
+    fn strict_policy() -> PasswordPolicy {
+        PasswordPolicy {
+            min_length: 12,
+            require_uppercase: true,
+            require_lowercase: true,
+            require_digit: true,
+            require_special: true,
+            history_count: 5,
+        }
+    }
+
+    #[test]
+    fn test_policy_accepts_complex_password() {
+        assert!(strict_policy().validate("Correct-Horse-42").is_ok());
+    }
+
+    #[test]
+    fn test_policy_lists_missing_requirements() {
+        let err = strict_policy().validate("short").unwrap_err();
+        let AppError::ValidationErrors(errors) = err else {
+            panic!("expected field errors");
+        };
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].field, "password");
+        let message = &errors[0].message;
+        assert!(message.contains("at least 12 characters"));
+        assert!(message.contains("an uppercase letter"));
+        assert!(message.contains("a digit"));
+        assert!(message.contains("a special character"));
+        assert!(!message.contains("a lowercase letter"));
+    }
 }
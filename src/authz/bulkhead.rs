@@ -0,0 +1,129 @@
+// Per-tenant bulkhead for authorization evaluations
+//
+// Each tenant gets its own semaphore, so one tenant flooding the service with
+// checks uses up only its own share of evaluations and database reads.
+// Checks beyond a tenant's limit are rejected right away rather than queued;
+// checks without a tenant share one semaphore.
+
+use crate::config::BulkheadConfig;
+use crate::errors::{AppError, Result, RetryAfter};
+use crate::observability::MetricsRecorder;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use uuid::Uuid;
+
+/// How long shed callers are told to wait before retrying
+const RETRY_AFTER_SECONDS: u64 = 1;
+
+/// Concurrency limits on authorization evaluations, per tenant
+pub struct TenantBulkhead {
+    default_limit: usize,
+    overrides: HashMap<Uuid, usize>,
+    semaphores: Mutex<HashMap<Option<Uuid>, Arc<Semaphore>>>,
+}
+
+impl TenantBulkhead {
+    pub fn new(default_limit: usize, overrides: HashMap<Uuid, usize>) -> Self {
+        Self {
+            default_limit: default_limit.max(1),
+            overrides,
+            semaphores: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn from_config(config: &BulkheadConfig) -> Self {
+        Self::new(
+            config.max_concurrent_per_tenant,
+            config.tenant_overrides.clone(),
+        )
+    }
+
+    /// Limit applied to a tenant
+    pub fn limit(&self, tenant_id: Option<Uuid>) -> usize {
+        tenant_id
+            .and_then(|id| self.overrides.get(&id).copied())
+            .unwrap_or(self.default_limit)
+            .max(1)
+    }
+
+    /// Take one of the tenant's evaluation slots, held until the permit drops
+    ///
+    /// Fails with a rate limit error when all of the tenant's slots are in use.
+    pub fn try_acquire(&self, tenant_id: Option<Uuid>) -> Result<OwnedSemaphorePermit> {
+        let semaphore = {
+            let mut semaphores = self
+                .semaphores
+                .lock()
+                .map_err(|_| AppError::Internal("Authz bulkhead lock poisoned".to_string()))?;
+            semaphores
+                .entry(tenant_id)
+                .or_insert_with(|| Arc::new(Semaphore::new(self.limit(tenant_id))))
+                .clone()
+        };
+
+        semaphore.try_acquire_owned().map_err(|_| {
+            MetricsRecorder::record_authz_error("bulkhead_full", tenant_id);
+            tracing::warn!(
+                tenant_id = ?tenant_id,
+                limit = self.limit(tenant_id),
+                "Authorization check shed: tenant concurrency limit reached"
+            );
+            AppError::RateLimitExceeded(Some(RetryAfter::Seconds(RETRY_AFTER_SECONDS)))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_requests_beyond_limit_shed_for_one_tenant_only() {
+        let bulkhead = Arc::new(TenantBulkhead::new(2, HashMap::new()));
+        let noisy = Some(Uuid::new_v4());
+        let quiet = Some(Uuid::new_v4());
+
+        // Two evaluations in flight for the noisy tenant, each holding a slot
+        let (release, _) = tokio::sync::broadcast::channel::<()>(1);
+        let mut in_flight = Vec::new();
+        for _ in 0..2 {
+            let permit = bulkhead.try_acquire(noisy).unwrap();
+            let mut done = release.subscribe();
+            in_flight.push(tokio::spawn(async move {
+                let _ = done.recv().await;
+                drop(permit);
+            }));
+        }
+
+        assert!(matches!(
+            bulkhead.try_acquire(noisy),
+            Err(AppError::RateLimitExceeded(Some(RetryAfter::Seconds(1))))
+        ));
+        assert!(bulkhead.try_acquire(quiet).is_ok());
+        assert!(bulkhead.try_acquire(None).is_ok());
+
+        // Once the in-flight evaluations finish the tenant is admitted again
+        release.send(()).unwrap();
+        for task in in_flight {
+            task.await.unwrap();
+        }
+        assert!(bulkhead.try_acquire(noisy).is_ok());
+    }
+
+    #[test]
+    fn test_tenant_override_replaces_default() {
+        let tenant_id = Uuid::new_v4();
+        let bulkhead = TenantBulkhead::new(1, HashMap::from([(tenant_id, 3)]));
+
+        let permits: Vec<_> = (0..3)
+            .map(|_| bulkhead.try_acquire(Some(tenant_id)).unwrap())
+            .collect();
+        assert!(bulkhead.try_acquire(Some(tenant_id)).is_err());
+        assert_eq!(permits.len(), 3);
+
+        let _other = bulkhead.try_acquire(Some(Uuid::new_v4())).unwrap();
+        assert!(bulkhead.try_acquire(None).is_ok());
+        assert_eq!(bulkhead.limit(Some(Uuid::new_v4())), 1);
+    }
+}
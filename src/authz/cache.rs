@@ -1,2 +1,563 @@
-// Policy decision caching
-// To be implemented in Task #10
+// Per-tenant policy caching
+//
+// Each instance keeps a Cedar engine per tenant, holding the tenant's active
+// policies together with the global ones. A policy write publishes a
+// `PolicyChange` on the policy bus (Redis pub/sub); every instance, including
+// the writer, drops the tenant's engine when the change arrives and reloads it
+// on next use. Changes carry a per-tenant version: an engine loaded before a
+// change is never stored after it, and repeated deliveries are ignored.
+// An engine is also dropped once one of its tenant's policies enters or leaves
+// its validity window.
+
+use crate::authz::engine::CedarEngine;
+use crate::errors::{AppError, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use futures::stream::{BoxStream, StreamExt};
+use once_cell::sync::{Lazy, OnceCell};
+use redis::aio::ConnectionManager;
+use redis::AsyncCommands;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tracing::{debug, info, warn};
+use uuid::Uuid;
+
+/// Redis channel policy changes are published on
+pub const POLICY_CHANGES_CHANNEL: &str = "agent_iam:policy_changes";
+
+/// Prefix of the Redis counters holding each tenant's policy version
+const POLICY_VERSION_KEY_PREFIX: &str = "agent_iam:policy_version:";
+
+/// Delay before resubscribing after the subscription is lost
+const RESUBSCRIBE_DELAY: Duration = Duration::from_secs(1);
+
+/// Notice that a tenant's policies changed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PolicyChange {
+    pub tenant_id: Uuid,
+    /// Policy version of the tenant after the change; increases with every change
+    pub version: i64,
+}
+
+/// Channel carrying policy changes between instances
+#[async_trait]
+pub trait PolicyBus: Send + Sync {
+    /// Assign the tenant's next policy version and announce it to every instance
+    async fn publish(&self, tenant_id: Uuid) -> Result<PolicyChange>;
+
+    /// Changes announced from now on, by any instance
+    async fn subscribe(&self) -> Result<BoxStream<'static, PolicyChange>>;
+
+    /// The tenant's current policy version; 0 if its policies never changed
+    async fn current_version(&self, tenant_id: Uuid) -> Result<i64>;
+}
+
+/// Policy bus over Redis: versions are `INCR` counters, changes are `PUBLISH`ed
+pub struct RedisPolicyBus {
+    // Subscriptions need a dedicated connection, not the shared manager
+    client: redis::Client,
+    manager: ConnectionManager,
+}
+
+impl RedisPolicyBus {
+    pub fn new(url: &str, manager: ConnectionManager) -> Result<Self> {
+        Ok(Self {
+            client: redis::Client::open(url)?,
+            manager,
+        })
+    }
+}
+
+#[async_trait]
+impl PolicyBus for RedisPolicyBus {
+    async fn publish(&self, tenant_id: Uuid) -> Result<PolicyChange> {
+        let mut conn = self.manager.clone();
+        let version: i64 = conn
+            .incr(format!("{}{}", POLICY_VERSION_KEY_PREFIX, tenant_id), 1)
+            .await?;
+
+        let change = PolicyChange { tenant_id, version };
+        let payload = serde_json::to_string(&change)
+            .map_err(|e| AppError::Internal(format!("Failed to encode policy change: {}", e)))?;
+        let _: i64 = conn.publish(POLICY_CHANGES_CHANNEL, payload).await?;
+
+        Ok(change)
+    }
+
+    async fn subscribe(&self) -> Result<BoxStream<'static, PolicyChange>> {
+        #[allow(deprecated)]
+        let mut pubsub = self.client.get_async_connection().await?.into_pubsub();
+        pubsub.subscribe(POLICY_CHANGES_CHANNEL).await?;
+
+        let changes = pubsub.into_on_message().filter_map(|msg| async move {
+            let payload: String = msg.get_payload().ok()?;
+            match serde_json::from_str(&payload) {
+                Ok(change) => Some(change),
+                Err(e) => {
+                    warn!("Ignoring malformed policy change: {}", e);
+                    None
+                }
+            }
+        });
+        Ok(changes.boxed())
+    }
+
+    async fn current_version(&self, tenant_id: Uuid) -> Result<i64> {
+        let mut conn = self.manager.clone();
+        let version: Option<i64> = conn
+            .get(format!("{}{}", POLICY_VERSION_KEY_PREFIX, tenant_id))
+            .await?;
+        Ok(version.unwrap_or(0))
+    }
+}
+
+#[derive(Default)]
+struct TenantEntry {
+    /// Highest policy version seen for the tenant
+    version: i64,
+    engine: Option<Arc<CedarEngine>>,
+    /// When a policy enters or leaves its window, making the engine stale
+    refresh_at: Option<DateTime<Utc>>,
+}
+
+impl TenantEntry {
+    /// The engine, unless a policy window boundary has passed since it was loaded
+    fn current_engine(&self) -> Option<Arc<CedarEngine>> {
+        match self.refresh_at {
+            Some(refresh_at) if refresh_at <= Utc::now() => None,
+            _ => self.engine.clone(),
+        }
+    }
+}
+
+/// Cedar engines with each tenant's policies, kept until the policies change
+#[derive(Default)]
+pub struct PolicyCache {
+    tenants: RwLock<HashMap<Uuid, TenantEntry>>,
+}
+
+impl PolicyCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The tenant's cached engine, if it has one
+    pub fn get(&self, tenant_id: Uuid) -> Result<Option<Arc<CedarEngine>>> {
+        Ok(self
+            .read()?
+            .get(&tenant_id)
+            .and_then(|entry| entry.current_engine()))
+    }
+
+    /// The tenant's cached engine with the policy version it was loaded at
+    pub fn get_versioned(&self, tenant_id: Uuid) -> Result<Option<(Arc<CedarEngine>, i64)>> {
+        Ok(self
+            .read()?
+            .get(&tenant_id)
+            .and_then(|entry| entry.current_engine().map(|engine| (engine, entry.version))))
+    }
+
+    /// Highest policy version seen for the tenant
+    ///
+    /// Read this before loading policies and pass it to `store`.
+    pub fn version(&self, tenant_id: Uuid) -> Result<i64> {
+        Ok(self
+            .read()?
+            .get(&tenant_id)
+            .map_or(0, |entry| entry.version))
+    }
+
+    /// Cache an engine whose policies were loaded at `loaded_at_version`
+    ///
+    /// Returns false, leaving the cache as it was, if the tenant's policies
+    /// changed while they were being loaded.
+    pub fn store(
+        &self,
+        tenant_id: Uuid,
+        loaded_at_version: i64,
+        engine: Arc<CedarEngine>,
+    ) -> Result<bool> {
+        self.store_until(tenant_id, loaded_at_version, engine, None)
+    }
+
+    /// Cache an engine like `store`, until `refresh_at` if given
+    ///
+    /// Pass the next time one of the tenant's policies enters or leaves its
+    /// validity window; the engine is reloaded on first use after it.
+    pub fn store_until(
+        &self,
+        tenant_id: Uuid,
+        loaded_at_version: i64,
+        engine: Arc<CedarEngine>,
+        refresh_at: Option<DateTime<Utc>>,
+    ) -> Result<bool> {
+        let mut tenants = self.write()?;
+        let entry = tenants.entry(tenant_id).or_default();
+        if entry.version != loaded_at_version {
+            return Ok(false);
+        }
+        entry.engine = Some(engine);
+        entry.refresh_at = refresh_at;
+        Ok(true)
+    }
+
+    /// Drop the tenant's engine if the change is newer than any seen so far
+    pub fn apply(&self, change: PolicyChange) -> Result<bool> {
+        let mut tenants = self.write()?;
+        let entry = tenants.entry(change.tenant_id).or_default();
+        if change.version <= entry.version {
+            return Ok(false);
+        }
+        entry.version = change.version;
+        entry.engine = None;
+        debug!(
+            tenant_id = %change.tenant_id,
+            version = change.version,
+            "Invalidated cached policies"
+        );
+        Ok(true)
+    }
+
+    /// Drop every cached engine, e.g. after changes may have been missed
+    pub fn clear(&self) -> Result<()> {
+        for entry in self.write()?.values_mut() {
+            entry.engine = None;
+        }
+        Ok(())
+    }
+
+    /// Announce that the tenant's policies changed, invalidating them here at once
+    ///
+    /// Without a bus only this instance's cache is invalidated. If publishing
+    /// fails, the tenant's engine is still dropped here.
+    pub async fn policy_changed(
+        &self,
+        bus: Option<&dyn PolicyBus>,
+        tenant_id: Uuid,
+    ) -> Result<PolicyChange> {
+        let change = match bus {
+            Some(bus) => match bus.publish(tenant_id).await {
+                Ok(change) => change,
+                Err(e) => {
+                    if let Some(entry) = self.write()?.get_mut(&tenant_id) {
+                        entry.engine = None;
+                    }
+                    return Err(e);
+                }
+            },
+            None => PolicyChange {
+                tenant_id,
+                version: self.version(tenant_id)? + 1,
+            },
+        };
+        self.apply(change)?;
+        Ok(change)
+    }
+
+    /// The tenant's policy version, fetched from the bus if no change has
+    /// been seen for the tenant since this instance started
+    ///
+    /// Call this before loading policies and pass the result to `store`, so
+    /// every instance reports the same version for the same policies. If the
+    /// bus cannot be reached the version seen so far is returned.
+    pub async fn sync_version(&self, bus: Option<&dyn PolicyBus>, tenant_id: Uuid) -> Result<i64> {
+        let version = self.version(tenant_id)?;
+        let Some(bus) = bus.filter(|_| version == 0) else {
+            return Ok(version);
+        };
+
+        match bus.current_version(tenant_id).await {
+            Ok(current) => {
+                self.apply(PolicyChange {
+                    tenant_id,
+                    version: current,
+                })?;
+                self.version(tenant_id)
+            }
+            Err(e) => {
+                warn!(tenant_id = %tenant_id, "Failed to fetch policy version: {}", e);
+                Ok(version)
+            }
+        }
+    }
+
+    /// Apply changes from the bus in the background
+    ///
+    /// Subscribes before returning, so no change published afterwards is
+    /// missed. If the subscription ends it is re-established and the whole
+    /// cache is cleared, since changes may have been lost in between.
+    pub async fn listen(self: Arc<Self>, bus: Arc<dyn PolicyBus>) -> Result<()> {
+        let mut changes = bus.subscribe().await?;
+
+        tokio::spawn(async move {
+            loop {
+                while let Some(change) = changes.next().await {
+                    if let Err(e) = self.apply(change) {
+                        warn!("Failed to apply policy change: {}", e);
+                    }
+                }
+
+                warn!("Policy change subscription ended; resubscribing");
+                changes = loop {
+                    tokio::time::sleep(RESUBSCRIBE_DELAY).await;
+                    match bus.subscribe().await {
+                        Ok(changes) => break changes,
+                        Err(e) => warn!("Failed to resubscribe to policy changes: {}", e),
+                    }
+                };
+                if let Err(e) = self.clear() {
+                    warn!("Failed to clear policy cache: {}", e);
+                }
+            }
+        });
+
+        Ok(())
+    }
+
+    fn read(&self) -> Result<std::sync::RwLockReadGuard<'_, HashMap<Uuid, TenantEntry>>> {
+        self.tenants
+            .read()
+            .map_err(|_| AppError::Internal("Policy cache lock poisoned".to_string()))
+    }
+
+    fn write(&self) -> Result<std::sync::RwLockWriteGuard<'_, HashMap<Uuid, TenantEntry>>> {
+        self.tenants
+            .write()
+            .map_err(|_| AppError::Internal("Policy cache lock poisoned".to_string()))
+    }
+}
+
+// Process-wide cache and bus used by the authorization endpoints
+static POLICY_CACHE: Lazy<Arc<PolicyCache>> = Lazy::new(|| Arc::new(PolicyCache::new()));
+static POLICY_BUS: OnceCell<Arc<dyn PolicyBus>> = OnceCell::new();
+
+/// The process-wide policy cache
+pub fn policy_cache() -> &'static Arc<PolicyCache> {
+    &POLICY_CACHE
+}
+
+/// Connect the process-wide cache to a bus; call once at startup
+pub async fn configure_policy_bus(bus: Arc<dyn PolicyBus>) -> Result<()> {
+    if POLICY_BUS.set(bus.clone()).is_err() {
+        return Err(AppError::Configuration(
+            "Policy bus already configured".to_string(),
+        ));
+    }
+    POLICY_CACHE.clone().listen(bus).await?;
+    info!("Listening for policy changes on {}", POLICY_CHANGES_CHANNEL);
+    Ok(())
+}
+
+/// The tenant's policy version in the process-wide cache, synced with the bus
+pub async fn sync_policy_version(tenant_id: Uuid) -> Result<i64> {
+    POLICY_CACHE
+        .sync_version(POLICY_BUS.get().map(|bus| bus.as_ref()), tenant_id)
+        .await
+}
+
+/// Announce a change to a tenant's policies through the process-wide bus
+pub async fn notify_policy_change(tenant_id: Uuid) -> Result<PolicyChange> {
+    POLICY_CACHE
+        .policy_changed(POLICY_BUS.get().map(|bus| bus.as_ref()), tenant_id)
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicI64, Ordering};
+    use tokio::sync::broadcast;
+
+    /// In-process stand-in for the Redis bus, shared by several caches
+    struct MemoryBus {
+        version: AtomicI64,
+        sender: broadcast::Sender<PolicyChange>,
+    }
+
+    impl MemoryBus {
+        fn new() -> Arc<Self> {
+            let (sender, _) = broadcast::channel(16);
+            Arc::new(Self {
+                version: AtomicI64::new(0),
+                sender,
+            })
+        }
+    }
+
+    #[async_trait]
+    impl PolicyBus for MemoryBus {
+        async fn publish(&self, tenant_id: Uuid) -> Result<PolicyChange> {
+            let version = self.version.fetch_add(1, Ordering::SeqCst) + 1;
+            let change = PolicyChange { tenant_id, version };
+            let _ = self.sender.send(change);
+            Ok(change)
+        }
+
+        async fn subscribe(&self) -> Result<BoxStream<'static, PolicyChange>> {
+            let receiver = self.sender.subscribe();
+            let changes = futures::stream::unfold(receiver, |mut receiver| async move {
+                let change = receiver.recv().await.ok()?;
+                Some((change, receiver))
+            });
+            Ok(changes.boxed())
+        }
+
+        async fn current_version(&self, _tenant_id: Uuid) -> Result<i64> {
+            Ok(self.version.load(Ordering::SeqCst))
+        }
+    }
+
+    async fn engine(policy: &str) -> Arc<CedarEngine> {
+        let engine = CedarEngine::new();
+        engine
+            .load_policies(vec![(Uuid::new_v4(), policy.to_string())])
+            .await
+            .unwrap();
+        Arc::new(engine)
+    }
+
+    async fn wait_for_invalidation(cache: &PolicyCache, tenant_id: Uuid) {
+        for _ in 0..100 {
+            if cache.get(tenant_id).unwrap().is_none() {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+        panic!("policy change was not applied");
+    }
+
+    #[tokio::test]
+    async fn test_change_on_one_instance_invalidates_the_other() {
+        let bus = MemoryBus::new();
+        let instance_a = Arc::new(PolicyCache::new());
+        let instance_b = Arc::new(PolicyCache::new());
+        instance_a.clone().listen(bus.clone()).await.unwrap();
+        instance_b.clone().listen(bus.clone()).await.unwrap();
+
+        let tenant_id = Uuid::new_v4();
+        let other_tenant = Uuid::new_v4();
+        let policy = "permit(principal, action, resource);";
+        for cache in [&instance_a, &instance_b] {
+            assert!(cache.store(tenant_id, 0, engine(policy).await).unwrap());
+            assert!(cache.store(other_tenant, 0, engine(policy).await).unwrap());
+        }
+
+        let change = instance_a
+            .policy_changed(Some(bus.as_ref() as &dyn PolicyBus), tenant_id)
+            .await
+            .unwrap();
+
+        // The writer drops its own entry at once; the other instance via the bus
+        assert!(instance_a.get(tenant_id).unwrap().is_none());
+        wait_for_invalidation(&instance_b, tenant_id).await;
+        assert_eq!(instance_b.version(tenant_id).unwrap(), change.version);
+
+        // Other tenants keep their engines
+        assert!(instance_b.get(other_tenant).unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_load_started_before_change_is_not_stored() {
+        let cache = PolicyCache::new();
+        let tenant_id = Uuid::new_v4();
+
+        let loaded_at = cache.version(tenant_id).unwrap();
+        cache
+            .apply(PolicyChange {
+                tenant_id,
+                version: 1,
+            })
+            .unwrap();
+
+        let stale = engine("permit(principal, action, resource);").await;
+        assert!(!cache.store(tenant_id, loaded_at, stale).unwrap());
+        assert!(cache.get(tenant_id).unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_repeated_or_older_changes_are_ignored() {
+        let cache = PolicyCache::new();
+        let tenant_id = Uuid::new_v4();
+        let change = PolicyChange {
+            tenant_id,
+            version: 3,
+        };
+
+        assert!(cache.apply(change).unwrap());
+        let fresh = engine("permit(principal, action, resource);").await;
+        assert!(cache.store(tenant_id, 3, fresh).unwrap());
+
+        assert!(!cache.apply(change).unwrap());
+        assert!(!cache
+            .apply(PolicyChange {
+                tenant_id,
+                version: 2
+            })
+            .unwrap());
+        assert!(cache.get(tenant_id).unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_new_instance_syncs_version_from_bus() {
+        let bus = MemoryBus::new();
+        let tenant_id = Uuid::new_v4();
+        let writer = PolicyCache::new();
+        for _ in 0..3 {
+            writer
+                .policy_changed(Some(bus.as_ref() as &dyn PolicyBus), tenant_id)
+                .await
+                .unwrap();
+        }
+
+        // An instance started after the changes reports the same version
+        let started_later = PolicyCache::new();
+        let version = started_later
+            .sync_version(Some(bus.as_ref() as &dyn PolicyBus), tenant_id)
+            .await
+            .unwrap();
+        assert_eq!(version, 3);
+
+        let policy = engine("permit(principal, action, resource);").await;
+        assert!(started_later.store(tenant_id, version, policy).unwrap());
+        let (_, cached_version) = started_later.get_versioned(tenant_id).unwrap().unwrap();
+        assert_eq!(cached_version, 3);
+
+        // Without a bus the local version is used
+        let local = PolicyCache::new();
+        assert_eq!(local.sync_version(None, tenant_id).await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_change_without_bus_invalidates_locally() {
+        let cache = PolicyCache::new();
+        let tenant_id = Uuid::new_v4();
+        let policy = engine("permit(principal, action, resource);").await;
+        assert!(cache.store(tenant_id, 0, policy).unwrap());
+
+        let change = cache.policy_changed(None, tenant_id).await.unwrap();
+
+        assert_eq!(change.version, 1);
+        assert!(cache.get(tenant_id).unwrap().is_none());
+    }
+    #[tokio::test]
+    async fn test_engine_dropped_at_window_boundary() {
+        let cache = PolicyCache::new();
+        let tenant_id = Uuid::new_v4();
+        let policy = "permit(principal, action, resource);";
+
+        let later = Utc::now() + chrono::Duration::hours(1);
+        assert!(cache
+            .store_until(tenant_id, 0, engine(policy).await, Some(later))
+            .unwrap());
+        assert!(cache.get(tenant_id).unwrap().is_some());
+
+        let passed = Utc::now() - chrono::Duration::seconds(1);
+        assert!(cache
+            .store_until(tenant_id, 0, engine(policy).await, Some(passed))
+            .unwrap());
+        assert!(cache.get(tenant_id).unwrap().is_none());
+        assert!(cache.get_versioned(tenant_id).unwrap().is_none());
+    }
+}
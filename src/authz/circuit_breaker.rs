@@ -0,0 +1,304 @@
+// Circuit breaker for database reads on the authorization hot path
+//
+// After `failure_threshold` consecutive database errors the circuit opens and
+// calls are short-circuited for `cooldown`, so a struggling database is not
+// hit by every authorization check. Once the cooldown has passed, one trial
+// call is let through (half-open): if it succeeds the circuit closes, if it
+// fails the circuit opens for another cooldown.
+
+use crate::config::CircuitBreakerConfig;
+use crate::errors::{AppError, Result};
+use crate::observability::MetricsRecorder;
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircuitState {
+    /// Calls go through
+    Closed,
+    /// Calls are short-circuited until the cooldown has passed
+    Open,
+    /// A trial call is in flight; other calls are short-circuited
+    HalfOpen,
+}
+
+impl CircuitState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CircuitState::Closed => "closed",
+            CircuitState::Open => "open",
+            CircuitState::HalfOpen => "half_open",
+        }
+    }
+}
+
+struct BreakerInner {
+    state: CircuitState,
+    consecutive_failures: u32,
+    /// When the circuit last opened, or when the current trial started
+    since: Instant,
+}
+
+pub struct CircuitBreaker {
+    name: &'static str,
+    failure_threshold: u32,
+    cooldown: Duration,
+    inner: Mutex<BreakerInner>,
+}
+
+impl CircuitBreaker {
+    /// Create a closed breaker; `name` labels its metrics
+    pub fn new(name: &'static str, failure_threshold: u32, cooldown: Duration) -> Self {
+        MetricsRecorder::set_circuit_breaker_state(name, CircuitState::Closed.as_str());
+        Self {
+            name,
+            failure_threshold: failure_threshold.max(1),
+            cooldown,
+            inner: Mutex::new(BreakerInner {
+                state: CircuitState::Closed,
+                consecutive_failures: 0,
+                since: Instant::now(),
+            }),
+        }
+    }
+
+    pub fn from_config(name: &'static str, config: &CircuitBreakerConfig) -> Self {
+        Self::new(
+            name,
+            config.failure_threshold,
+            Duration::from_secs(config.cooldown_seconds),
+        )
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Current state, without admitting a call
+    pub fn state(&self) -> Result<CircuitState> {
+        Ok(self.lock()?.state)
+    }
+
+    /// Run `op` unless the circuit is open
+    ///
+    /// Returns Ok(None) when the call was short-circuited. Only database errors,
+    /// including an exhausted connection pool, count towards opening the
+    /// circuit; other errors are returned without changing its state.
+    pub async fn call<T, F, Fut>(&self, op: F) -> Result<Option<T>>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        if !self.try_acquire()? {
+            return Ok(None);
+        }
+
+        match op().await {
+            Ok(value) => {
+                self.on_success()?;
+                Ok(Some(value))
+            }
+            Err(e @ (AppError::Database(_) | AppError::ServiceUnavailable(_))) => {
+                self.on_failure()?;
+                Err(e)
+            }
+            Err(e) => {
+                // The database answered, so a trial call still proves it is back
+                self.on_success()?;
+                Err(e)
+            }
+        }
+    }
+
+    /// Whether a call may go through, moving an open circuit to half-open once
+    /// the cooldown has passed
+    fn try_acquire(&self) -> Result<bool> {
+        let mut inner = self.lock()?;
+        match inner.state {
+            CircuitState::Closed => Ok(true),
+            // A trial that never reported back (e.g. its request was cancelled)
+            // is replaced once another cooldown has passed
+            CircuitState::Open | CircuitState::HalfOpen
+                if inner.since.elapsed() >= self.cooldown =>
+            {
+                inner.since = Instant::now();
+                self.transition(&mut inner, CircuitState::HalfOpen);
+                Ok(true)
+            }
+            CircuitState::Open | CircuitState::HalfOpen => Ok(false),
+        }
+    }
+
+    fn on_success(&self) -> Result<()> {
+        let mut inner = self.lock()?;
+        inner.consecutive_failures = 0;
+        if inner.state != CircuitState::Closed {
+            self.transition(&mut inner, CircuitState::Closed);
+        }
+        Ok(())
+    }
+
+    fn on_failure(&self) -> Result<()> {
+        let mut inner = self.lock()?;
+        inner.consecutive_failures = inner.consecutive_failures.saturating_add(1);
+        let trip = match inner.state {
+            CircuitState::Closed => inner.consecutive_failures >= self.failure_threshold,
+            CircuitState::HalfOpen => true,
+            // A call admitted before the circuit opened; it is already open
+            CircuitState::Open => false,
+        };
+        if trip {
+            inner.since = Instant::now();
+            self.transition(&mut inner, CircuitState::Open);
+        }
+        Ok(())
+    }
+
+    fn transition(&self, inner: &mut BreakerInner, state: CircuitState) {
+        inner.state = state;
+        MetricsRecorder::set_circuit_breaker_state(self.name, state.as_str());
+        MetricsRecorder::record_circuit_breaker_transition(self.name, state.as_str());
+        match state {
+            CircuitState::Open => warn!(
+                breaker = self.name,
+                consecutive_failures = inner.consecutive_failures,
+                cooldown_seconds = self.cooldown.as_secs(),
+                "Circuit breaker opened"
+            ),
+            _ => info!(
+                breaker = self.name,
+                state = state.as_str(),
+                "Circuit breaker state changed"
+            ),
+        }
+    }
+
+    fn lock(&self) -> Result<std::sync::MutexGuard<'_, BreakerInner>> {
+        self.inner
+            .lock()
+            .map_err(|_| AppError::Internal("Circuit breaker lock poisoned".to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    const COOLDOWN: Duration = Duration::from_millis(50);
+
+    async fn failing_load(calls: &AtomicU32) -> Result<Vec<String>> {
+        calls.fetch_add(1, Ordering::SeqCst);
+        Err(AppError::Database(sqlx::Error::PoolTimedOut))
+    }
+
+    async fn working_load(calls: &AtomicU32) -> Result<Vec<String>> {
+        calls.fetch_add(1, Ordering::SeqCst);
+        Ok(vec!["permit(principal, action, resource);".to_string()])
+    }
+
+    async fn open_breaker(breaker: &CircuitBreaker, calls: &AtomicU32) {
+        for _ in 0..3 {
+            assert!(breaker.call(|| failing_load(calls)).await.is_err());
+        }
+        assert_eq!(breaker.state().unwrap(), CircuitState::Open);
+    }
+
+    #[tokio::test]
+    async fn test_consecutive_failures_open_the_circuit() {
+        let breaker = CircuitBreaker::new("test", 3, COOLDOWN);
+        let calls = AtomicU32::new(0);
+
+        assert!(breaker.call(|| failing_load(&calls)).await.is_err());
+        assert!(breaker.call(|| failing_load(&calls)).await.is_err());
+        assert_eq!(breaker.state().unwrap(), CircuitState::Closed);
+        assert!(breaker.call(|| failing_load(&calls)).await.is_err());
+        assert_eq!(breaker.state().unwrap(), CircuitState::Open);
+
+        // While open, the loader is not called at all
+        let before = calls.load(Ordering::SeqCst);
+        assert!(breaker
+            .call(|| working_load(&calls))
+            .await
+            .unwrap()
+            .is_none());
+        assert_eq!(calls.load(Ordering::SeqCst), before);
+    }
+
+    #[tokio::test]
+    async fn test_success_resets_failure_count() {
+        let breaker = CircuitBreaker::new("test", 3, COOLDOWN);
+        let calls = AtomicU32::new(0);
+
+        for _ in 0..2 {
+            assert!(breaker.call(|| failing_load(&calls)).await.is_err());
+        }
+        assert!(breaker
+            .call(|| working_load(&calls))
+            .await
+            .unwrap()
+            .is_some());
+        for _ in 0..2 {
+            assert!(breaker.call(|| failing_load(&calls)).await.is_err());
+        }
+
+        assert_eq!(breaker.state().unwrap(), CircuitState::Closed);
+    }
+
+    #[tokio::test]
+    async fn test_half_opens_after_cooldown_and_closes_on_success() {
+        let breaker = CircuitBreaker::new("test", 3, COOLDOWN);
+        let calls = AtomicU32::new(0);
+        open_breaker(&breaker, &calls).await;
+
+        tokio::time::sleep(COOLDOWN + Duration::from_millis(10)).await;
+
+        // The trial call goes through and closes the circuit
+        let loaded = breaker.call(|| working_load(&calls)).await.unwrap();
+        assert!(loaded.is_some());
+        assert_eq!(breaker.state().unwrap(), CircuitState::Closed);
+    }
+
+    #[tokio::test]
+    async fn test_failed_trial_reopens_the_circuit() {
+        let breaker = CircuitBreaker::new("test", 3, COOLDOWN);
+        let calls = AtomicU32::new(0);
+        open_breaker(&breaker, &calls).await;
+
+        tokio::time::sleep(COOLDOWN + Duration::from_millis(10)).await;
+        assert!(breaker.call(|| failing_load(&calls)).await.is_err());
+        assert_eq!(breaker.state().unwrap(), CircuitState::Open);
+
+        // A single failed trial is enough; the next call waits out a new cooldown
+        assert!(breaker
+            .call(|| working_load(&calls))
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn test_only_one_trial_while_half_open() {
+        let breaker = CircuitBreaker::new("test", 3, COOLDOWN);
+        let calls = AtomicU32::new(0);
+        open_breaker(&breaker, &calls).await;
+        tokio::time::sleep(COOLDOWN + Duration::from_millis(10)).await;
+
+        assert!(breaker.try_acquire().unwrap());
+        assert_eq!(breaker.state().unwrap(), CircuitState::HalfOpen);
+        assert!(!breaker.try_acquire().unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_non_database_errors_do_not_count() {
+        let breaker = CircuitBreaker::new("test", 1, COOLDOWN);
+        let result: Result<Option<()>> = breaker
+            .call(|| async { Err(AppError::ValidationError("bad entity".to_string())) })
+            .await;
+
+        assert!(result.is_err());
+        assert_eq!(breaker.state().unwrap(), CircuitState::Closed);
+    }
+}
@@ -1,5 +1,5 @@
 // Cedar policy engine wrapper
-use crate::errors::Result;
+use crate::errors::{AppError, Result};
 use cedar_policy::{Authorizer, Decision, Entities, Policy, PolicySet, Request, Response};
 use std::sync::Arc;
 use tokio::sync::RwLock;
@@ -38,7 +38,10 @@ impl CedarEngine {
                 }
                 Err(e) => {
                     error!(policy_id = %policy_id, error = ?e, "Failed to parse Cedar policy");
-                    return Err(anyhow::anyhow!("Failed to parse policy {}: {}", policy_id, e).into());
+                    return Err(AppError::ValidationError(format!(
+                        "Failed to parse policy {}: {}",
+                        policy_id, e
+                    )));
                 }
             }
         }
@@ -51,10 +54,26 @@ impl CedarEngine {
         Ok(loaded_count)
     }
 
+    /// Replace the loaded policies with a Cedar policy set given as text
+    ///
+    /// Policies are named `policy0`, `policy1`, ... in the order they appear.
+    /// Returns the number of policies loaded.
+    pub async fn load_policy_text(&self, policy_text: &str) -> Result<usize> {
+        let policy_set: PolicySet = policy_text
+            .parse()
+            .map_err(|e| AppError::ValidationError(format!("Failed to parse policy set: {}", e)))?;
+        let loaded_count = policy_set.policies().count();
+
+        let mut policies = self.policies.write().await;
+        *policies = policy_set;
+
+        debug!(count = loaded_count, "Loaded Cedar policy set");
+        Ok(loaded_count)
+    }
+
     /// Add a single policy to the engine
     pub async fn add_policy(&self, policy_id: Uuid, policy_text: String) -> Result<()> {
-        let policy = Policy::parse(Some(policy_id.to_string()), policy_text)
-            .map_err(|e| anyhow::anyhow!("Failed to parse policy: {}", e))?;
+        let policy = Policy::parse(Some(policy_id.to_string()), policy_text)?;
 
         let mut policies = self.policies.write().await;
         policies.add(policy)?;
@@ -181,7 +200,26 @@ mod tests {
         let invalid_policy = "this is not valid Cedar syntax".to_string();
 
         let result = engine.add_policy(policy_id, invalid_policy).await;
-        assert!(result.is_err());
+        assert!(matches!(result, Err(AppError::ValidationError(_))));
+        assert_eq!(engine.policy_count().await, 0);
+    }
+
+    #[tokio::test]
+    async fn test_invalid_policy_in_batch_names_policy() {
+        let engine = CedarEngine::new();
+        let bad_id = Uuid::new_v4();
+
+        let result = engine
+            .load_policies(vec![
+                (Uuid::new_v4(), "permit(principal, action, resource);".to_string()),
+                (bad_id, "permit(".to_string()),
+            ])
+            .await;
+
+        match result {
+            Err(AppError::ValidationError(msg)) => assert!(msg.contains(&bad_id.to_string())),
+            other => panic!("expected validation error, got {:?}", other.map(|_| ())),
+        }
         assert_eq!(engine.policy_count().await, 0);
     }
 }
@@ -1,11 +1,14 @@
 // Authorization decision logic
-use crate::errors::Result;
-use cedar_policy::{Context, Entities, EntityId, EntityTypeName, EntityUid, Request};
+use crate::authz::validation::TYPED_CONTEXT_ACTIONS;
+use crate::domain::role;
+use crate::errors::{AppError, Result};
+use cedar_policy::{Context, Entities, EntityId, EntityTypeName, EntityUid, Request, Schema};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use sqlx::PgPool;
 use std::collections::HashMap;
 use std::str::FromStr;
+use std::sync::Arc;
 use tracing::{debug, warn};
 use uuid::Uuid;
 
@@ -15,6 +18,7 @@ pub struct AuthorizationRequestBuilder {
     action: Option<String>,
     resource: Option<String>,
     context: HashMap<String, Value>,
+    context_schema: Option<Arc<Schema>>,
 }
 
 impl AuthorizationRequestBuilder {
@@ -24,6 +28,7 @@ impl AuthorizationRequestBuilder {
             action: None,
             resource: None,
             context: HashMap::new(),
+            context_schema: None,
         }
     }
 
@@ -47,25 +52,39 @@ impl AuthorizationRequestBuilder {
         self
     }
 
+    /// Validate the context against the schema's declared context for the action
+    ///
+    /// Only applies to actions in `TYPED_CONTEXT_ACTIONS`.
+    pub fn context_schema(mut self, schema: Arc<Schema>) -> Self {
+        self.context_schema = Some(schema);
+        self
+    }
+
     pub fn build(self) -> Result<Request> {
         let principal = self
             .principal
-            .ok_or_else(|| anyhow::anyhow!("Principal is required"))?;
+            .ok_or_else(|| AppError::ValidationError("Principal is required".to_string()))?;
         let action = self
             .action
-            .ok_or_else(|| anyhow::anyhow!("Action is required"))?;
+            .ok_or_else(|| AppError::ValidationError("Action is required".to_string()))?;
         let resource = self
             .resource
-            .ok_or_else(|| anyhow::anyhow!("Resource is required"))?;
+            .ok_or_else(|| AppError::ValidationError("Resource is required".to_string()))?;
 
         let principal_uid = parse_entity_uid(&principal)?;
         let action_uid = parse_action_uid(&action)?;
         let resource_uid = parse_entity_uid(&resource)?;
 
-        let context = Context::from_json_value(
-            serde_json::to_value(&self.context)?,
-            None,
-        )?;
+        let context_json = serde_json::to_value(&self.context)
+            .map_err(|e| AppError::Internal(format!("Failed to serialize context: {}", e)))?;
+        let context = match &self.context_schema {
+            Some(schema) if has_typed_context(&action) => {
+                Context::from_json_value(context_json, Some((schema, &action_uid))).map_err(
+                    |e| AppError::ValidationError(format!("Invalid authorization context: {}", e)),
+                )?
+            }
+            _ => Context::from_json_value(context_json, None)?,
+        };
 
         Ok(Request::new(
             principal_uid,
@@ -83,20 +102,60 @@ impl Default for AuthorizationRequestBuilder {
     }
 }
 
-/// Parse an entity UID from a string like "User::\"alice\""
-fn parse_entity_uid(s: &str) -> Result<EntityUid> {
+/// Split an entity UID string like "User::\"alice\"" into its type and id
+pub(crate) fn split_entity_uid(s: &str) -> Result<(&str, &str)> {
     // Expected format: EntityType::"id"
     let parts: Vec<&str> = s.splitn(2, "::").collect();
     if parts.len() != 2 {
-        return Err(anyhow::anyhow!(
+        return Err(AppError::ValidationError(format!(
             "Invalid entity UID format: {}. Expected 'Type::\"id\"'",
             s
-        )
-        .into());
+        )));
     }
 
-    let type_name = EntityTypeName::from_str(parts[0])?;
-    let id_part = parts[1].trim_matches('"');
+    let id = parts[1];
+    let id = match id.strip_prefix('"').and_then(|id| id.strip_suffix('"')) {
+        Some(unquoted) => unquoted,
+        None => id,
+    };
+    validate_entity_id(s, id)?;
+
+    Ok((parts[0], id))
+}
+
+/// Reject entity ids that could be misread by Cedar or in logs
+///
+/// Ids may hold any printable characters except quotes and backslashes,
+/// which covers UUIDs, slugs, emails and paths. `::` is not allowed either,
+/// so the id cannot pass for a type path.
+fn validate_entity_id(uid: &str, id: &str) -> Result<()> {
+    let malformed = |reason: &str| {
+        Err(AppError::ValidationError(format!(
+            "Invalid entity UID {:?}: {}",
+            uid, reason
+        )))
+    };
+
+    if id.chars().any(char::is_control) {
+        return malformed("id contains control characters");
+    }
+    if id.contains('"') {
+        return malformed("id contains unbalanced or embedded quotes");
+    }
+    if id.contains('\\') {
+        return malformed("id contains a backslash");
+    }
+    if id.contains("::") {
+        return malformed("id contains '::'");
+    }
+    Ok(())
+}
+
+/// Parse an entity UID from a string like "User::\"alice\""
+pub(crate) fn parse_entity_uid(s: &str) -> Result<EntityUid> {
+    let (type_part, id_part) = split_entity_uid(s)?;
+
+    let type_name = EntityTypeName::from_str(type_part)?;
     let entity_id = EntityId::from_str(id_part)?;
 
     Ok(EntityUid::from_type_name_and_id(type_name, entity_id))
@@ -113,6 +172,20 @@ fn parse_action_uid(s: &str) -> Result<EntityUid> {
     parse_entity_uid(s)
 }
 
+/// Whether an action string names one of `TYPED_CONTEXT_ACTIONS`
+fn has_typed_context(action: &str) -> bool {
+    let name = if action.contains("::") {
+        match split_entity_uid(action) {
+            Ok(("Action", name)) => name,
+            _ => return false,
+        }
+    } else {
+        action
+    };
+
+    TYPED_CONTEXT_ACTIONS.contains(&name)
+}
+
 /// Create an empty entities set
 pub fn create_empty_entities() -> Result<Entities> {
     Ok(Entities::empty())
@@ -192,13 +265,23 @@ impl AuthzEvaluator {
         // In a real system, this would query the policies table and use Cedar
 
         // For now, allow all authenticated users to perform read operations
-        // and require specific permissions for write operations
+        // and require a permission granted through the identity's roles for
+        // write operations
         match action {
             "read" | "list" | "get" => Ok(true),
             _ => {
-                // Check if user has admin role or specific permission
-                // This is a placeholder - in production this would check actual roles
-                Ok(false)
+                let allowed =
+                    role::has_permission(&self.pool, *identity_id, resource_type, action).await?;
+                if !allowed {
+                    debug!(
+                        identity_id = %identity_id,
+                        tenant_id = %tenant_id,
+                        "No role grants {}:{}",
+                        resource_type,
+                        action
+                    );
+                }
+                Ok(allowed)
             }
         }
     }
@@ -232,6 +315,54 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_malformed_entity_uid_is_validation_error() {
+        for uid in ["invalid_format", "Not A Type::\"alice\"", "User-1::\"alice\""] {
+            assert!(
+                matches!(parse_entity_uid(uid), Err(AppError::ValidationError(_))),
+                "{}",
+                uid
+            );
+        }
+    }
+
+    #[test]
+    fn test_entity_id_with_control_characters_or_quotes_rejected() {
+        for uid in [
+            "User::\"al\nice\"",
+            "User::\"a\"b\"",
+            "User::\"alice",
+            "User::alice\"",
+            "User::\"al\\\"ice\"",
+            "User::\"a\u{0}b\"",
+            "User::\"Admin::\"root\"\"",
+        ] {
+            assert!(
+                matches!(parse_entity_uid(uid), Err(AppError::ValidationError(_))),
+                "{:?}",
+                uid
+            );
+        }
+    }
+
+    #[test]
+    fn test_entity_id_accepts_uuids_and_slugs() {
+        let uuid = Uuid::new_v4();
+        for uid in [
+            format!("User::\"{}\"", uuid),
+            format!("Agent::{}", uuid),
+            "Resource::\"reports/q1-2026\"".to_string(),
+            "Tenant::\"acme-corp\"".to_string(),
+            "User::\"alice@example.com\"".to_string(),
+        ] {
+            assert!(parse_entity_uid(&uid).is_ok(), "{}", uid);
+        }
+        assert_eq!(
+            split_entity_uid("User::\"alice\"").unwrap(),
+            ("User", "alice")
+        );
+    }
+
     #[test]
     fn test_request_builder() {
         let result = AuthorizationRequestBuilder::new()
@@ -243,6 +374,51 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    fn typed_request(action: &str, context: Value) -> Result<Request> {
+        let schema = Arc::new(crate::authz::validation::create_request_context_schema().unwrap());
+        let mut builder = AuthorizationRequestBuilder::new()
+            .principal("User::\"alice\"".to_string())
+            .action(action.to_string())
+            .resource("File::\"file1\"".to_string())
+            .context_schema(schema);
+        if let Value::Object(context) = context {
+            for (key, value) in context {
+                builder = builder.add_context(key, value);
+            }
+        }
+        builder.build()
+    }
+
+    #[test]
+    fn test_context_validated_against_schema() {
+        assert!(typed_request("read", serde_json::json!({"mfa": true, "ip": "10.0.0.1"})).is_ok());
+        assert!(typed_request("Action::\"read\"", serde_json::json!({})).is_ok());
+
+        // Misspelled key
+        assert!(matches!(
+            typed_request("read", serde_json::json!({"mfaa": true})),
+            Err(AppError::ValidationError(_))
+        ));
+        // Wrong type
+        assert!(matches!(
+            typed_request("delete", serde_json::json!({"mfa": "yes"})),
+            Err(AppError::ValidationError(_))
+        ));
+    }
+
+    #[test]
+    fn test_undeclared_action_context_untyped() {
+        assert!(typed_request("approve", serde_json::json!({"ticket": "T-1"})).is_ok());
+    }
+
+    #[test]
+    fn test_has_typed_context() {
+        assert!(has_typed_context("read"));
+        assert!(has_typed_context("Action::\"update\""));
+        assert!(!has_typed_context("approve"));
+        assert!(!has_typed_context("Custom::\"read\""));
+    }
+
     #[test]
     fn test_request_builder_missing_principal() {
         let result = AuthorizationRequestBuilder::new()
@@ -250,6 +426,6 @@ mod tests {
             .resource("File::\"file1\"".to_string())
             .build();
 
-        assert!(result.is_err());
+        assert!(matches!(result, Err(AppError::ValidationError(_))));
     }
 }
@@ -0,0 +1,268 @@
+// Cedar policy linting
+//
+// Validation rejects policies that cannot work; linting flags policies that
+// work but are probably not what the author meant. Warnings never block a
+// write. Shadowing is judged from the policy scopes alone: entity hierarchies
+// are not consulted and conditional forbids never shadow, so a reported
+// shadow is certain but not every shadow is reported.
+
+use cedar_policy::{
+    ActionConstraint, Effect, EntityTypeName, EntityUid, Policy, PolicySet, PrincipalConstraint,
+    ResourceConstraint, Schema,
+};
+use serde::Serialize;
+
+/// Permit with no scope constraints and no conditions
+pub const LINT_UNCONSTRAINED: &str = "unconstrained";
+/// Permit that an unconditional forbid overrides for every request
+pub const LINT_SHADOWED: &str = "shadowed";
+/// Action the schema does not declare, so its context is not type-checked
+pub const LINT_UNDECLARED_ACTION: &str = "undeclared_action";
+
+/// An authoring smell found in a policy
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LintWarning {
+    /// One of the `LINT_*` codes
+    pub code: String,
+    pub message: String,
+}
+
+impl LintWarning {
+    fn new(code: &str, message: String) -> Self {
+        Self {
+            code: code.to_string(),
+            message,
+        }
+    }
+}
+
+/// Lint `policy` against the schema and the policies already in force
+pub fn lint_policy(
+    policy: &Policy,
+    schema: Option<&Schema>,
+    existing: &PolicySet,
+) -> Vec<LintWarning> {
+    let mut warnings = Vec::new();
+
+    if policy.effect() == Effect::Permit {
+        let unscoped = matches!(Scope::from(policy.principal_constraint()), Scope::Any)
+            && matches!(policy.action_constraint(), ActionConstraint::Any)
+            && matches!(Scope::from(policy.resource_constraint()), Scope::Any);
+        if unscoped && !has_conditions(policy) {
+            warnings.push(LintWarning::new(
+                LINT_UNCONSTRAINED,
+                "policy is unconstrained: it permits every principal, action and resource"
+                    .to_string(),
+            ));
+        }
+
+        for forbid in existing.policies().filter(|other| overrides(other, policy)) {
+            warnings.push(LintWarning::new(
+                LINT_SHADOWED,
+                format!(
+                    "policy is unreachable: forbid {} denies every request it permits",
+                    forbid.id()
+                ),
+            ));
+        }
+    }
+
+    if let Some(declared) = schema.and_then(|schema| schema.action_entities().ok()) {
+        let actions = match policy.action_constraint() {
+            ActionConstraint::Any => vec![],
+            ActionConstraint::Eq(action) => vec![action],
+            ActionConstraint::In(actions) => actions,
+        };
+        for action in actions
+            .into_iter()
+            .filter(|action| declared.get(action).is_none())
+        {
+            warnings.push(LintWarning::new(
+                LINT_UNDECLARED_ACTION,
+                format!(
+                    "action {} is not declared in the schema, so its context is not type-checked",
+                    action
+                ),
+            ));
+        }
+    }
+
+    warnings
+}
+
+/// Whether `forbid` is an unconditional forbid matching every request `permit` matches
+fn overrides(forbid: &Policy, permit: &Policy) -> bool {
+    forbid.effect() == Effect::Forbid
+        && !has_conditions(forbid)
+        && Scope::from(forbid.principal_constraint()).covers(&permit.principal_constraint().into())
+        && action_covers(&forbid.action_constraint(), &permit.action_constraint())
+        && Scope::from(forbid.resource_constraint()).covers(&permit.resource_constraint().into())
+}
+
+/// Whether the policy has `when` or `unless` clauses
+///
+/// A policy that cannot be inspected is assumed to have them.
+fn has_conditions(policy: &Policy) -> bool {
+    match policy.to_json() {
+        Ok(json) => json["conditions"]
+            .as_array()
+            .map_or(true, |conditions| !conditions.is_empty()),
+        Err(_) => true,
+    }
+}
+
+fn action_covers(outer: &ActionConstraint, inner: &ActionConstraint) -> bool {
+    match (outer, inner) {
+        (ActionConstraint::Any, _) => true,
+        (ActionConstraint::Eq(outer), ActionConstraint::Eq(inner)) => outer == inner,
+        (ActionConstraint::In(outer), ActionConstraint::Eq(inner)) => outer.contains(inner),
+        (ActionConstraint::In(outer), ActionConstraint::In(inner)) => {
+            inner.iter().all(|action| outer.contains(action))
+        }
+        _ => false,
+    }
+}
+
+/// Principal or resource scope constraint
+#[derive(Debug)]
+enum Scope {
+    Any,
+    Eq(EntityUid),
+    In(EntityUid),
+    Is(EntityTypeName),
+    IsIn(EntityTypeName, EntityUid),
+}
+
+impl Scope {
+    /// Whether every entity matching `inner` also matches `self`
+    fn covers(&self, inner: &Scope) -> bool {
+        match (self, inner) {
+            (Scope::Any, _) => true,
+            (Scope::Eq(outer), Scope::Eq(inner)) => outer == inner,
+            // `in` is reflexive, so an entity is in itself
+            (Scope::In(outer), Scope::Eq(inner) | Scope::In(inner) | Scope::IsIn(_, inner)) => {
+                outer == inner
+            }
+            (Scope::Is(outer), Scope::Eq(inner)) => inner.type_name() == outer,
+            (Scope::Is(outer), Scope::Is(inner) | Scope::IsIn(inner, _)) => outer == inner,
+            (Scope::IsIn(outer_type, outer), Scope::Eq(inner)) => {
+                inner.type_name() == outer_type && inner == outer
+            }
+            (Scope::IsIn(outer_type, outer), Scope::IsIn(inner_type, inner)) => {
+                outer_type == inner_type && outer == inner
+            }
+            _ => false,
+        }
+    }
+}
+
+impl From<PrincipalConstraint> for Scope {
+    fn from(constraint: PrincipalConstraint) -> Self {
+        match constraint {
+            PrincipalConstraint::Any => Scope::Any,
+            PrincipalConstraint::Eq(uid) => Scope::Eq(uid),
+            PrincipalConstraint::In(uid) => Scope::In(uid),
+            PrincipalConstraint::Is(entity_type) => Scope::Is(entity_type),
+            PrincipalConstraint::IsIn(entity_type, uid) => Scope::IsIn(entity_type, uid),
+        }
+    }
+}
+
+impl From<ResourceConstraint> for Scope {
+    fn from(constraint: ResourceConstraint) -> Self {
+        match constraint {
+            ResourceConstraint::Any => Scope::Any,
+            ResourceConstraint::Eq(uid) => Scope::Eq(uid),
+            ResourceConstraint::In(uid) => Scope::In(uid),
+            ResourceConstraint::Is(entity_type) => Scope::Is(entity_type),
+            ResourceConstraint::IsIn(entity_type, uid) => Scope::IsIn(entity_type, uid),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::authz::validation::create_request_context_schema;
+
+    fn policy(id: &str, text: &str) -> Policy {
+        Policy::parse(Some(id.to_string()), text).unwrap()
+    }
+
+    fn policy_set(policies: &[Policy]) -> PolicySet {
+        PolicySet::from_policies(policies.iter().cloned()).unwrap()
+    }
+
+    fn codes(warnings: &[LintWarning]) -> Vec<&str> {
+        warnings.iter().map(|w| w.code.as_str()).collect()
+    }
+
+    #[test]
+    fn test_unconstrained_permit_flagged() {
+        let permit_all = policy("new", "permit(principal, action, resource);");
+        let warnings = lint_policy(&permit_all, None, &PolicySet::new());
+        assert_eq!(codes(&warnings), vec![LINT_UNCONSTRAINED]);
+
+        // A condition or a scope constraint is enough to narrow it
+        let conditional = policy(
+            "new",
+            "permit(principal, action, resource) when { context.mfa == true };",
+        );
+        assert!(lint_policy(&conditional, None, &PolicySet::new()).is_empty());
+        let scoped = policy(
+            "new",
+            r#"permit(principal == User::"alice", action, resource);"#,
+        );
+        assert!(lint_policy(&scoped, None, &PolicySet::new()).is_empty());
+
+        let forbid_all = policy("new", "forbid(principal, action, resource);");
+        assert!(lint_policy(&forbid_all, None, &PolicySet::new()).is_empty());
+    }
+
+    #[test]
+    fn test_permit_shadowed_by_forbid_flagged() {
+        let existing = policy_set(&[
+            policy(
+                "lockdown",
+                r#"forbid(principal is User, action, resource in Folder::"secret");"#,
+            ),
+            policy(
+                "no-mfa",
+                r#"forbid(principal, action, resource) unless { context.mfa };"#,
+            ),
+        ]);
+
+        let shadowed = policy(
+            "new",
+            r#"permit(principal == User::"alice", action, resource in Folder::"secret");"#,
+        );
+        let warnings = lint_policy(&shadowed, None, &existing);
+        assert_eq!(codes(&warnings), vec![LINT_SHADOWED]);
+        assert!(warnings[0].message.contains("lockdown"));
+
+        // Agents and other folders are outside the forbid's scope
+        let agent = policy(
+            "new",
+            r#"permit(principal == Agent::"bot", action, resource in Folder::"secret");"#,
+        );
+        assert!(lint_policy(&agent, None, &existing).is_empty());
+        let public = policy(
+            "new",
+            r#"permit(principal == User::"alice", action, resource in Folder::"public");"#,
+        );
+        assert!(lint_policy(&public, None, &existing).is_empty());
+    }
+
+    #[test]
+    fn test_action_missing_from_schema_flagged() {
+        let schema = create_request_context_schema().unwrap();
+        let permit = policy(
+            "new",
+            r#"permit(principal, action in [Action::"read", Action::"approve"], resource);"#,
+        );
+
+        let warnings = lint_policy(&permit, Some(&schema), &PolicySet::new());
+        assert_eq!(codes(&warnings), vec![LINT_UNDECLARED_ACTION]);
+        assert!(warnings[0].message.contains("approve"));
+    }
+}
@@ -1,5 +1,6 @@
 use crate::{
     api::routes::AppState,
+    auth::jwt::JwtClaims,
     authz::evaluator::AuthzEvaluator,
     errors::{AppError, Result},
 };
@@ -21,6 +22,18 @@ pub struct Principal {
     pub roles: Vec<String>,
 }
 
+impl Principal {
+    /// Principal of an authenticated access token; roles are looked up when evaluated
+    pub fn from_claims(claims: &JwtClaims) -> Result<Self> {
+        Ok(Self {
+            identity_id: claims.identity_id()?,
+            tenant_id: claims.tenant_id_uuid()?,
+            identity_type: claims.identity_type.clone(),
+            roles: vec![],
+        })
+    }
+}
+
 /// Resource information for authorization
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Resource {
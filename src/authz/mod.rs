@@ -1,6 +1,11 @@
 // Authorization module
+pub mod access;
 pub mod engine;
+pub mod entities;
 pub mod evaluator;
+pub mod lint;
 pub mod cache;
+pub mod bulkhead;
+pub mod circuit_breaker;
 pub mod middleware;
 pub mod validation;
@@ -211,9 +211,9 @@ impl PolicyValidator {
     /// Validate policy status
     pub fn validate_status(status: &str) -> Result<()> {
         match status.to_lowercase().as_str() {
-            "active" | "inactive" | "deleted" => Ok(()),
+            "active" | "inactive" | "archived" | "deleted" => Ok(()),
             _ => Err(AppError::ValidationError(format!(
-                "Invalid policy status: {}. Must be 'active', 'inactive', or 'deleted'",
+                "Invalid policy status: {}. Must be 'active', 'inactive', 'archived', or 'deleted'",
                 status
             ))),
         }
@@ -264,6 +264,91 @@ impl Default for PolicyValidator {
     }
 }
 
+/// Actions whose request context is typed by the context schemas
+///
+/// These are the actions the service itself checks (HTTP methods map to
+/// read/create/update/delete) plus those in the Agent IAM schema. Contexts
+/// for other actions are passed to Cedar untyped.
+pub const TYPED_CONTEXT_ACTIONS: &[&str] = &[
+    "read", "create", "update", "delete", "write", "execute", "admin",
+];
+
+/// Context attributes the service itself sets on authorization requests
+fn service_context_attributes() -> serde_json::Map<String, serde_json::Value> {
+    let attributes = serde_json::json!({
+        "mfa": { "type": "Boolean", "required": false },
+        "ip": { "type": "String", "required": false },
+        "host": { "type": "String", "required": false },
+        "method": { "type": "String", "required": false }
+    });
+    match attributes {
+        serde_json::Value::Object(attributes) => attributes,
+        _ => serde_json::Map::new(),
+    }
+}
+
+/// Schema for the `context` of authorization requests
+///
+/// Declares the context attributes the service sets; policies are validated
+/// against it.
+pub fn create_request_context_schema() -> Result<Schema> {
+    build_context_schema(service_context_attributes())
+}
+
+/// Schema for the `context` of a tenant's authorization requests
+///
+/// `attributes` is the tenant's `context_schema` setting, Cedar record
+/// attributes such as `{"department": {"type": "String"}}`. The attributes
+/// the service sets are always declared as well, with the service's types.
+/// A misspelled key such as `mfaa` is then rejected instead of silently never
+/// matching.
+pub fn create_tenant_context_schema(attributes: &serde_json::Value) -> Result<Schema> {
+    let mut declared = attributes.as_object().cloned().ok_or_else(|| {
+        AppError::ValidationError("Tenant context schema must be a JSON object".to_string())
+    })?;
+    declared.extend(service_context_attributes());
+    build_context_schema(declared)
+}
+
+fn build_context_schema(attributes: serde_json::Map<String, serde_json::Value>) -> Result<Schema> {
+    let context = serde_json::json!({
+        "type": "Record",
+        "attributes": attributes
+    });
+
+    let actions: serde_json::Map<String, serde_json::Value> = TYPED_CONTEXT_ACTIONS
+        .iter()
+        .map(|action| {
+            (
+                action.to_string(),
+                serde_json::json!({
+                    "appliesTo": {
+                        "principalTypes": ["User", "Service", "Agent"],
+                        "resourceTypes": ["Resource"],
+                        "context": context.clone()
+                    }
+                }),
+            )
+        })
+        .collect();
+
+    let schema_json = serde_json::json!({
+        "": {
+            "entityTypes": {
+                "User": {},
+                "Service": {},
+                "Agent": {},
+                "Resource": {}
+            },
+            "actions": actions
+        }
+    });
+
+    Schema::from_json_value(schema_json).map_err(|e| {
+        AppError::ValidationError(format!("Failed to create context schema: {}", e))
+    })
+}
+
 /// Helper function to create a basic Cedar schema for Agent IAM
 pub fn create_agent_iam_schema() -> Result<Schema> {
     let schema_json = r#"{
@@ -341,6 +426,7 @@ mod tests {
         assert!(PolicyValidator::validate_status("active").is_ok());
         assert!(PolicyValidator::validate_status("inactive").is_ok());
         assert!(PolicyValidator::validate_status("deleted").is_ok());
+        assert!(PolicyValidator::validate_status("archived").is_ok());
         assert!(PolicyValidator::validate_status("ACTIVE").is_ok());
         assert!(PolicyValidator::validate_status("invalid").is_err());
         assert!(PolicyValidator::validate_status("").is_err());
@@ -403,6 +489,28 @@ mod tests {
         assert!(!batch_result.results.get("policy3").unwrap().is_valid);
     }
 
+    #[test]
+    fn test_create_request_context_schema() {
+        assert!(create_request_context_schema().is_ok());
+    }
+
+    #[test]
+    fn test_create_tenant_context_schema() {
+        let attributes = serde_json::json!({
+            "department": { "type": "String", "required": false }
+        });
+        assert!(create_tenant_context_schema(&attributes).is_ok());
+
+        assert!(matches!(
+            create_tenant_context_schema(&serde_json::json!(["department"])),
+            Err(AppError::ValidationError(_))
+        ));
+        assert!(create_tenant_context_schema(&serde_json::json!({
+            "department": { "type": "NoSuchType" }
+        }))
+        .is_err());
+    }
+
     #[test]
     fn test_create_agent_iam_schema() {
         let result = create_agent_iam_schema();
@@ -1,6 +1,11 @@
+use crate::domain::audit::AuditEventType;
+use crate::domain::identity::MAX_DELEGATION_DEPTH;
 use crate::errors::{AppError, Result};
 use serde::Deserialize;
+use std::collections::HashMap;
 use std::env;
+use std::net::{IpAddr, SocketAddr};
+use uuid::Uuid;
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct Config {
@@ -9,10 +14,15 @@ pub struct Config {
     pub redis: RedisConfig,
     pub auth: AuthConfig,
     pub rate_limit: RateLimitConfig,
+    #[serde(default)]
+    pub authz: AuthzConfig,
     pub audit: AuditConfig,
     pub crypto: CryptoConfig,
     pub observability: ObservabilityConfig,
     pub security: SecurityConfig,
+    pub webhooks: WebhookConfig,
+    pub webauthn: WebauthnConfig,
+    pub oidc: OidcConfig,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -20,6 +30,38 @@ pub struct ServerConfig {
     pub host: String,
     pub port: u16,
     pub workers: usize,
+    /// Port for the gRPC authorization service (requires the `grpc` feature)
+    pub grpc_port: u16,
+    /// How long in-flight requests may run after shutdown begins before being cut off
+    pub shutdown_timeout_seconds: u64,
+}
+
+impl ServerConfig {
+    /// Address the HTTP server binds to
+    pub fn http_addr(&self) -> Result<SocketAddr> {
+        Ok(SocketAddr::new(self.ip()?, self.port))
+    }
+
+    /// Address the gRPC server binds to
+    pub fn grpc_addr(&self) -> Result<SocketAddr> {
+        Ok(SocketAddr::new(self.ip()?, self.grpc_port))
+    }
+
+    /// `host` as an IP address; IPv6 may be written with or without brackets
+    fn ip(&self) -> Result<IpAddr> {
+        let host = self.host.trim();
+        let unbracketed = host
+            .strip_prefix('[')
+            .and_then(|h| h.strip_suffix(']'))
+            .unwrap_or(host);
+
+        unbracketed.parse().map_err(|_| {
+            AppError::Configuration(format!(
+                "Invalid server host '{}': expected an IPv4 or IPv6 address",
+                self.host
+            ))
+        })
+    }
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -29,6 +71,9 @@ pub struct DatabaseConfig {
     pub min_connections: u32,
     pub acquire_timeout_seconds: u64,
     pub idle_timeout_seconds: u64,
+    /// Queries slower than this are logged and counted; 0 disables slow query logging
+    #[serde(default)]
+    pub slow_query_threshold_ms: u64,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -36,20 +81,71 @@ pub struct RedisConfig {
     pub url: String,
     pub pool_size: usize,
     pub connection_timeout_seconds: u64,
+    /// Retry policy for idempotent operations (revocation lookups, counter reads)
+    #[serde(default)]
+    pub retry: RedisRetryConfig,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct RedisRetryConfig {
+    /// Retries after the first attempt; 0 disables retrying
+    pub max_retries: u32,
+    pub initial_backoff_ms: u64,
+    pub max_backoff_ms: u64,
+}
+
+impl Default for RedisRetryConfig {
+    fn default() -> Self {
+        Self {
+            max_retries: 2,
+            initial_backoff_ms: 25,
+            max_backoff_ms: 250,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct AuthConfig {
     pub jwt_issuer: String,
     pub jwt_audience: String,
+    /// Downstream services access tokens may additionally be issued for (`aud` values)
+    #[serde(default)]
+    pub jwt_extra_audiences: Vec<String>,
     pub jwt_expiration_seconds: i64,
     pub refresh_token_expiration_seconds: i64,
     pub biscuit_root_key_id: String,
+    /// Largest serialized `task_scope` (in bytes) embedded in a Biscuit
+    pub max_task_scope_bytes: usize,
+    /// Largest number of top-level `task_scope` keys embedded in a Biscuit
+    pub max_task_scope_keys: usize,
+    /// Validated Biscuits whose claims are cached until they expire; 0 disables the cache
+    #[serde(default)]
+    pub biscuit_claims_cache_size: usize,
+    /// Seconds between purges of expired Biscuit revocations; 0 disables purging
+    #[serde(default)]
+    pub biscuit_revocation_gc_interval_seconds: u64,
+    /// Delegation depth at which provisioning an agent raises a warning; 0 disables it
+    #[serde(default)]
+    pub delegation_depth_warning: i32,
+    /// Parent links delegation chain queries follow before stopping; 0 keeps the default of 100
+    #[serde(default)]
+    pub delegation_query_max_depth: i32,
+    /// Largest `scope` claim (in bytes) put in access tokens; 0 leaves the claim out
+    #[serde(default)]
+    pub access_token_scope_max_bytes: usize,
+    /// Client context access tokens are bound to when issued
+    #[serde(default)]
+    pub token_binding: TokenBindingMode,
+    /// Proxies in front of the service that append to `X-Forwarded-For`; 0 uses the socket peer
+    #[serde(default)]
+    pub trusted_proxies: usize,
     pub password_min_length: usize,
     pub password_require_uppercase: bool,
     pub password_require_lowercase: bool,
     pub password_require_digit: bool,
     pub password_require_special: bool,
+    /// Number of previous passwords (besides the current one) that cannot be reused
+    pub password_history_count: usize,
     pub max_login_attempts: u32,
     pub lockout_duration_seconds: i64,
 }
@@ -60,6 +156,166 @@ pub struct RateLimitConfig {
     pub default_requests_per_hour: u64,
     pub default_requests_per_day: u64,
     pub auth_requests_per_minute: u64,
+    /// What to do with requests when the rate limit backend is unreachable
+    pub fail_mode: RateLimitFailMode,
+    /// Format of the Retry-After header on throttled responses
+    #[serde(default)]
+    pub retry_after_format: RetryAfterFormat,
+    /// Identity types (e.g. "service") whose access tokens bypass the default limiter
+    #[serde(default)]
+    pub exempt_identity_types: Vec<String>,
+    /// Shared token that internal callers send to bypass the default limiter (set via environment)
+    #[serde(default)]
+    pub internal_service_token: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RetryAfterFormat {
+    /// Delay in seconds (e.g. `Retry-After: 30`)
+    #[default]
+    Seconds,
+    /// HTTP-date (e.g. `Retry-After: Wed, 21 Oct 2015 07:28:00 GMT`)
+    HttpDate,
+}
+
+/// Behavior of the rate limiter when Redis cannot be reached
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RateLimitFailMode {
+    /// Allow the request and log a warning
+    Open,
+    /// Reject the request with an error
+    Closed,
+}
+
+impl RateLimitFailMode {
+    pub fn as_str(&self) -> &str {
+        match self {
+            RateLimitFailMode::Open => "open",
+            RateLimitFailMode::Closed => "closed",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct AuthzConfig {
+    /// Breaker around the policy and entity reads done for each decision
+    #[serde(default)]
+    pub circuit_breaker: CircuitBreakerConfig,
+    /// Limits on concurrent decisions per tenant
+    #[serde(default)]
+    pub bulkhead: BulkheadConfig,
+    /// Loading tenants' policies into the cache at startup
+    #[serde(default)]
+    pub warmup: PolicyWarmupConfig,
+    /// Caps on the policies each tenant may store
+    #[serde(default)]
+    pub policy_limits: PolicyLimitsConfig,
+    /// Seconds between sweeps for policies entering or leaving their validity
+    /// window; 0 disables them
+    #[serde(default)]
+    pub policy_window_interval_seconds: u64,
+}
+
+/// Caps on a tenant's active and inactive policies, checked when they are written
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct PolicyLimitsConfig {
+    /// Policies per tenant
+    pub max_policies_per_tenant: i64,
+    /// Total size of the tenant's Cedar policy text in bytes
+    pub max_policy_bytes_per_tenant: i64,
+}
+
+impl Default for PolicyLimitsConfig {
+    fn default() -> Self {
+        Self {
+            max_policies_per_tenant: 1000,
+            max_policy_bytes_per_tenant: 1024 * 1024,
+        }
+    }
+}
+
+/// Startup preloading of tenant policies into the policy cache
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct PolicyWarmupConfig {
+    pub enabled: bool,
+    /// Tenants to preload; empty means every active tenant
+    #[serde(default)]
+    pub tenants: Vec<Uuid>,
+}
+
+/// Per-tenant limits on concurrent authorization evaluations
+#[derive(Debug, Clone, Deserialize)]
+pub struct BulkheadConfig {
+    /// Evaluations a tenant may have in flight at once
+    pub max_concurrent_per_tenant: usize,
+    /// Limits for individual tenants, replacing the default
+    #[serde(default)]
+    pub tenant_overrides: HashMap<Uuid, usize>,
+}
+
+impl Default for BulkheadConfig {
+    fn default() -> Self {
+        Self {
+            max_concurrent_per_tenant: 64,
+            tenant_overrides: HashMap::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct CircuitBreakerConfig {
+    /// Consecutive database errors that open the circuit
+    pub failure_threshold: u32,
+    /// How long the circuit stays open before a trial call is let through
+    pub cooldown_seconds: u64,
+    /// How decisions are made while the circuit is open
+    #[serde(default)]
+    pub fallback: CircuitBreakerFallback,
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        Self {
+            failure_threshold: 5,
+            cooldown_seconds: 30,
+            fallback: CircuitBreakerFallback::default(),
+        }
+    }
+}
+
+/// What an access token is bound to when it is issued
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TokenBindingMode {
+    /// Tokens work from anywhere
+    #[default]
+    Off,
+    /// Tokens only work with the User-Agent they were issued to
+    UserAgent,
+    /// Tokens only work with the same User-Agent from the same IP subnet
+    IpAndUserAgent,
+}
+
+/// Authorization behavior while the database circuit is open
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CircuitBreakerFallback {
+    /// Deny every request
+    #[default]
+    DenyAll,
+    /// Evaluate against the last policies loaded, without entity attributes
+    LastKnown,
+}
+
+impl CircuitBreakerFallback {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CircuitBreakerFallback::DenyAll => "deny_all",
+            CircuitBreakerFallback::LastKnown => "last_known",
+        }
+    }
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -68,12 +324,63 @@ pub struct AuditConfig {
     pub async_batch_size: usize,
     pub async_flush_interval_seconds: u64,
     pub storage_backends: Vec<String>,
+    /// Retries of a failed batch write, with doubling backoff (0 disables)
+    #[serde(default)]
+    pub flush_retries: u32,
+    /// Wait before the first retry of a failed batch write
+    #[serde(default)]
+    pub flush_retry_backoff_ms: u64,
+    /// Dead-letter file for batches that still fail after their retries; unset
+    /// keeps them in memory for the next flush
+    #[serde(default)]
+    pub dead_letter_file: Option<String>,
+    /// File batches are appended to as soon as the primary storage fails; unset
+    /// leaves them to the retries and the dead-letter file
+    #[serde(default)]
+    pub fallback_file: Option<String>,
+    /// Tenants whose audit actor and resource IDs are stored pseudonymized
+    /// (requires `crypto.audit_pseudonym_key`)
+    #[serde(default)]
+    pub pseudonymize_tenants: Vec<Uuid>,
+    #[serde(default)]
+    pub exports: AuditExportConfig,
+}
+
+/// Asynchronous audit exports, downloaded through signed URLs
+#[derive(Debug, Clone, Deserialize)]
+pub struct AuditExportConfig {
+    /// Directory export files are written to
+    pub directory: String,
+    /// How long a download URL stays valid
+    pub url_ttl_seconds: u64,
+}
+
+impl Default for AuditExportConfig {
+    fn default() -> Self {
+        Self {
+            directory: "data/audit-exports".to_string(),
+            url_ttl_seconds: 900,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct CryptoConfig {
     pub key_rotation_days: u32,
     pub key_overlap_days: u32,
+    pub audit_signing_key_id: String,
+    /// Base64-encoded Ed25519 secret key for audit signing (set via environment)
+    pub audit_signing_key: Option<String>,
+    /// Base64-encoded 32-byte AES-256-GCM key for MFA secrets (set via environment)
+    pub mfa_encryption_key: Option<String>,
+    /// Base64-encoded Ed25519 secret key that signs agent Biscuits (set via environment)
+    pub biscuit_root_key: Option<String>,
+    /// Base64-encoded key (at least 32 bytes) that signs pagination cursors (set via environment)
+    pub cursor_signing_key: Option<String>,
+    /// Base64-encoded key (at least 32 bytes) that signs download URLs (set via environment)
+    pub download_signing_key: Option<String>,
+    /// Base64-encoded key (at least 32 bytes) for audit ID pseudonyms (set via environment)
+    pub audit_pseudonym_key: Option<String>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -82,6 +389,11 @@ pub struct ObservabilityConfig {
     pub log_format: String,
     pub metrics_enabled: bool,
     pub tracing_enabled: bool,
+    /// How long readiness results are reused; 0 disables caching
+    pub health_cache_ttl_ms: u64,
+    /// Tenants given their own `tenant_id` metric label; all others are `other`
+    #[serde(default)]
+    pub metrics_tenants: Vec<Uuid>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -94,6 +406,101 @@ pub struct SecurityConfig {
     pub cors_allowed_methods: Vec<String>,
     pub cors_allowed_headers: Vec<String>,
     pub cors_max_age_seconds: usize,
+    /// Largest accepted request body; larger requests get 413 Payload Too Large
+    pub max_request_body_bytes: usize,
+    #[serde(default)]
+    pub headers: SecurityHeadersConfig,
+}
+
+/// Headers added to every HTTP response
+#[derive(Debug, Clone, Deserialize)]
+pub struct SecurityHeadersConfig {
+    pub enabled: bool,
+    /// `X-Frame-Options` value; empty to leave the header out
+    pub frame_options: String,
+    /// `Referrer-Policy` value; empty to leave the header out
+    pub referrer_policy: String,
+    /// Path prefixes whose responses get `Cache-Control: no-store`
+    pub no_store_paths: Vec<String>,
+}
+
+impl Default for SecurityHeadersConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            frame_options: "DENY".to_string(),
+            referrer_policy: "no-referrer".to_string(),
+            no_store_paths: vec![
+                "/v1/auth".to_string(),
+                "/v1/identities".to_string(),
+                "/v1/agents".to_string(),
+                "/oauth2/token".to_string(),
+            ],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct WebhookConfig {
+    pub enabled: bool,
+    /// Maximum number of payloads waiting for delivery before new ones are dropped
+    pub queue_size: usize,
+    pub max_concurrent_deliveries: usize,
+    pub max_retries: u32,
+    pub initial_backoff_ms: u64,
+    pub max_backoff_ms: u64,
+    pub request_timeout_seconds: u64,
+    #[serde(default)]
+    pub endpoints: Vec<WebhookEndpointConfig>,
+    /// Receives account notifications (password reset tokens) for delivery to
+    /// users; independent of `enabled`
+    #[serde(default)]
+    pub account: Option<AccountWebhookConfig>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct WebhookEndpointConfig {
+    pub url: String,
+    /// Shared secret used to HMAC-sign payloads for this endpoint
+    pub secret: String,
+    /// Audit event types delivered to this endpoint
+    pub event_types: Vec<AuditEventType>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct AccountWebhookConfig {
+    pub url: String,
+    /// Shared secret used to HMAC-sign account notifications
+    pub secret: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct WebauthnConfig {
+    /// Relying party ID: the domain passkeys are scoped to
+    pub rp_id: String,
+    /// Origin browsers report during ceremonies (scheme, host and port)
+    pub rp_origin: String,
+    /// Name shown to users by their authenticator
+    pub rp_name: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct OidcConfig {
+    /// Key ID published in the JWKS and set on id_token headers
+    pub signing_key_id: String,
+    /// Base64-encoded PKCS#8 P-256 private key for id_tokens (set via environment)
+    pub signing_key: Option<String>,
+    pub id_token_expiration_seconds: i64,
+    /// Registered relying parties
+    #[serde(default)]
+    pub clients: Vec<OidcClientConfig>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct OidcClientConfig {
+    pub client_id: String,
+    /// Exact redirect URIs the client may use
+    pub redirect_uris: Vec<String>,
 }
 
 impl Config {
@@ -135,6 +542,22 @@ impl Config {
         if self.server.port == 0 {
             return Err(AppError::Configuration("Invalid port number".to_string()));
         }
+        if self.server.grpc_port == 0 || self.server.grpc_port == self.server.port {
+            return Err(AppError::Configuration(
+                "gRPC port must be non-zero and differ from the HTTP port".to_string(),
+            ));
+        }
+        if self.server.shutdown_timeout_seconds == 0 {
+            return Err(AppError::Configuration(
+                "Shutdown timeout must be greater than zero".to_string(),
+            ));
+        }
+        if self.server.workers == 0 {
+            return Err(AppError::Configuration(
+                "Server workers must be greater than zero".to_string(),
+            ));
+        }
+        self.server.http_addr()?;
 
         // Validate database config
         if self.database.url.is_empty() {
@@ -157,6 +580,57 @@ impl Config {
             ));
         }
 
+        if self
+            .auth
+            .jwt_extra_audiences
+            .iter()
+            .any(|aud| aud.trim().is_empty())
+        {
+            return Err(AppError::Configuration(
+                "JWT audiences cannot be empty".to_string(),
+            ));
+        }
+
+        if !(0..=MAX_DELEGATION_DEPTH).contains(&self.auth.delegation_depth_warning) {
+            return Err(AppError::Configuration(format!(
+                "Delegation depth warning must be between 0 and {}",
+                MAX_DELEGATION_DEPTH
+            )));
+        }
+
+        let query_depth = self.auth.delegation_query_max_depth;
+        if query_depth != 0 && query_depth < MAX_DELEGATION_DEPTH {
+            return Err(AppError::Configuration(format!(
+                "Delegation query max depth must be 0 (default) or at least {}",
+                MAX_DELEGATION_DEPTH
+            )));
+        }
+
+        if self.security.max_request_body_bytes == 0 {
+            return Err(AppError::Configuration(
+                "Max request body size must be greater than zero".to_string(),
+            ));
+        }
+
+        for (name, value) in [
+            ("X-Frame-Options", &self.security.headers.frame_options),
+            ("Referrer-Policy", &self.security.headers.referrer_policy),
+        ] {
+            if axum::http::HeaderValue::from_str(value).is_err() {
+                return Err(AppError::Configuration(format!(
+                    "Invalid {} header value",
+                    name
+                )));
+            }
+        }
+
+        // Validate audit export config
+        if self.audit.exports.directory.is_empty() || self.audit.exports.url_ttl_seconds == 0 {
+            return Err(AppError::Configuration(
+                "Audit exports require a directory and a URL TTL greater than zero".to_string(),
+            ));
+        }
+
         // Validate TLS config
         if self.security.tls_enabled {
             if self.security.tls_cert_path.is_empty() || self.security.tls_key_path.is_empty() {
@@ -166,6 +640,79 @@ impl Config {
             }
         }
 
+        // Validate Redis retry config
+        if self.redis.retry.initial_backoff_ms > self.redis.retry.max_backoff_ms {
+            return Err(AppError::Configuration(
+                "Redis retry initial backoff cannot exceed the max backoff".to_string(),
+            ));
+        }
+
+        // Validate authz circuit breaker config
+        let breaker = &self.authz.circuit_breaker;
+        if breaker.failure_threshold == 0 || breaker.cooldown_seconds == 0 {
+            return Err(AppError::Configuration(
+                "Circuit breaker failure threshold and cooldown must be greater than zero"
+                    .to_string(),
+            ));
+        }
+
+        // Validate authz bulkhead config
+        let bulkhead = &self.authz.bulkhead;
+        if bulkhead.max_concurrent_per_tenant == 0
+            || bulkhead.tenant_overrides.values().any(|limit| *limit == 0)
+        {
+            return Err(AppError::Configuration(
+                "Authz concurrency limits must be greater than zero".to_string(),
+            ));
+        }
+
+        // Validate tenant policy limits
+        let policy_limits = &self.authz.policy_limits;
+        if policy_limits.max_policies_per_tenant <= 0
+            || policy_limits.max_policy_bytes_per_tenant <= 0
+        {
+            return Err(AppError::Configuration(
+                "Tenant policy limits must be greater than zero".to_string(),
+            ));
+        }
+
+        // Validate OIDC clients
+        if self.oidc.id_token_expiration_seconds <= 0 {
+            return Err(AppError::Configuration(
+                "OIDC id_token expiration must be greater than zero".to_string(),
+            ));
+        }
+        for client in &self.oidc.clients {
+            if client.client_id.is_empty() || client.redirect_uris.is_empty() {
+                return Err(AppError::Configuration(
+                    "OIDC clients require a client_id and at least one redirect URI".to_string(),
+                ));
+            }
+            for uri in &client.redirect_uris {
+                url::Url::parse(uri).map_err(|e| {
+                    AppError::Configuration(format!("Invalid OIDC redirect URI {}: {}", uri, e))
+                })?;
+            }
+        }
+
+        // Validate webhook config
+        if self.webhooks.enabled {
+            for endpoint in &self.webhooks.endpoints {
+                if endpoint.url.is_empty() || endpoint.secret.is_empty() {
+                    return Err(AppError::Configuration(
+                        "Webhook endpoints require a url and secret".to_string(),
+                    ));
+                }
+            }
+        }
+        if let Some(account) = &self.webhooks.account {
+            if account.url.is_empty() || account.secret.is_empty() {
+                return Err(AppError::Configuration(
+                    "The account webhook requires a url and secret".to_string(),
+                ));
+            }
+        }
+
         Ok(())
     }
 }
@@ -183,4 +730,39 @@ mod tests {
         config.server.port = 0;
         assert!(config.validate().is_err());
     }
+
+    fn server_config(host: &str) -> ServerConfig {
+        ServerConfig {
+            host: host.to_string(),
+            port: 8080,
+            workers: 4,
+            grpc_port: 50051,
+            shutdown_timeout_seconds: 30,
+        }
+    }
+
+    #[test]
+    fn test_server_host_parsing() {
+        let addr = server_config("127.0.0.1").http_addr().unwrap();
+        assert_eq!(addr.to_string(), "127.0.0.1:8080");
+
+        let addr = server_config("::1").http_addr().unwrap();
+        assert_eq!(addr.to_string(), "[::1]:8080");
+
+        let addr = server_config("[::]").grpc_addr().unwrap();
+        assert_eq!(addr.to_string(), "[::]:50051");
+    }
+
+    #[test]
+    fn test_invalid_server_host() {
+        for host in ["localhost", "256.0.0.1", "", "127.0.0.1:8080"] {
+            let err = server_config(host).http_addr().unwrap_err();
+            assert!(
+                matches!(&err, AppError::Configuration(msg) if msg.contains("Invalid server host")),
+                "{}: {}",
+                host,
+                err
+            );
+        }
+    }
 }
@@ -0,0 +1,196 @@
+// Opaque pagination cursors (HMAC-SHA256)
+
+use crate::config::CryptoConfig;
+use crate::errors::{AppError, Result};
+use base64::{
+    engine::general_purpose::{STANDARD as BASE64, URL_SAFE_NO_PAD},
+    Engine as _,
+};
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use rand::{rngs::OsRng, RngCore};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use uuid::Uuid;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Minimum cursor key length in bytes
+const MIN_KEY_LEN: usize = 32;
+
+/// Keyset position of the last item on a page
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CursorPosition {
+    pub timestamp: DateTime<Utc>,
+    pub id: Uuid,
+}
+
+/// Signed cursor contents; the tenant binds a cursor to the caller that got it
+#[derive(Serialize, Deserialize)]
+struct CursorPayload {
+    tenant_id: Uuid,
+    #[serde(flatten)]
+    position: CursorPosition,
+}
+
+/// Signs and verifies keyset pagination cursors
+///
+/// A cursor is `base64url(payload).base64url(hmac)`, so clients can pass it
+/// back but not forge one for another position or tenant.
+pub struct CursorSigner {
+    key: Vec<u8>,
+}
+
+impl CursorSigner {
+    /// Create a signer from a secret key of at least 32 bytes
+    pub fn new(key: &[u8]) -> Result<Self> {
+        if key.len() < MIN_KEY_LEN {
+            return Err(AppError::Cryptographic(format!(
+                "Cursor signing key must be at least {} bytes, got {}",
+                MIN_KEY_LEN,
+                key.len()
+            )));
+        }
+
+        Ok(Self { key: key.to_vec() })
+    }
+
+    /// Create a signer with a random key
+    pub fn generate() -> Self {
+        let mut key = vec![0u8; MIN_KEY_LEN];
+        OsRng.fill_bytes(&mut key);
+        Self { key }
+    }
+
+    /// Load the signer from configuration
+    ///
+    /// The key is base64-encoded, normally provided via the
+    /// `AGENT_IAM__CRYPTO__CURSOR_SIGNING_KEY` environment variable. Without
+    /// one a key is generated, and cursors stop working after a restart or on
+    /// another replica.
+    pub fn from_config(config: &CryptoConfig) -> Result<Self> {
+        let Some(encoded) = config.cursor_signing_key.as_deref() else {
+            tracing::warn!("Cursor signing key not configured; generating an ephemeral key");
+            return Ok(Self::generate());
+        };
+
+        let bytes = BASE64.decode(encoded.trim()).map_err(|e| {
+            AppError::Configuration(format!("Cursor signing key is not valid base64: {}", e))
+        })?;
+
+        Self::new(&bytes)
+    }
+
+    /// Encode a position as an opaque cursor for `tenant_id`
+    pub fn encode_cursor(&self, tenant_id: Uuid, position: &CursorPosition) -> Result<String> {
+        let payload = serde_json::to_vec(&CursorPayload {
+            tenant_id,
+            position: position.clone(),
+        })
+        .map_err(|e| AppError::Internal(format!("Failed to serialize cursor: {}", e)))?;
+
+        let signature = self.mac(&payload)?.finalize().into_bytes();
+
+        Ok(format!(
+            "{}.{}",
+            URL_SAFE_NO_PAD.encode(&payload),
+            URL_SAFE_NO_PAD.encode(signature)
+        ))
+    }
+
+    /// Decode a cursor issued to `tenant_id`
+    ///
+    /// Malformed, tampered and cross-tenant cursors are all rejected with the
+    /// same error.
+    pub fn decode_cursor(&self, tenant_id: Uuid, cursor: &str) -> Result<CursorPosition> {
+        let invalid = || AppError::ValidationError("Invalid pagination cursor".to_string());
+
+        let (payload, signature) = cursor.split_once('.').ok_or_else(invalid)?;
+        let payload = URL_SAFE_NO_PAD.decode(payload).map_err(|_| invalid())?;
+        let signature = URL_SAFE_NO_PAD.decode(signature).map_err(|_| invalid())?;
+
+        self.mac(&payload)?
+            .verify_slice(&signature)
+            .map_err(|_| invalid())?;
+
+        let payload: CursorPayload = serde_json::from_slice(&payload).map_err(|_| invalid())?;
+        if payload.tenant_id != tenant_id {
+            return Err(invalid());
+        }
+
+        Ok(payload.position)
+    }
+
+    fn mac(&self, payload: &[u8]) -> Result<HmacSha256> {
+        let mut mac = HmacSha256::new_from_slice(&self.key)
+            .map_err(|e| AppError::Cryptographic(format!("Invalid cursor key: {}", e)))?;
+        mac.update(payload);
+        Ok(mac)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn position() -> CursorPosition {
+        CursorPosition {
+            timestamp: DateTime::parse_from_rfc3339("2026-02-12T10:00:00.123456Z")
+                .unwrap()
+                .with_timezone(&Utc),
+            id: Uuid::new_v4(),
+        }
+    }
+
+    #[test]
+    fn test_cursor_round_trip() {
+        let signer = CursorSigner::generate();
+        let tenant_id = Uuid::new_v4();
+        let position = position();
+
+        let cursor = signer.encode_cursor(tenant_id, &position).unwrap();
+
+        assert_eq!(signer.decode_cursor(tenant_id, &cursor).unwrap(), position);
+    }
+
+    #[test]
+    fn test_tampered_cursor_rejected() {
+        let signer = CursorSigner::generate();
+        let tenant_id = Uuid::new_v4();
+        let cursor = signer.encode_cursor(tenant_id, &position()).unwrap();
+        let (_, signature) = cursor.split_once('.').unwrap();
+
+        // Same signature over a different position
+        let forged_payload = serde_json::to_vec(&CursorPayload {
+            tenant_id,
+            position: position(),
+        })
+        .unwrap();
+        let forged = format!("{}.{}", URL_SAFE_NO_PAD.encode(forged_payload), signature);
+
+        for cursor in [forged.as_str(), "not-a-cursor", "e30.e30", ""] {
+            assert!(matches!(
+                signer.decode_cursor(tenant_id, cursor),
+                Err(AppError::ValidationError(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_cursor_bound_to_tenant_and_key() {
+        let signer = CursorSigner::generate();
+        let tenant_id = Uuid::new_v4();
+        let cursor = signer.encode_cursor(tenant_id, &position()).unwrap();
+
+        assert!(signer.decode_cursor(Uuid::new_v4(), &cursor).is_err());
+        assert!(CursorSigner::generate()
+            .decode_cursor(tenant_id, &cursor)
+            .is_err());
+    }
+
+    #[test]
+    fn test_short_key_rejected() {
+        assert!(CursorSigner::new(&[0u8; 16]).is_err());
+        assert!(CursorSigner::new(&[0u8; 32]).is_ok());
+    }
+}
@@ -0,0 +1,185 @@
+// Signed, expiring download tokens (HMAC-SHA256)
+
+use crate::config::CryptoConfig;
+use crate::errors::{AppError, Result};
+use base64::{
+    engine::general_purpose::{STANDARD as BASE64, URL_SAFE_NO_PAD},
+    Engine as _,
+};
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use rand::{rngs::OsRng, RngCore};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Minimum download signing key length in bytes
+const MIN_KEY_LEN: usize = 32;
+
+/// Signed token contents
+#[derive(Serialize, Deserialize)]
+struct DownloadPayload {
+    path: String,
+    /// Expiry as a Unix timestamp
+    exp: i64,
+}
+
+/// Signs and verifies download tokens
+///
+/// A token is `base64url(payload).base64url(hmac)` over the file path and its
+/// expiry, so whoever holds the URL can fetch that one file until it expires
+/// but cannot point it at another file or extend it.
+pub struct DownloadSigner {
+    key: Vec<u8>,
+}
+
+impl DownloadSigner {
+    /// Create a signer from a secret key of at least 32 bytes
+    pub fn new(key: &[u8]) -> Result<Self> {
+        if key.len() < MIN_KEY_LEN {
+            return Err(AppError::Cryptographic(format!(
+                "Download signing key must be at least {} bytes, got {}",
+                MIN_KEY_LEN,
+                key.len()
+            )));
+        }
+
+        Ok(Self { key: key.to_vec() })
+    }
+
+    /// Create a signer with a random key
+    pub fn generate() -> Self {
+        let mut key = vec![0u8; MIN_KEY_LEN];
+        OsRng.fill_bytes(&mut key);
+        Self { key }
+    }
+
+    /// Load the signer from configuration
+    ///
+    /// The key is base64-encoded, normally provided via the
+    /// `AGENT_IAM__CRYPTO__DOWNLOAD_SIGNING_KEY` environment variable. Without
+    /// one a key is generated, and download URLs stop working after a restart
+    /// or on another replica.
+    pub fn from_config(config: &CryptoConfig) -> Result<Self> {
+        let Some(encoded) = config.download_signing_key.as_deref() else {
+            tracing::warn!("Download signing key not configured; generating an ephemeral key");
+            return Ok(Self::generate());
+        };
+
+        let bytes = BASE64.decode(encoded.trim()).map_err(|e| {
+            AppError::Configuration(format!("Download signing key is not valid base64: {}", e))
+        })?;
+
+        Self::new(&bytes)
+    }
+
+    /// Sign a token for `path` that is valid until `expires_at`
+    pub fn sign(&self, path: &str, expires_at: DateTime<Utc>) -> Result<String> {
+        let payload = serde_json::to_vec(&DownloadPayload {
+            path: path.to_string(),
+            exp: expires_at.timestamp(),
+        })
+        .map_err(|e| AppError::Internal(format!("Failed to serialize download token: {}", e)))?;
+
+        let signature = self.mac(&payload)?.finalize().into_bytes();
+
+        Ok(format!(
+            "{}.{}",
+            URL_SAFE_NO_PAD.encode(&payload),
+            URL_SAFE_NO_PAD.encode(signature)
+        ))
+    }
+
+    /// Verify a token and return the path it was signed for
+    ///
+    /// Malformed, tampered and expired tokens are all rejected as forbidden.
+    pub fn verify(&self, token: &str, now: DateTime<Utc>) -> Result<String> {
+        let (payload, signature) = token.split_once('.').ok_or(AppError::Forbidden)?;
+        let payload = URL_SAFE_NO_PAD
+            .decode(payload)
+            .map_err(|_| AppError::Forbidden)?;
+        let signature = URL_SAFE_NO_PAD
+            .decode(signature)
+            .map_err(|_| AppError::Forbidden)?;
+
+        self.mac(&payload)?
+            .verify_slice(&signature)
+            .map_err(|_| AppError::Forbidden)?;
+
+        let payload: DownloadPayload =
+            serde_json::from_slice(&payload).map_err(|_| AppError::Forbidden)?;
+        if payload.exp <= now.timestamp() {
+            return Err(AppError::Forbidden);
+        }
+
+        Ok(payload.path)
+    }
+
+    fn mac(&self, payload: &[u8]) -> Result<HmacSha256> {
+        let mut mac = HmacSha256::new_from_slice(&self.key)
+            .map_err(|e| AppError::Cryptographic(format!("Invalid download key: {}", e)))?;
+        mac.update(payload);
+        Ok(mac)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn test_token_round_trip() {
+        let signer = DownloadSigner::generate();
+        let token = signer
+            .sign("tenant/export.ndjson", Utc::now() + Duration::minutes(5))
+            .unwrap();
+
+        assert_eq!(
+            signer.verify(&token, Utc::now()).unwrap(),
+            "tenant/export.ndjson"
+        );
+    }
+
+    #[test]
+    fn test_tampered_or_expired_token_rejected() {
+        let signer = DownloadSigner::generate();
+        let expires_at = Utc::now() + Duration::minutes(5);
+        let token = signer.sign("tenant/a.ndjson", expires_at).unwrap();
+        let (_, signature) = token.split_once('.').unwrap();
+
+        // Same signature over another path, and over a later expiry
+        let other_path = serde_json::to_vec(&DownloadPayload {
+            path: "tenant/b.ndjson".to_string(),
+            exp: expires_at.timestamp(),
+        })
+        .unwrap();
+        let extended = serde_json::to_vec(&DownloadPayload {
+            path: "tenant/a.ndjson".to_string(),
+            exp: expires_at.timestamp() + 3600,
+        })
+        .unwrap();
+
+        for forged in [other_path, extended] {
+            let forged = format!("{}.{}", URL_SAFE_NO_PAD.encode(forged), signature);
+            assert!(matches!(
+                signer.verify(&forged, Utc::now()),
+                Err(AppError::Forbidden)
+            ));
+        }
+        assert!(signer.verify("not-a-token", Utc::now()).is_err());
+        assert!(signer
+            .verify(&token, expires_at + Duration::seconds(1))
+            .is_err());
+        assert!(DownloadSigner::generate()
+            .verify(&token, Utc::now())
+            .is_err());
+    }
+
+    #[test]
+    fn test_short_key_rejected() {
+        assert!(DownloadSigner::new(&[0u8; 16]).is_err());
+        assert!(DownloadSigner::new(&[0u8; 32]).is_ok());
+    }
+}
@@ -0,0 +1,126 @@
+// Symmetric encryption of secrets at rest (AES-256-GCM)
+
+use crate::config::CryptoConfig;
+use crate::errors::{AppError, Result};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use rand::{rngs::OsRng, RngCore};
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, NONCE_LEN};
+
+/// Encrypts small secrets (such as TOTP seeds) before they are stored
+///
+/// Each ciphertext is base64(nonce || ciphertext || tag) with a fresh random
+/// nonce, so the same plaintext never encrypts to the same value twice.
+pub struct SecretCipher {
+    key: LessSafeKey,
+}
+
+impl SecretCipher {
+    /// Create a cipher from a 32-byte key
+    pub fn from_bytes(key_bytes: &[u8]) -> Result<Self> {
+        let key = UnboundKey::new(&AES_256_GCM, key_bytes).map_err(|_| {
+            AppError::Cryptographic(format!(
+                "Invalid encryption key length: expected 32 bytes, got {}",
+                key_bytes.len()
+            ))
+        })?;
+
+        Ok(Self {
+            key: LessSafeKey::new(key),
+        })
+    }
+
+    /// Load the MFA secret cipher from configuration
+    ///
+    /// Returns `None` when no key is configured, in which case MFA enrollment
+    /// is unavailable.
+    pub fn from_config(config: &CryptoConfig) -> Result<Option<Self>> {
+        let Some(encoded) = config.mfa_encryption_key.as_deref() else {
+            return Ok(None);
+        };
+
+        let bytes = BASE64.decode(encoded.trim()).map_err(|e| {
+            AppError::Configuration(format!("MFA encryption key is not valid base64: {}", e))
+        })?;
+
+        Self::from_bytes(&bytes).map(Some)
+    }
+
+    /// Encrypt a secret, returning a base64-encoded string
+    pub fn encrypt(&self, plaintext: &[u8]) -> Result<String> {
+        let mut nonce_bytes = [0u8; NONCE_LEN];
+        OsRng.fill_bytes(&mut nonce_bytes);
+
+        let mut in_out = plaintext.to_vec();
+        self.key
+            .seal_in_place_append_tag(
+                Nonce::assume_unique_for_key(nonce_bytes),
+                Aad::empty(),
+                &mut in_out,
+            )
+            .map_err(|_| AppError::Cryptographic("Failed to encrypt secret".to_string()))?;
+
+        let mut output = nonce_bytes.to_vec();
+        output.extend_from_slice(&in_out);
+        Ok(BASE64.encode(output))
+    }
+
+    /// Decrypt a value produced by `encrypt`
+    pub fn decrypt(&self, encoded: &str) -> Result<Vec<u8>> {
+        let bytes = BASE64
+            .decode(encoded)
+            .map_err(|e| AppError::Cryptographic(format!("Encrypted secret is not valid base64: {}", e)))?;
+
+        if bytes.len() < NONCE_LEN {
+            return Err(AppError::Cryptographic("Encrypted secret is too short".to_string()));
+        }
+
+        let (nonce_bytes, ciphertext) = bytes.split_at(NONCE_LEN);
+        let nonce = Nonce::try_assume_unique_for_key(nonce_bytes)
+            .map_err(|_| AppError::Cryptographic("Invalid nonce".to_string()))?;
+
+        let mut in_out = ciphertext.to_vec();
+        let plaintext = self
+            .key
+            .open_in_place(nonce, Aad::empty(), &mut in_out)
+            .map_err(|_| AppError::Cryptographic("Failed to decrypt secret".to_string()))?;
+
+        Ok(plaintext.to_vec())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cipher() -> SecretCipher {
+        SecretCipher::from_bytes(&[7u8; 32]).unwrap()
+    }
+
+    #[test]
+    fn test_encrypt_decrypt_round_trip() {
+        let cipher = cipher();
+        let encrypted = cipher.encrypt(b"totp-seed").unwrap();
+
+        assert_ne!(encrypted.as_bytes(), b"totp-seed");
+        assert_eq!(cipher.decrypt(&encrypted).unwrap(), b"totp-seed");
+    }
+
+    #[test]
+    fn test_encryption_uses_fresh_nonce() {
+        let cipher = cipher();
+        assert_ne!(cipher.encrypt(b"same").unwrap(), cipher.encrypt(b"same").unwrap());
+    }
+
+    #[test]
+    fn test_decrypt_with_wrong_key_fails() {
+        let encrypted = cipher().encrypt(b"totp-seed").unwrap();
+        let other = SecretCipher::from_bytes(&[8u8; 32]).unwrap();
+
+        assert!(other.decrypt(&encrypted).is_err());
+    }
+
+    #[test]
+    fn test_invalid_key_length_rejected() {
+        assert!(SecretCipher::from_bytes(&[0u8; 16]).is_err());
+    }
+}
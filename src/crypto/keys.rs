@@ -1,2 +1,364 @@
 // Key generation and rotation
-// To be implemented in later phases
+//
+// A key manager holds the active Ed25519 signing key for one purpose and the
+// retired keys still accepted for verification. Rotated keys are persisted and
+// shared between instances by `crypto::key_store`; the manager only holds what
+// was last loaded from there, plus the key it was started with.
+
+use crate::auth::biscuit::BiscuitManager;
+use crate::config::CryptoConfig;
+use crate::crypto::signing::AuditSigner;
+use crate::errors::{AppError, Result};
+use chrono::{DateTime, Duration, Utc};
+use ed25519_dalek::{SigningKey, VerifyingKey};
+use rand::rngs::OsRng;
+use std::sync::RwLock;
+use tracing::info;
+
+/// What a managed key is used for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyPurpose {
+    Biscuit,
+    AuditSigning,
+}
+
+impl KeyPurpose {
+    pub fn as_str(&self) -> &str {
+        match self {
+            KeyPurpose::Biscuit => "biscuit",
+            KeyPurpose::AuditSigning => "audit",
+        }
+    }
+}
+
+/// An Ed25519 key tracked by the key manager
+#[derive(Debug, Clone)]
+pub struct ManagedKey {
+    pub key_id: String,
+    pub signing_key: SigningKey,
+    pub created_at: DateTime<Utc>,
+    /// When the key stopped being the active signing key (None while active)
+    pub retired_at: Option<DateTime<Utc>>,
+}
+
+impl ManagedKey {
+    pub(crate) fn generate(purpose: KeyPurpose, now: DateTime<Utc>) -> Self {
+        Self {
+            key_id: format!("{}-{}", purpose.as_str(), now.format("%Y%m%d%H%M%S%3f")),
+            signing_key: SigningKey::generate(&mut OsRng),
+            created_at: now,
+            retired_at: None,
+        }
+    }
+
+    /// Track an existing Ed25519 secret, e.g. a key loaded from configuration
+    pub fn from_secret(key_id: String, secret_key_bytes: &[u8]) -> Result<Self> {
+        let secret: [u8; 32] = secret_key_bytes.try_into().map_err(|_| {
+            AppError::Cryptographic("Ed25519 secret key must be 32 bytes".to_string())
+        })?;
+
+        Ok(Self {
+            key_id,
+            signing_key: SigningKey::from_bytes(&secret),
+            created_at: Utc::now(),
+            retired_at: None,
+        })
+    }
+
+    /// Get the public key for this key
+    pub fn verifying_key(&self) -> VerifyingKey {
+        self.signing_key.verifying_key()
+    }
+
+    /// Get the secret key bytes
+    pub fn secret_key_bytes(&self) -> Vec<u8> {
+        self.signing_key.to_bytes().to_vec()
+    }
+
+    /// Build an audit signer from this key
+    pub fn audit_signer(&self) -> Result<AuditSigner> {
+        AuditSigner::from_bytes(self.key_id.clone(), &self.secret_key_bytes())
+    }
+
+    /// Build a Biscuit manager from this key
+    pub fn biscuit_manager(&self) -> Result<BiscuitManager> {
+        BiscuitManager::from_private_key(self.key_id.clone(), &self.secret_key_bytes())
+    }
+}
+
+/// Active key plus retired keys still accepted for verification
+#[derive(Debug)]
+struct KeyRing {
+    active: ManagedKey,
+    retired: Vec<ManagedKey>,
+}
+
+/// Rotating key manager
+///
+/// Keeps one active signing key plus any retired keys that are still within the
+/// overlap window, so tokens and signatures produced just before a rotation
+/// remain verifiable until the overlap elapses. A zero rotation period turns
+/// rotation off.
+pub struct KeyManager {
+    purpose: KeyPurpose,
+    rotation_period: Duration,
+    overlap_period: Duration,
+    /// Key the manager was started with, active until the first rotation
+    initial: ManagedKey,
+    ring: RwLock<KeyRing>,
+}
+
+impl KeyManager {
+    /// Create a key manager with an initial freshly generated key
+    pub fn new(purpose: KeyPurpose, rotation_period: Duration, overlap_period: Duration) -> Self {
+        Self::with_initial_key(
+            purpose,
+            rotation_period,
+            overlap_period,
+            ManagedKey::generate(purpose, Utc::now()),
+        )
+    }
+
+    /// Create a key manager seeded with an existing key
+    pub fn with_initial_key(
+        purpose: KeyPurpose,
+        rotation_period: Duration,
+        overlap_period: Duration,
+        initial_key: ManagedKey,
+    ) -> Self {
+        info!(
+            purpose = purpose.as_str(),
+            key_id = %initial_key.key_id,
+            "Initializing key manager"
+        );
+
+        Self {
+            purpose,
+            rotation_period,
+            overlap_period,
+            initial: initial_key.clone(),
+            ring: RwLock::new(KeyRing {
+                active: initial_key,
+                retired: Vec::new(),
+            }),
+        }
+    }
+
+    /// Create a key manager using the rotation settings from configuration
+    pub fn from_config(purpose: KeyPurpose, config: &CryptoConfig) -> Self {
+        Self::from_config_with_key(purpose, config, ManagedKey::generate(purpose, Utc::now()))
+    }
+
+    /// Create a key manager using the rotation settings from configuration,
+    /// starting from an existing key
+    pub fn from_config_with_key(
+        purpose: KeyPurpose,
+        config: &CryptoConfig,
+        initial_key: ManagedKey,
+    ) -> Self {
+        Self::with_initial_key(
+            purpose,
+            Duration::days(config.key_rotation_days as i64),
+            Duration::days(config.key_overlap_days as i64),
+            initial_key,
+        )
+    }
+
+    /// Get the key currently used for signing
+    pub fn current_signing_key(&self) -> Result<ManagedKey> {
+        Ok(self.read_ring()?.active.clone())
+    }
+
+    /// Get all keys that are valid for verification right now
+    pub fn verification_keys(&self) -> Result<Vec<ManagedKey>> {
+        self.verification_keys_at(Utc::now())
+    }
+
+    /// Get all keys that are valid for verification at the given time
+    ///
+    /// The active key is always first, followed by retired keys still in overlap.
+    pub fn verification_keys_at(&self, now: DateTime<Utc>) -> Result<Vec<ManagedKey>> {
+        let ring = self.read_ring()?;
+        Ok(std::iter::once(&ring.active)
+            .chain(ring.retired.iter().filter(|k| self.is_within_overlap(k, now)))
+            .cloned()
+            .collect())
+    }
+
+    /// What the managed keys are used for
+    pub fn purpose(&self) -> KeyPurpose {
+        self.purpose
+    }
+
+    /// How long retired keys stay valid for verification
+    pub fn overlap_period(&self) -> Duration {
+        self.overlap_period
+    }
+
+    /// The key the manager was started with
+    pub fn initial_key(&self) -> &ManagedKey {
+        &self.initial
+    }
+
+    /// Whether a key that became active at `since` is due for rotation at `now`
+    pub fn rotation_due(&self, since: DateTime<Utc>, now: DateTime<Utc>) -> bool {
+        self.rotation_period > Duration::zero() && now - since >= self.rotation_period
+    }
+
+    /// Replace the keys with rotated ones, newest (the active key) first
+    ///
+    /// The initial key is kept as retired since `first_rotated_at`, the time
+    /// the first rotated key was created, unless it is among `rotated`.
+    pub fn install(&self, rotated: Vec<ManagedKey>, first_rotated_at: DateTime<Utc>) -> Result<()> {
+        let mut keys = rotated.into_iter();
+        let Some(active) = keys.next() else {
+            return Ok(());
+        };
+        let mut retired: Vec<ManagedKey> = keys.collect();
+        if active.key_id != self.initial.key_id
+            && retired.iter().all(|k| k.key_id != self.initial.key_id)
+        {
+            let mut initial = self.initial.clone();
+            initial.retired_at = Some(first_rotated_at);
+            retired.push(initial);
+        }
+
+        let mut ring = self
+            .ring
+            .write()
+            .map_err(|_| AppError::Internal("Key ring lock poisoned".to_string()))?;
+        if ring.active.key_id != active.key_id {
+            info!(
+                purpose = self.purpose.as_str(),
+                key_id = %active.key_id,
+                "Installed rotated signing key"
+            );
+        }
+        *ring = KeyRing { active, retired };
+        Ok(())
+    }
+
+    fn read_ring(&self) -> Result<std::sync::RwLockReadGuard<'_, KeyRing>> {
+        self.ring
+            .read()
+            .map_err(|_| AppError::Internal("Key ring lock poisoned".to_string()))
+    }
+
+    fn is_within_overlap(&self, key: &ManagedKey, now: DateTime<Utc>) -> bool {
+        match key.retired_at {
+            None => true,
+            Some(retired_at) => now - retired_at < self.overlap_period,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn short_manager() -> KeyManager {
+        KeyManager::new(
+            KeyPurpose::AuditSigning,
+            Duration::seconds(10),
+            Duration::seconds(5),
+        )
+    }
+
+    #[test]
+    fn test_rotation_due_after_period() {
+        let manager = short_manager();
+        let since = manager.current_signing_key().unwrap().created_at;
+
+        assert!(!manager.rotation_due(since, since + Duration::seconds(5)));
+        assert!(manager.rotation_due(since, since + Duration::seconds(10)));
+    }
+
+    #[test]
+    fn test_installed_rotation_keeps_initial_key_during_overlap() {
+        let manager = short_manager();
+        let original = manager.current_signing_key().unwrap();
+        let rotation_time = original.created_at + Duration::seconds(11);
+        let rotated = ManagedKey::generate(KeyPurpose::AuditSigning, rotation_time);
+
+        manager
+            .install(vec![rotated.clone()], rotation_time)
+            .unwrap();
+        assert_eq!(
+            manager.current_signing_key().unwrap().key_id,
+            rotated.key_id
+        );
+
+        // Old key still verifies within the overlap window
+        let keys = manager
+            .verification_keys_at(rotation_time + Duration::seconds(2))
+            .unwrap();
+        assert_eq!(keys.len(), 2);
+        assert_eq!(keys[0].key_id, rotated.key_id);
+        assert_eq!(keys[1].key_id, original.key_id);
+    }
+
+    #[test]
+    fn test_old_key_drops_out_after_overlap() {
+        let manager = short_manager();
+        let original = manager.current_signing_key().unwrap();
+        let first_rotation = original.created_at + Duration::seconds(11);
+        let mut first = ManagedKey::generate(KeyPurpose::AuditSigning, first_rotation);
+        let second_rotation = first_rotation + Duration::seconds(11);
+        let second = ManagedKey::generate(KeyPurpose::AuditSigning, second_rotation);
+        first.retired_at = Some(second_rotation);
+
+        manager
+            .install(vec![second.clone(), first.clone()], first_rotation)
+            .unwrap();
+
+        let keys = manager
+            .verification_keys_at(second_rotation + Duration::seconds(2))
+            .unwrap();
+        let ids: Vec<&str> = keys.iter().map(|k| k.key_id.as_str()).collect();
+        assert_eq!(ids, vec![second.key_id.as_str(), first.key_id.as_str()]);
+
+        let keys = manager
+            .verification_keys_at(second_rotation + Duration::seconds(6))
+            .unwrap();
+        assert_eq!(keys.len(), 1);
+        assert_eq!(keys[0].key_id, second.key_id);
+    }
+
+    #[test]
+    fn test_installing_nothing_keeps_initial_key() {
+        let manager = short_manager();
+        let original = manager.current_signing_key().unwrap();
+
+        manager.install(Vec::new(), Utc::now()).unwrap();
+        assert_eq!(
+            manager.current_signing_key().unwrap().key_id,
+            original.key_id
+        );
+    }
+
+    #[test]
+    fn test_zero_rotation_period_never_due() {
+        let manager = KeyManager::new(KeyPurpose::Biscuit, Duration::zero(), Duration::zero());
+        let since = manager.current_signing_key().unwrap().created_at;
+
+        assert!(!manager.rotation_due(since, since + Duration::days(365)));
+    }
+
+    #[test]
+    fn test_from_secret_keeps_configured_key() {
+        let secret = [9u8; 32];
+        let key = ManagedKey::from_secret("configured".to_string(), &secret).unwrap();
+        assert_eq!(key.secret_key_bytes(), secret.to_vec());
+        assert!(ManagedKey::from_secret("short".to_string(), &secret[..16]).is_err());
+    }
+
+    #[test]
+    fn test_rotated_key_builds_signers() {
+        let manager = short_manager();
+        let key = manager.current_signing_key().unwrap();
+
+        let signer = key.audit_signer().unwrap();
+        assert_eq!(signer.key_id(), key.key_id);
+        assert_eq!(signer.public_key_bytes(), key.verifying_key().to_bytes().to_vec());
+    }
+}
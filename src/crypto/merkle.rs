@@ -0,0 +1,247 @@
+// Merkle trees over audit event hashes
+
+use crate::errors::{AppError, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// Domain separation prefixes so a leaf can never be confused with an inner node
+const LEAF_PREFIX: u8 = 0x00;
+const NODE_PREFIX: u8 = 0x01;
+
+/// A Merkle tree built over hex-encoded leaf hashes
+///
+/// `levels[0]` holds the hashed leaves and the last level holds the root.
+/// An unpaired node at the end of a level is promoted unchanged to the next
+/// level rather than duplicated, which avoids the duplicate-leaf ambiguity.
+#[derive(Debug, Clone)]
+pub struct MerkleTree {
+    levels: Vec<Vec<[u8; 32]>>,
+}
+
+impl MerkleTree {
+    /// Get the hex-encoded root hash
+    pub fn root(&self) -> String {
+        self.levels
+            .last()
+            .and_then(|level| level.first())
+            .map(hex::encode)
+            .unwrap_or_default()
+    }
+
+    /// Get the number of leaves in the tree
+    pub fn leaf_count(&self) -> usize {
+        self.levels.first().map(|l| l.len()).unwrap_or(0)
+    }
+}
+
+/// Which side a sibling hash sits on when recombining
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SiblingPosition {
+    Left,
+    Right,
+}
+
+/// A single step in an inclusion proof
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProofStep {
+    pub hash: String,
+    pub position: SiblingPosition,
+}
+
+/// Proof that a leaf is included in a tree with a given root
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InclusionProof {
+    pub leaf_index: usize,
+    pub leaf_hash: String,
+    pub path: Vec<ProofStep>,
+    pub root: String,
+}
+
+/// Build a Merkle tree over hex-encoded leaf hashes (e.g. audit event hashes)
+pub fn build_tree(leaves: &[String]) -> Result<MerkleTree> {
+    if leaves.is_empty() {
+        return Err(AppError::ValidationError(
+            "Cannot build a Merkle tree with no leaves".to_string(),
+        ));
+    }
+
+    let mut level = leaves
+        .iter()
+        .map(|leaf| decode_hash(leaf).map(|bytes| hash_leaf(&bytes)))
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut levels = Vec::new();
+    while level.len() > 1 {
+        let next = level
+            .chunks(2)
+            .map(|pair| match pair {
+                [left, right] => hash_node(left, right),
+                _ => pair[0],
+            })
+            .collect();
+        levels.push(level);
+        level = next;
+    }
+    levels.push(level);
+
+    Ok(MerkleTree { levels })
+}
+
+/// Produce an inclusion proof for the leaf at `index`
+pub fn inclusion_proof(tree: &MerkleTree, index: usize) -> Result<InclusionProof> {
+    if index >= tree.leaf_count() {
+        return Err(AppError::ValidationError(format!(
+            "Leaf index {} out of range (tree has {} leaves)",
+            index,
+            tree.leaf_count()
+        )));
+    }
+
+    let mut path = Vec::new();
+    let mut position = index;
+
+    // Walk every level except the root
+    for level in &tree.levels[..tree.levels.len() - 1] {
+        let sibling = if position % 2 == 0 {
+            level
+                .get(position + 1)
+                .map(|hash| (hash, SiblingPosition::Right))
+        } else {
+            Some((&level[position - 1], SiblingPosition::Left))
+        };
+
+        // An unpaired node is promoted without a sibling
+        if let Some((hash, side)) = sibling {
+            path.push(ProofStep {
+                hash: hex::encode(hash),
+                position: side,
+            });
+        }
+
+        position /= 2;
+    }
+
+    Ok(InclusionProof {
+        leaf_index: index,
+        leaf_hash: hex::encode(tree.levels[0][index]),
+        path,
+        root: tree.root(),
+    })
+}
+
+/// Verify that `leaf` (a hex-encoded event hash) is included under `root`
+pub fn verify_proof(leaf: &str, proof: &InclusionProof, root: &str) -> Result<bool> {
+    let mut current = hash_leaf(&decode_hash(leaf)?);
+
+    if hex::encode(current) != proof.leaf_hash {
+        return Ok(false);
+    }
+
+    for step in &proof.path {
+        let sibling = decode_digest(&step.hash)?;
+        current = match step.position {
+            SiblingPosition::Left => hash_node(&sibling, &current),
+            SiblingPosition::Right => hash_node(&current, &sibling),
+        };
+    }
+
+    Ok(hex::encode(current) == root)
+}
+
+fn decode_hash(hex_hash: &str) -> Result<Vec<u8>> {
+    hex::decode(hex_hash)
+        .map_err(|e| AppError::ValidationError(format!("Invalid hex hash: {}", e)))
+}
+
+fn decode_digest(hex_hash: &str) -> Result<[u8; 32]> {
+    decode_hash(hex_hash)?
+        .try_into()
+        .map_err(|_| AppError::ValidationError("Hash must be 32 bytes".to_string()))
+}
+
+fn hash_leaf(data: &[u8]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update([LEAF_PREFIX]);
+    hasher.update(data);
+    hasher.finalize().into()
+}
+
+fn hash_node(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update([NODE_PREFIX]);
+    hasher.update(left);
+    hasher.update(right);
+    hasher.finalize().into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn leaves(count: usize) -> Vec<String> {
+        (0..count)
+            .map(|i| hex::encode(Sha256::digest(format!("event-{}", i).as_bytes())))
+            .collect()
+    }
+
+    #[test]
+    fn test_build_tree_root_is_deterministic() {
+        let leaves = leaves(5);
+        let root1 = build_tree(&leaves).unwrap().root();
+        let root2 = build_tree(&leaves).unwrap().root();
+
+        assert_eq!(root1.len(), 64);
+        assert_eq!(root1, root2);
+    }
+
+    #[test]
+    fn test_empty_tree_rejected() {
+        assert!(build_tree(&[]).is_err());
+    }
+
+    #[test]
+    fn test_proof_round_trip_for_every_leaf() {
+        for count in [1, 2, 3, 4, 7, 8, 13] {
+            let leaves = leaves(count);
+            let tree = build_tree(&leaves).unwrap();
+            let root = tree.root();
+
+            for (index, leaf) in leaves.iter().enumerate() {
+                let proof = inclusion_proof(&tree, index).unwrap();
+                assert_eq!(proof.root, root);
+                assert!(
+                    verify_proof(leaf, &proof, &root).unwrap(),
+                    "proof for leaf {} of {} should verify",
+                    index,
+                    count
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_proof_fails_for_wrong_leaf() {
+        let leaves = leaves(6);
+        let tree = build_tree(&leaves).unwrap();
+        let proof = inclusion_proof(&tree, 2).unwrap();
+
+        assert!(!verify_proof(&leaves[3], &proof, &tree.root()).unwrap());
+    }
+
+    #[test]
+    fn test_proof_fails_for_wrong_root() {
+        let leaves = leaves(6);
+        let tree = build_tree(&leaves).unwrap();
+        let proof = inclusion_proof(&tree, 2).unwrap();
+        let other_root = build_tree(&leaves[..5]).unwrap().root();
+
+        assert!(!verify_proof(&leaves[2], &proof, &other_root).unwrap());
+    }
+
+    #[test]
+    fn test_proof_index_out_of_range() {
+        let tree = build_tree(&leaves(3)).unwrap();
+        assert!(inclusion_proof(&tree, 3).is_err());
+    }
+}
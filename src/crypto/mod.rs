@@ -1,4 +1,10 @@
 // Cryptography module
 pub mod keys;
+pub mod key_store;
 pub mod signing;
 pub mod kms;
+pub mod merkle;
+pub mod encryption;
+pub mod cursor;
+pub mod download;
+pub mod pseudonym;
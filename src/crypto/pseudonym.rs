@@ -0,0 +1,208 @@
+// Deterministic pseudonyms for audit identifiers (HMAC-SHA256)
+//
+// For tenants that opt in, the actor and resource IDs of audit events are
+// replaced by keyed hashes before the events are hashed and stored. The same
+// ID always maps to the same pseudonym within a tenant, so events stay
+// correlatable, but the original cannot be recovered without the key. The
+// tenant is part of the hash input, so pseudonyms do not correlate across
+// tenants.
+
+use crate::config::CryptoConfig;
+use crate::domain::audit::AuditEvent;
+use crate::errors::{AppError, Result};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::collections::HashSet;
+use uuid::Uuid;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Minimum pseudonymization key length in bytes
+const MIN_KEY_LEN: usize = 32;
+
+/// Prefix marking a pseudonymized resource ID
+pub const PSEUDONYM_PREFIX: &str = "pseudo:";
+
+/// Pseudonymizes audit identifiers for the configured tenants
+pub struct AuditPseudonymizer {
+    key: Vec<u8>,
+    tenants: HashSet<Uuid>,
+}
+
+impl AuditPseudonymizer {
+    /// Create a pseudonymizer for `tenants` from a secret key of at least 32 bytes
+    pub fn new(key: &[u8], tenants: impl IntoIterator<Item = Uuid>) -> Result<Self> {
+        if key.len() < MIN_KEY_LEN {
+            return Err(AppError::Cryptographic(format!(
+                "Audit pseudonymization key must be at least {} bytes, got {}",
+                MIN_KEY_LEN,
+                key.len()
+            )));
+        }
+
+        Ok(Self {
+            key: key.to_vec(),
+            tenants: tenants.into_iter().collect(),
+        })
+    }
+
+    /// Load the pseudonymizer for `tenants` from configuration
+    ///
+    /// Returns `None` when no tenant is listed. The key is base64-encoded,
+    /// normally provided via the `AGENT_IAM__CRYPTO__AUDIT_PSEUDONYM_KEY`
+    /// environment variable, and is required once a tenant is listed: a
+    /// generated key would change every pseudonym on restart.
+    pub fn from_config(config: &CryptoConfig, tenants: &[Uuid]) -> Result<Option<Self>> {
+        if tenants.is_empty() {
+            return Ok(None);
+        }
+
+        let encoded = config.audit_pseudonym_key.as_deref().ok_or_else(|| {
+            AppError::Configuration(
+                "crypto.audit_pseudonym_key is required to pseudonymize audit events".to_string(),
+            )
+        })?;
+        let bytes = BASE64.decode(encoded.trim()).map_err(|e| {
+            AppError::Configuration(format!(
+                "Audit pseudonymization key is not valid base64: {}",
+                e
+            ))
+        })?;
+
+        Self::new(&bytes, tenants.iter().copied()).map(Some)
+    }
+
+    /// Whether events of `tenant_id` are pseudonymized
+    pub fn applies_to(&self, tenant_id: Uuid) -> bool {
+        self.tenants.contains(&tenant_id)
+    }
+
+    /// Replace the actor and resource IDs of an event of a listed tenant
+    pub fn pseudonymize(&self, mut event: AuditEvent) -> Result<AuditEvent> {
+        if !self.applies_to(event.tenant_id) {
+            return Ok(event);
+        }
+
+        let tenant_id = event.tenant_id;
+        event.actor_identity_id = event
+            .actor_identity_id
+            .map(|actor| self.pseudonymize_id(tenant_id, actor))
+            .transpose()?;
+        event.resource_id = event
+            .resource_id
+            .map(|resource| self.pseudonymize_str(tenant_id, &resource))
+            .transpose()?;
+        Ok(event)
+    }
+
+    /// Pseudonym of an identity ID, itself a (version 8) UUID
+    pub fn pseudonymize_id(&self, tenant_id: Uuid, id: Uuid) -> Result<Uuid> {
+        let mac = self.mac(tenant_id, "identity", id.as_bytes())?;
+        let mut bytes = [0u8; 16];
+        bytes.copy_from_slice(&mac[..16]);
+        Ok(uuid::Builder::from_custom_bytes(bytes).into_uuid())
+    }
+
+    /// Pseudonym of a resource ID, marked with `PSEUDONYM_PREFIX`
+    pub fn pseudonymize_str(&self, tenant_id: Uuid, value: &str) -> Result<String> {
+        let mac = self.mac(tenant_id, "resource", value.as_bytes())?;
+        Ok(format!("{}{}", PSEUDONYM_PREFIX, hex::encode(mac)))
+    }
+
+    /// HMAC over the tenant, the kind of identifier and its value
+    fn mac(&self, tenant_id: Uuid, kind: &str, value: &[u8]) -> Result<Vec<u8>> {
+        let mut mac = HmacSha256::new_from_slice(&self.key).map_err(|e| {
+            AppError::Cryptographic(format!("Invalid audit pseudonymization key: {}", e))
+        })?;
+        mac.update(tenant_id.as_bytes());
+        mac.update(kind.as_bytes());
+        mac.update(&[0]);
+        mac.update(value);
+        Ok(mac.finalize().into_bytes().to_vec())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audit::tamper_proof::{HashChain, HashableEvent};
+    use crate::domain::audit::AuditEventType;
+
+    fn pseudonymizer(tenant_id: Uuid) -> AuditPseudonymizer {
+        AuditPseudonymizer::new(&[7u8; 32], [tenant_id]).unwrap()
+    }
+
+    fn event(tenant_id: Uuid, actor: Uuid) -> AuditEvent {
+        AuditEvent::new(
+            tenant_id,
+            AuditEventType::IdentityUpdated,
+            "update".to_string(),
+            "identity".to_string(),
+        )
+        .with_actor(actor)
+        .with_resource_id("user-42".to_string())
+    }
+
+    #[test]
+    fn test_pseudonyms_are_deterministic_per_tenant() {
+        let tenant_id = Uuid::new_v4();
+        let actor = Uuid::new_v4();
+        let pseudonymizer = pseudonymizer(tenant_id);
+
+        let first = pseudonymizer.pseudonymize(event(tenant_id, actor)).unwrap();
+        let second = pseudonymizer.pseudonymize(event(tenant_id, actor)).unwrap();
+
+        assert_eq!(first.actor_identity_id, second.actor_identity_id);
+        assert_eq!(first.resource_id, second.resource_id);
+        assert_ne!(first.actor_identity_id, Some(actor));
+        assert!(first
+            .resource_id
+            .as_deref()
+            .is_some_and(|id| id.starts_with(PSEUDONYM_PREFIX)));
+
+        // Other keys, other tenants and unlisted tenants differ
+        let other_key = AuditPseudonymizer::new(&[8u8; 32], [tenant_id]).unwrap();
+        assert_ne!(
+            other_key.pseudonymize_id(tenant_id, actor).unwrap(),
+            pseudonymizer.pseudonymize_id(tenant_id, actor).unwrap()
+        );
+        let other_tenant = Uuid::new_v4();
+        assert_ne!(
+            pseudonymizer.pseudonymize_id(other_tenant, actor).unwrap(),
+            pseudonymizer.pseudonymize_id(tenant_id, actor).unwrap()
+        );
+        let unlisted = pseudonymizer
+            .pseudonymize(event(other_tenant, actor))
+            .unwrap();
+        assert_eq!(unlisted.actor_identity_id, Some(actor));
+        assert_eq!(unlisted.resource_id.as_deref(), Some("user-42"));
+    }
+
+    #[test]
+    fn test_hash_chain_verifies_over_pseudonymized_events() {
+        let tenant_id = Uuid::new_v4();
+        let pseudonymizer = pseudonymizer(tenant_id);
+        let chain = HashChain::new();
+
+        let mut previous_hash = None;
+        let mut stored = Vec::new();
+        for _ in 0..3 {
+            let event = pseudonymizer
+                .pseudonymize(event(tenant_id, Uuid::new_v4()))
+                .unwrap();
+            let hashable = HashableEvent::from_audit_event(Uuid::new_v4(), &event, previous_hash);
+            let hash = chain.compute_hash(&hashable).unwrap();
+            assert!(chain.verify_hash(&hashable, &hash).unwrap());
+            previous_hash = Some(hash);
+            stored.push(hashable);
+        }
+
+        assert!(chain.verify_chain(&stored).unwrap());
+    }
+
+    #[test]
+    fn test_short_key_rejected() {
+        assert!(AuditPseudonymizer::new(&[1u8; 16], [Uuid::new_v4()]).is_err());
+    }
+}
@@ -1,2 +1,202 @@
-// Token signing
-// To be implemented in Task #6
+// Audit event signing with Ed25519
+
+use crate::audit::tamper_proof::{HashChain, HashableEvent};
+use crate::config::CryptoConfig;
+use crate::errors::{AppError, Result};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use rand::rngs::OsRng;
+
+/// Ed25519 signer for audit events
+///
+/// Signs the canonical form of each `HashableEvent` (the same representation
+/// the hash chain uses), giving non-repudiation on top of chain integrity.
+/// Signatures are base64-encoded so they fit the `audit_logs.signature` column.
+pub struct AuditSigner {
+    signing_key: SigningKey,
+    key_id: String,
+}
+
+impl AuditSigner {
+    /// Create a signer with a freshly generated keypair
+    pub fn generate(key_id: String) -> Self {
+        Self {
+            signing_key: SigningKey::generate(&mut OsRng),
+            key_id,
+        }
+    }
+
+    /// Create a signer from a 32-byte Ed25519 secret key
+    pub fn from_bytes(key_id: String, secret_key_bytes: &[u8]) -> Result<Self> {
+        let secret: [u8; 32] = secret_key_bytes.try_into().map_err(|_| {
+            AppError::Cryptographic(format!(
+                "Invalid audit signing key length: expected 32 bytes, got {}",
+                secret_key_bytes.len()
+            ))
+        })?;
+
+        Ok(Self {
+            signing_key: SigningKey::from_bytes(&secret),
+            key_id,
+        })
+    }
+
+    /// Load the signer from configuration
+    ///
+    /// The key is a base64-encoded 32-byte secret, normally provided via the
+    /// `AGENT_IAM__CRYPTO__AUDIT_SIGNING_KEY` environment variable.
+    pub fn from_config(config: &CryptoConfig) -> Result<Self> {
+        let encoded = config.audit_signing_key.as_deref().ok_or_else(|| {
+            AppError::Configuration(
+                "Audit signing key must be set via AGENT_IAM__CRYPTO__AUDIT_SIGNING_KEY".to_string(),
+            )
+        })?;
+
+        let bytes = BASE64.decode(encoded.trim()).map_err(|e| {
+            AppError::Configuration(format!("Audit signing key is not valid base64: {}", e))
+        })?;
+
+        Self::from_bytes(config.audit_signing_key_id.clone(), &bytes)
+    }
+
+    /// Get the identifier of the key used for signing
+    pub fn key_id(&self) -> &str {
+        &self.key_id
+    }
+
+    /// Get the public key used to verify signatures
+    pub fn verifying_key(&self) -> VerifyingKey {
+        self.signing_key.verifying_key()
+    }
+
+    /// Get the public key bytes
+    pub fn public_key_bytes(&self) -> Vec<u8> {
+        self.verifying_key().to_bytes().to_vec()
+    }
+
+    /// Export the secret key bytes (use with caution!)
+    pub fn secret_key_bytes(&self) -> Vec<u8> {
+        self.signing_key.to_bytes().to_vec()
+    }
+
+    /// Sign arbitrary bytes and return a base64-encoded signature
+    pub fn sign_bytes(&self, data: &[u8]) -> String {
+        let signature = self.signing_key.sign(data);
+        BASE64.encode(signature.to_bytes())
+    }
+
+    /// Sign the canonical form of an audit event
+    pub fn sign_event(&self, event: &HashableEvent) -> Result<String> {
+        let canonical = HashChain::new().canonicalize(event)?;
+        Ok(self.sign_bytes(canonical.as_bytes()))
+    }
+
+    /// Verify a base64-encoded signature over an audit event
+    pub fn verify_event(&self, event: &HashableEvent, signature: &str) -> Result<bool> {
+        verify_event_signature(&self.verifying_key(), event, signature)
+    }
+}
+
+/// Verify a base64-encoded signature over arbitrary bytes
+///
+/// Returns Ok(false) if the signature does not match, and an error only if the
+/// signature cannot be decoded at all.
+pub fn verify_signature(verifying_key: &VerifyingKey, data: &[u8], signature: &str) -> Result<bool> {
+    let bytes = BASE64
+        .decode(signature)
+        .map_err(|e| AppError::Cryptographic(format!("Invalid signature encoding: {}", e)))?;
+
+    let signature = Signature::from_slice(&bytes)
+        .map_err(|e| AppError::Cryptographic(format!("Invalid signature: {}", e)))?;
+
+    Ok(verifying_key.verify(data, &signature).is_ok())
+}
+
+/// Verify a base64-encoded signature over an audit event's canonical form
+pub fn verify_event_signature(
+    verifying_key: &VerifyingKey,
+    event: &HashableEvent,
+    signature: &str,
+) -> Result<bool> {
+    let canonical = HashChain::new().canonicalize(event)?;
+    verify_signature(verifying_key, canonical.as_bytes(), signature)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_test_event() -> HashableEvent {
+        HashableEvent {
+            id: uuid::Uuid::new_v4(),
+            tenant_id: uuid::Uuid::new_v4(),
+            actor_identity_id: Some(uuid::Uuid::new_v4()),
+            event_type: "authentication".to_string(),
+            action: "login".to_string(),
+            resource_type: "session".to_string(),
+            resource_id: Some("session-1".to_string()),
+            decision: Some("allow".to_string()),
+            timestamp: "2026-02-12T10:00:00Z".to_string(),
+            previous_hash: None,
+            metadata: serde_json::json!({"ip": "127.0.0.1"}),
+        }
+    }
+
+    #[test]
+    fn test_sign_verify_round_trip() {
+        let signer = AuditSigner::generate("audit-test".to_string());
+        let event = create_test_event();
+
+        let signature = signer.sign_event(&event).unwrap();
+        assert!(signer.verify_event(&event, &signature).unwrap());
+    }
+
+    #[test]
+    fn test_tampered_event_fails_verification() {
+        let signer = AuditSigner::generate("audit-test".to_string());
+        let event = create_test_event();
+        let signature = signer.sign_event(&event).unwrap();
+
+        let mut tampered = event.clone();
+        tampered.decision = Some("deny".to_string());
+        assert!(!signer.verify_event(&tampered, &signature).unwrap());
+
+        let mut tampered = event;
+        tampered.metadata = serde_json::json!({"ip": "10.0.0.1"});
+        assert!(!signer.verify_event(&tampered, &signature).unwrap());
+    }
+
+    #[test]
+    fn test_wrong_key_fails_verification() {
+        let signer = AuditSigner::generate("audit-a".to_string());
+        let other = AuditSigner::generate("audit-b".to_string());
+        let event = create_test_event();
+
+        let signature = signer.sign_event(&event).unwrap();
+        assert!(!other.verify_event(&event, &signature).unwrap());
+    }
+
+    #[test]
+    fn test_key_persistence() {
+        let signer1 = AuditSigner::generate("audit-test".to_string());
+        let signer2 =
+            AuditSigner::from_bytes("audit-test".to_string(), &signer1.secret_key_bytes()).unwrap();
+        let event = create_test_event();
+
+        let signature = signer1.sign_event(&event).unwrap();
+        assert!(signer2.verify_event(&event, &signature).unwrap());
+    }
+
+    #[test]
+    fn test_invalid_key_length() {
+        let result = AuditSigner::from_bytes("audit-test".to_string(), &[0u8; 16]);
+        assert!(matches!(result, Err(AppError::Cryptographic(_))));
+    }
+
+    #[test]
+    fn test_invalid_signature_encoding() {
+        let signer = AuditSigner::generate("audit-test".to_string());
+        let event = create_test_event();
+        assert!(signer.verify_event(&event, "not base64!").is_err());
+    }
+}
@@ -1,26 +1,30 @@
 // Database queries for identities
 
 use crate::db::schema::Identity;
+use crate::db::slow_query::timed;
 use crate::errors::{AppError, Result};
 use sqlx::PgPool;
 use uuid::Uuid;
 
 /// Get an identity by email
 pub async fn get_by_email(pool: &PgPool, email: &str) -> Result<Option<Identity>> {
-    let identity = sqlx::query_as!(
-        Identity,
-        r#"
-        SELECT
-            id, tenant_id, identity_type, name, email, status,
-            parent_identity_id, task_id, task_scope, expires_at,
-            password_hash, api_key_hash, metadata, created_at,
-            updated_at, last_login_at
-        FROM identities
-        WHERE email = $1 AND status = 'active'
-        "#,
-        email
+    let identity = timed(
+        "get_identity_by_email",
+        sqlx::query_as!(
+            Identity,
+            r#"
+            SELECT
+                id, tenant_id, identity_type, name, email, status,
+                parent_identity_id, task_id, task_scope, expires_at,
+                password_hash, api_key_hash, metadata, created_by, created_at,
+                updated_at, last_login_at
+            FROM identities
+            WHERE email = $1 AND status = 'active'
+            "#,
+            email
+        )
+        .fetch_optional(pool),
     )
-    .fetch_optional(pool)
     .await?;
 
     Ok(identity)
@@ -28,20 +32,23 @@ pub async fn get_by_email(pool: &PgPool, email: &str) -> Result<Option<Identity>
 
 /// Get an identity by ID
 pub async fn get_by_id(pool: &PgPool, id: Uuid) -> Result<Option<Identity>> {
-    let identity = sqlx::query_as!(
-        Identity,
-        r#"
-        SELECT
-            id, tenant_id, identity_type, name, email, status,
-            parent_identity_id, task_id, task_scope, expires_at,
-            password_hash, api_key_hash, metadata, created_at,
-            updated_at, last_login_at
-        FROM identities
-        WHERE id = $1 AND status = 'active'
-        "#,
-        id
+    let identity = timed(
+        "get_identity_by_id",
+        sqlx::query_as!(
+            Identity,
+            r#"
+            SELECT
+                id, tenant_id, identity_type, name, email, status,
+                parent_identity_id, task_id, task_scope, expires_at,
+                password_hash, api_key_hash, metadata, created_by, created_at,
+                updated_at, last_login_at
+            FROM identities
+            WHERE id = $1 AND status = 'active'
+            "#,
+            id
+        )
+        .fetch_optional(pool),
     )
-    .fetch_optional(pool)
     .await?;
 
     Ok(identity)
@@ -65,6 +72,25 @@ pub async fn update_last_login(pool: &PgPool, id: Uuid) -> Result<()> {
     Ok(())
 }
 
+/// Activate an identity awaiting email verification
+///
+/// Returns false if the identity is not pending verification (e.g. it was
+/// already verified or has since been suspended).
+pub async fn mark_email_verified(pool: &PgPool, id: Uuid) -> Result<bool> {
+    let result = sqlx::query!(
+        r#"
+        UPDATE identities
+        SET status = 'active', updated_at = NOW()
+        WHERE id = $1 AND status = 'pending_verification'
+        "#,
+        id
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
 /// Check if an identity exists by email
 pub async fn exists_by_email(pool: &PgPool, email: &str) -> Result<bool> {
     let result = sqlx::query!(
@@ -102,4 +128,34 @@ mod tests {
         let result = get_by_email(&pool, "test@example.com").await;
         assert!(result.is_ok());
     }
+
+    #[tokio::test]
+    #[ignore] // Requires database
+    async fn test_mark_email_verified_only_once() {
+        let pool = create_test_pool().await;
+        let slug = format!("verify-{}", Uuid::new_v4());
+        let tenant_id: Uuid =
+            sqlx::query_scalar("INSERT INTO tenants (name, slug) VALUES ($1, $1) RETURNING id")
+                .bind(&slug)
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        let identity_id: Uuid = sqlx::query_scalar(
+            "INSERT INTO identities (tenant_id, identity_type, name, email, status)
+             VALUES ($1, 'user', $2, $3, 'pending_verification') RETURNING id",
+        )
+        .bind(tenant_id)
+        .bind(&slug)
+        .bind(format!("{}@example.com", slug))
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+
+        // Unverified users are not returned for login or token issuance
+        assert!(get_by_id(&pool, identity_id).await.unwrap().is_none());
+
+        assert!(mark_email_verified(&pool, identity_id).await.unwrap());
+        assert!(get_by_id(&pool, identity_id).await.unwrap().is_some());
+        assert!(!mark_email_verified(&pool, identity_id).await.unwrap());
+    }
 }
@@ -0,0 +1,79 @@
+// Persisted JWT signing secrets
+//
+// Rotations are recorded here so that every instance signs with the same
+// secret and a restart does not revert to the one from the environment.
+
+use crate::errors::Result;
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+/// A signing secret installed by a rotation
+#[derive(Debug, Clone)]
+pub struct JwtSigningKey {
+    pub id: Uuid,
+    pub secret_encrypted: String,
+    pub created_at: DateTime<Utc>,
+    pub previous_cleared_at: Option<DateTime<Utc>>,
+}
+
+/// Record a new signing secret, making it the current one
+pub async fn insert(
+    pool: &PgPool,
+    secret_encrypted: &str,
+    rotated_by: Uuid,
+) -> Result<JwtSigningKey> {
+    let key = sqlx::query_as!(
+        JwtSigningKey,
+        r#"
+        INSERT INTO jwt_signing_keys (secret_encrypted, rotated_by)
+        VALUES ($1, $2)
+        RETURNING id, secret_encrypted, created_at, previous_cleared_at
+        "#,
+        secret_encrypted,
+        rotated_by
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(key)
+}
+
+/// The current signing secret and the one it replaced, newest first
+pub async fn latest(pool: &PgPool) -> Result<Vec<JwtSigningKey>> {
+    let keys = sqlx::query_as!(
+        JwtSigningKey,
+        r#"
+        SELECT id, secret_encrypted, created_at, previous_cleared_at
+        FROM jwt_signing_keys
+        ORDER BY created_at DESC, id DESC
+        LIMIT 2
+        "#
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(keys)
+}
+
+/// Stop accepting the secret replaced by the current one
+///
+/// Returns false if no rotation has been recorded or it was already cleared.
+pub async fn clear_previous(pool: &PgPool) -> Result<bool> {
+    let result = sqlx::query!(
+        r#"
+        UPDATE jwt_signing_keys
+        SET previous_cleared_at = NOW()
+        WHERE id = (
+            SELECT id FROM jwt_signing_keys
+            ORDER BY created_at DESC, id DESC
+            LIMIT 1
+        )
+        AND previous_cleared_at IS NULL
+        "#
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
@@ -0,0 +1,115 @@
+// Database queries for TOTP MFA enrollments
+
+use crate::errors::Result;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+/// A stored TOTP enrollment
+#[derive(Debug, Clone)]
+pub struct TotpEnrollment {
+    pub identity_id: Uuid,
+    pub secret_encrypted: String,
+    pub enabled: bool,
+    pub last_used_step: Option<i64>,
+}
+
+impl TotpEnrollment {
+    /// Last accepted time step, for replay protection
+    pub fn last_used_step(&self) -> Option<u64> {
+        self.last_used_step.and_then(|s| u64::try_from(s).ok())
+    }
+}
+
+/// Get the TOTP enrollment for an identity
+pub async fn get_totp(pool: &PgPool, identity_id: Uuid) -> Result<Option<TotpEnrollment>> {
+    let enrollment = sqlx::query_as!(
+        TotpEnrollment,
+        r#"
+        SELECT identity_id, secret_encrypted, enabled, last_used_step
+        FROM mfa_totp
+        WHERE identity_id = $1
+        "#,
+        identity_id
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(enrollment)
+}
+
+/// Check whether an identity has confirmed TOTP enrollment
+pub async fn is_totp_enabled(pool: &PgPool, identity_id: Uuid) -> Result<bool> {
+    let result = sqlx::query!(
+        r#"
+        SELECT EXISTS(
+            SELECT 1 FROM mfa_totp WHERE identity_id = $1 AND enabled
+        ) as "exists!"
+        "#,
+        identity_id
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(result.exists)
+}
+
+/// Store a new, not yet confirmed, secret for an identity
+///
+/// Re-enrolling replaces any pending secret. An already enabled enrollment is
+/// left untouched; returns false in that case.
+pub async fn store_pending_totp(pool: &PgPool, identity_id: Uuid, secret_encrypted: &str) -> Result<bool> {
+    let result = sqlx::query!(
+        r#"
+        INSERT INTO mfa_totp (identity_id, secret_encrypted)
+        VALUES ($1, $2)
+        ON CONFLICT (identity_id) DO UPDATE
+        SET secret_encrypted = EXCLUDED.secret_encrypted,
+            last_used_step = NULL,
+            created_at = NOW()
+        WHERE NOT mfa_totp.enabled
+        "#,
+        identity_id,
+        secret_encrypted
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected() == 1)
+}
+
+/// Record a used time step
+///
+/// Only succeeds if the step is newer than the last used one, which makes the
+/// replay check atomic across concurrent requests. Returns false on replay.
+pub async fn record_used_step(pool: &PgPool, identity_id: Uuid, step: u64) -> Result<bool> {
+    let result = sqlx::query!(
+        r#"
+        UPDATE mfa_totp
+        SET last_used_step = $2
+        WHERE identity_id = $1
+          AND (last_used_step IS NULL OR last_used_step < $2)
+        "#,
+        identity_id,
+        step as i64
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected() == 1)
+}
+
+/// Mark a pending enrollment as enabled
+pub async fn enable_totp(pool: &PgPool, identity_id: Uuid) -> Result<()> {
+    sqlx::query!(
+        r#"
+        UPDATE mfa_totp
+        SET enabled = TRUE, enabled_at = NOW()
+        WHERE identity_id = $1
+        "#,
+        identity_id
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
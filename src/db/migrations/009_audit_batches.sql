@@ -0,0 +1,22 @@
+-- Merkle batch anchors for audit logs
+
+CREATE TABLE audit_batches (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    merkle_root VARCHAR(64) NOT NULL,
+    event_count INTEGER NOT NULL,
+    root_signature VARCHAR(255),
+    signing_key_id VARCHAR(255),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    CONSTRAINT positive_event_count CHECK (event_count > 0)
+);
+
+CREATE INDEX idx_audit_batches_created ON audit_batches(created_at DESC);
+
+-- Link each audit event to its batch and leaf position
+ALTER TABLE audit_logs
+    ADD COLUMN event_hash VARCHAR(64),
+    ADD COLUMN batch_id UUID REFERENCES audit_batches(id) ON DELETE SET NULL,
+    ADD COLUMN batch_index INTEGER;
+
+CREATE INDEX idx_audit_batch ON audit_logs(batch_id, batch_index) WHERE batch_id IS NOT NULL;
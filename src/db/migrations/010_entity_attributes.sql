@@ -0,0 +1,12 @@
+-- Custom attributes surfaced to Cedar policies as entity record attributes
+-- (each tenant keeps its own attributes for the same entity UID)
+
+CREATE TABLE entity_attributes (
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    entity_uid VARCHAR(512) NOT NULL,
+    key VARCHAR(255) NOT NULL,
+    value JSONB NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    PRIMARY KEY (tenant_id, entity_uid, key)
+);
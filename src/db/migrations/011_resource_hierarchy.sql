@@ -0,0 +1,14 @@
+-- Parent links between entities so Cedar `in` checks can follow ancestry
+-- (e.g. File::"report" in Folder::"finance" in Project::"q3"), per tenant
+
+CREATE TABLE resource_hierarchy (
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    child_uid VARCHAR(512) NOT NULL,
+    parent_uid VARCHAR(512) NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    PRIMARY KEY (tenant_id, child_uid, parent_uid),
+    CONSTRAINT no_self_parent CHECK (child_uid <> parent_uid)
+);
+
+CREATE INDEX idx_resource_hierarchy_parent ON resource_hierarchy(tenant_id, parent_uid);
@@ -0,0 +1,12 @@
+-- TOTP multi-factor authentication for user identities
+
+CREATE TABLE mfa_totp (
+    identity_id UUID PRIMARY KEY REFERENCES identities(id) ON DELETE CASCADE,
+    -- AES-256-GCM encrypted secret, base64(nonce || ciphertext || tag)
+    secret_encrypted TEXT NOT NULL,
+    enabled BOOLEAN NOT NULL DEFAULT FALSE,
+    -- Last accepted time step, so a code cannot be replayed
+    last_used_step BIGINT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    enabled_at TIMESTAMPTZ
+);
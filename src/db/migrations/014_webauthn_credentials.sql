@@ -0,0 +1,14 @@
+-- WebAuthn passkeys registered by user identities
+
+CREATE TABLE webauthn_credentials (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    identity_id UUID NOT NULL REFERENCES identities(id) ON DELETE CASCADE,
+    -- Base64url credential ID as reported by the authenticator
+    credential_id TEXT NOT NULL UNIQUE,
+    -- Serialized passkey (public key, counter, flags)
+    passkey JSONB NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    last_used_at TIMESTAMPTZ
+);
+
+CREATE INDEX idx_webauthn_credentials_identity ON webauthn_credentials(identity_id);
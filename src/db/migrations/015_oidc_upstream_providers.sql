@@ -0,0 +1,33 @@
+-- Upstream OpenID providers (Google, Okta, ...) tenants let their users log in with
+
+CREATE TABLE oidc_upstream_providers (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    -- Short name used in login URLs, unique within the tenant
+    slug VARCHAR(100) NOT NULL,
+    issuer TEXT NOT NULL,
+    client_id TEXT NOT NULL,
+    -- Encrypted (AES-256-GCM); NULL for public clients relying on PKCE alone
+    client_secret_encrypted TEXT,
+    scopes TEXT NOT NULL DEFAULT 'openid email profile',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    UNIQUE (tenant_id, slug)
+);
+
+CREATE TRIGGER update_oidc_upstream_providers_updated_at BEFORE UPDATE ON oidc_upstream_providers
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
+
+-- Local identities linked to an upstream account
+CREATE TABLE oidc_identity_links (
+    provider_id UUID NOT NULL REFERENCES oidc_upstream_providers(id) ON DELETE CASCADE,
+    -- Upstream `sub` claim, stable per provider
+    subject TEXT NOT NULL,
+    identity_id UUID NOT NULL REFERENCES identities(id) ON DELETE CASCADE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    PRIMARY KEY (provider_id, subject)
+);
+
+CREATE INDEX idx_oidc_identity_links_identity ON oidc_identity_links(identity_id);
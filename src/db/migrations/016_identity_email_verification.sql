@@ -0,0 +1,6 @@
+-- Users must verify their email before they can log in
+
+ALTER TABLE identities DROP CONSTRAINT valid_identity_status;
+
+ALTER TABLE identities ADD CONSTRAINT valid_identity_status
+    CHECK (status IN ('active', 'pending_verification', 'suspended', 'deleted'));
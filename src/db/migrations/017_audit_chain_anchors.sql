@@ -0,0 +1,16 @@
+-- Signed genesis anchors for audit chains whose oldest events were archived
+
+CREATE TABLE audit_chain_anchors (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    anchor_hash VARCHAR(64) NOT NULL,
+    archived_event_count BIGINT NOT NULL,
+    archived_through TIMESTAMPTZ NOT NULL,
+    signature VARCHAR(255) NOT NULL,
+    signing_key_id VARCHAR(255) NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    CONSTRAINT positive_archived_event_count CHECK (archived_event_count > 0)
+);
+
+CREATE INDEX idx_audit_chain_anchors_tenant ON audit_chain_anchors(tenant_id, created_at DESC);
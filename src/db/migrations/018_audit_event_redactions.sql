@@ -0,0 +1,17 @@
+-- Signed records of audit events whose actor was erased
+--
+-- An event's stored hash covers its actor, so erasing the actor would leave
+-- the event unverifiable. Each redaction ties the event's original hash (its
+-- Merkle leaf) to the hash of its redacted form.
+
+CREATE TABLE audit_event_redactions (
+    event_id UUID PRIMARY KEY,
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    original_event_hash VARCHAR(64) NOT NULL,
+    redacted_event_hash VARCHAR(64) NOT NULL,
+    signature VARCHAR(255) NOT NULL,
+    signing_key_id VARCHAR(255) NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_audit_event_redactions_tenant ON audit_event_redactions(tenant_id);
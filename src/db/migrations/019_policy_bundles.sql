@@ -0,0 +1,15 @@
+-- Versioned policy bundles
+--
+-- Importing a bundle replaces a tenant's whole policy set: the policies it
+-- replaces are archived rather than deleted, and the tenant's bundle version
+-- is bumped so exports can be told apart.
+
+ALTER TABLE policies DROP CONSTRAINT valid_policy_status;
+ALTER TABLE policies ADD CONSTRAINT valid_policy_status
+    CHECK (status IN ('active', 'inactive', 'archived', 'deleted'));
+
+CREATE TABLE policy_bundle_versions (
+    tenant_id UUID PRIMARY KEY REFERENCES tenants(id) ON DELETE CASCADE,
+    version BIGINT NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
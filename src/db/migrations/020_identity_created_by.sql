@@ -0,0 +1,11 @@
+-- Record which identity created each identity
+--
+-- Agents were already attributed through parent_identity_id; their parent is
+-- also who created them, so existing agents are backfilled from it.
+
+ALTER TABLE identities
+    ADD COLUMN created_by UUID REFERENCES identities(id) ON DELETE SET NULL;
+
+UPDATE identities SET created_by = parent_identity_id WHERE identity_type = 'agent';
+
+CREATE INDEX idx_identities_created_by ON identities(created_by);
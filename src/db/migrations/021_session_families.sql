@@ -0,0 +1,9 @@
+-- Refresh-token family of each session
+--
+-- A login's access and refresh sessions share the family of its refresh
+-- token, so every session descending from one login can be revoked together.
+-- Sessions created before this migration have no family.
+
+ALTER TABLE sessions ADD COLUMN family_id VARCHAR(255);
+
+CREATE INDEX idx_sessions_family ON sessions(family_id) WHERE family_id IS NOT NULL;
@@ -0,0 +1,10 @@
+-- Client context a session's token is bound to
+--
+-- With token binding enabled, the client's IP subnet (e.g. 203.0.113.0/24)
+-- and a SHA-256 of its User-Agent are recorded when the token is issued, and
+-- the token is refused when presented from elsewhere. NULL means the session
+-- is not bound in that respect.
+
+ALTER TABLE sessions
+    ADD COLUMN bound_subnet VARCHAR(64),
+    ADD COLUMN bound_user_agent_hash VARCHAR(64);
@@ -0,0 +1,14 @@
+-- Revoked Biscuit tokens
+--
+-- The Redis revocation list expires with its keys and is lost on a flush.
+-- Revocation IDs are also recorded here until the token would have expired
+-- anyway, so revoked (and used single-use) Biscuits stay refused. Expired
+-- rows are purged periodically.
+
+CREATE TABLE biscuit_revocations (
+    revocation_id VARCHAR(255) PRIMARY KEY,
+    revoked_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    expires_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX idx_biscuit_revocations_expires ON biscuit_revocations(expires_at);
@@ -0,0 +1,9 @@
+-- Policy annotations
+--
+-- Cedar annotations of each policy (`@owner("payments")`), as a JSON object
+-- of key to value, extracted when the policy is written. Policies written
+-- before this migration get theirs on their next update or bundle import.
+
+ALTER TABLE policies ADD COLUMN annotations JSONB NOT NULL DEFAULT '{}'::jsonb;
+
+CREATE INDEX idx_policies_annotations ON policies USING GIN (annotations);
@@ -0,0 +1,22 @@
+-- Validity windows of policies
+--
+-- An active policy only applies from valid_from (inclusive) until valid_until
+-- (exclusive); NULL leaves that side open. Policies are filtered by their
+-- window when they are loaded for a decision. A background task also marks
+-- active policies whose window has ended as 'expired', so listings show they
+-- no longer apply.
+
+ALTER TABLE policies
+    ADD COLUMN valid_from TIMESTAMPTZ,
+    ADD COLUMN valid_until TIMESTAMPTZ,
+    ADD CONSTRAINT valid_policy_window
+        CHECK (valid_from IS NULL OR valid_until IS NULL OR valid_from < valid_until);
+
+ALTER TABLE policies DROP CONSTRAINT valid_policy_status;
+ALTER TABLE policies ADD CONSTRAINT valid_policy_status
+    CHECK (status IN ('active', 'inactive', 'expired', 'archived', 'deleted'));
+
+CREATE INDEX idx_policies_valid_until ON policies(valid_until)
+    WHERE status = 'active' AND valid_until IS NOT NULL;
+CREATE INDEX idx_policies_valid_from ON policies(valid_from)
+    WHERE status = 'active' AND valid_from IS NOT NULL;
@@ -0,0 +1,17 @@
+-- JWT signing secrets installed by rotation
+--
+-- The newest row is the signing secret on every instance; the row before it
+-- (or the secret from the environment when there is only one) stays valid for
+-- verification for one refresh-token lifetime, unless the admin dropped it.
+
+CREATE TABLE jwt_signing_keys (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    -- Encrypted (AES-256-GCM)
+    secret_encrypted TEXT NOT NULL,
+    rotated_by UUID REFERENCES identities(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    -- Set when the previous secret was dropped before its window elapsed
+    previous_cleared_at TIMESTAMPTZ
+);
+
+CREATE INDEX idx_jwt_signing_keys_created ON jwt_signing_keys(created_at DESC);
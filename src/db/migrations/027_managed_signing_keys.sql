@@ -0,0 +1,17 @@
+-- Biscuit root and audit signing keys installed by scheduled rotation
+--
+-- Every instance loads the newest key of a purpose as its signing key and the
+-- keys retired within the overlap window for verification. Rotations take a
+-- transaction-level advisory lock on the purpose, so only one instance
+-- rotates when a key falls due.
+
+CREATE TABLE managed_signing_keys (
+    key_id TEXT PRIMARY KEY,
+    purpose TEXT NOT NULL,
+    -- Ed25519 secret key, encrypted (AES-256-GCM)
+    secret_encrypted TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL,
+    retired_at TIMESTAMPTZ
+);
+
+CREATE INDEX idx_managed_signing_keys_purpose ON managed_signing_keys(purpose, created_at DESC);
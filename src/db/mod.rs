@@ -1,6 +1,16 @@
 pub mod pool;
+pub mod transaction;
 pub mod schema;
 pub mod identities;
 pub mod sessions;
+pub mod password_history;
+pub mod mfa;
+pub mod webauthn;
+pub mod oidc_upstream;
+pub mod biscuit_revocations;
+pub mod jwt_keys;
+pub mod signing_keys;
+pub mod slow_query;
 
-pub use pool::{create_pool, run_migrations, health_check};
+pub use pool::{create_pool, health_check, migration_status, run_migrations, MigrationStatus};
+pub use transaction::with_tx;
@@ -1,7 +1,28 @@
 use crate::{config::DatabaseConfig, errors::Result};
-use sqlx::{postgres::PgPoolOptions, PgPool};
+use sqlx::{migrate::Migrator, postgres::PgPoolOptions, PgPool};
 use std::time::Duration;
 
+/// Migrations embedded in the binary at compile time
+pub static MIGRATOR: Migrator = sqlx::migrate!("./src/db/migrations");
+
+/// Schema version applied to the database compared with the binary's migrations
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MigrationStatus {
+    /// Highest successfully applied migration version, if any
+    pub applied_version: Option<i64>,
+    /// Highest migration version embedded in this binary
+    pub expected_version: Option<i64>,
+    /// Whether any migration is recorded as failed (partially applied)
+    pub dirty: bool,
+}
+
+impl MigrationStatus {
+    /// Whether the database schema matches what this binary expects
+    pub fn is_current(&self) -> bool {
+        !self.dirty && self.applied_version == self.expected_version
+    }
+}
+
 /// Create a PostgreSQL connection pool
 pub async fn create_pool(config: &DatabaseConfig) -> Result<PgPool> {
     tracing::info!("Creating database connection pool");
@@ -25,7 +46,7 @@ pub async fn create_pool(config: &DatabaseConfig) -> Result<PgPool> {
 /// Run database migrations
 pub async fn run_migrations(pool: &PgPool) -> Result<()> {
     tracing::info!("Running database migrations");
-    sqlx::migrate!("./src/db/migrations").run(pool).await?;
+    MIGRATOR.run(pool).await?;
     tracing::info!("Database migrations completed");
     Ok(())
 }
@@ -35,3 +56,24 @@ pub async fn health_check(pool: &PgPool) -> Result<()> {
     sqlx::query("SELECT 1").execute(pool).await?;
     Ok(())
 }
+
+/// Compare the applied schema version against the embedded migration set
+pub async fn migration_status(pool: &PgPool) -> Result<MigrationStatus> {
+    // _sqlx_migrations is managed by sqlx itself, so it is queried at runtime
+    let (applied_version, dirty): (Option<i64>, bool) = sqlx::query_as(
+        r#"
+        SELECT
+            MAX(version) FILTER (WHERE success),
+            COALESCE(BOOL_OR(NOT success), false)
+        FROM _sqlx_migrations
+        "#,
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(MigrationStatus {
+        applied_version,
+        expected_version: MIGRATOR.iter().map(|m| m.version).max(),
+        dirty,
+    })
+}
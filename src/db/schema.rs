@@ -39,6 +39,8 @@ pub struct Identity {
     pub password_hash: Option<String>,
     pub api_key_hash: Option<String>,
     pub metadata: serde_json::Value,
+    /// Identity that created this one, if created by an authenticated caller
+    pub created_by: Option<Uuid>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
     pub last_login_at: Option<DateTime<Utc>>,
@@ -164,6 +166,40 @@ pub struct AuditLog {
     pub timestamp: DateTime<Utc>,
     pub signature: Option<String>,
     pub previous_event_hash: Option<String>,
+    pub event_hash: Option<String>,
+    pub batch_id: Option<Uuid>,
+    pub batch_index: Option<i32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct AuditBatch {
+    pub id: Uuid,
+    pub merkle_root: String,
+    pub event_count: i32,
+    pub root_signature: Option<String>,
+    pub signing_key_id: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+// ============================================================================
+// Entity Attribute
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct EntityAttribute {
+    pub tenant_id: Uuid,
+    pub entity_uid: String,
+    pub key: String,
+    pub value: serde_json::Value,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct ResourceParent {
+    pub tenant_id: Uuid,
+    pub child_uid: String,
+    pub parent_uid: String,
+    pub created_at: DateTime<Utc>,
 }
 
 // ============================================================================
@@ -1,9 +1,13 @@
 // Database queries for sessions
 
+use crate::auth::binding::ClientBinding;
 use crate::db::schema::Session;
 use crate::errors::Result;
+use crate::redis::revocation;
 use chrono::{DateTime, Utc};
-use sqlx::PgPool;
+use redis::aio::ConnectionLike;
+use sqlx::{PgConnection, PgPool};
+use std::collections::BTreeMap;
 use uuid::Uuid;
 
 /// Create a new session
@@ -51,6 +55,84 @@ pub async fn create(
     Ok(session)
 }
 
+/// Token pair recorded for a login
+pub struct LoginSessions {
+    pub access_token_id: String,
+    pub access_expires_at: DateTime<Utc>,
+    pub refresh_token_id: String,
+    pub refresh_expires_at: DateTime<Utc>,
+    /// Family of the refresh token, shared by both sessions
+    pub family_id: String,
+    /// Client context both sessions are bound to
+    pub binding: ClientBinding,
+}
+
+/// Store the access and refresh sessions for a login and update the
+/// identity's last login time
+///
+/// Run this inside a transaction (see `db::with_tx`) so a failure part way
+/// through leaves no partial sessions behind.
+pub async fn record_login(
+    conn: &mut PgConnection,
+    identity_id: Uuid,
+    tenant_id: Uuid,
+    sessions: &LoginSessions,
+) -> Result<()> {
+    // Store access token session
+    sqlx::query!(
+        r#"
+        INSERT INTO sessions (
+            identity_id, tenant_id, token_id, token_type, expires_at, family_id,
+            bound_subnet, bound_user_agent_hash
+        )
+        VALUES ($1, $2, $3, 'jwt', $4, $5, $6, $7)
+        "#,
+        identity_id,
+        tenant_id,
+        sessions.access_token_id,
+        sessions.access_expires_at,
+        sessions.family_id,
+        sessions.binding.subnet,
+        sessions.binding.user_agent_hash
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    // Store refresh token session
+    sqlx::query!(
+        r#"
+        INSERT INTO sessions (
+            identity_id, tenant_id, token_id, token_type, expires_at, family_id,
+            bound_subnet, bound_user_agent_hash
+        )
+        VALUES ($1, $2, $3, 'refresh', $4, $5, $6, $7)
+        "#,
+        identity_id,
+        tenant_id,
+        sessions.refresh_token_id,
+        sessions.refresh_expires_at,
+        sessions.family_id,
+        sessions.binding.subnet,
+        sessions.binding.user_agent_hash
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    // Update last login time
+    sqlx::query!(
+        r#"
+        UPDATE identities
+        SET last_login_at = NOW()
+        WHERE id = $1
+        "#,
+        identity_id
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+}
+
 /// Get a session by token ID
 pub async fn get_by_token_id(pool: &PgPool, token_id: &str) -> Result<Option<Session>> {
     let session = sqlx::query_as!(
@@ -71,6 +153,28 @@ pub async fn get_by_token_id(pool: &PgPool, token_id: &str) -> Result<Option<Ses
     Ok(session)
 }
 
+/// List every session of an identity, including revoked and expired ones,
+/// newest first
+pub async fn list_for_identity(pool: &PgPool, identity_id: Uuid) -> Result<Vec<Session>> {
+    let sessions = sqlx::query_as!(
+        Session,
+        r#"
+        SELECT
+            id, identity_id, tenant_id, token_id, token_type,
+            scope, delegation_chain, created_at, expires_at,
+            revoked_at, last_used_at, ip_address, user_agent, metadata
+        FROM sessions
+        WHERE identity_id = $1
+        ORDER BY created_at DESC
+        "#,
+        identity_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(sessions)
+}
+
 /// Revoke a session by token ID
 pub async fn revoke(pool: &PgPool, token_id: &str) -> Result<()> {
     sqlx::query!(
@@ -90,25 +194,139 @@ pub async fn revoke(pool: &PgPool, token_id: &str) -> Result<()> {
 }
 
 /// Revoke all sessions for an identity
-pub async fn revoke_all_for_identity(pool: &PgPool, identity_id: Uuid) -> Result<u64> {
-    let result = sqlx::query!(
+///
+/// The revoked token IDs that have not yet expired are also added to the
+/// Redis revocation list for the rest of their lifetime, so access tokens
+/// checked against Redis stop working right away.
+pub async fn revoke_all_for_identity<C>(
+    pool: &PgPool,
+    redis_conn: &mut C,
+    identity_id: Uuid,
+) -> Result<u64>
+where
+    C: ConnectionLike + Clone + Send + Sync,
+{
+    let revoked = sqlx::query!(
         r#"
         UPDATE sessions
         SET revoked_at = NOW()
         WHERE identity_id = $1 AND revoked_at IS NULL
+        RETURNING token_id, expires_at
         "#,
         identity_id
     )
-    .execute(pool)
+    .fetch_all(pool)
     .await?;
 
+    for session in &revoked {
+        push_revocation(redis_conn, &session.token_id, session.expires_at).await?;
+    }
+
     tracing::info!(
         "Revoked {} sessions for identity {}",
-        result.rows_affected(),
+        revoked.len(),
         identity_id
     );
 
-    Ok(result.rows_affected())
+    Ok(revoked.len() as u64)
+}
+
+/// Sessions a batch revocation applies to, always within one tenant
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SessionSelector {
+    /// Every session of one identity
+    Identity(Uuid),
+    /// Every session descending from one login's refresh-token family
+    Family(String),
+    /// Every session in the tenant
+    Tenant,
+}
+
+/// Outcome of a batch revocation
+#[derive(Debug, Default)]
+pub struct BatchRevocation {
+    /// Sessions marked revoked in the database
+    pub sessions: u64,
+    /// Unexpired token IDs added to the Redis revocation list
+    pub tokens: u64,
+    /// Revoked sessions per identity
+    pub per_identity: BTreeMap<Uuid, u64>,
+}
+
+/// Revoke every active session in `tenant_id` that matches `selector`
+///
+/// Like `revoke_all_for_identity`, token IDs that have not yet expired are
+/// also added to the Redis revocation list for the rest of their lifetime.
+pub async fn revoke_matching<C>(
+    pool: &PgPool,
+    redis_conn: &mut C,
+    tenant_id: Uuid,
+    selector: &SessionSelector,
+) -> Result<BatchRevocation>
+where
+    C: ConnectionLike + Clone + Send + Sync,
+{
+    let (identity_id, family_id) = match selector {
+        SessionSelector::Identity(identity_id) => (Some(*identity_id), None),
+        SessionSelector::Family(family_id) => (None, Some(family_id.as_str())),
+        SessionSelector::Tenant => (None, None),
+    };
+
+    let revoked = sqlx::query!(
+        r#"
+        UPDATE sessions
+        SET revoked_at = NOW()
+        WHERE tenant_id = $1
+          AND revoked_at IS NULL
+          AND ($2::uuid IS NULL OR identity_id = $2)
+          AND ($3::text IS NULL OR family_id = $3)
+        RETURNING identity_id, token_id, expires_at
+        "#,
+        tenant_id,
+        identity_id,
+        family_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut outcome = BatchRevocation {
+        sessions: revoked.len() as u64,
+        ..BatchRevocation::default()
+    };
+    for session in &revoked {
+        *outcome.per_identity.entry(session.identity_id).or_default() += 1;
+        if push_revocation(redis_conn, &session.token_id, session.expires_at).await? {
+            outcome.tokens += 1;
+        }
+    }
+
+    tracing::info!(
+        tenant_id = %tenant_id,
+        selector = ?selector,
+        sessions = outcome.sessions,
+        tokens = outcome.tokens,
+        "Batch revoked sessions"
+    );
+
+    Ok(outcome)
+}
+
+/// Add a revoked session's token ID to the Redis revocation list until it
+/// expires; returns false if it already has
+async fn push_revocation<C>(
+    redis_conn: &mut C,
+    token_id: &str,
+    expires_at: DateTime<Utc>,
+) -> Result<bool>
+where
+    C: ConnectionLike + Clone + Send + Sync,
+{
+    let ttl_seconds = (expires_at - Utc::now()).num_seconds();
+    if ttl_seconds <= 0 {
+        return Ok(false);
+    }
+    revocation::revoke_token(redis_conn, token_id, ttl_seconds).await?;
+    Ok(true)
 }
 
 /// Update last used time for a session
@@ -188,4 +406,221 @@ mod tests {
         let session = result.unwrap();
         assert_eq!(session.token_id, token_id);
     }
+
+    async fn create_identity(pool: &PgPool) -> (Uuid, Uuid) {
+        let slug = format!("sessions-{}", Uuid::new_v4());
+        let tenant_id: Uuid =
+            sqlx::query_scalar("INSERT INTO tenants (name, slug) VALUES ($1, $1) RETURNING id")
+                .bind(&slug)
+                .fetch_one(pool)
+                .await
+                .unwrap();
+        let identity_id: Uuid = sqlx::query_scalar(
+            "INSERT INTO identities (tenant_id, identity_type, name) VALUES ($1, 'user', $2) RETURNING id",
+        )
+        .bind(tenant_id)
+        .bind(&slug)
+        .fetch_one(pool)
+        .await
+        .unwrap();
+
+        (identity_id, tenant_id)
+    }
+
+    fn login_sessions(access_token_id: String, refresh_token_id: String) -> LoginSessions {
+        let now = Utc::now();
+        LoginSessions {
+            access_token_id,
+            access_expires_at: now + Duration::hours(1),
+            refresh_token_id,
+            refresh_expires_at: now + Duration::days(7),
+            family_id: Uuid::new_v4().to_string(),
+            binding: ClientBinding::default(),
+        }
+    }
+
+    #[tokio::test]
+    #[ignore] // Requires database
+    async fn test_record_login_stores_both_sessions() {
+        let pool = create_test_pool().await;
+        let (identity_id, tenant_id) = create_identity(&pool).await;
+        let sessions = login_sessions(Uuid::new_v4().to_string(), Uuid::new_v4().to_string());
+        let access_token_id = sessions.access_token_id.clone();
+        let refresh_token_id = sessions.refresh_token_id.clone();
+
+        crate::db::with_tx(&pool, move |conn| {
+            Box::pin(async move { record_login(conn, identity_id, tenant_id, &sessions).await })
+        })
+        .await
+        .unwrap();
+
+        assert!(get_by_token_id(&pool, &access_token_id).await.unwrap().is_some());
+        assert!(get_by_token_id(&pool, &refresh_token_id).await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    #[ignore] // Requires database
+    async fn test_record_login_failure_leaves_no_partial_sessions() {
+        let pool = create_test_pool().await;
+        let (identity_id, tenant_id) = create_identity(&pool).await;
+
+        // Reusing the access token ID makes the refresh insert violate the
+        // unique constraint after the access session has been written
+        let token_id = Uuid::new_v4().to_string();
+        let sessions = login_sessions(token_id.clone(), token_id.clone());
+
+        let result = crate::db::with_tx(&pool, move |conn| {
+            Box::pin(async move { record_login(conn, identity_id, tenant_id, &sessions).await })
+        })
+        .await;
+
+        assert!(result.is_err());
+        assert!(get_by_token_id(&pool, &token_id).await.unwrap().is_none());
+
+        let last_login: Option<DateTime<Utc>> =
+            sqlx::query_scalar("SELECT last_login_at FROM identities WHERE id = $1")
+                .bind(identity_id)
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert!(last_login.is_none());
+    }
+
+    async fn redis_conn() -> redis::aio::ConnectionManager {
+        let config = crate::config::RedisConfig {
+            url: "redis://localhost:6379".to_string(),
+            pool_size: 10,
+            connection_timeout_seconds: 5,
+            retry: Default::default(),
+        };
+        crate::redis::create_client(&config).await.unwrap()
+    }
+
+    /// Record a login and return its sessions' token IDs and family
+    async fn login(pool: &PgPool, identity_id: Uuid, tenant_id: Uuid) -> ([String; 2], String) {
+        let sessions = login_sessions(Uuid::new_v4().to_string(), Uuid::new_v4().to_string());
+        let token_ids = [
+            sessions.access_token_id.clone(),
+            sessions.refresh_token_id.clone(),
+        ];
+        let family_id = sessions.family_id.clone();
+        crate::db::with_tx(pool, move |conn| {
+            Box::pin(async move { record_login(conn, identity_id, tenant_id, &sessions).await })
+        })
+        .await
+        .unwrap();
+        (token_ids, family_id)
+    }
+
+    #[tokio::test]
+    #[ignore] // Requires database and Redis
+    async fn test_revoke_family_leaves_other_logins_active() {
+        let pool = create_test_pool().await;
+        let mut redis_conn = redis_conn().await;
+        let (identity_id, tenant_id) = create_identity(&pool).await;
+        let (revoked_tokens, family_id) = login(&pool, identity_id, tenant_id).await;
+        let (kept_tokens, _) = login(&pool, identity_id, tenant_id).await;
+
+        let outcome = revoke_matching(
+            &pool,
+            &mut redis_conn,
+            tenant_id,
+            &SessionSelector::Family(family_id),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(outcome.sessions, 2);
+        assert_eq!(outcome.tokens, 2);
+        assert_eq!(outcome.per_identity.get(&identity_id), Some(&2));
+        for token_id in &revoked_tokens {
+            assert!(revocation::is_token_revoked(&mut redis_conn, token_id)
+                .await
+                .unwrap());
+            assert!(get_by_token_id(&pool, token_id).await.unwrap().is_none());
+        }
+        for token_id in &kept_tokens {
+            assert!(!revocation::is_token_revoked(&mut redis_conn, token_id)
+                .await
+                .unwrap());
+            assert!(get_by_token_id(&pool, token_id).await.unwrap().is_some());
+        }
+    }
+
+    #[tokio::test]
+    #[ignore] // Requires database and Redis
+    async fn test_revoke_tenant_covers_every_identity_in_it() {
+        let pool = create_test_pool().await;
+        let mut redis_conn = redis_conn().await;
+        let (first, tenant_id) = create_identity(&pool).await;
+        let second: Uuid = sqlx::query_scalar(
+            "INSERT INTO identities (tenant_id, identity_type, name) VALUES ($1, 'user', 'second') RETURNING id",
+        )
+        .bind(tenant_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        let (first_tokens, _) = login(&pool, first, tenant_id).await;
+        let (second_tokens, _) = login(&pool, second, tenant_id).await;
+        let (other, other_tenant) = create_identity(&pool).await;
+        let (other_tokens, _) = login(&pool, other, other_tenant).await;
+
+        let outcome = revoke_matching(&pool, &mut redis_conn, tenant_id, &SessionSelector::Tenant)
+            .await
+            .unwrap();
+
+        assert_eq!(outcome.sessions, 4);
+        assert_eq!(outcome.per_identity.len(), 2);
+        for token_id in first_tokens.iter().chain(&second_tokens) {
+            assert!(revocation::is_token_revoked(&mut redis_conn, token_id)
+                .await
+                .unwrap());
+        }
+        for token_id in &other_tokens {
+            assert!(get_by_token_id(&pool, token_id).await.unwrap().is_some());
+        }
+
+        // Nothing is left to revoke the second time
+        let again = revoke_matching(&pool, &mut redis_conn, tenant_id, &SessionSelector::Tenant)
+            .await
+            .unwrap();
+        assert_eq!(again.sessions, 0);
+    }
+
+    #[tokio::test]
+    #[ignore] // Requires database and Redis
+    async fn test_revoke_all_adds_tokens_to_redis_revocation_list() {
+        let pool = create_test_pool().await;
+        let (identity_id, tenant_id) = create_identity(&pool).await;
+        let sessions = login_sessions(Uuid::new_v4().to_string(), Uuid::new_v4().to_string());
+        let token_ids = [
+            sessions.access_token_id.clone(),
+            sessions.refresh_token_id.clone(),
+        ];
+        crate::db::with_tx(&pool, move |conn| {
+            Box::pin(async move { record_login(conn, identity_id, tenant_id, &sessions).await })
+        })
+        .await
+        .unwrap();
+
+        let config = crate::config::RedisConfig {
+            url: "redis://localhost:6379".to_string(),
+            pool_size: 10,
+            connection_timeout_seconds: 5,
+            retry: Default::default(),
+        };
+        let mut redis_conn = crate::redis::create_client(&config).await.unwrap();
+
+        let revoked = revoke_all_for_identity(&pool, &mut redis_conn, identity_id)
+            .await
+            .unwrap();
+
+        assert_eq!(revoked, 2);
+        for token_id in &token_ids {
+            assert!(revocation::is_token_revoked(&mut redis_conn, token_id)
+                .await
+                .unwrap());
+            assert!(get_by_token_id(&pool, token_id).await.unwrap().is_none());
+        }
+    }
 }
@@ -0,0 +1,119 @@
+// Persisted Biscuit and audit signing keys
+//
+// Scheduled rotations are recorded here so that every instance signs with the
+// same key and a restart keeps the keys issued tokens and signatures need.
+
+use crate::errors::Result;
+use chrono::{DateTime, Utc};
+use sqlx::{PgConnection, PgPool};
+
+/// A signing key installed by a rotation
+#[derive(Debug, Clone)]
+pub struct StoredSigningKey {
+    pub key_id: String,
+    pub secret_encrypted: String,
+    pub created_at: DateTime<Utc>,
+    pub retired_at: Option<DateTime<Utc>>,
+}
+
+/// Hold the rotation lock of `purpose` until the transaction ends
+pub async fn lock_purpose(conn: &mut PgConnection, purpose: &str) -> Result<()> {
+    sqlx::query!(
+        "SELECT pg_advisory_xact_lock(hashtext('managed_signing_keys:' || $1))",
+        purpose
+    )
+    .fetch_one(conn)
+    .await?;
+
+    Ok(())
+}
+
+/// Creation time of the active key of `purpose`, if any rotation is recorded
+pub async fn active_since(conn: &mut PgConnection, purpose: &str) -> Result<Option<DateTime<Utc>>> {
+    let created_at = sqlx::query_scalar!(
+        r#"
+        SELECT created_at
+        FROM managed_signing_keys
+        WHERE purpose = $1 AND retired_at IS NULL
+        ORDER BY created_at DESC
+        LIMIT 1
+        "#,
+        purpose
+    )
+    .fetch_optional(conn)
+    .await?;
+
+    Ok(created_at)
+}
+
+/// Retire the active key of `purpose` and record `key_id` as the new one
+pub async fn insert_rotated(
+    conn: &mut PgConnection,
+    purpose: &str,
+    key_id: &str,
+    secret_encrypted: &str,
+    rotated_at: DateTime<Utc>,
+) -> Result<()> {
+    sqlx::query!(
+        r#"
+        UPDATE managed_signing_keys
+        SET retired_at = $2
+        WHERE purpose = $1 AND retired_at IS NULL
+        "#,
+        purpose,
+        rotated_at
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    sqlx::query!(
+        r#"
+        INSERT INTO managed_signing_keys (key_id, purpose, secret_encrypted, created_at)
+        VALUES ($1, $2, $3, $4)
+        "#,
+        key_id,
+        purpose,
+        secret_encrypted,
+        rotated_at
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+}
+
+/// Keys of `purpose` that are active or were retired after `retired_after`,
+/// newest first
+pub async fn in_use(
+    pool: &PgPool,
+    purpose: &str,
+    retired_after: DateTime<Utc>,
+) -> Result<Vec<StoredSigningKey>> {
+    let keys = sqlx::query_as!(
+        StoredSigningKey,
+        r#"
+        SELECT key_id, secret_encrypted, created_at, retired_at
+        FROM managed_signing_keys
+        WHERE purpose = $1 AND (retired_at IS NULL OR retired_at > $2)
+        ORDER BY created_at DESC, key_id DESC
+        "#,
+        purpose,
+        retired_after
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(keys)
+}
+
+/// When the first key of `purpose` was rotated in, if ever
+pub async fn first_rotated_at(pool: &PgPool, purpose: &str) -> Result<Option<DateTime<Utc>>> {
+    let first = sqlx::query_scalar!(
+        r#"SELECT MIN(created_at) AS "first" FROM managed_signing_keys WHERE purpose = $1"#,
+        purpose
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(first)
+}
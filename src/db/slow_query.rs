@@ -0,0 +1,121 @@
+// Slow query logging
+//
+// Queries on the authorization and identity paths are wrapped in `timed`.
+// Those taking longer than `database.slow_query_threshold_ms` are logged at
+// warn level and counted in `db_slow_queries_total`, both under a static
+// statement label; the SQL and its parameters are never logged.
+
+use crate::observability::MetricsRecorder;
+use once_cell::sync::OnceCell;
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+static SLOW_QUERY_THRESHOLD: OnceCell<Duration> = OnceCell::new();
+
+/// Set the duration above which queries are logged as slow; call once at startup
+///
+/// 0 disables slow query logging, as does not calling this.
+pub fn configure_slow_query_threshold(threshold_ms: u64) {
+    if SLOW_QUERY_THRESHOLD
+        .set(Duration::from_millis(threshold_ms))
+        .is_err()
+    {
+        tracing::warn!("Slow query threshold already configured; ignoring new setting");
+    }
+}
+
+/// Current slow query threshold; `None` when slow queries are not logged
+pub fn slow_query_threshold() -> Option<Duration> {
+    SLOW_QUERY_THRESHOLD
+        .get()
+        .copied()
+        .filter(|threshold| !threshold.is_zero())
+}
+
+/// Run a query, logging it under `statement` if it is slow
+pub async fn timed<F, T>(statement: &'static str, query: F) -> T
+where
+    F: Future<Output = T>,
+{
+    match slow_query_threshold() {
+        Some(threshold) => timed_with_threshold(statement, threshold, query).await,
+        None => query.await,
+    }
+}
+
+async fn timed_with_threshold<F, T>(statement: &'static str, threshold: Duration, query: F) -> T
+where
+    F: Future<Output = T>,
+{
+    let started = Instant::now();
+    let result = query.await;
+    let elapsed = started.elapsed();
+
+    if elapsed > threshold {
+        tracing::warn!(
+            statement,
+            duration_ms = elapsed.as_millis() as u64,
+            threshold_ms = threshold.as_millis() as u64,
+            "Slow database query"
+        );
+        MetricsRecorder::record_slow_query(statement);
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io;
+    use std::sync::{Arc, Mutex};
+
+    /// Log output captured by a test subscriber
+    #[derive(Clone, Default)]
+    struct CapturedLogs(Arc<Mutex<Vec<u8>>>);
+
+    impl io::Write for CapturedLogs {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_slow_query_logged_and_counted() {
+        let logs = CapturedLogs::default();
+        let writer = logs.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .with_writer(move || writer.clone())
+            .with_ansi(false)
+            .finish();
+        let _guard = tracing::subscriber::set_default(subscriber);
+        let counted = || {
+            MetricsRecorder::export()
+                .unwrap()
+                .contains("db_slow_queries_total{statement=\"slow_query_test\"} 1")
+        };
+
+        let fast =
+            timed_with_threshold("slow_query_test", Duration::from_secs(5), async { 1 }).await;
+        assert_eq!(fast, 1);
+        assert!(!counted());
+
+        let slow = timed_with_threshold("slow_query_test", Duration::from_millis(10), async {
+            tokio::time::sleep(Duration::from_millis(30)).await;
+            2
+        })
+        .await;
+        assert_eq!(slow, 2);
+        assert!(counted());
+
+        let output = String::from_utf8(logs.0.lock().unwrap().clone()).unwrap();
+        assert_eq!(output.matches("Slow database query").count(), 1);
+        assert!(output.contains("WARN"));
+        assert!(output.contains("statement=\"slow_query_test\""));
+    }
+}
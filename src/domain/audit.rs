@@ -94,6 +94,11 @@ pub enum AuditEventType {
     IdentityCreated,
     IdentityUpdated,
     IdentityDeleted,
+    IdentityExported,
+    IdentityErased,
+    TenantCreated,
+    TenantUpdated,
+    TenantDeleted,
     RoleAssigned,
     RoleRevoked,
     PolicyCreated,
@@ -106,6 +111,7 @@ pub enum AuditEventType {
     TokenRefreshed,
     TokenRevoked,
     RateLimitExceeded,
+    RateLimitReset,
     ConfigurationChanged,
     SystemEvent,
 }
@@ -118,6 +124,11 @@ impl AuditEventType {
             AuditEventType::IdentityCreated => "identity_created",
             AuditEventType::IdentityUpdated => "identity_updated",
             AuditEventType::IdentityDeleted => "identity_deleted",
+            AuditEventType::IdentityExported => "identity_exported",
+            AuditEventType::IdentityErased => "identity_erased",
+            AuditEventType::TenantCreated => "tenant_created",
+            AuditEventType::TenantUpdated => "tenant_updated",
+            AuditEventType::TenantDeleted => "tenant_deleted",
             AuditEventType::RoleAssigned => "role_assigned",
             AuditEventType::RoleRevoked => "role_revoked",
             AuditEventType::PolicyCreated => "policy_created",
@@ -130,6 +141,7 @@ impl AuditEventType {
             AuditEventType::TokenRefreshed => "token_refreshed",
             AuditEventType::TokenRevoked => "token_revoked",
             AuditEventType::RateLimitExceeded => "rate_limit_exceeded",
+            AuditEventType::RateLimitReset => "rate_limit_reset",
             AuditEventType::ConfigurationChanged => "configuration_changed",
             AuditEventType::SystemEvent => "system_event",
         }
@@ -160,4 +172,59 @@ pub struct PersistedAuditEvent {
     pub event: AuditEvent,
     pub signature: Option<String>,
     pub previous_event_hash: Option<String>,
+    /// Hash of this event, used as its Merkle leaf
+    pub event_hash: Option<String>,
+    /// Batch this event was anchored in
+    pub batch_id: Option<Uuid>,
+    /// Leaf position within the batch's Merkle tree
+    pub batch_index: Option<i32>,
+}
+
+/// Merkle root anchoring a batch of persisted audit events
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditBatchAnchor {
+    pub id: Uuid,
+    pub merkle_root: String,
+    pub event_count: i32,
+    pub root_signature: Option<String>,
+    pub signing_key_id: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Genesis anchor for a tenant's audit chain after older events were archived
+///
+/// Stands in for the archived segment: the first retained event's
+/// `previous_hash` must equal `anchor_hash`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChainAnchor {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    /// Hash of the last archived event
+    pub anchor_hash: String,
+    /// Number of events in the archived segment
+    pub archived_event_count: i64,
+    /// Timestamp of the last archived event
+    pub archived_through: DateTime<Utc>,
+    /// Base64 Ed25519 signature over the anchor's canonical form
+    pub signature: String,
+    pub signing_key_id: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Signed record of an audit event whose actor was erased
+///
+/// The event's stored hash stays the original one, so Merkle proofs keep
+/// working; its current content must hash to `redacted_event_hash`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventRedaction {
+    pub event_id: Uuid,
+    pub tenant_id: Uuid,
+    /// Hash the event was logged with
+    pub original_event_hash: String,
+    /// Hash of the event as it is now stored
+    pub redacted_event_hash: String,
+    /// Base64 Ed25519 signature over the redaction's canonical form
+    pub signature: String,
+    pub signing_key_id: String,
+    pub created_at: DateTime<Utc>,
 }
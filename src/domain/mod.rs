@@ -2,5 +2,9 @@
 pub mod identity;
 pub mod session;
 pub mod policy;
+pub mod policy_bundle;
 pub mod role;
 pub mod audit;
+pub mod tenant;
+pub mod export;
+pub mod task_scope;
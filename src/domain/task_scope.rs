@@ -0,0 +1,175 @@
+// Task scope of an agent
+//
+// The scope is stored on agent identities and embedded in their Biscuits.
+// Known fields are typed; any other top-level keys are kept as-is in `custom`
+// so callers can still extend the scope.
+
+use crate::authz::validation::TYPED_CONTEXT_ACTIONS;
+use crate::errors::{AppError, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+
+/// What an agent is allowed to do for its task
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TaskScope {
+    /// Actions the agent may perform; each must be a known action
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allowed_actions: Vec<String>,
+    /// Resource prefixes the agent is limited to, e.g. `reports/`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub resource_prefixes: Vec<String>,
+    /// Actions the agent may never perform, even if allowed elsewhere
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub denied_actions: Vec<String>,
+    /// Whether the agent's token may be used only once, for one-shot operations
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub single_use: bool,
+    /// Other scope entries, passed through untouched
+    #[serde(flatten)]
+    pub custom: Map<String, Value>,
+}
+
+impl TaskScope {
+    /// Parse a scope given as JSON, such as one stored on an identity
+    pub fn from_json(value: Value) -> Result<Self> {
+        if !value.is_object() {
+            return Err(AppError::ValidationError(
+                "Task scope must be a JSON object".to_string(),
+            ));
+        }
+        serde_json::from_value(value)
+            .map_err(|e| AppError::ValidationError(format!("Invalid task scope: {}", e)))
+    }
+
+    /// Check the typed fields
+    pub fn validate(&self) -> Result<()> {
+        for action in self.allowed_actions.iter().chain(&self.denied_actions) {
+            if !TYPED_CONTEXT_ACTIONS.contains(&action.as_str()) {
+                return Err(AppError::ValidationError(format!(
+                    "Unknown task scope action '{}'; expected one of: {}",
+                    action,
+                    TYPED_CONTEXT_ACTIONS.join(", ")
+                )));
+            }
+        }
+        if let Some(action) = self
+            .allowed_actions
+            .iter()
+            .find(|action| self.denied_actions.contains(action))
+        {
+            return Err(AppError::ValidationError(format!(
+                "Task scope action '{}' is both allowed and denied",
+                action
+            )));
+        }
+        for prefix in &self.resource_prefixes {
+            if prefix.trim().is_empty() || prefix.chars().any(|c| c.is_control()) {
+                return Err(AppError::ValidationError(
+                    "Task scope resource prefixes must be non-empty and printable".to_string(),
+                ));
+            }
+        }
+        if self.custom.keys().any(|key| key.is_empty()) {
+            return Err(AppError::ValidationError(
+                "Task scope keys must not be empty".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// The scope as a JSON object, as stored on the identity
+    pub fn to_json(&self) -> Result<Value> {
+        serde_json::to_value(self)
+            .map_err(|e| AppError::Internal(format!("Failed to serialize task scope: {}", e)))
+    }
+
+    /// Top-level scope entries, as embedded in a Biscuit
+    pub fn to_map(&self) -> Result<HashMap<String, Value>> {
+        match self.to_json()? {
+            Value::Object(map) => Ok(map.into_iter().collect()),
+            _ => Err(AppError::Internal(
+                "Task scope did not serialize to an object".to_string(),
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_typed_fields_round_trip() {
+        let value = json!({
+            "allowed_actions": ["read", "update"],
+            "resource_prefixes": ["reports/"],
+            "denied_actions": ["delete"],
+            "max_cost_usd": 5
+        });
+
+        let scope = TaskScope::from_json(value.clone()).unwrap();
+        assert_eq!(scope.allowed_actions, ["read", "update"]);
+        assert_eq!(scope.resource_prefixes, ["reports/"]);
+        assert_eq!(scope.denied_actions, ["delete"]);
+        assert_eq!(scope.custom.get("max_cost_usd"), Some(&json!(5)));
+        assert!(scope.validate().is_ok());
+
+        assert_eq!(scope.to_json().unwrap(), value);
+        assert_eq!(scope.to_map().unwrap().len(), 4);
+    }
+
+    #[test]
+    fn test_untyped_scope_kept_as_custom_entries() {
+        let scope = TaskScope::from_json(json!({ "resources": ["reports"] })).unwrap();
+
+        assert!(scope.allowed_actions.is_empty());
+        assert_eq!(
+            scope.to_json().unwrap(),
+            json!({ "resources": ["reports"] })
+        );
+        assert_eq!(TaskScope::default().to_json().unwrap(), json!({}));
+    }
+
+    #[test]
+    fn test_single_use_flag_round_trips() {
+        let scope = TaskScope::from_json(json!({ "single_use": true })).unwrap();
+
+        assert!(scope.single_use);
+        assert!(scope.custom.is_empty());
+        assert_eq!(scope.to_json().unwrap(), json!({ "single_use": true }));
+        assert!(!TaskScope::from_json(json!({})).unwrap().single_use);
+    }
+
+    #[test]
+    fn test_malformed_scope_rejected() {
+        assert!(TaskScope::from_json(json!(["read"])).is_err());
+        assert!(TaskScope::from_json(json!({ "allowed_actions": "read" })).is_err());
+        assert!(TaskScope::from_json(json!({ "denied_actions": [1] })).is_err());
+    }
+
+    #[test]
+    fn test_unknown_action_rejected() {
+        let scope =
+            TaskScope::from_json(json!({ "allowed_actions": ["read", "teleport"] })).unwrap();
+
+        assert!(matches!(
+            scope.validate(),
+            Err(AppError::ValidationError(msg)) if msg.contains("teleport")
+        ));
+    }
+
+    #[test]
+    fn test_contradictory_or_blank_entries_rejected() {
+        let both = TaskScope::from_json(json!({
+            "allowed_actions": ["delete"],
+            "denied_actions": ["delete"]
+        }))
+        .unwrap();
+        assert!(both.validate().is_err());
+
+        let blank = TaskScope::from_json(json!({ "resource_prefixes": [" "] })).unwrap();
+        assert!(blank.validate().is_err());
+    }
+}
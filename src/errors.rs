@@ -1,11 +1,98 @@
+use crate::observability::MetricsRecorder;
 use axum::{
-    http::StatusCode,
+    http::{header, HeaderValue, StatusCode},
     response::{IntoResponse, Response},
     Json,
 };
+use chrono::{DateTime, Utc};
+use serde::Serialize;
 use serde_json::json;
 use std::fmt;
 
+/// Seconds a client is asked to wait when no database connection was available
+const DB_UNAVAILABLE_RETRY_AFTER_SECONDS: u64 = 1;
+
+/// When a throttled client may retry, as sent in the `Retry-After` header
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RetryAfter {
+    /// Delay in seconds
+    Seconds(u64),
+    /// Absolute time, sent as an HTTP-date
+    Date(DateTime<Utc>),
+}
+
+impl RetryAfter {
+    /// Header value per RFC 9110 section 10.2.3
+    pub fn header_value(&self) -> String {
+        match self {
+            RetryAfter::Seconds(seconds) => seconds.to_string(),
+            RetryAfter::Date(date) => date.format("%a, %d %b %Y %H:%M:%S GMT").to_string(),
+        }
+    }
+}
+
+/// A validation failure tied to one request field
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FieldError {
+    pub field: String,
+    pub message: String,
+}
+
+impl FieldError {
+    pub fn new(field: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            field: field.into(),
+            message: message.into(),
+        }
+    }
+}
+
+/// Collects field errors so a request reports all of its problems at once
+#[derive(Debug, Default)]
+pub struct FieldErrors(Vec<FieldError>);
+
+impl FieldErrors {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, field: impl Into<String>, message: impl Into<String>) {
+        self.0.push(FieldError::new(field, message));
+    }
+
+    /// Record the outcome of a check of a single field
+    ///
+    /// Validation errors are kept against `field` (nested field errors keep
+    /// their own names); any other error is returned.
+    pub fn check(&mut self, field: &str, result: Result<()>) -> Result<()> {
+        match result {
+            Ok(()) => Ok(()),
+            Err(AppError::ValidationError(message)) => {
+                self.add(field, message);
+                Ok(())
+            }
+            Err(AppError::ValidationErrors(errors)) => {
+                self.0.extend(errors);
+                Ok(())
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// `Ok` if nothing was collected, otherwise a `ValidationErrors` error
+    pub fn into_result(self) -> Result<()> {
+        if self.0.is_empty() {
+            Ok(())
+        } else {
+            Err(AppError::ValidationErrors(self.0))
+        }
+    }
+}
+
 /// Application-wide error type
 #[derive(Debug)]
 pub enum AppError {
@@ -23,6 +110,7 @@ pub enum AppError {
     TokenExpired,
     TokenRevoked,
     Unauthorized,
+    EmailNotVerified,
 
     // Authorization errors
     Forbidden,
@@ -38,10 +126,20 @@ pub enum AppError {
     SessionExpired,
 
     // Rate limiting
-    RateLimitExceeded,
+    RateLimitExceeded(Option<RetryAfter>),
+
+    // Temporarily unable to serve requests (e.g. database pool exhausted)
+    ServiceUnavailable(Option<RetryAfter>),
 
     // Validation errors
     ValidationError(String),
+    ValidationErrors(Vec<FieldError>),
+
+    // Lookup errors
+    NotFound(String),
+
+    // Conflicting concurrent or repeated requests
+    Conflict(String),
 
     // Configuration errors
     Configuration(String),
@@ -65,6 +163,7 @@ impl fmt::Display for AppError {
             AppError::TokenExpired => write!(f, "Token has expired"),
             AppError::TokenRevoked => write!(f, "Token has been revoked"),
             AppError::Unauthorized => write!(f, "Unauthorized"),
+            AppError::EmailNotVerified => write!(f, "Email address has not been verified"),
             AppError::Forbidden => write!(f, "Forbidden"),
             AppError::PolicyEvaluation(msg) => write!(f, "Policy evaluation error: {}", msg),
             AppError::IdentityNotFound => write!(f, "Identity not found"),
@@ -72,8 +171,18 @@ impl fmt::Display for AppError {
             AppError::InvalidIdentityType => write!(f, "Invalid identity type"),
             AppError::SessionNotFound => write!(f, "Session not found"),
             AppError::SessionExpired => write!(f, "Session has expired"),
-            AppError::RateLimitExceeded => write!(f, "Rate limit exceeded"),
+            AppError::RateLimitExceeded(_) => write!(f, "Rate limit exceeded"),
+            AppError::ServiceUnavailable(_) => write!(f, "Service unavailable"),
             AppError::ValidationError(msg) => write!(f, "Validation error: {}", msg),
+            AppError::ValidationErrors(errors) => {
+                let fields: Vec<String> = errors
+                    .iter()
+                    .map(|e| format!("{}: {}", e.field, e.message))
+                    .collect();
+                write!(f, "Validation error: {}", fields.join("; "))
+            }
+            AppError::NotFound(msg) => write!(f, "Not found: {}", msg),
+            AppError::Conflict(msg) => write!(f, "Conflict: {}", msg),
             AppError::Configuration(msg) => write!(f, "Configuration error: {}", msg),
             AppError::Cryptographic(msg) => write!(f, "Cryptographic error: {}", msg),
             AppError::Internal(msg) => write!(f, "Internal error: {}", msg),
@@ -84,9 +193,22 @@ impl fmt::Display for AppError {
 impl std::error::Error for AppError {}
 
 // Convert from various error types
+//
+// Failing to get a pool connection means the database is overloaded or
+// shutting down, not that the request went wrong, so clients are told to
+// retry.
 impl From<sqlx::Error> for AppError {
     fn from(err: sqlx::Error) -> Self {
-        AppError::Database(err)
+        let reason = match err {
+            sqlx::Error::PoolTimedOut => "timed_out",
+            sqlx::Error::PoolClosed => "closed",
+            err => return AppError::Database(err),
+        };
+        tracing::warn!(reason, "No database connection available");
+        MetricsRecorder::record_db_pool_exhausted(reason);
+        AppError::ServiceUnavailable(Some(RetryAfter::Seconds(
+            DB_UNAVAILABLE_RETRY_AFTER_SECONDS,
+        )))
     }
 }
 
@@ -113,6 +235,67 @@ impl From<jsonwebtoken::errors::Error> for AppError {
     }
 }
 
+// Cedar errors: malformed input is a validation error, failures while
+// assembling or evaluating policies are evaluation errors
+impl From<cedar_policy::ParseErrors> for AppError {
+    fn from(err: cedar_policy::ParseErrors) -> Self {
+        AppError::ValidationError(format!("Invalid Cedar syntax: {}", err))
+    }
+}
+
+impl From<cedar_policy::ContextJsonError> for AppError {
+    fn from(err: cedar_policy::ContextJsonError) -> Self {
+        AppError::ValidationError(format!("Invalid authorization context: {}", err))
+    }
+}
+
+impl From<cedar_policy::RequestValidationError> for AppError {
+    fn from(err: cedar_policy::RequestValidationError) -> Self {
+        AppError::ValidationError(format!("Invalid authorization request: {}", err))
+    }
+}
+
+impl From<cedar_policy::PolicySetError> for AppError {
+    fn from(err: cedar_policy::PolicySetError) -> Self {
+        AppError::PolicyEvaluation(format!("Failed to build policy set: {}", err))
+    }
+}
+
+impl From<cedar_policy::AuthorizationError> for AppError {
+    fn from(err: cedar_policy::AuthorizationError) -> Self {
+        AppError::PolicyEvaluation(err.to_string())
+    }
+}
+
+// Some Cedar `FromStr` impls (e.g. `EntityId`) cannot fail
+impl From<std::convert::Infallible> for AppError {
+    fn from(err: std::convert::Infallible) -> Self {
+        match err {}
+    }
+}
+
+impl AppError {
+    /// `WWW-Authenticate` challenge for bearer-token failures (RFC 6750)
+    ///
+    /// A request without credentials gets a bare `Bearer` challenge; a bad
+    /// token or one lacking the required privileges names the error.
+    fn www_authenticate(&self) -> Option<String> {
+        let (error, description) = match self {
+            AppError::Unauthorized => return Some("Bearer".to_string()),
+            AppError::TokenValidation(_) => ("invalid_token", "Invalid token"),
+            AppError::TokenRevoked => ("invalid_token", "Token revoked"),
+            AppError::TokenExpired => ("expired_token", "Token expired"),
+            AppError::SessionExpired => ("expired_token", "Session expired"),
+            AppError::Forbidden => ("insufficient_scope", "Forbidden"),
+            _ => return None,
+        };
+        Some(format!(
+            "Bearer error=\"{}\", error_description=\"{}\"",
+            error, description
+        ))
+    }
+}
+
 // Implement IntoResponse for Axum
 impl IntoResponse for AppError {
     fn into_response(self) -> Response {
@@ -134,6 +317,7 @@ impl IntoResponse for AppError {
             AppError::TokenExpired => (StatusCode::UNAUTHORIZED, "Token expired"),
             AppError::TokenRevoked => (StatusCode::UNAUTHORIZED, "Token revoked"),
             AppError::Unauthorized => (StatusCode::UNAUTHORIZED, "Unauthorized"),
+            AppError::EmailNotVerified => (StatusCode::FORBIDDEN, "Email not verified"),
             AppError::Forbidden => (StatusCode::FORBIDDEN, "Forbidden"),
             AppError::PolicyEvaluation(_) => {
                 tracing::error!("Policy evaluation error: {:?}", self);
@@ -144,8 +328,15 @@ impl IntoResponse for AppError {
             AppError::InvalidIdentityType => (StatusCode::BAD_REQUEST, "Invalid identity type"),
             AppError::SessionNotFound => (StatusCode::NOT_FOUND, "Session not found"),
             AppError::SessionExpired => (StatusCode::UNAUTHORIZED, "Session expired"),
-            AppError::RateLimitExceeded => (StatusCode::TOO_MANY_REQUESTS, "Rate limit exceeded"),
+            AppError::RateLimitExceeded(_) => (StatusCode::TOO_MANY_REQUESTS, "Rate limit exceeded"),
+            AppError::ServiceUnavailable(_) => (
+                StatusCode::SERVICE_UNAVAILABLE,
+                "Service temporarily unavailable",
+            ),
             AppError::ValidationError(_) => (StatusCode::BAD_REQUEST, self.to_string().as_str()),
+            AppError::ValidationErrors(_) => (StatusCode::BAD_REQUEST, "Validation failed"),
+            AppError::NotFound(msg) => (StatusCode::NOT_FOUND, msg.as_str()),
+            AppError::Conflict(msg) => (StatusCode::CONFLICT, msg.as_str()),
             AppError::Configuration(_) => {
                 tracing::error!("Configuration error: {:?}", self);
                 (StatusCode::INTERNAL_SERVER_ERROR, "Internal server error")
@@ -160,14 +351,196 @@ impl IntoResponse for AppError {
             }
         };
 
-        let body = Json(json!({
+        let mut body = json!({
             "error": error_message,
             "status": status.as_u16(),
-        }));
+        });
+        if let AppError::ValidationErrors(errors) = &self {
+            body["errors"] = json!(errors);
+        }
+        let body = Json(body);
+
+        let mut response = (status, body).into_response();
+
+        if let Some(challenge) = self.www_authenticate() {
+            if let Ok(value) = HeaderValue::from_str(&challenge) {
+                response
+                    .headers_mut()
+                    .insert(header::WWW_AUTHENTICATE, value);
+            }
+        }
+
+        if let AppError::RateLimitExceeded(Some(retry_after))
+        | AppError::ServiceUnavailable(Some(retry_after)) = &self
+        {
+            if let Ok(value) = HeaderValue::from_str(&retry_after.header_value()) {
+                response.headers_mut().insert(header::RETRY_AFTER, value);
+            }
+        }
 
-        (status, body).into_response()
+        response
     }
 }
 
 /// Result type alias for the application
 pub type Result<T> = std::result::Result<T, AppError>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_rate_limit_response_includes_retry_after_seconds() {
+        let response = AppError::RateLimitExceeded(Some(RetryAfter::Seconds(30))).into_response();
+
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(response.headers().get(header::RETRY_AFTER).unwrap(), "30");
+    }
+
+    #[test]
+    fn test_retry_after_http_date() {
+        let date = Utc.with_ymd_and_hms(2015, 10, 21, 7, 28, 0).unwrap();
+        assert_eq!(
+            RetryAfter::Date(date).header_value(),
+            "Wed, 21 Oct 2015 07:28:00 GMT"
+        );
+    }
+
+    #[test]
+    fn test_cedar_parse_errors_are_validation_errors() {
+        let err: AppError = "permit(".parse::<cedar_policy::PolicySet>().unwrap_err().into();
+        assert!(matches!(err, AppError::ValidationError(_)));
+        assert_eq!(err.into_response().status(), StatusCode::BAD_REQUEST);
+    }
+
+    fn challenge(err: AppError) -> (StatusCode, Option<String>) {
+        let response = err.into_response();
+        let challenge = response
+            .headers()
+            .get(header::WWW_AUTHENTICATE)
+            .map(|value| value.to_str().unwrap().to_string());
+        (response.status(), challenge)
+    }
+
+    #[test]
+    fn test_www_authenticate_per_variant() {
+        let cases = [
+            (
+                AppError::TokenValidation("bad signature".to_string()),
+                StatusCode::UNAUTHORIZED,
+                r#"Bearer error="invalid_token", error_description="Invalid token""#,
+            ),
+            (
+                AppError::TokenRevoked,
+                StatusCode::UNAUTHORIZED,
+                r#"Bearer error="invalid_token", error_description="Token revoked""#,
+            ),
+            (
+                AppError::TokenExpired,
+                StatusCode::UNAUTHORIZED,
+                r#"Bearer error="expired_token", error_description="Token expired""#,
+            ),
+            (
+                AppError::Forbidden,
+                StatusCode::FORBIDDEN,
+                r#"Bearer error="insufficient_scope", error_description="Forbidden""#,
+            ),
+            (AppError::Unauthorized, StatusCode::UNAUTHORIZED, "Bearer"),
+        ];
+
+        for (err, status, expected) in cases {
+            assert_eq!(challenge(err), (status, Some(expected.to_string())));
+        }
+    }
+
+    #[test]
+    fn test_no_challenge_for_other_errors() {
+        assert_eq!(challenge(AppError::InvalidCredentials).1, None);
+        assert_eq!(challenge(AppError::EmailNotVerified).1, None);
+        assert_eq!(challenge(AppError::NotFound("x".to_string())).1, None);
+    }
+
+    #[tokio::test]
+    async fn test_field_errors_serialized_as_array() {
+        let mut errors = FieldErrors::new();
+        errors.add("email", "Invalid email format");
+        errors
+            .check(
+                "name",
+                Err(AppError::ValidationError(
+                    "Identity name cannot be empty".to_string(),
+                )),
+            )
+            .unwrap();
+        let response = errors.into_result().unwrap_err().into_response();
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(
+            body,
+            json!({
+                "error": "Validation failed",
+                "status": 400,
+                "errors": [
+                    { "field": "email", "message": "Invalid email format" },
+                    { "field": "name", "message": "Identity name cannot be empty" },
+                ],
+            })
+        );
+    }
+
+    #[test]
+    fn test_field_errors_pass_other_errors_through() {
+        let mut errors = FieldErrors::new();
+        assert!(matches!(
+            errors.check("name", Err(AppError::Forbidden)),
+            Err(AppError::Forbidden)
+        ));
+        assert!(errors.is_empty());
+        assert!(errors.into_result().is_ok());
+    }
+
+    #[test]
+    fn test_pool_timeout_is_service_unavailable() {
+        let counted = |reason: &str| {
+            let line = format!("db_pool_exhausted_total{{reason=\"{}\"}}", reason);
+            MetricsRecorder::export()
+                .unwrap()
+                .lines()
+                .find(|l| l.starts_with(&line))
+                .and_then(|l| l.rsplit(' ').next()?.parse::<u64>().ok())
+                .unwrap_or(0)
+        };
+        let before = counted("timed_out");
+
+        let err: AppError = sqlx::Error::PoolTimedOut.into();
+        assert!(matches!(err, AppError::ServiceUnavailable(Some(_))));
+        assert_eq!(counted("timed_out"), before + 1);
+
+        let response = err.into_response();
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(response.headers().get(header::RETRY_AFTER).unwrap(), "1");
+
+        let closed: AppError = sqlx::Error::PoolClosed.into();
+        assert_eq!(
+            closed.into_response().status(),
+            StatusCode::SERVICE_UNAVAILABLE
+        );
+
+        // Other database errors are still internal errors
+        let not_found: AppError = sqlx::Error::RowNotFound.into();
+        assert!(matches!(not_found, AppError::Database(_)));
+    }
+
+    #[test]
+    fn test_rate_limit_response_without_retry_after() {
+        let response = AppError::RateLimitExceeded(None).into_response();
+
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert!(response.headers().get(header::RETRY_AFTER).is_none());
+    }
+}
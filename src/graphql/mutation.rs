@@ -0,0 +1,356 @@
+// GraphQL mutations (admin only)
+
+use crate::authz::cache::notify_policy_change;
+use crate::authz::lint::{lint_policy, LintWarning};
+use crate::authz::validation::{create_request_context_schema, PolicyValidator};
+use crate::db;
+use crate::domain::audit::{AuditEvent, AuditEventType};
+use crate::domain::policy::{
+    ensure_policy_capacity, lock_tenant_policies, policy_annotations, policy_limits,
+    validate_policy_window,
+};
+use crate::errors::{AppError, FieldErrors};
+use crate::graphql::types::{CreatedPolicy, Identity, Policy, PolicyInput};
+use crate::graphql::{
+    audit_sink, db_pool, principal_id, redis_conn, require_admin, GraphQLResultExt,
+};
+use async_graphql::{Context, Object};
+use serde_json::json;
+use uuid::Uuid;
+
+pub struct MutationRoot;
+
+#[Object]
+impl MutationRoot {
+    /// Change an identity's status (active, suspended, deleted)
+    ///
+    /// Audited like the REST API: `IdentityDeleted` for `deleted`,
+    /// `IdentityUpdated` otherwise. Leaving `active` revokes the identity's
+    /// sessions, so its tokens stop working right away.
+    async fn update_identity_status(
+        &self,
+        ctx: &Context<'_>,
+        id: Uuid,
+        status: String,
+    ) -> async_graphql::Result<Identity> {
+        let tenant_id = require_admin(ctx).await?;
+
+        if !matches!(status.as_str(), "active" | "suspended" | "deleted") {
+            return Err(AppError::ValidationError(format!(
+                "Invalid identity status: {}. Must be 'active', 'suspended', or 'deleted'",
+                status
+            )))
+            .gql();
+        }
+
+        let previous_status = sqlx::query_scalar!(
+            "SELECT status FROM identities WHERE id = $1 AND tenant_id = $2",
+            id,
+            tenant_id
+        )
+        .fetch_optional(db_pool(ctx)?)
+        .await
+        .gql()?
+        .ok_or(AppError::IdentityNotFound)
+        .gql()?;
+
+        let identity = sqlx::query_as!(
+            Identity,
+            r#"
+            UPDATE identities
+            SET status = $3
+            WHERE id = $1 AND tenant_id = $2
+            RETURNING id, tenant_id, identity_type, name, email, status, parent_identity_id,
+                      task_id, expires_at, created_at, updated_at
+            "#,
+            id,
+            tenant_id,
+            status
+        )
+        .fetch_optional(db_pool(ctx)?)
+        .await
+        .gql()?
+        .ok_or(AppError::IdentityNotFound)
+        .gql()?;
+
+        if identity.status != "active" {
+            let mut redis_conn = redis_conn(ctx)?;
+            db::sessions::revoke_all_for_identity(db_pool(ctx)?, &mut redis_conn, identity.id)
+                .await
+                .gql()?;
+        }
+
+        let (event_type, action) = if identity.status == "deleted" {
+            (AuditEventType::IdentityDeleted, "delete_identity")
+        } else {
+            (AuditEventType::IdentityUpdated, "update_identity_status")
+        };
+        let event = AuditEvent::new(
+            tenant_id,
+            event_type,
+            action.to_string(),
+            "identity".to_string(),
+        )
+        .with_actor(principal_id(ctx)?)
+        .with_resource_id(identity.id.to_string())
+        .with_metadata(json!({
+            "before_status": previous_status,
+            "after_status": identity.status,
+        }));
+        audit_sink(ctx)?.log(event).await.gql()?;
+
+        Ok(identity)
+    }
+
+    /// Create a policy in the caller's tenant, returning lint warnings about it
+    async fn create_policy(
+        &self,
+        ctx: &Context<'_>,
+        input: PolicyInput,
+    ) -> async_graphql::Result<CreatedPolicy> {
+        let tenant_id = require_admin(ctx).await?;
+        validate_policy_input(&input).gql()?;
+        let warnings = lint_new_policy(db_pool(ctx)?, tenant_id, &input.policy_cedar)
+            .await
+            .gql()?;
+        let annotations = policy_annotations(&input.policy_cedar).gql()?;
+
+        // Checking the caps and inserting under the tenant's lock keeps
+        // concurrent creates from together going over them
+        let mut tx = db_pool(ctx)?.begin().await.gql()?;
+        lock_tenant_policies(&mut tx, tenant_id).await.gql()?;
+        ensure_policy_capacity(
+            &mut *tx,
+            &policy_limits(),
+            tenant_id,
+            &input.policy_cedar,
+            None,
+        )
+        .await
+        .gql()?;
+
+        let policy = sqlx::query_as!(
+            Policy,
+            r#"
+            INSERT INTO policies (
+                tenant_id, name, description, policy_cedar, resource_type, priority, effect,
+                annotations, valid_from, valid_until
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+            RETURNING id, tenant_id, name, description, policy_cedar, resource_type, priority,
+                      effect, status, version, annotations, valid_from, valid_until,
+                      created_at, updated_at
+            "#,
+            tenant_id,
+            input.name,
+            input.description,
+            input.policy_cedar,
+            input.resource_type,
+            input.priority,
+            input.effect.to_lowercase(),
+            annotations,
+            input.valid_from,
+            input.valid_until
+        )
+        .fetch_one(&mut *tx)
+        .await
+        .gql()?;
+        tx.commit().await.gql()?;
+
+        policies_changed(tenant_id).await;
+        let event = policy_event(
+            tenant_id,
+            AuditEventType::PolicyCreated,
+            "create_policy",
+            policy.id,
+        )
+        .with_actor(principal_id(ctx)?)
+        .with_metadata(json!({ "name": policy.name, "version": policy.version }));
+        audit_sink(ctx)?.log(event).await.gql()?;
+
+        Ok(CreatedPolicy {
+            policy,
+            warnings: warnings.into_iter().map(Into::into).collect(),
+        })
+    }
+
+    /// Replace a policy's definition, bumping its version
+    async fn update_policy(
+        &self,
+        ctx: &Context<'_>,
+        id: Uuid,
+        input: PolicyInput,
+    ) -> async_graphql::Result<Policy> {
+        let tenant_id = require_admin(ctx).await?;
+        validate_policy_input(&input).gql()?;
+        let annotations = policy_annotations(&input.policy_cedar).gql()?;
+
+        let mut tx = db_pool(ctx)?.begin().await.gql()?;
+        lock_tenant_policies(&mut tx, tenant_id).await.gql()?;
+        ensure_policy_capacity(
+            &mut *tx,
+            &policy_limits(),
+            tenant_id,
+            &input.policy_cedar,
+            Some(id),
+        )
+        .await
+        .gql()?;
+
+        let policy = sqlx::query_as!(
+            Policy,
+            r#"
+            UPDATE policies
+            SET name = $3, description = $4, policy_cedar = $5, resource_type = $6,
+                priority = $7, effect = $8, annotations = $9, valid_from = $10,
+                valid_until = $11, version = version + 1
+            WHERE id = $1 AND tenant_id = $2 AND status <> 'deleted'
+            RETURNING id, tenant_id, name, description, policy_cedar, resource_type, priority,
+                      effect, status, version, annotations, valid_from, valid_until,
+                      created_at, updated_at
+            "#,
+            id,
+            tenant_id,
+            input.name,
+            input.description,
+            input.policy_cedar,
+            input.resource_type,
+            input.priority,
+            input.effect.to_lowercase(),
+            annotations,
+            input.valid_from,
+            input.valid_until
+        )
+        .fetch_optional(&mut *tx)
+        .await
+        .gql()?;
+        tx.commit().await.gql()?;
+
+        let policy = policy
+            .ok_or_else(|| AppError::NotFound("Policy not found".to_string()))
+            .gql()?;
+
+        policies_changed(tenant_id).await;
+        let event = policy_event(
+            tenant_id,
+            AuditEventType::PolicyUpdated,
+            "update_policy",
+            policy.id,
+        )
+        .with_actor(principal_id(ctx)?)
+        .with_metadata(json!({ "name": policy.name, "version": policy.version }));
+        audit_sink(ctx)?.log(event).await.gql()?;
+
+        Ok(policy)
+    }
+
+    /// Soft-delete a policy; returns false if it did not exist
+    async fn delete_policy(&self, ctx: &Context<'_>, id: Uuid) -> async_graphql::Result<bool> {
+        let tenant_id = require_admin(ctx).await?;
+
+        let result = sqlx::query!(
+            r#"
+            UPDATE policies
+            SET status = 'deleted'
+            WHERE id = $1 AND tenant_id = $2 AND status <> 'deleted'
+            "#,
+            id,
+            tenant_id
+        )
+        .execute(db_pool(ctx)?)
+        .await
+        .gql()?;
+
+        let deleted = result.rows_affected() > 0;
+        if deleted {
+            policies_changed(tenant_id).await;
+            let event = policy_event(
+                tenant_id,
+                AuditEventType::PolicyDeleted,
+                "delete_policy",
+                id,
+            )
+            .with_actor(principal_id(ctx)?);
+            audit_sink(ctx)?.log(event).await.gql()?;
+        }
+        Ok(deleted)
+    }
+}
+
+/// Event recorded when a tenant's policy is written
+fn policy_event(
+    tenant_id: Uuid,
+    event_type: AuditEventType,
+    action: &str,
+    policy_id: Uuid,
+) -> AuditEvent {
+    AuditEvent::new(
+        tenant_id,
+        event_type,
+        action.to_string(),
+        "policy".to_string(),
+    )
+    .with_resource_id(policy_id.to_string())
+}
+
+/// Invalidate the tenant's cached policies on every instance
+///
+/// The write has already been committed, so a failure to announce it is
+/// logged rather than returned.
+async fn policies_changed(tenant_id: Uuid) {
+    if let Err(e) = notify_policy_change(tenant_id).await {
+        tracing::error!(tenant_id = %tenant_id, "Failed to announce policy change: {}", e);
+    }
+}
+
+/// Lint a validated policy against the tenant's active policies, with global ones
+///
+/// Stored policies that no longer parse are left out of the comparison.
+async fn lint_new_policy(
+    pool: &sqlx::PgPool,
+    tenant_id: Uuid,
+    policy_cedar: &str,
+) -> crate::errors::Result<Vec<LintWarning>> {
+    let policy = cedar_policy::Policy::parse(None, policy_cedar)
+        .map_err(|e| AppError::ValidationError(format!("Failed to parse policy: {}", e)))?;
+
+    let stored = sqlx::query!(
+        r#"
+        SELECT id, policy_cedar
+        FROM policies
+        WHERE status = 'active' AND (tenant_id = $1 OR tenant_id IS NULL)
+        "#,
+        tenant_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut existing = cedar_policy::PolicySet::new();
+    for row in stored {
+        let id = Some(row.id.to_string());
+        if let Ok(parsed) = cedar_policy::Policy::parse(id, row.policy_cedar) {
+            existing.add(parsed)?;
+        }
+    }
+
+    let schema = create_request_context_schema()?;
+    Ok(lint_policy(&policy, Some(&schema), &existing))
+}
+
+fn validate_policy_input(input: &PolicyInput) -> crate::errors::Result<()> {
+    let mut errors = FieldErrors::new();
+    errors.check("name", PolicyValidator::validate_policy_name(&input.name))?;
+    errors.check("effect", PolicyValidator::validate_effect(&input.effect))?;
+    errors.check("priority", PolicyValidator::validate_priority(input.priority))?;
+    errors.check(
+        "validUntil",
+        validate_policy_window(input.valid_from, input.valid_until),
+    )?;
+
+    let result = PolicyValidator::new().validate_policy_string(&input.policy_cedar)?;
+    if !result.is_valid {
+        errors.add("policyCedar", result.errors.join("; "));
+    }
+
+    errors.into_result()
+}
@@ -0,0 +1,163 @@
+// GraphQL read queries
+
+use crate::graphql::types::{AuditEvent, Identity, Policy, PolicyAnnotationFilter};
+use crate::graphql::{clamp_limit, db_pool, principal_tenant, GraphQLResultExt};
+use async_graphql::{Context, Object};
+use uuid::Uuid;
+
+pub struct QueryRoot;
+
+#[Object]
+impl QueryRoot {
+    /// Look up an identity in the caller's tenant
+    async fn identity(&self, ctx: &Context<'_>, id: Uuid) -> async_graphql::Result<Option<Identity>> {
+        let tenant_id = principal_tenant(ctx)?;
+
+        sqlx::query_as!(
+            Identity,
+            r#"
+            SELECT id, tenant_id, identity_type, name, email, status, parent_identity_id,
+                   task_id, expires_at, created_at, updated_at
+            FROM identities
+            WHERE id = $1 AND tenant_id = $2
+            "#,
+            id,
+            tenant_id
+        )
+        .fetch_optional(db_pool(ctx)?)
+        .await
+        .gql()
+    }
+
+    /// List identities in the caller's tenant
+    async fn identities(
+        &self,
+        ctx: &Context<'_>,
+        identity_type: Option<String>,
+        status: Option<String>,
+        #[graphql(default = 50)] limit: i64,
+        #[graphql(default = 0)] offset: i64,
+    ) -> async_graphql::Result<Vec<Identity>> {
+        let tenant_id = principal_tenant(ctx)?;
+
+        sqlx::query_as!(
+            Identity,
+            r#"
+            SELECT id, tenant_id, identity_type, name, email, status, parent_identity_id,
+                   task_id, expires_at, created_at, updated_at
+            FROM identities
+            WHERE tenant_id = $1
+              AND ($2::TEXT IS NULL OR identity_type = $2)
+              AND ($3::TEXT IS NULL OR status = $3)
+            ORDER BY created_at DESC
+            LIMIT $4 OFFSET $5
+            "#,
+            tenant_id,
+            identity_type,
+            status,
+            clamp_limit(limit),
+            offset.max(0)
+        )
+        .fetch_all(db_pool(ctx)?)
+        .await
+        .gql()
+    }
+
+    /// Look up a policy in the caller's tenant
+    async fn policy(&self, ctx: &Context<'_>, id: Uuid) -> async_graphql::Result<Option<Policy>> {
+        let tenant_id = principal_tenant(ctx)?;
+
+        sqlx::query_as!(
+            Policy,
+            r#"
+            SELECT id, tenant_id, name, description, policy_cedar, resource_type, priority,
+                   effect, status, version, annotations, valid_from, valid_until,
+                   created_at, updated_at
+            FROM policies
+            WHERE id = $1 AND tenant_id = $2
+            "#,
+            id,
+            tenant_id
+        )
+        .fetch_optional(db_pool(ctx)?)
+        .await
+        .gql()
+    }
+
+    /// List policies in the caller's tenant (deleted policies are excluded unless requested),
+    /// optionally only those carrying an annotation
+    async fn policies(
+        &self,
+        ctx: &Context<'_>,
+        status: Option<String>,
+        annotation: Option<PolicyAnnotationFilter>,
+        #[graphql(default = 50)] limit: i64,
+        #[graphql(default = 0)] offset: i64,
+    ) -> async_graphql::Result<Vec<Policy>> {
+        let tenant_id = principal_tenant(ctx)?;
+        let (annotation_key, annotation_value) = match annotation {
+            Some(filter) => (Some(filter.key), filter.value),
+            None => (None, None),
+        };
+
+        sqlx::query_as!(
+            Policy,
+            r#"
+            SELECT id, tenant_id, name, description, policy_cedar, resource_type, priority,
+                   effect, status, version, annotations, valid_from, valid_until,
+                   created_at, updated_at
+            FROM policies
+            WHERE tenant_id = $1
+              AND (($2::TEXT IS NULL AND status <> 'deleted') OR status = $2)
+              AND ($3::TEXT IS NULL OR annotations ? $3)
+              AND ($4::TEXT IS NULL OR annotations @> jsonb_build_object($3::TEXT, $4::TEXT))
+            ORDER BY priority DESC, created_at ASC
+            LIMIT $5 OFFSET $6
+            "#,
+            tenant_id,
+            status,
+            annotation_key,
+            annotation_value,
+            clamp_limit(limit),
+            offset.max(0)
+        )
+        .fetch_all(db_pool(ctx)?)
+        .await
+        .gql()
+    }
+
+    /// List audit events in the caller's tenant, newest first
+    async fn audit_events(
+        &self,
+        ctx: &Context<'_>,
+        event_type: Option<String>,
+        actor_identity_id: Option<Uuid>,
+        #[graphql(default = 50)] limit: i64,
+        #[graphql(default = 0)] offset: i64,
+    ) -> async_graphql::Result<Vec<AuditEvent>> {
+        let tenant_id = principal_tenant(ctx)?;
+
+        sqlx::query_as!(
+            AuditEvent,
+            r#"
+            SELECT id, actor_identity_id, event_type, action, resource_type, resource_id,
+                   decision, decision_reason, request_id,
+                   metadata, timestamp
+            FROM audit_logs
+            WHERE tenant_id = $1
+              AND ($2::TEXT IS NULL OR event_type = $2)
+              AND ($3::UUID IS NULL OR actor_identity_id = $3)
+            ORDER BY timestamp DESC
+            LIMIT $4 OFFSET $5
+            "#,
+            tenant_id,
+            event_type,
+            actor_identity_id,
+            clamp_limit(limit),
+            offset.max(0)
+        )
+        .fetch_all(db_pool(ctx)?)
+        .await
+        .gql()
+    }
+}
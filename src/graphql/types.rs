@@ -0,0 +1,199 @@
+// GraphQL object types
+
+use crate::api::identities::{chain_truncated, get_delegation_chain_query, DelegationChainNode};
+use crate::authz::lint::LintWarning;
+use crate::domain::identity::delegation_query_max_depth;
+use crate::domain::policy::annotation_pairs;
+use crate::graphql::{db_pool, GraphQLResultExt};
+use async_graphql::{ComplexObject, Context, InputObject, Json, SimpleObject};
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+/// An identity (user, service, or agent)
+#[derive(Debug, Clone, SimpleObject, sqlx::FromRow)]
+#[graphql(complex)]
+pub struct Identity {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub identity_type: String,
+    pub name: String,
+    pub email: Option<String>,
+    pub status: String,
+    pub parent_identity_id: Option<Uuid>,
+    pub task_id: Option<String>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[ComplexObject]
+impl Identity {
+    /// Delegation chain from this identity up to its root (depth 0 is this identity)
+    async fn delegation_chain(&self, ctx: &Context<'_>) -> async_graphql::Result<Vec<DelegationNode>> {
+        let max_depth = delegation_query_max_depth();
+        let nodes = get_delegation_chain_query(db_pool(ctx)?, self.id, self.tenant_id, max_depth)
+            .await
+            .gql()?;
+        Ok(nodes.into_iter().map(DelegationNode::from).collect())
+    }
+
+    /// Whether the delegation chain reached the configured query depth and may continue beyond it
+    async fn delegation_chain_truncated(&self, ctx: &Context<'_>) -> async_graphql::Result<bool> {
+        let max_depth = delegation_query_max_depth();
+        let nodes = get_delegation_chain_query(db_pool(ctx)?, self.id, self.tenant_id, max_depth)
+            .await
+            .gql()?;
+        Ok(chain_truncated(&nodes, max_depth))
+    }
+}
+
+/// A node in an identity's delegation chain
+#[derive(Debug, Clone, SimpleObject)]
+pub struct DelegationNode {
+    pub id: Uuid,
+    pub identity_type: String,
+    pub name: String,
+    pub email: Option<String>,
+    pub status: String,
+    pub parent_identity_id: Option<Uuid>,
+    pub task_id: Option<String>,
+    pub task_scope: Option<Json<serde_json::Value>>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub depth: i32,
+}
+
+impl From<DelegationChainNode> for DelegationNode {
+    fn from(node: DelegationChainNode) -> Self {
+        Self {
+            id: node.id,
+            identity_type: node.identity_type,
+            name: node.name,
+            email: node.email,
+            status: node.status,
+            parent_identity_id: node.parent_identity_id,
+            task_id: node.task_id,
+            task_scope: node.task_scope.map(Json),
+            expires_at: node.expires_at,
+            created_at: node.created_at,
+            depth: node.depth,
+        }
+    }
+}
+
+/// A Cedar policy
+#[derive(Debug, Clone, SimpleObject, sqlx::FromRow)]
+#[graphql(complex)]
+pub struct Policy {
+    pub id: Uuid,
+    pub tenant_id: Option<Uuid>,
+    pub name: String,
+    pub description: Option<String>,
+    pub policy_cedar: String,
+    pub resource_type: Option<String>,
+    pub priority: i32,
+    pub effect: String,
+    pub status: String,
+    pub version: i32,
+    #[graphql(skip)]
+    pub annotations: serde_json::Value,
+    /// Start of the policy's validity window (inclusive); open when null
+    pub valid_from: Option<DateTime<Utc>>,
+    /// End of the policy's validity window (exclusive); open when null
+    pub valid_until: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[ComplexObject]
+impl Policy {
+    /// Cedar annotations of the policy (`@owner("payments")`), sorted by key
+    #[graphql(name = "annotations")]
+    async fn annotation_list(&self) -> Vec<PolicyAnnotation> {
+        annotation_pairs(&self.annotations)
+            .into_iter()
+            .map(|(key, value)| PolicyAnnotation { key, value })
+            .collect()
+    }
+}
+
+/// A Cedar annotation of a policy
+#[derive(Debug, Clone, SimpleObject)]
+pub struct PolicyAnnotation {
+    pub key: String,
+    pub value: String,
+}
+
+/// Filter on a policy annotation; without a value any policy with the key matches
+#[derive(Debug, Clone, InputObject)]
+pub struct PolicyAnnotationFilter {
+    pub key: String,
+    pub value: Option<String>,
+}
+
+/// A newly created policy, with any lint warnings about it
+#[derive(Debug, Clone, SimpleObject)]
+pub struct CreatedPolicy {
+    pub policy: Policy,
+    pub warnings: Vec<PolicyWarning>,
+}
+
+/// An authoring smell found in a policy; it does not block the write
+#[derive(Debug, Clone, SimpleObject)]
+pub struct PolicyWarning {
+    pub code: String,
+    pub message: String,
+}
+
+impl From<LintWarning> for PolicyWarning {
+    fn from(warning: LintWarning) -> Self {
+        Self {
+            code: warning.code,
+            message: warning.message,
+        }
+    }
+}
+
+/// Input for creating or updating a policy
+#[derive(Debug, Clone, InputObject)]
+pub struct PolicyInput {
+    pub name: String,
+    pub description: Option<String>,
+    pub policy_cedar: String,
+    pub resource_type: Option<String>,
+    #[graphql(default = 0)]
+    pub priority: i32,
+    #[graphql(default_with = "\"allow\".to_string()")]
+    pub effect: String,
+    /// The policy applies from this time on
+    pub valid_from: Option<DateTime<Utc>>,
+    /// The policy stops applying at this time
+    pub valid_until: Option<DateTime<Utc>>,
+}
+
+/// A recorded audit event
+#[derive(Debug, Clone, SimpleObject, sqlx::FromRow)]
+#[graphql(complex)]
+pub struct AuditEvent {
+    pub id: Uuid,
+    pub actor_identity_id: Option<Uuid>,
+    pub event_type: String,
+    pub action: String,
+    pub resource_type: String,
+    pub resource_id: Option<String>,
+    pub decision: Option<String>,
+    pub decision_reason: Option<String>,
+    pub request_id: Option<Uuid>,
+    #[graphql(skip)]
+    pub metadata: Option<serde_json::Value>,
+    pub timestamp: DateTime<Utc>,
+}
+
+#[ComplexObject]
+impl AuditEvent {
+    /// Free-form event metadata
+    #[graphql(name = "metadata")]
+    async fn metadata_json(&self) -> Option<Json<serde_json::Value>> {
+        self.metadata.clone().map(Json)
+    }
+}
@@ -4,14 +4,23 @@ pub mod api;
 pub mod audit;
 pub mod auth;
 pub mod authz;
+pub mod clock;
 pub mod config;
 pub mod crypto;
 pub mod db;
 pub mod domain;
 pub mod errors;
+#[cfg(feature = "grpc")]
+pub mod grpc;
+#[cfg(feature = "graphql")]
+pub mod graphql;
 pub mod observability;
+pub mod oidc;
 pub mod rate_limit;
 pub mod redis;
+pub mod scim;
+pub mod server;
+pub mod webhooks;
 
 pub use config::Config;
 pub use errors::{AppError, Result};
@@ -1,18 +1,63 @@
 use agent_iam::{
-    api::create_router,
+    api::{
+        authz::{configure_bulkhead, configure_circuit_breaker, spawn_policy_warmup},
+        create_router, AppState,
+    },
+    audit::{
+        export::AuditExports,
+        logger::{AuditLoggerBuilder, AuditLoggerConfig},
+        storage::{AuditStorage, FallbackAuditStorage, FileAuditStorage, PostgresAuditStorage},
+    },
+    auth::{
+        binding::{configure_token_binding, configure_trusted_proxies},
+        biscuit::BiscuitManager,
+        jwt::JwtManager,
+        jwt_rotation::{self, spawn_key_sync},
+        password::PasswordPolicy,
+        webauthn::WebauthnService,
+    },
+    authz::cache::{configure_policy_bus, RedisPolicyBus},
     config::Config,
-    db::{create_pool, run_migrations},
-    observability::init_tracing,
+    crypto::{
+        encryption::SecretCipher,
+        key_store,
+        keys::{KeyManager, KeyPurpose, ManagedKey},
+        pseudonym::AuditPseudonymizer,
+        signing::AuditSigner,
+    },
+    db::{
+        biscuit_revocations::spawn_revocation_gc, create_pool, run_migrations,
+        slow_query::configure_slow_query_threshold,
+    },
+    domain::{
+        identity::{configure_delegation_depth_warning, configure_delegation_query_max_depth},
+        policy::{configure_policy_limits, spawn_policy_window_scheduler},
+    },
+    observability::{init_tracing, metrics::configure_metric_tenants, HealthChecker},
+    oidc::{upstream::UpstreamClient, OidcProvider},
+    rate_limit::RateLimiter,
     redis::create_client,
+    server,
+    webhooks::{AccountNotifier, WebhookDispatcher},
 };
-use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
 
-#[tokio::main]
-async fn main() -> anyhow::Result<()> {
+fn main() -> anyhow::Result<()> {
     // Load configuration
     let config = Config::load()?;
     config.validate()?;
 
+    // Size the runtime from `server.workers`
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(config.server.workers)
+        .enable_all()
+        .build()?;
+
+    runtime.block_on(run(config))
+}
+
+async fn run(config: Config) -> anyhow::Result<()> {
     // Initialize tracing/logging
     init_tracing(&config.observability);
 
@@ -27,25 +72,257 @@ async fn main() -> anyhow::Result<()> {
     run_migrations(&db_pool).await?;
     tracing::info!("Database migrations completed");
 
+    // Log authorization and identity queries slower than the threshold
+    configure_slow_query_threshold(config.database.slow_query_threshold_ms);
+
+    // Label only allowlisted tenants in metrics to keep label cardinality bounded
+    configure_metric_tenants(&config.observability.metrics_tenants);
+
+    // Stop hitting the database from authorization checks while it is failing
+    configure_circuit_breaker(&config.authz.circuit_breaker);
+
+    // Keep one tenant's authorization traffic from starving the others
+    configure_bulkhead(&config.authz.bulkhead);
+
+    // Cap how many policies, and how much policy text, each tenant can store
+    configure_policy_limits(config.authz.policy_limits);
+
+    // Warn before agents reach the delegation depth limit
+    configure_delegation_depth_warning(config.auth.delegation_depth_warning);
+
+    // Bound how far delegation chain queries follow parent links
+    configure_delegation_query_max_depth(config.auth.delegation_query_max_depth);
+
+    // Refuse access tokens used from a client context they were not issued to
+    configure_token_binding(config.auth.token_binding);
+
+    // Resolve client IPs from the socket peer or the trusted proxies' X-Forwarded-For
+    configure_trusted_proxies(config.auth.trusted_proxies);
+
     // Create Redis connection
     let redis_manager = create_client(&config.redis).await?;
     tracing::info!("Redis connection established");
 
-    // Create router
-    let app = create_router(db_pool.clone(), redis_manager.clone());
+    // Invalidate cached tenant policies when another instance changes them
+    configure_policy_bus(Arc::new(RedisPolicyBus::new(
+        &config.redis.url,
+        redis_manager.clone(),
+    )?))
+    .await?;
+
+    // Preload tenant policies in the background
+    spawn_policy_warmup(db_pool.clone(), &config.authz.warmup);
+
+    // Periodically expire policies past their validity window and announce window changes
+    if config.authz.policy_window_interval_seconds > 0 {
+        spawn_policy_window_scheduler(
+            db_pool.clone(),
+            Duration::from_secs(config.authz.policy_window_interval_seconds),
+        );
+    }
+
+    // Encryption for stored MFA secrets, upstream OIDC client secrets and rotated
+    // JWT secrets and signing keys (MFA enrollment and rotation are disabled
+    // without a key)
+    let mfa_cipher = SecretCipher::from_config(&config.crypto)?.map(Arc::new);
+    if mfa_cipher.is_none() {
+        tracing::warn!("MFA encryption key not configured; MFA enrollment is unavailable");
+    }
+
+    // Create JWT manager (shared so secret rotation applies to every request),
+    // signing with the most recently rotated secret if there is one
+    let jwt_manager = Arc::new(JwtManager::new(&config)?);
+    jwt_rotation::sync(&db_pool, &jwt_manager, mfa_cipher.as_deref()).await?;
 
-    // Bind server
-    let addr = SocketAddr::from(([0, 0, 0, 0], config.server.port));
-    tracing::info!("Listening on http://{}", addr);
+    // Pick up rotations made on other instances and drop the previous JWT secret
+    // once its validation window has passed
+    spawn_key_sync(
+        db_pool.clone(),
+        jwt_manager.clone(),
+        mfa_cipher.clone(),
+        Duration::from_secs(60),
+    );
 
-    // Start server
-    let listener = tokio::net::TcpListener::bind(addr).await?;
+    // Start the gRPC authorization service alongside the HTTP server
+    #[cfg(feature = "grpc")]
+    {
+        let grpc_addr = config.server.grpc_addr()?;
+        let grpc_pool = db_pool.clone();
+        let grpc_redis = redis_manager.clone();
+        let grpc_jwt_manager = jwt_manager.clone();
+        tokio::spawn(async move {
+            let served =
+                agent_iam::grpc::serve(grpc_addr, grpc_pool, grpc_redis, grpc_jwt_manager).await;
+            if let Err(e) = served {
+                tracing::error!("gRPC server stopped: {}", e);
+            }
+        });
+    }
+
+    // Create the audit logger (also feeds webhooks and the live audit stream),
+    // spilling to a local file when the database cannot take a batch and
+    // dead-lettering batches that still fail after retries
+    let audit_storage: Arc<dyn AuditStorage> = match &config.audit.fallback_file {
+        Some(path) => Arc::new(FallbackAuditStorage::new(
+            Box::new(PostgresAuditStorage::new(db_pool.clone())),
+            Box::new(FileAuditStorage::new(path)),
+        )),
+        None => Arc::new(PostgresAuditStorage::new(db_pool.clone())),
+    };
+    let audit_logger_config = AuditLoggerConfig {
+        batch_size: config.audit.async_batch_size,
+        batch_timeout_ms: config.audit.async_flush_interval_seconds * 1000,
+        max_flush_retries: config.audit.flush_retries,
+        retry_backoff_ms: config.audit.flush_retry_backoff_ms,
+        ..AuditLoggerConfig::default()
+    };
+    // Events and batch roots are signed when an audit signing key is configured
+    let audit_signer = if config.crypto.audit_signing_key.is_some() {
+        Some(Arc::new(AuditSigner::from_config(&config.crypto)?))
+    } else {
+        None
+    };
+    let mut audit_builder = AuditLoggerBuilder::new(audit_storage, audit_logger_config);
+    if let Some(path) = &config.audit.dead_letter_file {
+        audit_builder = audit_builder.dead_letter(Arc::new(FileAuditStorage::new(path)));
+    }
+    // Rotated every `crypto.key_rotation_days`, starting from the configured key
+    let audit_keys = match &audit_signer {
+        Some(signer) => Some(Arc::new(KeyManager::from_config_with_key(
+            KeyPurpose::AuditSigning,
+            &config.crypto,
+            ManagedKey::from_secret(signer.key_id().to_string(), &signer.secret_key_bytes())?,
+        ))),
+        None => None,
+    };
+    if let Some(keys) = &audit_keys {
+        audit_builder = audit_builder.signing_keys(keys.clone());
+    }
+    let mut audit_logger = audit_builder.build();
+    if config.webhooks.enabled {
+        audit_logger = audit_logger.with_webhooks(Arc::new(WebhookDispatcher::new(&config.webhooks)?));
+    }
+    // Tenants listed in `audit.pseudonymize_tenants` get keyed hashes of their audit IDs
+    if let Some(pseudonymizer) =
+        AuditPseudonymizer::from_config(&config.crypto, &config.audit.pseudonymize_tenants)?
+    {
+        audit_logger = audit_logger.with_pseudonymizer(Arc::new(pseudonymizer));
+    }
+    let audit_logger = Arc::new(audit_logger);
+
+    // Shared rate limiter (also used by the admin rate-limit endpoints)
+    let rate_limiter = Arc::new(tokio::sync::Mutex::new(RateLimiter::new(
+        redis_manager.clone(),
+        config.rate_limit.clone(),
+    )));
+
+    // Passkey relying party
+    let webauthn = Arc::new(WebauthnService::from_config(&config.webauthn)?);
+
+    // OpenID Provider for web app sign-in
+    let oidc = Arc::new(OidcProvider::from_config(&config)?);
+
+    // Client for tenants' upstream OpenID Providers
+    let upstream_oidc = Arc::new(UpstreamClient::from_config(&config)?);
+
+    // Password rules and delivery of password reset tokens
+    let password_policy = Arc::new(PasswordPolicy::from_config(&config.auth));
+    let account_notifier = Arc::new(AccountNotifier::from_config(&config.webhooks)?);
+
+    // Signing key for agent Biscuit tokens, rotated like the audit signing key,
+    // with revocations kept in the database
+    let biscuit = BiscuitManager::from_config(&config.auth, &config.crypto)?
+        .with_revocation_store(db_pool.clone());
+    let biscuit_keys = Arc::new(KeyManager::from_config_with_key(
+        KeyPurpose::Biscuit,
+        &config.crypto,
+        ManagedKey::from_secret(
+            config.auth.biscuit_root_key_id.clone(),
+            &biscuit.private_key_bytes(),
+        )?,
+    ));
+    let biscuit = Arc::new(biscuit.with_key_manager(biscuit_keys.clone()));
+
+    // Load the persisted Biscuit and audit signing keys, then rotate them when
+    // due and pick up other instances' rotations (requires the encryption key)
+    let managed_keys: Vec<Arc<KeyManager>> =
+        std::iter::once(biscuit_keys).chain(audit_keys).collect();
+    for keys in &managed_keys {
+        key_store::sync(&db_pool, keys, mfa_cipher.as_deref(), chrono::Utc::now()).await?;
+    }
+    match &mfa_cipher {
+        Some(cipher) => {
+            key_store::spawn_key_sync(
+                db_pool.clone(),
+                managed_keys,
+                cipher.clone(),
+                Duration::from_secs(60),
+            );
+        }
+        None if config.crypto.key_rotation_days > 0 => tracing::warn!(
+            "Encryption key not configured; Biscuit and audit signing keys are not rotated"
+        ),
+        None => {}
+    }
+
+    // Periodically purge revocations of Biscuits that have expired anyway
+    if config.auth.biscuit_revocation_gc_interval_seconds > 0 {
+        spawn_revocation_gc(
+            db_pool.clone(),
+            Duration::from_secs(config.auth.biscuit_revocation_gc_interval_seconds),
+        );
+    }
+
+    // Signing key for policy bundle exports (the audit signing key when configured)
+    let policy_signer = match &audit_signer {
+        Some(signer) => signer.clone(),
+        None => {
+            tracing::warn!(
+                "Audit signing key not configured; exported policy bundles stop verifying on restart"
+            );
+            Arc::new(AuditSigner::generate(
+                config.crypto.audit_signing_key_id.clone(),
+            ))
+        }
+    };
+
+    // Background audit exports, fetched through signed download URLs
+    let audit_exports = Arc::new(AuditExports::from_config(&config)?);
+
+    // Dependency checks behind the readiness probes
+    let health_checker = Arc::new(
+        HealthChecker::new(db_pool.clone(), redis_manager.clone())
+            .with_cache_ttl(Duration::from_millis(config.observability.health_cache_ttl_ms)),
+    );
+
+    // Create router
+    let state = AppState {
+        db_pool: db_pool.clone(),
+        redis_manager: redis_manager.clone(),
+        health_checker,
+        jwt_manager,
+        audit_logger,
+        rate_limiter,
+        mfa_cipher,
+        webauthn,
+        oidc,
+        upstream_oidc,
+        password_policy,
+        account_notifier,
+        biscuit,
+        policy_signer,
+        audit_exports,
+    };
+    let app = create_router(state, &config.security);
+
+    // Bind server (TLS or plaintext depending on security settings)
+    let addr = config.server.http_addr()?;
 
     tracing::info!("Agent IAM service is ready to accept requests");
 
-    axum::serve(listener, app)
-        .await
-        .map_err(|e| anyhow::anyhow!("Server error: {}", e))?;
+    server::serve(app, addr, &config).await?;
+
+    tracing::info!("Agent IAM service stopped");
 
     Ok(())
 }
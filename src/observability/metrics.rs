@@ -1,8 +1,39 @@
-use once_cell::sync::Lazy;
+use once_cell::sync::{Lazy, OnceCell};
 use prometheus::{
-    register_histogram_vec, register_int_counter_vec, register_int_gauge, HistogramVec,
-    IntCounterVec, IntGauge, TextEncoder,
+    register_histogram_vec, register_int_counter, register_int_counter_vec, register_int_gauge,
+    register_int_gauge_vec, HistogramVec, IntCounter, IntCounterVec, IntGauge, IntGaugeVec,
+    TextEncoder,
 };
+use std::collections::HashSet;
+use uuid::Uuid;
+
+// Tenants that get their own label value; all others are counted as `other`
+static METRIC_TENANTS: OnceCell<HashSet<Uuid>> = OnceCell::new();
+
+/// Label value for tenants not on the metrics allowlist (and for no tenant)
+pub const OTHER_TENANT_LABEL: &str = "other";
+
+/// Set the tenants labeled individually in metrics; call once at startup
+///
+/// Without this, every tenant is labeled `other`.
+pub fn configure_metric_tenants(tenants: &[Uuid]) {
+    if METRIC_TENANTS
+        .set(tenants.iter().copied().collect())
+        .is_err()
+    {
+        tracing::warn!("Metric tenants already configured; ignoring new setting");
+    }
+}
+
+/// Bounded `tenant_id` label value: the tenant if allowlisted, else `other`
+pub fn tenant_label(tenant_id: Option<Uuid>) -> String {
+    match tenant_id {
+        Some(tenant_id) if METRIC_TENANTS.get().is_some_and(|t| t.contains(&tenant_id)) => {
+            tenant_id.to_string()
+        }
+        _ => OTHER_TENANT_LABEL.to_string(),
+    }
+}
 
 // Metrics registry
 static HTTP_REQUESTS_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
@@ -28,7 +59,7 @@ static AUTHZ_REQUESTS_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
     register_int_counter_vec!(
         "authz_requests_total",
         "Total number of authorization requests",
-        &["decision", "resource_type"]
+        &["decision", "resource_type", "tenant_id"]
     )
     .unwrap()
 });
@@ -37,7 +68,7 @@ static AUTHZ_LATENCY: Lazy<HistogramVec> = Lazy::new(|| {
     register_histogram_vec!(
         "authz_latency_seconds",
         "Authorization decision latency in seconds",
-        &["decision"],
+        &["decision", "tenant_id"],
         vec![0.001, 0.002, 0.005, 0.010, 0.020, 0.050, 0.100]
     )
     .unwrap()
@@ -47,7 +78,7 @@ static AUTHZ_ERRORS_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
     register_int_counter_vec!(
         "authz_policy_evaluation_errors_total",
         "Total number of policy evaluation errors",
-        &["error_type"]
+        &["error_type", "tenant_id"]
     )
     .unwrap()
 });
@@ -65,6 +96,150 @@ static RATE_LIMIT_EXCEEDED_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
     .unwrap()
 });
 
+static HEALTH_CHECK_CACHE_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "health_check_cache_total",
+        "Readiness checks served from cache (hit) or run against dependencies (miss)",
+        &["result"]
+    )
+    .unwrap()
+});
+
+static BISCUIT_CACHE_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "biscuit_cache_total",
+        "Biscuit validations served from the claims cache (hit) or parsed (miss)",
+        &["result"]
+    )
+    .unwrap()
+});
+
+static RATE_LIMIT_EXEMPT_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "rate_limit_exempt_total",
+        "Total number of requests that bypassed the default rate limiter",
+        &["reason"]
+    )
+    .unwrap()
+});
+
+static RATE_LIMIT_BACKEND_ERRORS_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "rate_limit_backend_errors_total",
+        "Total number of rate limit checks that failed to reach the backend",
+        &["fail_mode"]
+    )
+    .unwrap()
+});
+
+static TOKEN_ISSUANCE_DURATION: Lazy<HistogramVec> = Lazy::new(|| {
+    register_histogram_vec!(
+        "token_issuance_duration_seconds",
+        "End-to-end token issuance latency in seconds, from request to signed tokens",
+        &["grant_type"],
+        vec![0.010, 0.025, 0.050, 0.100, 0.250, 0.500, 1.0, 2.5]
+    )
+    .unwrap()
+});
+
+static CIRCUIT_BREAKER_STATE: Lazy<IntGaugeVec> = Lazy::new(|| {
+    register_int_gauge_vec!(
+        "circuit_breaker_state",
+        "Current circuit breaker state (1 for the active state, 0 otherwise)",
+        &["breaker", "state"]
+    )
+    .unwrap()
+});
+
+static CIRCUIT_BREAKER_TRANSITIONS_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "circuit_breaker_transitions_total",
+        "Total number of circuit breaker state changes, by the state entered",
+        &["breaker", "state"]
+    )
+    .unwrap()
+});
+
+static CIRCUIT_BREAKER_FALLBACK_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "circuit_breaker_fallback_total",
+        "Total number of calls served by a fallback while a circuit was open",
+        &["breaker", "fallback"]
+    )
+    .unwrap()
+});
+
+static DELEGATION_DEPTH: Lazy<IntGaugeVec> = Lazy::new(|| {
+    register_int_gauge_vec!(
+        "delegation_depth",
+        "Delegation depth of the agent most recently provisioned in a tenant",
+        &["tenant_id"]
+    )
+    .unwrap()
+});
+
+static AUDIT_DEAD_LETTER_EVENTS_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "audit_dead_letter_events_total",
+        "Audit events moved to the dead-letter queue after exhausting retries, by outcome",
+        &["outcome"]
+    )
+    .unwrap()
+});
+
+static AUDIT_FLUSH_RETRIES_TOTAL: Lazy<IntCounter> = Lazy::new(|| {
+    register_int_counter!(
+        "audit_flush_retries_total",
+        "Total number of retried audit batch writes"
+    )
+    .unwrap()
+});
+
+static AUDIT_EVENTS_DROPPED_TOTAL: Lazy<IntCounter> = Lazy::new(|| {
+    register_int_counter!(
+        "audit_events_dropped_total",
+        "Audit events dropped because the audit queue was full"
+    )
+    .unwrap()
+});
+
+static AUDIT_FALLBACK_EVENTS_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "audit_fallback_events_total",
+        "Audit events diverted to the fallback storage after the primary failed, by outcome",
+        &["outcome"]
+    )
+    .unwrap()
+});
+
+static AUDIT_RETAINED_EVENTS_DROPPED_TOTAL: Lazy<IntCounter> = Lazy::new(|| {
+    register_int_counter!(
+        "audit_retained_events_dropped_total",
+        "Unwritten audit events dropped because the retention limit was reached"
+    )
+    .unwrap()
+});
+
+static DB_SLOW_QUERIES_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "db_slow_queries_total",
+        "Database queries slower than the slow query threshold, by statement",
+        &["statement"]
+    )
+    .unwrap()
+});
+
+static DB_POOL_EXHAUSTED_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "db_pool_exhausted_total",
+        "Requests refused because no database connection could be acquired, by reason",
+        &["reason"]
+    )
+    .unwrap()
+});
+
+const CIRCUIT_STATES: [&str; 3] = ["closed", "open", "half_open"];
+
 pub struct MetricsRecorder;
 
 impl MetricsRecorder {
@@ -80,30 +255,114 @@ impl MetricsRecorder {
             .observe(duration);
     }
 
-    pub fn record_authz_request(decision: &str, resource_type: &str) {
+    pub fn record_authz_request(decision: &str, resource_type: &str, tenant_id: Option<Uuid>) {
         AUTHZ_REQUESTS_TOTAL
-            .with_label_values(&[decision, resource_type])
+            .with_label_values(&[decision, resource_type, &tenant_label(tenant_id)])
             .inc();
     }
 
-    pub fn record_authz_latency(decision: &str, duration: f64) {
-        AUTHZ_LATENCY.with_label_values(&[decision]).observe(duration);
+    pub fn record_authz_latency(decision: &str, tenant_id: Option<Uuid>, duration: f64) {
+        AUTHZ_LATENCY
+            .with_label_values(&[decision, &tenant_label(tenant_id)])
+            .observe(duration);
     }
 
-    pub fn record_authz_error(error_type: &str) {
-        AUTHZ_ERRORS_TOTAL.with_label_values(&[error_type]).inc();
+    pub fn record_authz_error(error_type: &str, tenant_id: Option<Uuid>) {
+        AUTHZ_ERRORS_TOTAL
+            .with_label_values(&[error_type, &tenant_label(tenant_id)])
+            .inc();
     }
 
     pub fn set_active_sessions(count: i64) {
         ACTIVE_SESSIONS.set(count);
     }
 
-    pub fn record_rate_limit_exceeded(tenant_id: &str, limit_type: &str) {
+    pub fn record_rate_limit_exceeded(tenant_id: Option<Uuid>, limit_type: &str) {
         RATE_LIMIT_EXCEEDED_TOTAL
-            .with_label_values(&[tenant_id, limit_type])
+            .with_label_values(&[&tenant_label(tenant_id), limit_type])
+            .inc();
+    }
+
+    pub fn record_health_check_cache(result: &str) {
+        HEALTH_CHECK_CACHE_TOTAL.with_label_values(&[result]).inc();
+    }
+
+    pub fn record_biscuit_cache(result: &str) {
+        BISCUIT_CACHE_TOTAL.with_label_values(&[result]).inc();
+    }
+
+    pub fn record_rate_limit_exempt(reason: &str) {
+        RATE_LIMIT_EXEMPT_TOTAL.with_label_values(&[reason]).inc();
+    }
+
+    pub fn record_rate_limit_backend_error(fail_mode: &str) {
+        RATE_LIMIT_BACKEND_ERRORS_TOTAL
+            .with_label_values(&[fail_mode])
+            .inc();
+    }
+
+    pub fn record_token_issuance(grant_type: &str, duration: f64) {
+        TOKEN_ISSUANCE_DURATION
+            .with_label_values(&[grant_type])
+            .observe(duration);
+    }
+
+    pub fn set_circuit_breaker_state(breaker: &str, state: &str) {
+        for candidate in CIRCUIT_STATES {
+            CIRCUIT_BREAKER_STATE
+                .with_label_values(&[breaker, candidate])
+                .set(i64::from(candidate == state));
+        }
+    }
+
+    pub fn record_circuit_breaker_transition(breaker: &str, state: &str) {
+        CIRCUIT_BREAKER_TRANSITIONS_TOTAL
+            .with_label_values(&[breaker, state])
             .inc();
     }
 
+    pub fn record_circuit_breaker_fallback(breaker: &str, fallback: &str) {
+        CIRCUIT_BREAKER_FALLBACK_TOTAL
+            .with_label_values(&[breaker, fallback])
+            .inc();
+    }
+
+    pub fn set_delegation_depth(tenant_id: &str, depth: i64) {
+        DELEGATION_DEPTH.with_label_values(&[tenant_id]).set(depth);
+    }
+
+    pub fn record_audit_dead_letter(outcome: &str, events: u64) {
+        AUDIT_DEAD_LETTER_EVENTS_TOTAL
+            .with_label_values(&[outcome])
+            .inc_by(events);
+    }
+
+    pub fn record_audit_flush_retry() {
+        AUDIT_FLUSH_RETRIES_TOTAL.inc();
+    }
+
+    pub fn record_audit_event_dropped() {
+        AUDIT_EVENTS_DROPPED_TOTAL.inc();
+    }
+
+    pub fn record_audit_fallback(outcome: &str, events: u64) {
+        AUDIT_FALLBACK_EVENTS_TOTAL
+            .with_label_values(&[outcome])
+            .inc_by(events);
+    }
+
+    pub fn record_audit_retained_events_dropped(events: u64) {
+        AUDIT_RETAINED_EVENTS_DROPPED_TOTAL.inc_by(events);
+    }
+
+    pub fn record_slow_query(statement: &str) {
+        DB_SLOW_QUERIES_TOTAL.with_label_values(&[statement]).inc();
+    }
+
+    pub fn record_db_pool_exhausted(reason: &str) {
+        DB_POOL_EXHAUSTED_TOTAL.with_label_values(&[reason]).inc();
+    }
+
     /// Export all metrics in Prometheus format
     pub fn export() -> Result<String, prometheus::Error> {
         let encoder = TextEncoder::new();
@@ -111,3 +370,44 @@ impl MetricsRecorder {
         encoder.encode_to_string(&metric_families)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_token_issuance_is_exported() {
+        let count = || {
+            TOKEN_ISSUANCE_DURATION
+                .with_label_values(&["password"])
+                .get_sample_count()
+        };
+        let before = count();
+
+        MetricsRecorder::record_token_issuance("password", 0.042);
+
+        assert_eq!(count(), before + 1);
+        let exported = MetricsRecorder::export().unwrap();
+        assert!(exported
+            .contains("token_issuance_duration_seconds_count{grant_type=\"password\"}"));
+    }
+
+    #[test]
+    fn test_unlisted_tenant_recorded_as_other() {
+        let listed = Uuid::new_v4();
+        configure_metric_tenants(&[listed]);
+        let count = |tenant: &str| {
+            RATE_LIMIT_EXCEEDED_TOTAL
+                .with_label_values(&[tenant, "metric_tenants_test"])
+                .get()
+        };
+        let others_before = count(OTHER_TENANT_LABEL);
+
+        MetricsRecorder::record_rate_limit_exceeded(Some(Uuid::new_v4()), "metric_tenants_test");
+        MetricsRecorder::record_rate_limit_exceeded(None, "metric_tenants_test");
+        MetricsRecorder::record_rate_limit_exceeded(Some(listed), "metric_tenants_test");
+
+        assert_eq!(count(OTHER_TENANT_LABEL), others_before + 2);
+        assert_eq!(count(&listed.to_string()), 1);
+    }
+}
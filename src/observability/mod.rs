@@ -2,6 +2,6 @@ pub mod health;
 pub mod metrics;
 pub mod tracing;
 
-pub use health::{HealthChecker, HealthStatus};
+pub use health::{DetailedHealth, HealthChecker, HealthStatus};
 pub use metrics::MetricsRecorder;
 pub use tracing::init_tracing;
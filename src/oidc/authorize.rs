@@ -0,0 +1,476 @@
+// Authorization endpoint
+//
+// GET shows a login form for a validated authorization request; POST checks
+// the credentials (and TOTP code, if enrolled) and redirects back to the
+// client with a single-use authorization code.
+
+use crate::api::auth::verify_password_login;
+use crate::api::mfa::check_code;
+use crate::api::routes::AppState;
+use crate::db;
+use crate::errors::{AppError, Result};
+use crate::oidc::{has_scope, pkce, AuthorizationGrant, OidcProvider};
+use crate::redis::oidc_code;
+use axum::{
+    extract::{Query, State},
+    http::{header, StatusCode},
+    response::{Html, IntoResponse, Redirect, Response},
+    Form,
+};
+use serde::Deserialize;
+
+/// Authorization request parameters (OpenID Connect Core section 3.1.2.1)
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct AuthorizationRequest {
+    #[serde(default)]
+    pub response_type: String,
+    #[serde(default)]
+    pub client_id: String,
+    #[serde(default)]
+    pub redirect_uri: String,
+    #[serde(default)]
+    pub scope: String,
+    pub state: Option<String>,
+    pub nonce: Option<String>,
+    pub code_challenge: Option<String>,
+    pub code_challenge_method: Option<String>,
+}
+
+/// Login form posted back to the authorization endpoint
+#[derive(Debug, Deserialize)]
+pub struct LoginForm {
+    #[serde(flatten)]
+    pub request: AuthorizationRequest,
+    #[serde(default)]
+    pub email: String,
+    #[serde(default)]
+    pub password: String,
+    /// TOTP code, required when the user has MFA enabled
+    pub code: Option<String>,
+}
+
+#[derive(Debug)]
+pub enum AuthorizeError {
+    /// The client or redirect URI cannot be trusted, so the error is shown to
+    /// the user instead of being redirected
+    Rejected(String),
+    /// Reported to the client at its redirect URI
+    Redirect {
+        redirect_uri: String,
+        state: Option<String>,
+        error: &'static str,
+        description: String,
+    },
+}
+
+impl IntoResponse for AuthorizeError {
+    fn into_response(self) -> Response {
+        match self {
+            AuthorizeError::Rejected(message) => page(StatusCode::BAD_REQUEST, error_page(&message)),
+            AuthorizeError::Redirect {
+                redirect_uri,
+                state,
+                error,
+                description,
+            } => redirect_to(
+                &redirect_uri,
+                &[
+                    ("error", Some(error)),
+                    ("error_description", Some(description.as_str())),
+                    ("state", state.as_deref()),
+                ],
+            ),
+        }
+    }
+}
+
+impl AuthorizationRequest {
+    /// Check the request against the registered client
+    pub fn validate(&self, provider: &OidcProvider) -> std::result::Result<(), AuthorizeError> {
+        let client = provider
+            .client(&self.client_id)
+            .ok_or_else(|| AuthorizeError::Rejected("Unknown client".to_string()))?;
+
+        if !client.redirect_uris.iter().any(|uri| uri == &self.redirect_uri) {
+            return Err(AuthorizeError::Rejected(
+                "Redirect URI is not registered for this client".to_string(),
+            ));
+        }
+
+        if self.response_type != "code" {
+            return Err(self.redirect_error(
+                "unsupported_response_type",
+                "Only the authorization code flow is supported",
+            ));
+        }
+
+        if !has_scope(&self.scope, "openid") {
+            return Err(self.redirect_error("invalid_scope", "The openid scope is required"));
+        }
+
+        match (self.code_challenge.as_deref(), self.code_challenge_method.as_deref()) {
+            (None, _) => Err(self.redirect_error("invalid_request", "code_challenge is required")),
+            // A missing method means "plain", which is not accepted
+            (Some(_), method) if method != Some(pkce::METHOD_S256) => Err(
+                self.redirect_error("invalid_request", "code_challenge_method must be S256"),
+            ),
+            (Some(challenge), _) if !pkce::is_valid_challenge(challenge) => {
+                Err(self.redirect_error("invalid_request", "Malformed code_challenge"))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn redirect_error(&self, error: &'static str, description: &str) -> AuthorizeError {
+        AuthorizeError::Redirect {
+            redirect_uri: self.redirect_uri.clone(),
+            state: self.state.clone(),
+            error,
+            description: description.to_string(),
+        }
+    }
+
+    /// Parameters carried through the login form
+    fn hidden_fields(&self) -> Vec<(&'static str, &str)> {
+        let mut fields = vec![
+            ("response_type", self.response_type.as_str()),
+            ("client_id", self.client_id.as_str()),
+            ("redirect_uri", self.redirect_uri.as_str()),
+            ("scope", self.scope.as_str()),
+        ];
+        for (name, value) in [
+            ("state", &self.state),
+            ("nonce", &self.nonce),
+            ("code_challenge", &self.code_challenge),
+            ("code_challenge_method", &self.code_challenge_method),
+        ] {
+            if let Some(value) = value {
+                fields.push((name, value.as_str()));
+            }
+        }
+        fields
+    }
+}
+
+// ============================================================================
+// Handlers
+// ============================================================================
+
+/// GET /oauth2/authorize
+pub async fn authorize(
+    State(state): State<AppState>,
+    Query(request): Query<AuthorizationRequest>,
+) -> Response {
+    match request.validate(&state.oidc) {
+        Ok(()) => page(StatusCode::OK, login_page(&request, None)),
+        Err(e) => e.into_response(),
+    }
+}
+
+/// POST /oauth2/authorize
+pub async fn submit_login(State(state): State<AppState>, Form(form): Form<LoginForm>) -> Response {
+    let request = &form.request;
+    if let Err(e) = request.validate(&state.oidc) {
+        return e.into_response();
+    }
+
+    match complete_login(&state, &form).await {
+        Ok(code) => redirect_to(
+            &request.redirect_uri,
+            &[("code", Some(code.as_str())), ("state", request.state.as_deref())],
+        ),
+        Err(AppError::InvalidCredentials) => page(
+            StatusCode::UNAUTHORIZED,
+            login_page(request, Some("Invalid credentials")),
+        ),
+        Err(AppError::ValidationError(message)) => {
+            page(StatusCode::BAD_REQUEST, login_page(request, Some(&message)))
+        }
+        Err(e) => e.into_response(),
+    }
+}
+
+/// Authenticate the user and issue an authorization code
+async fn complete_login(state: &AppState, form: &LoginForm) -> Result<String> {
+    let login = verify_password_login(state, &form.email, &form.password).await?;
+    if login.identity_type != "user" {
+        return Err(AppError::InvalidCredentials);
+    }
+
+    // Enrolled users must also pass TOTP
+    let enrollment = db::mfa::get_totp(&state.db_pool, login.id)
+        .await?
+        .filter(|e| e.enabled);
+    let mfa = match enrollment {
+        Some(enrollment) => {
+            let code = form
+                .code
+                .as_deref()
+                .map(str::trim)
+                .filter(|c| !c.is_empty())
+                .ok_or_else(|| {
+                    AppError::ValidationError(
+                        "Enter the code from your authenticator app".to_string(),
+                    )
+                })?;
+            if !check_code(state, &enrollment, code).await? {
+                tracing::warn!("Invalid MFA code for identity: {}", login.id);
+                return Err(AppError::InvalidCredentials);
+            }
+            true
+        }
+        None => false,
+    };
+
+    let identity = db::identities::get_by_id(&state.db_pool, login.id)
+        .await?
+        .ok_or(AppError::InvalidCredentials)?;
+
+    let request = &form.request;
+    let grant = AuthorizationGrant {
+        client_id: request.client_id.clone(),
+        redirect_uri: request.redirect_uri.clone(),
+        code_challenge: request.code_challenge.clone().unwrap_or_default(),
+        scope: request.scope.clone(),
+        nonce: request.nonce.clone(),
+        identity_id: identity.id,
+        tenant_id: identity.tenant_id,
+        identity_type: identity.identity_type,
+        email: identity.email.filter(|_| has_scope(&request.scope, "email")),
+        name: Some(identity.name).filter(|_| has_scope(&request.scope, "profile")),
+        mfa,
+        auth_time: chrono::Utc::now().timestamp(),
+    };
+
+    let mut redis_conn = state.redis_manager.clone();
+    let code = oidc_code::create_code(&mut redis_conn, &grant).await?;
+
+    tracing::info!(
+        "OIDC authorization code issued for identity {} to client {}",
+        identity.id,
+        request.client_id
+    );
+
+    Ok(code)
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+/// Redirect to a client URI with extra query parameters
+fn redirect_to(redirect_uri: &str, params: &[(&str, Option<&str>)]) -> Response {
+    let Ok(mut url) = url::Url::parse(redirect_uri) else {
+        return page(StatusCode::BAD_REQUEST, error_page("Invalid redirect URI"));
+    };
+
+    {
+        let mut query = url.query_pairs_mut();
+        for (name, value) in params {
+            if let Some(value) = value {
+                query.append_pair(name, value);
+            }
+        }
+    }
+
+    Redirect::to(url.as_str()).into_response()
+}
+
+/// HTML response that may not be framed by other sites
+fn page(status: StatusCode, html: Html<String>) -> Response {
+    (status, [(header::X_FRAME_OPTIONS, "DENY")], html).into_response()
+}
+
+fn login_page(request: &AuthorizationRequest, error: Option<&str>) -> Html<String> {
+    let hidden: String = request
+        .hidden_fields()
+        .into_iter()
+        .map(|(name, value)| {
+            format!(
+                r#"<input type="hidden" name="{}" value="{}">"#,
+                name,
+                escape_html(value)
+            )
+        })
+        .collect();
+    let error = error
+        .map(|e| format!(r#"<p class="error">{}</p>"#, escape_html(e)))
+        .unwrap_or_default();
+
+    Html(format!(
+        r#"<!DOCTYPE html>
+<html>
+<head><meta charset="utf-8"><title>Sign in</title></head>
+<body>
+<h1>Sign in to {client}</h1>
+{error}
+<form method="post" action="/oauth2/authorize">
+{hidden}
+<label>Email <input type="email" name="email" autocomplete="username" required></label>
+<label>Password <input type="password" name="password" autocomplete="current-password" required></label>
+<label>Authenticator code (if enabled) <input type="text" name="code" inputmode="numeric" autocomplete="one-time-code"></label>
+<button type="submit">Sign in</button>
+</form>
+</body>
+</html>"#,
+        client = escape_html(&request.client_id),
+    ))
+}
+
+fn error_page(message: &str) -> Html<String> {
+    Html(format!(
+        "<!DOCTYPE html>\n<html>\n<head><meta charset=\"utf-8\"><title>Sign-in error</title></head>\n<body><h1>Sign-in error</h1><p>{}</p></body>\n</html>",
+        escape_html(message)
+    ))
+}
+
+fn escape_html(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#x27;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::OidcClientConfig;
+    use crate::oidc::signing::IdTokenSigner;
+    use axum::{body::Body, extract::FromRequest, http::Request};
+
+    fn provider() -> OidcProvider {
+        OidcProvider::new(
+            "https://iam.example.com",
+            IdTokenSigner::generate("oidc-test".to_string()).unwrap(),
+            3600,
+            vec![OidcClientConfig {
+                client_id: "dashboard".to_string(),
+                redirect_uris: vec!["https://app.example.com/callback".to_string()],
+            }],
+        )
+    }
+
+    fn request() -> AuthorizationRequest {
+        AuthorizationRequest {
+            response_type: "code".to_string(),
+            client_id: "dashboard".to_string(),
+            redirect_uri: "https://app.example.com/callback".to_string(),
+            scope: "openid email".to_string(),
+            state: Some("af0ifjsldkj".to_string()),
+            nonce: Some("n-0S6_WzA2Mj".to_string()),
+            code_challenge: Some(pkce::challenge_for(
+                "dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk",
+            )),
+            code_challenge_method: Some("S256".to_string()),
+        }
+    }
+
+    fn redirect_error(result: std::result::Result<(), AuthorizeError>) -> &'static str {
+        match result {
+            Err(AuthorizeError::Redirect { error, .. }) => error,
+            other => panic!("expected a redirect error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_valid_request() {
+        assert!(request().validate(&provider()).is_ok());
+    }
+
+    #[test]
+    fn test_untrusted_client_or_redirect_is_not_redirected() {
+        let provider = provider();
+
+        let unknown_client = AuthorizationRequest {
+            client_id: "other".to_string(),
+            ..request()
+        };
+        assert!(matches!(
+            unknown_client.validate(&provider),
+            Err(AuthorizeError::Rejected(_))
+        ));
+
+        let unregistered = AuthorizationRequest {
+            redirect_uri: "https://evil.example.com/callback".to_string(),
+            ..request()
+        };
+        assert!(matches!(
+            unregistered.validate(&provider),
+            Err(AuthorizeError::Rejected(_))
+        ));
+    }
+
+    #[test]
+    fn test_request_errors_are_redirected() {
+        let provider = provider();
+
+        let token_flow = AuthorizationRequest {
+            response_type: "token".to_string(),
+            ..request()
+        };
+        assert_eq!(redirect_error(token_flow.validate(&provider)), "unsupported_response_type");
+
+        let no_openid = AuthorizationRequest {
+            scope: "email".to_string(),
+            ..request()
+        };
+        assert_eq!(redirect_error(no_openid.validate(&provider)), "invalid_scope");
+
+        let no_pkce = AuthorizationRequest {
+            code_challenge: None,
+            ..request()
+        };
+        assert_eq!(redirect_error(no_pkce.validate(&provider)), "invalid_request");
+
+        let plain = AuthorizationRequest {
+            code_challenge_method: None,
+            ..request()
+        };
+        assert_eq!(redirect_error(plain.validate(&provider)), "invalid_request");
+    }
+
+    #[test]
+    fn test_error_redirect_carries_state() {
+        let response = AuthorizeError::Redirect {
+            redirect_uri: "https://app.example.com/callback?tab=1".to_string(),
+            state: Some("xyz".to_string()),
+            error: "invalid_scope",
+            description: "The openid scope is required".to_string(),
+        }
+        .into_response();
+
+        let location = response.headers()[header::LOCATION].to_str().unwrap();
+        assert!(location.starts_with("https://app.example.com/callback?tab=1&error=invalid_scope"));
+        assert!(location.ends_with("&state=xyz"));
+    }
+
+    #[test]
+    fn test_login_page_escapes_parameters() {
+        let request = AuthorizationRequest {
+            state: Some(r#""><script>alert(1)</script>"#.to_string()),
+            ..request()
+        };
+
+        let Html(html) = login_page(&request, None);
+        assert!(!html.contains("<script>"));
+        assert!(html.contains("&quot;&gt;&lt;script&gt;"));
+    }
+
+    #[tokio::test]
+    async fn test_login_form_deserialize() {
+        let body = "response_type=code&client_id=dashboard&redirect_uri=https%3A%2F%2Fapp.example.com%2Fcallback\
+            &scope=openid&code_challenge=abc&code_challenge_method=S256\
+            &email=alice%40example.com&password=secret&code=";
+        let req = Request::post("/oauth2/authorize")
+            .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+            .body(Body::from(body))
+            .unwrap();
+
+        let Form(form) = Form::<LoginForm>::from_request(req, &()).await.unwrap();
+        assert_eq!(form.email, "alice@example.com");
+        assert_eq!(form.request.redirect_uri, "https://app.example.com/callback");
+        assert_eq!(form.request.state, None);
+    }
+}
@@ -0,0 +1,102 @@
+// OIDC discovery (OpenID Connect Discovery 1.0) and JWKS endpoints
+
+use crate::api::routes::AppState;
+use crate::oidc::{pkce::METHOD_S256, signing::JwkSet, OidcProvider, SUPPORTED_SCOPES};
+use axum::{extract::State, Json};
+use serde::Serialize;
+
+/// Provider metadata served at `/.well-known/openid-configuration`
+#[derive(Debug, Serialize)]
+pub struct DiscoveryDocument {
+    pub issuer: String,
+    pub authorization_endpoint: String,
+    pub token_endpoint: String,
+    pub jwks_uri: String,
+    pub scopes_supported: Vec<&'static str>,
+    pub response_types_supported: Vec<&'static str>,
+    pub response_modes_supported: Vec<&'static str>,
+    pub grant_types_supported: Vec<&'static str>,
+    pub subject_types_supported: Vec<&'static str>,
+    pub id_token_signing_alg_values_supported: Vec<&'static str>,
+    pub token_endpoint_auth_methods_supported: Vec<&'static str>,
+    pub code_challenge_methods_supported: Vec<&'static str>,
+    pub claims_supported: Vec<&'static str>,
+}
+
+impl DiscoveryDocument {
+    pub fn for_provider(provider: &OidcProvider) -> Self {
+        Self {
+            issuer: provider.issuer().to_string(),
+            authorization_endpoint: provider.endpoint("/oauth2/authorize"),
+            token_endpoint: provider.endpoint("/oauth2/token"),
+            jwks_uri: provider.endpoint("/.well-known/jwks.json"),
+            scopes_supported: SUPPORTED_SCOPES.to_vec(),
+            response_types_supported: vec!["code"],
+            response_modes_supported: vec!["query"],
+            grant_types_supported: vec!["authorization_code"],
+            subject_types_supported: vec!["public"],
+            id_token_signing_alg_values_supported: vec!["ES256"],
+            // Public clients only; possession of the code verifier is the proof
+            token_endpoint_auth_methods_supported: vec!["none"],
+            code_challenge_methods_supported: vec![METHOD_S256],
+            claims_supported: vec![
+                "iss", "sub", "aud", "exp", "iat", "auth_time", "nonce", "amr", "tenant_id",
+                "email", "name",
+            ],
+        }
+    }
+}
+
+/// GET /.well-known/openid-configuration
+pub async fn openid_configuration(State(state): State<AppState>) -> Json<DiscoveryDocument> {
+    Json(DiscoveryDocument::for_provider(&state.oidc))
+}
+
+/// GET /.well-known/jwks.json
+pub async fn jwks(State(state): State<AppState>) -> Json<JwkSet> {
+    Json(state.oidc.jwks())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::oidc::signing::IdTokenSigner;
+
+    #[test]
+    fn test_discovery_document_contents() {
+        let provider = OidcProvider::new(
+            "https://iam.example.com/",
+            IdTokenSigner::generate("oidc-test".to_string()).unwrap(),
+            3600,
+            vec![],
+        );
+
+        let json = serde_json::to_value(DiscoveryDocument::for_provider(&provider)).unwrap();
+
+        assert_eq!(json["issuer"], "https://iam.example.com");
+        assert_eq!(json["authorization_endpoint"], "https://iam.example.com/oauth2/authorize");
+        assert_eq!(json["token_endpoint"], "https://iam.example.com/oauth2/token");
+        assert_eq!(json["jwks_uri"], "https://iam.example.com/.well-known/jwks.json");
+        assert_eq!(json["response_types_supported"], serde_json::json!(["code"]));
+        assert_eq!(json["code_challenge_methods_supported"], serde_json::json!(["S256"]));
+        assert_eq!(json["id_token_signing_alg_values_supported"], serde_json::json!(["ES256"]));
+        assert_eq!(json["token_endpoint_auth_methods_supported"], serde_json::json!(["none"]));
+        assert!(json["scopes_supported"]
+            .as_array()
+            .unwrap()
+            .contains(&serde_json::json!("openid")));
+    }
+
+    #[test]
+    fn test_jwks_publishes_signing_key() {
+        let signer = IdTokenSigner::generate("oidc-test".to_string()).unwrap();
+        let json = serde_json::to_value(signer.jwks()).unwrap();
+
+        let key = &json["keys"][0];
+        assert_eq!(key["kty"], "EC");
+        assert_eq!(key["crv"], "P-256");
+        assert_eq!(key["alg"], "ES256");
+        assert_eq!(key["use"], "sig");
+        assert_eq!(key["kid"], "oidc-test");
+    }
+}
@@ -0,0 +1,392 @@
+// Login through upstream OpenID Providers
+//
+// Each tenant can register providers (e.g. a corporate Okta or Google
+// Workspace). Users start at the provider's login endpoint, are sent upstream,
+// and come back to the callback, where the upstream id_token is validated and
+// mapped to a local identity that receives the usual API tokens.
+
+use crate::api::auth::{issue_tokens, LoginResponse};
+use crate::api::routes::AppState;
+use crate::auth::middleware::require_admin;
+use crate::crypto::encryption::SecretCipher;
+use crate::db;
+use crate::db::oidc_upstream::{LinkOutcome, UpstreamProvider};
+use crate::domain::audit::{AuditEvent, AuditEventType};
+use crate::errors::{AppError, Result};
+use crate::oidc::pkce;
+use crate::oidc::upstream::PendingLogin;
+use crate::redis::oidc_login;
+use axum::{
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    response::Redirect,
+    Json,
+};
+use serde::Deserialize;
+
+const DEFAULT_SCOPES: &str = "openid email profile";
+
+/// Query parameters of the redirect back from the upstream provider
+#[derive(Debug, Deserialize)]
+pub struct CallbackQuery {
+    pub code: Option<String>,
+    pub state: Option<String>,
+    pub error: Option<String>,
+    pub error_description: Option<String>,
+}
+
+/// Request to register or replace an upstream provider
+#[derive(Debug, Deserialize)]
+pub struct UpsertProviderRequest {
+    pub issuer: String,
+    pub client_id: String,
+    /// Omit for public clients
+    pub client_secret: Option<String>,
+    /// Space-separated; must include `openid`
+    pub scopes: Option<String>,
+}
+
+impl UpsertProviderRequest {
+    fn validate(&self) -> Result<()> {
+        let issuer = url::Url::parse(&self.issuer)
+            .map_err(|e| AppError::ValidationError(format!("Invalid issuer URL: {}", e)))?;
+        // Discovery and token requests go to the issuer, so plain http is
+        // only accepted for providers on the same host (e.g. in tests)
+        let allowed = match issuer.scheme() {
+            "https" => true,
+            "http" => is_loopback(&issuer),
+            _ => false,
+        };
+        if !allowed {
+            return Err(AppError::ValidationError(
+                "Issuer must be an https URL".to_string(),
+            ));
+        }
+
+        if self.client_id.trim().is_empty() {
+            return Err(AppError::ValidationError(
+                "client_id is required".to_string(),
+            ));
+        }
+
+        if let Some(scopes) = &self.scopes {
+            if !crate::oidc::has_scope(scopes, "openid") {
+                return Err(AppError::ValidationError(
+                    "scopes must include openid".to_string(),
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Whether a URL points at this host
+fn is_loopback(url: &url::Url) -> bool {
+    match url.host() {
+        Some(url::Host::Domain(host)) => host.eq_ignore_ascii_case("localhost"),
+        Some(url::Host::Ipv4(ip)) => ip.is_loopback(),
+        Some(url::Host::Ipv6(ip)) => ip.is_loopback(),
+        None => false,
+    }
+}
+
+fn validate_slug(slug: &str) -> Result<()> {
+    let valid = !slug.is_empty()
+        && slug.len() <= 64
+        && slug
+            .bytes()
+            .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'-');
+
+    if valid {
+        Ok(())
+    } else {
+        Err(AppError::ValidationError(
+            "Provider slug must be 1-64 lowercase letters, digits or hyphens".to_string(),
+        ))
+    }
+}
+
+/// Upstream client secrets are encrypted with the same key as TOTP secrets
+fn cipher(state: &AppState) -> Result<&SecretCipher> {
+    state.mfa_cipher.as_deref().ok_or_else(|| {
+        AppError::Configuration("Secret encryption key is not configured".to_string())
+    })
+}
+
+fn decrypt_client_secret(state: &AppState, provider: &UpstreamProvider) -> Result<Option<String>> {
+    let Some(encrypted) = provider.client_secret_encrypted.as_deref() else {
+        return Ok(None);
+    };
+
+    let bytes = cipher(state)?.decrypt(encrypted)?;
+    String::from_utf8(bytes)
+        .map(Some)
+        .map_err(|_| AppError::Cryptographic("Stored client secret is not valid UTF-8".to_string()))
+}
+
+/// GET /v1/auth/oidc/:tenant/:provider/login
+///
+/// Redirect the browser to the upstream provider
+pub async fn begin_login(
+    State(state): State<AppState>,
+    Path((tenant_slug, provider_slug)): Path<(String, String)>,
+) -> Result<Redirect> {
+    let provider = db::oidc_upstream::get_by_slugs(&state.db_pool, &tenant_slug, &provider_slug)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Login provider not found".to_string()))?;
+
+    let metadata = state.upstream_oidc.discover(&provider.issuer).await?;
+
+    let pending = PendingLogin {
+        provider_id: provider.id,
+        nonce: pkce::generate_verifier(),
+        code_verifier: pkce::generate_verifier(),
+    };
+
+    let mut redis_conn = state.redis_manager.clone();
+    let login_state = oidc_login::store_pending(&mut redis_conn, &pending).await?;
+
+    let url = state.upstream_oidc.authorization_url(
+        &metadata,
+        &provider.client_id,
+        &provider.scopes,
+        &login_state,
+        &pending.nonce,
+        &pkce::challenge_for(&pending.code_verifier),
+    )?;
+
+    Ok(Redirect::to(&url))
+}
+
+/// GET /v1/auth/oidc/callback
+///
+/// Finish an upstream login and issue local tokens
+pub async fn callback(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<CallbackQuery>,
+) -> Result<Json<LoginResponse>> {
+    if let Some(error) = query.error {
+        tracing::warn!(
+            error = %error,
+            description = query.error_description.as_deref().unwrap_or(""),
+            "Upstream OIDC login failed"
+        );
+        return Err(AppError::InvalidCredentials);
+    }
+
+    let login_state = query
+        .state
+        .ok_or_else(|| AppError::ValidationError("Missing state parameter".to_string()))?;
+    let code = query
+        .code
+        .ok_or_else(|| AppError::ValidationError("Missing code parameter".to_string()))?;
+
+    // Single use: a replayed callback finds nothing
+    let mut redis_conn = state.redis_manager.clone();
+    let pending = oidc_login::take_pending(&mut redis_conn, &login_state)
+        .await?
+        .ok_or(AppError::InvalidCredentials)?;
+
+    let provider = db::oidc_upstream::get_by_id(&state.db_pool, pending.provider_id)
+        .await?
+        .ok_or(AppError::InvalidCredentials)?;
+    let client_secret = decrypt_client_secret(&state, &provider)?;
+
+    let upstream = state
+        .upstream_oidc
+        .complete_login(
+            &provider.issuer,
+            &provider.client_id,
+            client_secret.as_deref(),
+            &pending,
+            &code,
+        )
+        .await?;
+
+    let outcome =
+        db::oidc_upstream::link_or_provision(&state.db_pool, &provider, &upstream).await?;
+
+    let identity = db::identities::get_by_id(&state.db_pool, outcome.identity_id())
+        .await?
+        .ok_or(AppError::InvalidCredentials)?;
+
+    if identity.status != "active" {
+        return Err(AppError::InvalidCredentials);
+    }
+
+    match outcome {
+        LinkOutcome::Provisioned(_) => {
+            let event = AuditEvent::new(
+                identity.tenant_id,
+                AuditEventType::IdentityCreated,
+                "oidc_provision".to_string(),
+                "identity".to_string(),
+            )
+            .with_actor(identity.id)
+            .with_resource_id(identity.id.to_string());
+            state.audit_logger.log(event).await?;
+        }
+        LinkOutcome::Linked(_) => {
+            tracing::info!(
+                "Linked upstream account from {} to identity: {}",
+                provider.slug,
+                identity.id
+            );
+        }
+        LinkOutcome::Existing(_) => {}
+    }
+
+    // MFA, if any, happened upstream and cannot be verified here
+    let token_pair = issue_tokens(
+        &state,
+        &headers,
+        identity.id,
+        identity.tenant_id,
+        &identity.identity_type,
+        false,
+    )
+    .await?;
+
+    tracing::info!(
+        "Successful upstream OIDC login via {} for identity: {}",
+        provider.slug,
+        identity.id
+    );
+
+    Ok(Json(token_pair.into()))
+}
+
+/// GET /v1/admin/oidc/providers
+///
+/// List the tenant's upstream providers
+pub async fn list_providers(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<UpstreamProvider>>> {
+    let claims = require_admin(&state, &headers).await?;
+
+    let providers = db::oidc_upstream::list(&state.db_pool, claims.tenant_id_uuid()?).await?;
+
+    Ok(Json(providers))
+}
+
+/// PUT /v1/admin/oidc/providers/:slug
+///
+/// Register or replace an upstream provider for the tenant
+pub async fn upsert_provider(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(slug): Path<String>,
+    Json(req): Json<UpsertProviderRequest>,
+) -> Result<Json<UpstreamProvider>> {
+    let claims = require_admin(&state, &headers).await?;
+    validate_slug(&slug)?;
+    req.validate()?;
+
+    let client_secret_encrypted = req
+        .client_secret
+        .as_deref()
+        .map(|secret| cipher(&state)?.encrypt(secret.as_bytes()))
+        .transpose()?;
+
+    let tenant_id = claims.tenant_id_uuid()?;
+    let provider = db::oidc_upstream::upsert(
+        &state.db_pool,
+        tenant_id,
+        &slug,
+        req.issuer.trim_end_matches('/'),
+        &req.client_id,
+        client_secret_encrypted.as_deref(),
+        req.scopes.as_deref().unwrap_or(DEFAULT_SCOPES),
+    )
+    .await?;
+
+    let event = AuditEvent::new(
+        tenant_id,
+        AuditEventType::ConfigurationChanged,
+        "upsert_oidc_provider".to_string(),
+        "oidc_provider".to_string(),
+    )
+    .with_actor(claims.identity_id()?)
+    .with_resource_id(provider.id.to_string());
+    state.audit_logger.log(event).await?;
+
+    Ok(Json(provider))
+}
+
+/// DELETE /v1/admin/oidc/providers/:slug
+///
+/// Remove an upstream provider; identities it provisioned are kept
+pub async fn delete_provider(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(slug): Path<String>,
+) -> Result<StatusCode> {
+    let claims = require_admin(&state, &headers).await?;
+    let tenant_id = claims.tenant_id_uuid()?;
+
+    if !db::oidc_upstream::delete(&state.db_pool, tenant_id, &slug).await? {
+        return Err(AppError::NotFound("Login provider not found".to_string()));
+    }
+
+    let event = AuditEvent::new(
+        tenant_id,
+        AuditEventType::ConfigurationChanged,
+        "delete_oidc_provider".to_string(),
+        "oidc_provider".to_string(),
+    )
+    .with_actor(claims.identity_id()?)
+    .with_resource_id(slug);
+    state.audit_logger.log(event).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(issuer: &str, scopes: Option<&str>) -> UpsertProviderRequest {
+        UpsertProviderRequest {
+            issuer: issuer.to_string(),
+            client_id: "agent-iam".to_string(),
+            client_secret: None,
+            scopes: scopes.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_upsert_request_validation() {
+        assert!(request("https://idp.example.com", None).validate().is_ok());
+        assert!(request("not a url", None).validate().is_err());
+        assert!(request("ftp://idp.example.com", None).validate().is_err());
+        assert!(request("http://idp.example.com", None).validate().is_err());
+        assert!(request("http://localhost:8080", None).validate().is_ok());
+        assert!(request("http://127.0.0.1:8080", None).validate().is_ok());
+        assert!(request("http://[::1]:8080", None).validate().is_ok());
+        assert!(request("https://idp.example.com", Some("email profile"))
+            .validate()
+            .is_err());
+    }
+
+    #[test]
+    fn test_provider_slug_validation() {
+        assert!(validate_slug("okta-prod").is_ok());
+        assert!(validate_slug("").is_err());
+        assert!(validate_slug("Okta").is_err());
+        assert!(validate_slug("okta/prod").is_err());
+    }
+
+    #[test]
+    fn test_callback_query_accepts_upstream_error() {
+        let uri: axum::http::Uri =
+            "/v1/auth/oidc/callback?error=access_denied&error_description=User+cancelled&state=abc"
+                .parse()
+                .unwrap();
+        let Query(query) = Query::<CallbackQuery>::try_from_uri(&uri).unwrap();
+        assert_eq!(query.error.as_deref(), Some("access_denied"));
+        assert!(query.code.is_none());
+    }
+}
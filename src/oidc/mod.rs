@@ -0,0 +1,216 @@
+// OpenID Connect
+//
+// As a provider, lets simple web apps sign users in with Agent IAM using the
+// authorization code flow. Only public clients with PKCE (S256) are supported;
+// clients are registered in configuration. id_tokens are ES256-signed and
+// verifiable with the published JWKS, while the access token is the usual
+// HS256 API token.
+//
+// As a relying party, lets users of a tenant sign in through the tenant's own
+// upstream providers (see `federation`).
+
+pub mod authorize;
+pub mod discovery;
+pub mod federation;
+pub mod pkce;
+pub mod signing;
+pub mod token;
+pub mod upstream;
+
+use crate::config::{Config, OidcClientConfig};
+use crate::errors::{AppError, Result};
+use axum::{
+    http::{header, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use signing::{IdTokenClaims, IdTokenSigner, JwkSet};
+use uuid::Uuid;
+
+/// Scopes understood by the provider; `openid` is required
+pub const SUPPORTED_SCOPES: &[&str] = &["openid", "profile", "email"];
+
+/// OpenID Provider state shared by the OIDC endpoints
+pub struct OidcProvider {
+    issuer: String,
+    signer: IdTokenSigner,
+    id_token_expiration_seconds: i64,
+    clients: Vec<OidcClientConfig>,
+}
+
+impl OidcProvider {
+    pub fn new(
+        issuer: &str,
+        signer: IdTokenSigner,
+        id_token_expiration_seconds: i64,
+        clients: Vec<OidcClientConfig>,
+    ) -> Self {
+        Self {
+            issuer: issuer.trim_end_matches('/').to_string(),
+            signer,
+            id_token_expiration_seconds,
+            clients,
+        }
+    }
+
+    /// Build the provider from configuration; the issuer is `auth.jwt_issuer`
+    pub fn from_config(config: &Config) -> Result<Self> {
+        Ok(Self::new(
+            &config.auth.jwt_issuer,
+            IdTokenSigner::from_config(&config.oidc)?,
+            config.oidc.id_token_expiration_seconds,
+            config.oidc.clients.clone(),
+        ))
+    }
+
+    pub fn issuer(&self) -> &str {
+        &self.issuer
+    }
+
+    /// Look up a registered client
+    pub fn client(&self, client_id: &str) -> Option<&OidcClientConfig> {
+        self.clients.iter().find(|c| c.client_id == client_id)
+    }
+
+    /// Absolute URL of an endpoint served by this provider
+    pub fn endpoint(&self, path: &str) -> String {
+        format!("{}{}", self.issuer, path)
+    }
+
+    pub fn jwks(&self) -> JwkSet {
+        self.signer.jwks()
+    }
+
+    /// Sign the id_token for a redeemed authorization grant
+    pub fn id_token(&self, grant: &AuthorizationGrant, now: i64) -> Result<String> {
+        let amr = if grant.mfa {
+            vec!["pwd".to_string(), "otp".to_string()]
+        } else {
+            vec!["pwd".to_string()]
+        };
+
+        self.signer.sign(&IdTokenClaims {
+            iss: self.issuer.clone(),
+            sub: grant.identity_id.to_string(),
+            aud: grant.client_id.clone(),
+            exp: now + self.id_token_expiration_seconds,
+            iat: now,
+            auth_time: grant.auth_time,
+            nonce: grant.nonce.clone(),
+            amr,
+            tenant_id: grant.tenant_id.to_string(),
+            email: grant.email.clone(),
+            name: grant.name.clone(),
+        })
+    }
+}
+
+/// What an authorization code stands for, stored until the code is redeemed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuthorizationGrant {
+    pub client_id: String,
+    pub redirect_uri: String,
+    /// PKCE S256 challenge the code verifier must match
+    pub code_challenge: String,
+    pub scope: String,
+    pub nonce: Option<String>,
+    pub identity_id: Uuid,
+    pub tenant_id: Uuid,
+    pub identity_type: String,
+    /// Only set when the `email` scope was granted
+    pub email: Option<String>,
+    /// Only set when the `profile` scope was granted
+    pub name: Option<String>,
+    pub mfa: bool,
+    /// Unix timestamp of the login
+    pub auth_time: i64,
+}
+
+/// Whether a space-separated scope string includes a scope
+pub fn has_scope(scope: &str, wanted: &str) -> bool {
+    scope.split_whitespace().any(|s| s == wanted)
+}
+
+/// Error response of the token endpoint (RFC 6749 section 5.2)
+#[derive(Debug)]
+pub struct OAuthError {
+    pub status: StatusCode,
+    pub error: &'static str,
+    pub description: String,
+}
+
+impl OAuthError {
+    pub fn new(error: &'static str, description: impl Into<String>) -> Self {
+        // invalid_client is the one error RFC 6749 answers with 401
+        let status = if error == "invalid_client" {
+            StatusCode::UNAUTHORIZED
+        } else {
+            StatusCode::BAD_REQUEST
+        };
+
+        Self {
+            status,
+            error,
+            description: description.into(),
+        }
+    }
+}
+
+impl From<AppError> for OAuthError {
+    fn from(err: AppError) -> Self {
+        let description = err.to_string();
+        // Reuse the standard mapping (and its logging of internal errors)
+        let status = err.into_response().status();
+
+        if status.is_server_error() {
+            Self {
+                status,
+                error: "server_error",
+                description: "Internal server error".to_string(),
+            }
+        } else {
+            Self {
+                status,
+                error: "invalid_request",
+                description,
+            }
+        }
+    }
+}
+
+impl IntoResponse for OAuthError {
+    fn into_response(self) -> Response {
+        let body = serde_json::json!({
+            "error": self.error,
+            "error_description": self.description,
+        });
+
+        let mut response = (self.status, Json(body)).into_response();
+        response
+            .headers_mut()
+            .insert(header::CACHE_CONTROL, HeaderValue::from_static("no-store"));
+        response
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_has_scope() {
+        assert!(has_scope("openid email", "email"));
+        assert!(!has_scope("openid emails", "email"));
+    }
+
+    #[test]
+    fn test_oauth_error_status() {
+        assert_eq!(OAuthError::new("invalid_grant", "x").status, StatusCode::BAD_REQUEST);
+        assert_eq!(OAuthError::new("invalid_client", "x").status, StatusCode::UNAUTHORIZED);
+
+        let err = OAuthError::from(AppError::Internal("secret detail".to_string()));
+        assert_eq!(err.error, "server_error");
+        assert_eq!(err.description, "Internal server error");
+    }
+}
@@ -0,0 +1,206 @@
+// id_token signing (ES256) and the published JWKS
+
+use crate::config::OidcConfig;
+use crate::errors::{AppError, Result};
+use base64::{
+    engine::general_purpose::{STANDARD as BASE64, URL_SAFE_NO_PAD},
+    Engine as _,
+};
+use jsonwebtoken::{encode, Algorithm, EncodingKey, Header};
+use ring::{
+    rand::SystemRandom,
+    signature::{EcdsaKeyPair, KeyPair, ECDSA_P256_SHA256_FIXED_SIGNING},
+};
+use serde::{Deserialize, Serialize};
+
+/// Claims of an OIDC id_token (OpenID Connect Core section 2)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IdTokenClaims {
+    pub iss: String,
+    /// Identity ID
+    pub sub: String,
+    /// Client ID of the relying party
+    pub aud: String,
+    pub exp: i64,
+    pub iat: i64,
+    /// When the user authenticated
+    pub auth_time: i64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nonce: Option<String>,
+    /// Authentication methods used (RFC 8176), e.g. ["pwd", "otp"]
+    pub amr: Vec<String>,
+    pub tenant_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+}
+
+/// Public signing key in JWK form (RFC 7517)
+#[derive(Debug, Clone, Serialize)]
+pub struct Jwk {
+    pub kty: &'static str,
+    pub crv: &'static str,
+    pub x: String,
+    pub y: String,
+    #[serde(rename = "use")]
+    pub key_use: &'static str,
+    pub alg: &'static str,
+    pub kid: String,
+}
+
+/// Document served at the `jwks_uri`
+#[derive(Debug, Clone, Serialize)]
+pub struct JwkSet {
+    pub keys: Vec<Jwk>,
+}
+
+/// Signs id_tokens with a P-256 key
+///
+/// Access tokens stay HS256 with the shared JWT secret; id_tokens are verified
+/// by third-party relying parties and so need a key we can publish.
+pub struct IdTokenSigner {
+    key_id: String,
+    encoding_key: EncodingKey,
+    /// Uncompressed public point: 0x04 || x || y
+    public_key: Vec<u8>,
+}
+
+impl IdTokenSigner {
+    /// Create a signer with a freshly generated key
+    pub fn generate(key_id: String) -> Result<Self> {
+        let pkcs8 = EcdsaKeyPair::generate_pkcs8(&ECDSA_P256_SHA256_FIXED_SIGNING, &SystemRandom::new())
+            .map_err(|_| AppError::Cryptographic("Failed to generate id_token signing key".to_string()))?;
+
+        Self::from_pkcs8(key_id, pkcs8.as_ref())
+    }
+
+    /// Create a signer from a PKCS#8 DER-encoded P-256 private key
+    pub fn from_pkcs8(key_id: String, pkcs8: &[u8]) -> Result<Self> {
+        let key_pair = EcdsaKeyPair::from_pkcs8(&ECDSA_P256_SHA256_FIXED_SIGNING, pkcs8, &SystemRandom::new())
+            .map_err(|e| AppError::Cryptographic(format!("Invalid id_token signing key: {}", e)))?;
+
+        Ok(Self {
+            key_id,
+            encoding_key: EncodingKey::from_ec_der(pkcs8),
+            public_key: key_pair.public_key().as_ref().to_vec(),
+        })
+    }
+
+    /// Load the signer from configuration
+    ///
+    /// Without a configured key a new one is generated, so id_tokens issued
+    /// before a restart can no longer be verified.
+    pub fn from_config(config: &OidcConfig) -> Result<Self> {
+        let Some(encoded) = config.signing_key.as_deref() else {
+            tracing::warn!("OIDC signing key not configured; using an ephemeral key");
+            return Self::generate(config.signing_key_id.clone());
+        };
+
+        let bytes = BASE64.decode(encoded.trim()).map_err(|e| {
+            AppError::Configuration(format!("OIDC signing key is not valid base64: {}", e))
+        })?;
+
+        Self::from_pkcs8(config.signing_key_id.clone(), &bytes)
+    }
+
+    pub fn key_id(&self) -> &str {
+        &self.key_id
+    }
+
+    /// The public key as a JWK
+    pub fn jwk(&self) -> Jwk {
+        // Skip the 0x04 uncompressed-point marker; x and y are 32 bytes each
+        let (x, y) = self.public_key[1..].split_at(32);
+
+        Jwk {
+            kty: "EC",
+            crv: "P-256",
+            x: URL_SAFE_NO_PAD.encode(x),
+            y: URL_SAFE_NO_PAD.encode(y),
+            key_use: "sig",
+            alg: "ES256",
+            kid: self.key_id.clone(),
+        }
+    }
+
+    pub fn jwks(&self) -> JwkSet {
+        JwkSet {
+            keys: vec![self.jwk()],
+        }
+    }
+
+    /// Sign an id_token
+    pub fn sign<T: Serialize>(&self, claims: &T) -> Result<String> {
+        let mut header = Header::new(Algorithm::ES256);
+        header.kid = Some(self.key_id.clone());
+
+        encode(&header, claims, &self.encoding_key)
+            .map_err(|e| AppError::Cryptographic(format!("Failed to sign id_token: {}", e)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jsonwebtoken::{decode, decode_header, jwk::JwkSet as ParsedJwkSet, DecodingKey, Validation};
+
+    fn claims() -> IdTokenClaims {
+        let now = chrono::Utc::now().timestamp();
+        IdTokenClaims {
+            iss: "https://iam.example.com".to_string(),
+            sub: uuid::Uuid::new_v4().to_string(),
+            aud: "dashboard".to_string(),
+            exp: now + 300,
+            iat: now,
+            auth_time: now,
+            nonce: Some("n-0S6_WzA2Mj".to_string()),
+            amr: vec!["pwd".to_string()],
+            tenant_id: uuid::Uuid::new_v4().to_string(),
+            email: None,
+            name: None,
+        }
+    }
+
+    #[test]
+    fn test_id_token_verifies_against_jwks() {
+        let signer = IdTokenSigner::generate("oidc-test".to_string()).unwrap();
+        let token = signer.sign(&claims()).unwrap();
+
+        let jwks: ParsedJwkSet =
+            serde_json::from_value(serde_json::to_value(signer.jwks()).unwrap()).unwrap();
+        let header = decode_header(&token).unwrap();
+        assert_eq!(header.alg, Algorithm::ES256);
+
+        let jwk = jwks.find(header.kid.as_deref().unwrap()).unwrap();
+        let mut validation = Validation::new(Algorithm::ES256);
+        validation.set_audience(&["dashboard"]);
+        validation.set_issuer(&["https://iam.example.com"]);
+
+        let decoded = decode::<IdTokenClaims>(&token, &DecodingKey::from_jwk(jwk).unwrap(), &validation)
+            .unwrap();
+        assert_eq!(decoded.claims.nonce.as_deref(), Some("n-0S6_WzA2Mj"));
+    }
+
+    #[test]
+    fn test_key_round_trips_through_config() {
+        let pkcs8 = EcdsaKeyPair::generate_pkcs8(&ECDSA_P256_SHA256_FIXED_SIGNING, &SystemRandom::new())
+            .unwrap();
+        let config = OidcConfig {
+            signing_key_id: "oidc-test".to_string(),
+            signing_key: Some(BASE64.encode(pkcs8.as_ref())),
+            id_token_expiration_seconds: 3600,
+            clients: vec![],
+        };
+
+        let a = IdTokenSigner::from_config(&config).unwrap();
+        let b = IdTokenSigner::from_config(&config).unwrap();
+        assert_eq!(a.jwk().x, b.jwk().x);
+        assert_eq!(a.jwk().y, b.jwk().y);
+    }
+
+    #[test]
+    fn test_invalid_key_rejected() {
+        assert!(IdTokenSigner::from_pkcs8("oidc-test".to_string(), b"not a key").is_err());
+    }
+}
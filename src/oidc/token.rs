@@ -0,0 +1,227 @@
+// Token endpoint (authorization code grant with PKCE)
+
+use crate::api::auth::issue_tokens;
+use crate::api::routes::AppState;
+use crate::db;
+use crate::oidc::{pkce, AuthorizationGrant, OAuthError};
+use crate::redis::oidc_code;
+use axum::{
+    extract::State,
+    http::{header, HeaderMap, HeaderValue},
+    response::{IntoResponse, Response},
+    Form, Json,
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Deserialize)]
+pub struct TokenRequest {
+    pub grant_type: String,
+    pub code: Option<String>,
+    pub redirect_uri: Option<String>,
+    pub client_id: Option<String>,
+    pub code_verifier: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TokenResponse {
+    pub access_token: String,
+    pub token_type: String,
+    pub expires_in: i64,
+    pub refresh_token: String,
+    pub id_token: String,
+    pub scope: String,
+}
+
+/// Check a token request against the grant its code was issued for
+pub fn check_grant(grant: &AuthorizationGrant, req: &TokenRequest) -> Result<(), OAuthError> {
+    if req.client_id.as_deref() != Some(grant.client_id.as_str()) {
+        return Err(OAuthError::new("invalid_grant", "Code was issued to another client"));
+    }
+    if req.redirect_uri.as_deref() != Some(grant.redirect_uri.as_str()) {
+        return Err(OAuthError::new("invalid_grant", "redirect_uri does not match"));
+    }
+
+    let verifier = req
+        .code_verifier
+        .as_deref()
+        .ok_or_else(|| OAuthError::new("invalid_request", "code_verifier is required"))?;
+    if !pkce::verify(verifier, &grant.code_challenge) {
+        return Err(OAuthError::new("invalid_grant", "PKCE verification failed"));
+    }
+
+    Ok(())
+}
+
+/// POST /oauth2/token
+pub async fn token(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Form(req): Form<TokenRequest>,
+) -> Result<Response, OAuthError> {
+    if req.grant_type != "authorization_code" {
+        return Err(OAuthError::new(
+            "unsupported_grant_type",
+            "Only authorization_code is supported",
+        ));
+    }
+
+    let client_id = req.client_id.as_deref().unwrap_or_default();
+    if state.oidc.client(client_id).is_none() {
+        return Err(OAuthError::new("invalid_client", "Unknown client"));
+    }
+
+    let code = req
+        .code
+        .as_deref()
+        .ok_or_else(|| OAuthError::new("invalid_request", "code is required"))?;
+
+    // Codes are consumed before any check, so a failed exchange burns the code
+    let mut redis_conn = state.redis_manager.clone();
+    let grant: AuthorizationGrant = oidc_code::take_code(&mut redis_conn, code)
+        .await?
+        .ok_or_else(|| OAuthError::new("invalid_grant", "Code is invalid or expired"))?;
+
+    check_grant(&grant, &req)?;
+
+    // The user may have been suspended since logging in
+    if db::identities::get_by_id(&state.db_pool, grant.identity_id)
+        .await?
+        .is_none()
+    {
+        return Err(OAuthError::new("invalid_grant", "Identity is no longer active"));
+    }
+
+    let token_pair = issue_tokens(
+        &state,
+        &headers,
+        grant.identity_id,
+        grant.tenant_id,
+        &grant.identity_type,
+        grant.mfa,
+    )
+    .await?;
+    let id_token = state.oidc.id_token(&grant, chrono::Utc::now().timestamp())?;
+
+    tracing::info!(
+        "OIDC tokens issued for identity {} to client {}",
+        grant.identity_id,
+        grant.client_id
+    );
+
+    let mut response = Json(TokenResponse {
+        access_token: token_pair.access_token,
+        token_type: token_pair.token_type,
+        expires_in: token_pair.expires_in,
+        refresh_token: token_pair.refresh_token,
+        id_token,
+        scope: grant.scope,
+    })
+    .into_response();
+    response
+        .headers_mut()
+        .insert(header::CACHE_CONTROL, HeaderValue::from_static("no-store"));
+    Ok(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::oidc::{signing::IdTokenClaims, signing::IdTokenSigner, OidcProvider};
+    use jsonwebtoken::{decode, jwk::JwkSet, Algorithm, DecodingKey, Validation};
+    use uuid::Uuid;
+
+    const VERIFIER: &str = "dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk";
+
+    fn grant() -> AuthorizationGrant {
+        AuthorizationGrant {
+            client_id: "dashboard".to_string(),
+            redirect_uri: "https://app.example.com/callback".to_string(),
+            code_challenge: pkce::challenge_for(VERIFIER),
+            scope: "openid email".to_string(),
+            nonce: Some("n-0S6_WzA2Mj".to_string()),
+            identity_id: Uuid::new_v4(),
+            tenant_id: Uuid::new_v4(),
+            identity_type: "user".to_string(),
+            email: Some("alice@example.com".to_string()),
+            name: None,
+            mfa: true,
+            auth_time: chrono::Utc::now().timestamp(),
+        }
+    }
+
+    fn token_request() -> TokenRequest {
+        TokenRequest {
+            grant_type: "authorization_code".to_string(),
+            code: Some("code".to_string()),
+            redirect_uri: Some("https://app.example.com/callback".to_string()),
+            client_id: Some("dashboard".to_string()),
+            code_verifier: Some(VERIFIER.to_string()),
+        }
+    }
+
+    #[test]
+    fn test_code_exchange_produces_valid_id_token() {
+        let provider = OidcProvider::new(
+            "https://iam.example.com",
+            IdTokenSigner::generate("oidc-test".to_string()).unwrap(),
+            3600,
+            vec![],
+        );
+        let grant = grant();
+
+        check_grant(&grant, &token_request()).unwrap();
+        let id_token = provider
+            .id_token(&grant, chrono::Utc::now().timestamp())
+            .unwrap();
+
+        // Verify the way a relying party would: with the published JWKS
+        let jwks: JwkSet =
+            serde_json::from_value(serde_json::to_value(provider.jwks()).unwrap()).unwrap();
+        let mut validation = Validation::new(Algorithm::ES256);
+        validation.set_audience(&["dashboard"]);
+        validation.set_issuer(&["https://iam.example.com"]);
+        let claims = decode::<IdTokenClaims>(
+            &id_token,
+            &DecodingKey::from_jwk(&jwks.keys[0]).unwrap(),
+            &validation,
+        )
+        .unwrap()
+        .claims;
+
+        assert_eq!(claims.sub, grant.identity_id.to_string());
+        assert_eq!(claims.tenant_id, grant.tenant_id.to_string());
+        assert_eq!(claims.nonce.as_deref(), Some("n-0S6_WzA2Mj"));
+        assert_eq!(claims.email.as_deref(), Some("alice@example.com"));
+        assert_eq!(claims.amr, vec!["pwd", "otp"]);
+        assert_eq!(claims.exp - claims.iat, 3600);
+    }
+
+    #[test]
+    fn test_exchange_rejects_mismatches() {
+        let grant = grant();
+
+        let wrong_verifier = TokenRequest {
+            code_verifier: Some("a".repeat(43)),
+            ..token_request()
+        };
+        assert_eq!(check_grant(&grant, &wrong_verifier).unwrap_err().error, "invalid_grant");
+
+        let wrong_redirect = TokenRequest {
+            redirect_uri: Some("https://app.example.com/other".to_string()),
+            ..token_request()
+        };
+        assert_eq!(check_grant(&grant, &wrong_redirect).unwrap_err().error, "invalid_grant");
+
+        let other_client = TokenRequest {
+            client_id: Some("other".to_string()),
+            ..token_request()
+        };
+        assert_eq!(check_grant(&grant, &other_client).unwrap_err().error, "invalid_grant");
+
+        let no_verifier = TokenRequest {
+            code_verifier: None,
+            ..token_request()
+        };
+        assert_eq!(check_grant(&grant, &no_verifier).unwrap_err().error, "invalid_request");
+    }
+}
@@ -0,0 +1,496 @@
+// Upstream OpenID providers (Agent IAM as relying party)
+//
+// Runs the authorization code flow (with PKCE) against a tenant's configured
+// provider and validates the returned id_token against the provider's JWKS.
+
+use crate::config::Config;
+use crate::errors::{AppError, Result};
+use jsonwebtoken::{decode, decode_header, jwk::JwkSet, Algorithm, DecodingKey, Validation};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
+use std::time::Duration;
+use uuid::Uuid;
+
+/// Timeout for each request to an upstream provider
+const REQUEST_TIMEOUT_SECONDS: u64 = 10;
+
+/// The upstream account an id_token vouches for
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UpstreamIdentity {
+    /// `sub` claim, stable per provider
+    pub subject: String,
+    pub email: Option<String>,
+    pub email_verified: bool,
+    pub name: Option<String>,
+}
+
+/// The parts of the provider's discovery document we use
+#[derive(Debug, Clone, Deserialize)]
+pub struct ProviderMetadata {
+    pub issuer: String,
+    pub authorization_endpoint: String,
+    pub token_endpoint: String,
+    pub jwks_uri: String,
+}
+
+/// Login in progress, stored until the provider redirects back
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingLogin {
+    pub provider_id: Uuid,
+    pub nonce: String,
+    pub code_verifier: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct UpstreamClaims {
+    sub: String,
+    nonce: Option<String>,
+    email: Option<String>,
+    /// Usually a boolean, but some providers send "true"/"false"
+    email_verified: Option<Value>,
+    name: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    id_token: Option<String>,
+}
+
+/// HTTP client for upstream providers
+pub struct UpstreamClient {
+    http: reqwest::Client,
+    callback_url: String,
+}
+
+impl UpstreamClient {
+    pub fn new(callback_url: String) -> Result<Self> {
+        let http = reqwest::Client::builder()
+            .timeout(Duration::from_secs(REQUEST_TIMEOUT_SECONDS))
+            .build()
+            .map_err(|e| AppError::Internal(format!("Failed to build HTTP client: {}", e)))?;
+
+        Ok(Self { http, callback_url })
+    }
+
+    /// The callback is served by this service under `auth.jwt_issuer`
+    pub fn from_config(config: &Config) -> Result<Self> {
+        Self::new(format!(
+            "{}/v1/auth/oidc/callback",
+            config.auth.jwt_issuer.trim_end_matches('/')
+        ))
+    }
+
+    /// Redirect URI registered with upstream providers
+    pub fn callback_url(&self) -> &str {
+        &self.callback_url
+    }
+
+    /// Fetch a provider's discovery document
+    pub async fn discover(&self, issuer: &str) -> Result<ProviderMetadata> {
+        let issuer = issuer.trim_end_matches('/');
+        let metadata: ProviderMetadata = self
+            .get_json(&format!("{}/.well-known/openid-configuration", issuer))
+            .await?;
+
+        // A mismatch means the configured issuer is wrong (or something is
+        // impersonating the provider); id_tokens would fail validation anyway
+        if metadata.issuer.trim_end_matches('/') != issuer {
+            return Err(AppError::Configuration(format!(
+                "Upstream issuer mismatch: configured {}, discovered {}",
+                issuer, metadata.issuer
+            )));
+        }
+
+        Ok(metadata)
+    }
+
+    /// URL to send the browser to at the start of a login
+    pub fn authorization_url(
+        &self,
+        metadata: &ProviderMetadata,
+        client_id: &str,
+        scopes: &str,
+        state: &str,
+        nonce: &str,
+        code_challenge: &str,
+    ) -> Result<String> {
+        let mut url = url::Url::parse(&metadata.authorization_endpoint).map_err(|e| {
+            AppError::Configuration(format!("Invalid upstream authorization endpoint: {}", e))
+        })?;
+
+        url.query_pairs_mut()
+            .append_pair("response_type", "code")
+            .append_pair("client_id", client_id)
+            .append_pair("redirect_uri", &self.callback_url)
+            .append_pair("scope", scopes)
+            .append_pair("state", state)
+            .append_pair("nonce", nonce)
+            .append_pair("code_challenge", code_challenge)
+            .append_pair("code_challenge_method", "S256");
+
+        Ok(url.into())
+    }
+
+    /// Exchange an authorization code for the upstream id_token
+    pub async fn exchange_code(
+        &self,
+        metadata: &ProviderMetadata,
+        client_id: &str,
+        client_secret: Option<&str>,
+        code: &str,
+        code_verifier: &str,
+    ) -> Result<String> {
+        let mut form = vec![
+            ("grant_type", "authorization_code"),
+            ("code", code),
+            ("redirect_uri", self.callback_url.as_str()),
+            ("client_id", client_id),
+            ("code_verifier", code_verifier),
+        ];
+        if let Some(secret) = client_secret {
+            form.push(("client_secret", secret));
+        }
+
+        let response = self
+            .http
+            .post(&metadata.token_endpoint)
+            .form(&form)
+            .send()
+            .await
+            .map_err(|e| AppError::Internal(format!("Upstream token request failed: {}", e)))?;
+
+        // A rejected code is the user's problem, not ours
+        if response.status().is_client_error() {
+            tracing::warn!(
+                "Upstream token endpoint rejected the code: {}",
+                response.status()
+            );
+            return Err(AppError::InvalidCredentials);
+        }
+
+        let tokens: TokenResponse = response
+            .error_for_status()
+            .map_err(|e| AppError::Internal(format!("Upstream token request failed: {}", e)))?
+            .json()
+            .await
+            .map_err(|e| AppError::Internal(format!("Invalid upstream token response: {}", e)))?;
+
+        tokens.id_token.ok_or_else(|| {
+            AppError::TokenValidation("Upstream did not return an id_token".to_string())
+        })
+    }
+
+    /// Fetch the provider's signing keys
+    pub async fn fetch_jwks(&self, metadata: &ProviderMetadata) -> Result<JwkSet> {
+        self.get_json(&metadata.jwks_uri).await
+    }
+
+    /// Finish a login: redeem the code and validate the id_token
+    pub async fn complete_login(
+        &self,
+        issuer: &str,
+        client_id: &str,
+        client_secret: Option<&str>,
+        pending: &PendingLogin,
+        code: &str,
+    ) -> Result<UpstreamIdentity> {
+        let metadata = self.discover(issuer).await?;
+        let id_token = self
+            .exchange_code(
+                &metadata,
+                client_id,
+                client_secret,
+                code,
+                &pending.code_verifier,
+            )
+            .await?;
+        let jwks = self.fetch_jwks(&metadata).await?;
+
+        validate_id_token(
+            &id_token,
+            &jwks,
+            &metadata.issuer,
+            client_id,
+            &pending.nonce,
+        )
+    }
+
+    async fn get_json<T: DeserializeOwned>(&self, url: &str) -> Result<T> {
+        self.http
+            .get(url)
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| AppError::Internal(format!("Upstream request to {} failed: {}", url, e)))?
+            .json()
+            .await
+            .map_err(|e| {
+                AppError::Internal(format!("Invalid upstream response from {}: {}", url, e))
+            })
+    }
+}
+
+/// Validate an upstream id_token and extract the account it identifies
+pub fn validate_id_token(
+    id_token: &str,
+    jwks: &JwkSet,
+    issuer: &str,
+    client_id: &str,
+    nonce: &str,
+) -> Result<UpstreamIdentity> {
+    let header = decode_header(id_token)?;
+
+    // The provider's keys are public, so a symmetric algorithm would let
+    // anyone holding the JWKS mint tokens
+    if matches!(
+        header.alg,
+        Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512
+    ) {
+        return Err(AppError::TokenValidation(
+            "Symmetric id_token algorithms are not accepted".to_string(),
+        ));
+    }
+
+    let jwk = match header.kid.as_deref() {
+        Some(kid) => jwks.find(kid),
+        None if jwks.keys.len() == 1 => jwks.keys.first(),
+        None => None,
+    }
+    .ok_or_else(|| AppError::TokenValidation("No upstream key matches the id_token".to_string()))?;
+
+    let mut validation = Validation::new(header.alg);
+    validation.set_issuer(&[issuer]);
+    validation.set_audience(&[client_id]);
+
+    let claims =
+        decode::<UpstreamClaims>(id_token, &DecodingKey::from_jwk(jwk)?, &validation)?.claims;
+
+    if claims.nonce.as_deref() != Some(nonce) {
+        return Err(AppError::TokenValidation(
+            "id_token nonce mismatch".to_string(),
+        ));
+    }
+
+    let email_verified = match claims.email_verified {
+        Some(Value::Bool(verified)) => verified,
+        Some(Value::String(s)) => s.eq_ignore_ascii_case("true"),
+        _ => false,
+    };
+
+    Ok(UpstreamIdentity {
+        subject: claims.sub,
+        email: claims.email,
+        email_verified,
+        name: claims.name,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::oidc::signing::IdTokenSigner;
+    use wiremock::matchers::{body_string_contains, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn upstream_claims(issuer: &str, nonce: &str) -> Value {
+        let now = chrono::Utc::now().timestamp();
+        serde_json::json!({
+            "iss": issuer,
+            "sub": "00u1abcd",
+            "aud": "agent-iam",
+            "iat": now,
+            "exp": now + 300,
+            "nonce": nonce,
+            "email": "alice@example.com",
+            "email_verified": true,
+            "name": "Alice Smith",
+        })
+    }
+
+    fn jwks(signer: &IdTokenSigner) -> JwkSet {
+        serde_json::from_value(serde_json::to_value(signer.jwks()).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_validate_id_token() {
+        let signer = IdTokenSigner::generate("upstream-key".to_string()).unwrap();
+        let token = signer
+            .sign(&upstream_claims("https://idp.example.com", "nonce-1"))
+            .unwrap();
+
+        let identity = validate_id_token(
+            &token,
+            &jwks(&signer),
+            "https://idp.example.com",
+            "agent-iam",
+            "nonce-1",
+        )
+        .unwrap();
+        assert_eq!(
+            identity,
+            UpstreamIdentity {
+                subject: "00u1abcd".to_string(),
+                email: Some("alice@example.com".to_string()),
+                email_verified: true,
+                name: Some("Alice Smith".to_string()),
+            }
+        );
+    }
+
+    #[test]
+    fn test_validate_id_token_rejects_mismatches() {
+        let signer = IdTokenSigner::generate("upstream-key".to_string()).unwrap();
+        let token = signer
+            .sign(&upstream_claims("https://idp.example.com", "nonce-1"))
+            .unwrap();
+        let keys = jwks(&signer);
+
+        // Wrong nonce, audience and issuer
+        assert!(validate_id_token(
+            &token,
+            &keys,
+            "https://idp.example.com",
+            "agent-iam",
+            "other"
+        )
+        .is_err());
+        assert!(
+            validate_id_token(&token, &keys, "https://idp.example.com", "other", "nonce-1")
+                .is_err()
+        );
+        assert!(validate_id_token(
+            &token,
+            &keys,
+            "https://evil.example.com",
+            "agent-iam",
+            "nonce-1"
+        )
+        .is_err());
+
+        // Signed by a key the provider does not publish
+        let other = IdTokenSigner::generate("upstream-key".to_string()).unwrap();
+        assert!(validate_id_token(
+            &token,
+            &jwks(&other),
+            "https://idp.example.com",
+            "agent-iam",
+            "nonce-1"
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_validate_id_token_rejects_symmetric_algorithms() {
+        let signer = IdTokenSigner::generate("upstream-key".to_string()).unwrap();
+        let token = jsonwebtoken::encode(
+            &jsonwebtoken::Header::new(Algorithm::HS256),
+            &upstream_claims("https://idp.example.com", "nonce-1"),
+            &jsonwebtoken::EncodingKey::from_secret(b"secret"),
+        )
+        .unwrap();
+
+        let err = validate_id_token(
+            &token,
+            &jwks(&signer),
+            "https://idp.example.com",
+            "agent-iam",
+            "nonce-1",
+        )
+        .unwrap_err();
+        assert!(matches!(err, AppError::TokenValidation(_)));
+    }
+
+    #[tokio::test]
+    async fn test_complete_login_against_mock_provider() {
+        let server = MockServer::start().await;
+        let issuer = server.uri();
+        let signer = IdTokenSigner::generate("upstream-key".to_string()).unwrap();
+
+        Mock::given(method("GET"))
+            .and(path("/.well-known/openid-configuration"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "issuer": issuer,
+                "authorization_endpoint": format!("{}/authorize", issuer),
+                "token_endpoint": format!("{}/token", issuer),
+                "jwks_uri": format!("{}/keys", issuer),
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/keys"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(signer.jwks()))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/token"))
+            .and(body_string_contains("code=upstream-code"))
+            .and(body_string_contains("code_verifier=verifier"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "access_token": "upstream-access-token",
+                "token_type": "Bearer",
+                "id_token": signer.sign(&upstream_claims(&issuer, "nonce-1")).unwrap(),
+            })))
+            .mount(&server)
+            .await;
+
+        let client =
+            UpstreamClient::new("https://iam.example.com/v1/auth/oidc/callback".to_string())
+                .unwrap();
+        let pending = PendingLogin {
+            provider_id: Uuid::new_v4(),
+            nonce: "nonce-1".to_string(),
+            code_verifier: "verifier".to_string(),
+        };
+
+        let identity = client
+            .complete_login(
+                &issuer,
+                "agent-iam",
+                Some("client-secret"),
+                &pending,
+                "upstream-code",
+            )
+            .await
+            .unwrap();
+        assert_eq!(identity.subject, "00u1abcd");
+        assert!(identity.email_verified);
+
+        // A code the provider rejects is a failed login
+        let err = client
+            .complete_login(&issuer, "agent-iam", None, &pending, "bad-code")
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AppError::InvalidCredentials));
+    }
+
+    #[test]
+    fn test_authorization_url() {
+        let client =
+            UpstreamClient::new("https://iam.example.com/v1/auth/oidc/callback".to_string())
+                .unwrap();
+        let metadata = ProviderMetadata {
+            issuer: "https://idp.example.com".to_string(),
+            authorization_endpoint: "https://idp.example.com/authorize".to_string(),
+            token_endpoint: "https://idp.example.com/token".to_string(),
+            jwks_uri: "https://idp.example.com/keys".to_string(),
+        };
+
+        let url = client
+            .authorization_url(
+                &metadata,
+                "agent-iam",
+                "openid email",
+                "state-1",
+                "nonce-1",
+                "challenge",
+            )
+            .unwrap();
+        assert!(url.starts_with(
+            "https://idp.example.com/authorize?response_type=code&client_id=agent-iam"
+        ));
+        assert!(url
+            .contains("redirect_uri=https%3A%2F%2Fiam.example.com%2Fv1%2Fauth%2Foidc%2Fcallback"));
+        assert!(url.contains("scope=openid+email"));
+        assert!(url.contains("code_challenge_method=S256"));
+    }
+}
@@ -0,0 +1,112 @@
+// Rate-limit exemptions for internal and trusted callers
+
+use crate::audit::tamper_proof::constant_time_compare;
+use crate::auth::jwt::JwtManager;
+use crate::config::RateLimitConfig;
+use crate::observability::MetricsRecorder;
+use axum::http::HeaderMap;
+use std::sync::Arc;
+
+/// Header carrying the shared internal service token
+pub const INTERNAL_SERVICE_TOKEN_HEADER: &str = "x-internal-service-token";
+
+/// Why a request skipped the default rate limiter
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExemptionReason {
+    /// Request carried the configured internal service token
+    ServiceToken,
+    /// Caller's access token is for an exempt identity type
+    IdentityType,
+}
+
+impl ExemptionReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ExemptionReason::ServiceToken => "service_token",
+            ExemptionReason::IdentityType => "identity_type",
+        }
+    }
+}
+
+/// Decides which requests bypass the default rate limiter
+///
+/// Identity types are read from a validated access token. Revocation is not
+/// checked here: a revoked token skips throttling but is still rejected by
+/// the handler.
+#[derive(Default)]
+pub struct RateLimitExemptions {
+    jwt_manager: Option<Arc<JwtManager>>,
+    identity_types: Vec<String>,
+    service_token: Option<String>,
+}
+
+impl RateLimitExemptions {
+    /// No exemptions: every request is rate limited
+    pub fn none() -> Self {
+        Self::default()
+    }
+
+    pub fn from_config(config: &RateLimitConfig, jwt_manager: Arc<JwtManager>) -> Self {
+        Self {
+            jwt_manager: Some(jwt_manager),
+            identity_types: config.exempt_identity_types.clone(),
+            service_token: config
+                .internal_service_token
+                .clone()
+                .filter(|token| !token.is_empty()),
+        }
+    }
+
+    /// Reason the request is exempt, if it is
+    pub fn check(&self, headers: &HeaderMap) -> Option<ExemptionReason> {
+        if self.has_service_token(headers) {
+            return Some(ExemptionReason::ServiceToken);
+        }
+        if self.has_exempt_identity(headers) {
+            return Some(ExemptionReason::IdentityType);
+        }
+        None
+    }
+
+    /// Like `check`, recording a metric for exempt requests
+    pub fn check_and_record(&self, headers: &HeaderMap) -> Option<ExemptionReason> {
+        let reason = self.check(headers)?;
+        MetricsRecorder::record_rate_limit_exempt(reason.as_str());
+        Some(reason)
+    }
+
+    fn has_service_token(&self, headers: &HeaderMap) -> bool {
+        let (Some(expected), Some(provided)) = (
+            self.service_token.as_deref(),
+            headers
+                .get(INTERNAL_SERVICE_TOKEN_HEADER)
+                .and_then(|h| h.to_str().ok()),
+        ) else {
+            return false;
+        };
+
+        constant_time_compare(expected, provided)
+    }
+
+    fn has_exempt_identity(&self, headers: &HeaderMap) -> bool {
+        let Some(jwt_manager) = &self.jwt_manager else {
+            return false;
+        };
+        if self.identity_types.is_empty() {
+            return false;
+        }
+
+        let Some(token) = headers
+            .get("authorization")
+            .and_then(|h| h.to_str().ok())
+            .and_then(|h| h.strip_prefix("Bearer "))
+        else {
+            return false;
+        };
+
+        match jwt_manager.validate_access_token(token) {
+            Ok(claims) => self.identity_types.contains(&claims.identity_type),
+            Err(_) => false,
+        }
+    }
+}
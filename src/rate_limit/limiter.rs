@@ -1,17 +1,75 @@
-use crate::config::RateLimitConfig;
-use crate::errors::Result;
+use crate::config::{RateLimitConfig, RateLimitFailMode, RetryAfterFormat};
+use crate::errors::{AppError, Result, RetryAfter};
+use crate::observability::MetricsRecorder;
 use crate::rate_limit::sliding_window::{RateLimitResult, SlidingWindowRateLimiter};
-use redis::aio::ConnectionManager;
+use redis::aio::{ConnectionLike, ConnectionManager};
+
+/// A rate-limit bucket tracked for every identifier
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RateLimitBucket {
+    Minute,
+    Hour,
+    Day,
+    Auth,
+}
+
+impl RateLimitBucket {
+    pub const ALL: [RateLimitBucket; 4] = [
+        RateLimitBucket::Minute,
+        RateLimitBucket::Hour,
+        RateLimitBucket::Day,
+        RateLimitBucket::Auth,
+    ];
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            RateLimitBucket::Minute => "minute",
+            RateLimitBucket::Hour => "hour",
+            RateLimitBucket::Day => "day",
+            RateLimitBucket::Auth => "auth",
+        }
+    }
+
+    /// Redis key for this bucket and identifier
+    pub fn key(&self, identifier: &str) -> String {
+        let prefix = match self {
+            RateLimitBucket::Minute => "default",
+            RateLimitBucket::Hour => "hourly",
+            RateLimitBucket::Day => "daily",
+            RateLimitBucket::Auth => "auth",
+        };
+        format!("{}:{}", prefix, identifier)
+    }
+
+    pub fn window_seconds(&self) -> u64 {
+        match self {
+            RateLimitBucket::Minute | RateLimitBucket::Auth => 60,
+            RateLimitBucket::Hour => 3600,
+            RateLimitBucket::Day => 86400,
+        }
+    }
+}
+
+/// Current usage of one bucket
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BucketUsage {
+    pub bucket: RateLimitBucket,
+    pub limit: u64,
+    pub count: u64,
+}
 
 /// Rate limiter for different contexts
-pub struct RateLimiter {
-    limiter: SlidingWindowRateLimiter,
+pub struct RateLimiter<C = ConnectionManager> {
+    limiter: SlidingWindowRateLimiter<C>,
     config: RateLimitConfig,
 }
 
-impl RateLimiter {
+impl<C> RateLimiter<C>
+where
+    C: ConnectionLike + Clone + Send + Sync,
+{
     /// Create a new rate limiter
-    pub fn new(redis: ConnectionManager, config: RateLimitConfig) -> Self {
+    pub fn new(redis: C, config: RateLimitConfig) -> Self {
         Self {
             limiter: SlidingWindowRateLimiter::new(redis),
             config,
@@ -20,34 +78,22 @@ impl RateLimiter {
 
     /// Check rate limit for authentication endpoints
     pub async fn check_auth_rate_limit(&mut self, identifier: &str) -> Result<RateLimitResult> {
-        let key = format!("auth:{}", identifier);
-        let limit = self.config.auth_requests_per_minute;
-        self.limiter.check_and_increment(&key, limit, 60).await
+        self.check_bucket(RateLimitBucket::Auth, identifier).await
     }
 
     /// Check default rate limit (per minute)
     pub async fn check_default_rate_limit(&mut self, identifier: &str) -> Result<RateLimitResult> {
-        let key = format!("default:{}", identifier);
-        let limit = self.config.default_requests_per_minute;
-        self.limiter.check_and_increment(&key, limit, 60).await
+        self.check_bucket(RateLimitBucket::Minute, identifier).await
     }
 
     /// Check hourly rate limit
     pub async fn check_hourly_rate_limit(&mut self, identifier: &str) -> Result<RateLimitResult> {
-        let key = format!("hourly:{}", identifier);
-        let limit = self.config.default_requests_per_hour;
-        self.limiter
-            .check_and_increment(&key, limit, 3600)
-            .await
+        self.check_bucket(RateLimitBucket::Hour, identifier).await
     }
 
     /// Check daily rate limit
     pub async fn check_daily_rate_limit(&mut self, identifier: &str) -> Result<RateLimitResult> {
-        let key = format!("daily:{}", identifier);
-        let limit = self.config.default_requests_per_day;
-        self.limiter
-            .check_and_increment(&key, limit, 86400)
-            .await
+        self.check_bucket(RateLimitBucket::Day, identifier).await
     }
 
     /// Check custom rate limit
@@ -57,9 +103,7 @@ impl RateLimiter {
         limit: u64,
         window_seconds: u64,
     ) -> Result<RateLimitResult> {
-        self.limiter
-            .check_and_increment(identifier, limit, window_seconds)
-            .await
+        self.check(identifier, limit, window_seconds).await
     }
 
     /// Get current count for a key
@@ -73,11 +117,139 @@ impl RateLimiter {
     pub async fn reset(&mut self, identifier: &str) -> Result<()> {
         self.limiter.reset(identifier).await
     }
+
+    /// Error to return for a request the limiter rejected
+    pub fn exceeded_error(&self, result: &RateLimitResult) -> AppError {
+        let retry_after = result.retry_after().map(|seconds| match self.config.retry_after_format {
+            RetryAfterFormat::Seconds => RetryAfter::Seconds(seconds),
+            RetryAfterFormat::HttpDate => {
+                RetryAfter::Date(chrono::Utc::now() + chrono::Duration::seconds(seconds as i64))
+            }
+        });
+        AppError::RateLimitExceeded(retry_after)
+    }
+
+    /// Configured limit for a bucket
+    pub fn limit(&self, bucket: RateLimitBucket) -> u64 {
+        match bucket {
+            RateLimitBucket::Minute => self.config.default_requests_per_minute,
+            RateLimitBucket::Hour => self.config.default_requests_per_hour,
+            RateLimitBucket::Day => self.config.default_requests_per_day,
+            RateLimitBucket::Auth => self.config.auth_requests_per_minute,
+        }
+    }
+
+    /// Current usage of every bucket for an identifier (read-only)
+    pub async fn bucket_usage(&mut self, identifier: &str) -> Result<Vec<BucketUsage>> {
+        let mut usage = Vec::with_capacity(RateLimitBucket::ALL.len());
+        for bucket in RateLimitBucket::ALL {
+            let count = self
+                .limiter
+                .get_current_count(&bucket.key(identifier), bucket.window_seconds())
+                .await?;
+            usage.push(BucketUsage {
+                bucket,
+                limit: self.limit(bucket),
+                count,
+            });
+        }
+        Ok(usage)
+    }
+
+    /// Clear every bucket for an identifier
+    pub async fn reset_all(&mut self, identifier: &str) -> Result<()> {
+        for bucket in RateLimitBucket::ALL {
+            self.limiter.reset(&bucket.key(identifier)).await?;
+        }
+        Ok(())
+    }
+
+    async fn check_bucket(
+        &mut self,
+        bucket: RateLimitBucket,
+        identifier: &str,
+    ) -> Result<RateLimitResult> {
+        let limit = self.limit(bucket);
+        self.check(&bucket.key(identifier), limit, bucket.window_seconds())
+            .await
+    }
+
+    /// Check and increment a window, applying the configured fail mode on backend errors
+    async fn check(&mut self, key: &str, limit: u64, window_seconds: u64) -> Result<RateLimitResult> {
+        match self.limiter.check_and_increment(key, limit, window_seconds).await {
+            Ok(result) => Ok(result),
+            Err(e) => self.on_backend_error(e, key, limit, window_seconds),
+        }
+    }
+
+    fn on_backend_error(
+        &self,
+        error: AppError,
+        key: &str,
+        limit: u64,
+        window_seconds: u64,
+    ) -> Result<RateLimitResult> {
+        let fail_mode = self.config.fail_mode;
+        MetricsRecorder::record_rate_limit_backend_error(fail_mode.as_str());
+
+        match fail_mode {
+            RateLimitFailMode::Closed => Err(error),
+            RateLimitFailMode::Open => {
+                tracing::warn!(
+                    key = %key,
+                    error = %error,
+                    "Rate limit backend unavailable, allowing request (fail-open)"
+                );
+                Ok(RateLimitResult::unchecked(limit, window_seconds))
+            }
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::redis::mock::FlakyConnection;
+
+    fn config(fail_mode: RateLimitFailMode) -> RateLimitConfig {
+        RateLimitConfig {
+            default_requests_per_minute: 100,
+            default_requests_per_hour: 1000,
+            default_requests_per_day: 10000,
+            auth_requests_per_minute: 10,
+            fail_mode,
+            retry_after_format: RetryAfterFormat::Seconds,
+            exempt_identity_types: Vec::new(),
+            internal_service_token: None,
+        }
+    }
+
+    #[test]
+    fn test_bucket_keys() {
+        assert_eq!(RateLimitBucket::Minute.key("ip:1.2.3.4"), "default:ip:1.2.3.4");
+        assert_eq!(RateLimitBucket::Hour.key("ip:1.2.3.4"), "hourly:ip:1.2.3.4");
+        assert_eq!(RateLimitBucket::Day.window_seconds(), 86400);
+    }
+
+    #[tokio::test]
+    async fn test_fail_open_allows_when_redis_is_down() {
+        let mut limiter = RateLimiter::new(FlakyConnection::down(), config(RateLimitFailMode::Open));
+
+        let result = limiter.check_default_rate_limit("test_user").await.unwrap();
+
+        assert!(result.allowed);
+        assert_eq!(result.limit, 100);
+        assert_eq!(result.current, 0);
+    }
+
+    #[tokio::test]
+    async fn test_fail_closed_denies_when_redis_is_down() {
+        let mut limiter = RateLimiter::new(FlakyConnection::down(), config(RateLimitFailMode::Closed));
+
+        let result = limiter.check_auth_rate_limit("user@example.com").await;
+
+        assert!(result.is_err());
+    }
 
     #[tokio::test]
     #[ignore] // Requires Redis
@@ -104,4 +276,29 @@ mod tests {
 
         limiter.reset("default:test_user").await.unwrap();
     }
+
+    #[tokio::test]
+    #[ignore] // Requires Redis
+    async fn test_bucket_usage_and_reset_all() {
+        let config = crate::config::Config::load().unwrap();
+        let redis = crate::redis::create_client(&config.redis).await.unwrap();
+        let mut limiter = RateLimiter::new(redis, config.rate_limit);
+
+        let identifier = "test:bucket_usage";
+        limiter.reset_all(identifier).await.unwrap();
+
+        limiter.check_default_rate_limit(identifier).await.unwrap();
+        limiter.check_default_rate_limit(identifier).await.unwrap();
+        limiter.check_hourly_rate_limit(identifier).await.unwrap();
+
+        let usage = limiter.bucket_usage(identifier).await.unwrap();
+        let count = |bucket| usage.iter().find(|u| u.bucket == bucket).unwrap().count;
+        assert_eq!(count(RateLimitBucket::Minute), 2);
+        assert_eq!(count(RateLimitBucket::Hour), 1);
+        assert_eq!(count(RateLimitBucket::Day), 0);
+
+        limiter.reset_all(identifier).await.unwrap();
+        let usage = limiter.bucket_usage(identifier).await.unwrap();
+        assert!(usage.iter().all(|u| u.count == 0));
+    }
 }
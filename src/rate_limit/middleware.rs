@@ -1,4 +1,6 @@
 use crate::errors::AppError;
+use crate::observability::MetricsRecorder;
+use crate::rate_limit::exemptions::RateLimitExemptions;
 use crate::rate_limit::limiter::RateLimiter;
 use axum::{
     extract::Request,
@@ -6,23 +8,35 @@ use axum::{
     middleware::Next,
     response::{IntoResponse, Response},
 };
+use redis::aio::ConnectionLike;
 use std::sync::Arc;
 use tokio::sync::Mutex;
 
 /// Rate limiting middleware
-pub async fn rate_limit_middleware(
-    limiter: Arc<Mutex<RateLimiter>>,
+///
+/// Exempt callers (see `RateLimitExemptions`) skip the limiter entirely and
+/// get no rate limit headers.
+pub async fn rate_limit_middleware<C>(
+    limiter: Arc<Mutex<RateLimiter<C>>>,
+    exemptions: Arc<RateLimitExemptions>,
     headers: HeaderMap,
     request: Request,
     next: Next,
-) -> Result<Response, AppError> {
+) -> Result<Response, AppError>
+where
+    C: ConnectionLike + Clone + Send + Sync,
+{
+    if let Some(reason) = exemptions.check_and_record(&headers) {
+        tracing::debug!(reason = reason.as_str(), "Request exempt from rate limiting");
+        return Ok(next.run(request).await);
+    }
+
     // Extract identifier (IP address, user ID, or API key)
     let identifier = extract_identifier(&headers);
 
     // Check rate limit
     let mut limiter_guard = limiter.lock().await;
     let result = limiter_guard.check_default_rate_limit(&identifier).await?;
-    drop(limiter_guard);
 
     if !result.allowed {
         tracing::warn!(
@@ -31,9 +45,12 @@ pub async fn rate_limit_middleware(
             current = %result.current,
             "Rate limit exceeded"
         );
+        // The caller is not authenticated yet, so its tenant is unknown here
+        MetricsRecorder::record_rate_limit_exceeded(None, "default");
 
-        return Err(AppError::RateLimitExceeded);
+        return Err(limiter_guard.exceeded_error(&result));
     }
+    drop(limiter_guard);
 
     // Add rate limit headers to response
     let mut response = next.run(request).await;
@@ -113,17 +130,19 @@ fn add_rate_limit_headers(headers: &mut HeaderMap, result: &crate::rate_limit::s
 }
 
 /// Auth-specific rate limiting middleware
-pub async fn auth_rate_limit_middleware(
-    limiter: Arc<Mutex<RateLimiter>>,
+pub async fn auth_rate_limit_middleware<C>(
+    limiter: Arc<Mutex<RateLimiter<C>>>,
     headers: HeaderMap,
     request: Request,
     next: Next,
-) -> Result<Response, AppError> {
+) -> Result<Response, AppError>
+where
+    C: ConnectionLike + Clone + Send + Sync,
+{
     let identifier = extract_identifier(&headers);
 
     let mut limiter_guard = limiter.lock().await;
     let result = limiter_guard.check_auth_rate_limit(&identifier).await?;
-    drop(limiter_guard);
 
     if !result.allowed {
         tracing::warn!(
@@ -132,9 +151,11 @@ pub async fn auth_rate_limit_middleware(
             current = %result.current,
             "Auth rate limit exceeded"
         );
+        MetricsRecorder::record_rate_limit_exceeded(None, "auth");
 
-        return Err(AppError::RateLimitExceeded);
+        return Err(limiter_guard.exceeded_error(&result));
     }
+    drop(limiter_guard);
 
     let mut response = next.run(request).await;
     add_rate_limit_headers(response.headers_mut(), &result);
@@ -145,7 +166,180 @@ pub async fn auth_rate_limit_middleware(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use axum::http::HeaderValue;
+    use crate::auth::jwt::JwtManager;
+    use crate::config::{Config, RateLimitConfig, RateLimitFailMode, RetryAfterFormat};
+    use crate::rate_limit::exemptions::INTERNAL_SERVICE_TOKEN_HEADER;
+    use crate::redis::mock::FlakyConnection;
+    use axum::{body::Body, http::HeaderValue, routing::get, Router};
+    use std::time::{SystemTime, UNIX_EPOCH};
+    use tower::ServiceExt;
+    use uuid::Uuid;
+
+    /// Limiter whose Redis reports the window as full, resetting in `reset_in` seconds
+    fn exhausted_limiter(reset_in: u64, format: RetryAfterFormat) -> Arc<Mutex<RateLimiter<FlakyConnection>>> {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+        let reply = redis::Value::Bulk(vec![
+            redis::Value::Int(0),
+            redis::Value::Int(100),
+            redis::Value::Int(0),
+            redis::Value::Int((now + reset_in) as i64),
+        ]);
+
+        let limiter = RateLimiter::new(FlakyConnection::responding(reply), limiter_config(format));
+        Arc::new(Mutex::new(limiter))
+    }
+
+    fn limiter_config(format: RetryAfterFormat) -> RateLimitConfig {
+        RateLimitConfig {
+            default_requests_per_minute: 100,
+            default_requests_per_hour: 1000,
+            default_requests_per_day: 10000,
+            auth_requests_per_minute: 10,
+            fail_mode: RateLimitFailMode::Closed,
+            retry_after_format: format,
+            exempt_identity_types: vec!["service".to_string()],
+            internal_service_token: Some("internal-token".to_string()),
+        }
+    }
+
+    fn throttled_app(limiter: Arc<Mutex<RateLimiter<FlakyConnection>>>) -> Router {
+        app_with_exemptions(limiter, RateLimitExemptions::none())
+    }
+
+    fn app_with_exemptions(
+        limiter: Arc<Mutex<RateLimiter<FlakyConnection>>>,
+        exemptions: RateLimitExemptions,
+    ) -> Router {
+        let exemptions = Arc::new(exemptions);
+        Router::new()
+            .route("/", get(|| async { "ok" }))
+            .layer(axum::middleware::from_fn(move |headers, request, next| {
+                rate_limit_middleware(limiter.clone(), exemptions.clone(), headers, request, next)
+            }))
+    }
+
+    fn create_jwt_manager() -> Arc<JwtManager> {
+        std::env::set_var(
+            "AGENT_IAM__AUTH__JWT_SECRET",
+            "test-secret-key-for-jwt-signing-minimum-length-requirement",
+        );
+        let mut config = Config::default();
+        config.auth.jwt_expiration_seconds = 900;
+        config.auth.refresh_token_expiration_seconds = 2592000;
+        Arc::new(JwtManager::new(&config).unwrap())
+    }
+
+    async fn status_with_headers(app: Router, headers: &[(&str, String)]) -> StatusCode {
+        let mut request = axum::http::Request::get("/");
+        for (name, value) in headers {
+            request = request.header(*name, value);
+        }
+        app.oneshot(request.body(Body::empty()).unwrap())
+            .await
+            .unwrap()
+            .status()
+    }
+
+    #[tokio::test]
+    async fn test_exempt_callers_are_never_throttled() {
+        let jwt_manager = create_jwt_manager();
+        let exemptions = RateLimitExemptions::from_config(
+            &limiter_config(RetryAfterFormat::Seconds),
+            jwt_manager.clone(),
+        );
+        let app = app_with_exemptions(exhausted_limiter(30, RetryAfterFormat::Seconds), exemptions);
+
+        let service_token = jwt_manager
+            .generate_access_token(Uuid::new_v4(), Uuid::new_v4(), "service")
+            .unwrap();
+        let user_token = jwt_manager
+            .generate_access_token(Uuid::new_v4(), Uuid::new_v4(), "user")
+            .unwrap();
+
+        for _ in 0..3 {
+            let status = status_with_headers(
+                app.clone(),
+                &[("authorization", format!("Bearer {}", service_token))],
+            )
+            .await;
+            assert_eq!(status, StatusCode::OK);
+
+            let status = status_with_headers(
+                app.clone(),
+                &[(INTERNAL_SERVICE_TOKEN_HEADER, "internal-token".to_string())],
+            )
+            .await;
+            assert_eq!(status, StatusCode::OK);
+        }
+
+        // Regular users and wrong service tokens are still limited
+        let status = status_with_headers(
+            app.clone(),
+            &[("authorization", format!("Bearer {}", user_token))],
+        )
+        .await;
+        assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+
+        let status = status_with_headers(
+            app,
+            &[(INTERNAL_SERVICE_TOKEN_HEADER, "guessed-token".to_string())],
+        )
+        .await;
+        assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+    }
+
+    #[tokio::test]
+    async fn test_no_exemptions_by_default() {
+        let jwt_manager = create_jwt_manager();
+        let service_token = jwt_manager
+            .generate_access_token(Uuid::new_v4(), Uuid::new_v4(), "service")
+            .unwrap();
+        let app = throttled_app(exhausted_limiter(30, RetryAfterFormat::Seconds));
+
+        let status = status_with_headers(
+            app,
+            &[
+                ("authorization", format!("Bearer {}", service_token)),
+                (INTERNAL_SERVICE_TOKEN_HEADER, String::new()),
+            ],
+        )
+        .await;
+        assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+    }
+
+    #[tokio::test]
+    async fn test_throttled_response_includes_retry_after_seconds() {
+        let app = throttled_app(exhausted_limiter(30, RetryAfterFormat::Seconds));
+
+        let response = app
+            .oneshot(axum::http::Request::get("/").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        let retry_after: u64 = response.headers()["retry-after"]
+            .to_str()
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert!((29..=30).contains(&retry_after), "got {}", retry_after);
+    }
+
+    #[tokio::test]
+    async fn test_throttled_response_includes_retry_after_http_date() {
+        let app = throttled_app(exhausted_limiter(30, RetryAfterFormat::HttpDate));
+
+        let response = app
+            .oneshot(axum::http::Request::get("/").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        let value = response.headers()["retry-after"].to_str().unwrap();
+        let retry_at = chrono::DateTime::parse_from_rfc2822(value).unwrap();
+        let delay = retry_at.timestamp() - chrono::Utc::now().timestamp();
+        assert!((28..=30).contains(&delay), "got {}", delay);
+    }
 
     #[test]
     fn test_extract_identifier_from_auth() {
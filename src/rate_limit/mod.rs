@@ -1,7 +1,9 @@
+pub mod exemptions;
 pub mod limiter;
 pub mod middleware;
 pub mod sliding_window;
 
-pub use limiter::RateLimiter;
+pub use exemptions::{ExemptionReason, RateLimitExemptions};
+pub use limiter::{BucketUsage, RateLimitBucket, RateLimiter};
 pub use middleware::{auth_rate_limit_middleware, rate_limit_middleware};
 pub use sliding_window::{RateLimitResult, SlidingWindowRateLimiter};
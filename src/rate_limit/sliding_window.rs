@@ -1,15 +1,19 @@
 use crate::errors::Result;
-use redis::aio::ConnectionManager;
+use crate::redis::retry::with_retry;
+use redis::aio::{ConnectionLike, ConnectionManager};
 use std::time::{SystemTime, UNIX_EPOCH};
 
 /// Sliding window rate limiter using Redis sorted sets
-pub struct SlidingWindowRateLimiter {
-    redis: ConnectionManager,
+pub struct SlidingWindowRateLimiter<C = ConnectionManager> {
+    redis: C,
 }
 
-impl SlidingWindowRateLimiter {
+impl<C> SlidingWindowRateLimiter<C>
+where
+    C: ConnectionLike + Clone + Send + Sync,
+{
     /// Create a new sliding window rate limiter
-    pub fn new(redis: ConnectionManager) -> Self {
+    pub fn new(redis: C) -> Self {
         Self { redis }
     }
 
@@ -109,28 +113,41 @@ impl SlidingWindowRateLimiter {
     }
 
     /// Get current count without incrementing
+    ///
+    /// This is a pure read: entries older than the window are ignored, not removed.
     pub async fn get_current_count(&mut self, key: &str, window_seconds: u64) -> Result<u64> {
-        let now = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .map_err(|e| crate::errors::AppError::Internal(format!("Time error: {}", e)))?
-            .as_secs();
-
-        let window_start = now.saturating_sub(window_seconds);
+        let window_start = window_start(window_seconds)?;
 
         use redis::AsyncCommands;
 
-        // Remove old entries
-        let _: i64 = self
-            .redis
-            .zrembyscore(key, "-inf", window_start as i64)
-            .await?;
-
-        // Count current entries
-        let count: u64 = self.redis.zcard(key).await?;
+        // Count entries strictly newer than the window start (matching what
+        // check_and_increment keeps after pruning)
+        let min = format!("({}", window_start);
+        let count: u64 = with_retry("get_current_count", || {
+            let mut redis = self.redis.clone();
+            let min = min.clone();
+            async move { redis.zcount(key, min, "+inf").await }
+        })
+        .await?;
 
         Ok(count)
     }
 
+    /// Remove entries that have fallen out of the window, returning how many were removed
+    pub async fn prune(&mut self, key: &str, window_seconds: u64) -> Result<u64> {
+        let window_start = window_start(window_seconds)?;
+
+        use redis::AsyncCommands;
+
+        let removed: u64 = with_retry("prune", || {
+            let mut redis = self.redis.clone();
+            async move { redis.zrembyscore(key, "-inf", window_start as i64).await }
+        })
+        .await?;
+
+        Ok(removed)
+    }
+
     /// Reset rate limit for a specific key
     pub async fn reset(&mut self, key: &str) -> Result<()> {
         use redis::AsyncCommands;
@@ -142,6 +159,16 @@ impl SlidingWindowRateLimiter {
     }
 }
 
+/// Start of the sliding window ending now
+fn window_start(window_seconds: u64) -> Result<u64> {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_err(|e| crate::errors::AppError::Internal(format!("Time error: {}", e)))?
+        .as_secs();
+
+    Ok(now.saturating_sub(window_seconds))
+}
+
 /// Result of a rate limit check
 #[derive(Debug, Clone)]
 pub struct RateLimitResult {
@@ -158,14 +185,32 @@ pub struct RateLimitResult {
 }
 
 impl RateLimitResult {
+    /// Result used when the limit could not be checked and the request is let through
+    pub fn unchecked(limit: u64, window_seconds: u64) -> Self {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+
+        Self {
+            allowed: true,
+            limit,
+            remaining: limit,
+            reset: now + window_seconds,
+            current: 0,
+        }
+    }
+
     /// Get the number of seconds until the rate limit resets
+    ///
+    /// Never less than one second, so a throttled client is not told to retry immediately.
     pub fn retry_after(&self) -> Option<u64> {
         if !self.allowed {
             let now = SystemTime::now()
                 .duration_since(UNIX_EPOCH)
                 .ok()?
                 .as_secs();
-            Some(self.reset.saturating_sub(now))
+            Some(self.reset.saturating_sub(now).max(1))
         } else {
             None
         }
@@ -183,6 +228,7 @@ mod tests {
             url: "redis://localhost:6379".to_string(),
             pool_size: 10,
             connection_timeout_seconds: 5,
+            retry: Default::default(),
         };
 
         let redis = crate::redis::create_client(&config).await.unwrap();
@@ -217,6 +263,7 @@ mod tests {
             url: "redis://localhost:6379".to_string(),
             pool_size: 10,
             connection_timeout_seconds: 5,
+            retry: Default::default(),
         };
 
         let redis = crate::redis::create_client(&config).await.unwrap();
@@ -251,6 +298,7 @@ mod tests {
             url: "redis://localhost:6379".to_string(),
             pool_size: 10,
             connection_timeout_seconds: 5,
+            retry: Default::default(),
         };
 
         let redis = crate::redis::create_client(&config).await.unwrap();
@@ -273,6 +321,45 @@ mod tests {
         limiter.reset(test_key).await.unwrap();
     }
 
+    #[tokio::test]
+    #[ignore] // Requires Redis
+    async fn test_get_current_count_does_not_mutate() {
+        use redis::AsyncCommands;
+
+        let config = crate::config::RedisConfig {
+            url: "redis://localhost:6379".to_string(),
+            pool_size: 10,
+            connection_timeout_seconds: 5,
+            retry: Default::default(),
+        };
+
+        let mut redis = crate::redis::create_client(&config).await.unwrap();
+        let mut limiter = SlidingWindowRateLimiter::new(redis.clone());
+
+        let test_key = "test:sliding_window:pure_read";
+        limiter.reset(test_key).await.unwrap();
+
+        // One stale entry (two hours old) and two inside the window
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() as f64;
+        let _: () = redis.zadd(test_key, "stale", now - 7200.0).await.unwrap();
+        let _: () = redis.zadd(test_key, "recent-1", now - 10.0).await.unwrap();
+        let _: () = redis.zadd(test_key, "recent-2", now).await.unwrap();
+
+        let count = limiter.get_current_count(test_key, 60).await.unwrap();
+        assert_eq!(count, 2);
+
+        // The stale entry is still stored
+        let stored: u64 = redis.zcard(test_key).await.unwrap();
+        assert_eq!(stored, 3);
+
+        // Explicit pruning removes it
+        assert_eq!(limiter.prune(test_key, 60).await.unwrap(), 1);
+        let stored: u64 = redis.zcard(test_key).await.unwrap();
+        assert_eq!(stored, 2);
+
+        limiter.reset(test_key).await.unwrap();
+    }
+
     #[tokio::test]
     #[ignore] // Requires Redis
     async fn test_reset() {
@@ -280,6 +367,7 @@ mod tests {
             url: "redis://localhost:6379".to_string(),
             pool_size: 10,
             connection_timeout_seconds: 5,
+            retry: Default::default(),
         };
 
         let redis = crate::redis::create_client(&config).await.unwrap();
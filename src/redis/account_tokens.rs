@@ -0,0 +1,164 @@
+// Single-use account tokens (email verification, password reset) using Redis
+//
+// Only a SHA-256 hash of each token is stored, so a Redis dump does not
+// contain usable tokens.
+
+use crate::errors::{AppError, Result};
+use crate::redis::retry::with_retry;
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use rand::{rngs::OsRng, RngCore};
+use redis::{aio::ConnectionLike, AsyncCommands};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+const TOKEN_PREFIX: &str = "account_token:";
+
+/// What a token may be used for; a token issued for one purpose is never
+/// accepted for another
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenPurpose {
+    EmailVerification,
+    PasswordReset,
+}
+
+impl TokenPurpose {
+    /// Seconds a token stays valid
+    pub fn ttl_seconds(&self) -> u64 {
+        match self {
+            TokenPurpose::EmailVerification => 24 * 60 * 60,
+            TokenPurpose::PasswordReset => 60 * 60,
+        }
+    }
+
+    fn key(&self, token: &str) -> String {
+        let kind = match self {
+            TokenPurpose::EmailVerification => "verify_email",
+            TokenPurpose::PasswordReset => "reset_password",
+        };
+        let digest = hex::encode(Sha256::digest(token.as_bytes()));
+        format!("{}{}:{}", TOKEN_PREFIX, kind, digest)
+    }
+}
+
+/// Issue a token for an identity and return it (32 random bytes, base64url)
+pub async fn issue_token<C>(
+    manager: &mut C,
+    purpose: TokenPurpose,
+    identity_id: Uuid,
+) -> Result<String>
+where
+    C: ConnectionLike + Clone + Send + Sync,
+{
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+    let token = URL_SAFE_NO_PAD.encode(bytes);
+    let key = purpose.key(&token);
+
+    // SET with a TTL is idempotent, so it is safe to retry
+    with_retry("issue_account_token", || {
+        let mut conn = manager.clone();
+        let key = key.clone();
+        async move {
+            conn.set_ex::<_, _, ()>(&key, identity_id.to_string(), purpose.ttl_seconds())
+                .await
+        }
+    })
+    .await?;
+    Ok(token)
+}
+
+/// Consume a token, returning the identity it was issued for
+///
+/// Expired, unknown and already-used tokens all yield `None`. GETDEL is not
+/// retried because a retry after a lost reply would find the key gone.
+pub async fn take_token<C>(
+    manager: &mut C,
+    purpose: TokenPurpose,
+    token: &str,
+) -> Result<Option<Uuid>>
+where
+    C: ConnectionLike + Clone + Send + Sync,
+{
+    let value: Option<String> = manager.get_del(purpose.key(token)).await?;
+
+    value
+        .map(|v| {
+            Uuid::parse_str(&v)
+                .map_err(|e| AppError::Internal(format!("Corrupt account token: {}", e)))
+        })
+        .transpose()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::redis::mock::FlakyConnection;
+    use redis::Value;
+
+    #[test]
+    fn test_key_stores_hash_not_token() {
+        let key = TokenPurpose::EmailVerification.key("secret-token");
+
+        assert!(!key.contains("secret-token"));
+        assert_eq!(key, TokenPurpose::EmailVerification.key("secret-token"));
+        assert_ne!(key, TokenPurpose::EmailVerification.key("other-token"));
+    }
+
+    #[test]
+    fn test_purposes_do_not_share_tokens() {
+        assert_ne!(
+            TokenPurpose::EmailVerification.key("token"),
+            TokenPurpose::PasswordReset.key("token")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_issue_token_retries_and_returns_random_token() {
+        let mut conn = FlakyConnection::failing(1);
+
+        let token = issue_token(&mut conn, TokenPurpose::EmailVerification, Uuid::new_v4())
+            .await
+            .unwrap();
+        assert_eq!(token.len(), 43);
+        assert_eq!(conn.calls(), 2);
+
+        let other = issue_token(&mut conn, TokenPurpose::EmailVerification, Uuid::new_v4())
+            .await
+            .unwrap();
+        assert_ne!(token, other);
+    }
+
+    #[tokio::test]
+    async fn test_take_token_returns_identity() {
+        let identity_id = Uuid::new_v4();
+        let mut conn =
+            FlakyConnection::responding(Value::Data(identity_id.to_string().into_bytes()));
+
+        let taken = take_token(&mut conn, TokenPurpose::EmailVerification, "token")
+            .await
+            .unwrap();
+        assert_eq!(taken, Some(identity_id));
+    }
+
+    #[tokio::test]
+    async fn test_take_unknown_or_used_token() {
+        let mut conn = FlakyConnection::responding(Value::Nil);
+
+        let taken = take_token(&mut conn, TokenPurpose::EmailVerification, "token")
+            .await
+            .unwrap();
+        assert_eq!(taken, None);
+    }
+
+    #[tokio::test]
+    async fn test_take_token_is_not_retried() {
+        let mut conn = FlakyConnection::failing(1);
+
+        assert!(
+            take_token(&mut conn, TokenPurpose::EmailVerification, "token")
+                .await
+                .is_err()
+        );
+        assert_eq!(conn.calls(), 1);
+    }
+}
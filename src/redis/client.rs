@@ -6,6 +6,9 @@ use std::time::Duration;
 pub async fn create_client(config: &RedisConfig) -> Result<ConnectionManager> {
     tracing::info!("Creating Redis client");
 
+    // Apply the retry policy used by idempotent operations
+    crate::redis::retry::configure(config.retry);
+
     let client = Client::open(config.url.as_str())?;
 
     let manager = ConnectionManager::new(client).await?;
@@ -115,6 +115,7 @@ mod tests {
             url: "redis://localhost:6379".to_string(),
             pool_size: 10,
             connection_timeout_seconds: 5,
+            retry: Default::default(),
         };
 
         let manager = crate::redis::create_client(&config).await.unwrap();
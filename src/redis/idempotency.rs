@@ -0,0 +1,201 @@
+// Idempotency records for retried requests using Redis
+//
+// The first request with a key stores a pending record (SET NX); once it
+// succeeds the record is replaced by the response. Later requests with the
+// same key get that response back instead of running again.
+
+use crate::errors::{AppError, Result};
+use crate::redis::retry::with_retry;
+use redis::{aio::ConnectionLike, AsyncCommands};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+const IDEMPOTENCY_PREFIX: &str = "idempotency:";
+
+/// Seconds a stored response can be replayed
+pub const IDEMPOTENCY_TTL_SECONDS: u64 = 24 * 60 * 60;
+
+/// Stored state of an idempotency key
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct IdempotencyRecord {
+    /// Hash of the request that first used the key
+    request_hash: String,
+    /// Response body, or None while the first request is still running
+    response: Option<Value>,
+}
+
+/// What to do with a request that carries an idempotency key
+#[derive(Debug, Clone, PartialEq)]
+pub enum IdempotencyState {
+    /// First use of the key: run the request, then `complete` or `release`
+    New,
+    /// The key was already used for the same request; return this response
+    Replay(Value),
+}
+
+/// Claim `key` for a request, or find the response of an earlier one
+///
+/// Reusing a key for a different request, or while the first request is
+/// still running, is a conflict. Not retried: a retried SET NX that had
+/// reached Redis would find its own record and report a conflict.
+pub async fn begin<C>(manager: &mut C, key: &str, request_hash: &str) -> Result<IdempotencyState>
+where
+    C: ConnectionLike + Clone + Send + Sync,
+{
+    let redis_key = format!("{}{}", IDEMPOTENCY_PREFIX, key);
+
+    let existing: Option<String> = manager.get(&redis_key).await?;
+    if let Some(existing) = existing {
+        return resolve(&existing, request_hash);
+    }
+
+    let pending = serialize(&IdempotencyRecord {
+        request_hash: request_hash.to_string(),
+        response: None,
+    })?;
+    let acquired: Option<String> = redis::cmd("SET")
+        .arg(&redis_key)
+        .arg(pending)
+        .arg("NX")
+        .arg("EX")
+        .arg(IDEMPOTENCY_TTL_SECONDS)
+        .query_async(manager)
+        .await?;
+    if acquired.is_some() {
+        return Ok(IdempotencyState::New);
+    }
+
+    // A concurrent request with the same key claimed it first
+    let existing: Option<String> = manager.get(&redis_key).await?;
+    match existing {
+        Some(existing) => resolve(&existing, request_hash),
+        None => Err(in_progress()),
+    }
+}
+
+/// Store the response of a request started with `begin`
+pub async fn complete<C>(
+    manager: &mut C,
+    key: &str,
+    request_hash: &str,
+    response: &Value,
+) -> Result<()>
+where
+    C: ConnectionLike + Clone + Send + Sync,
+{
+    let redis_key = format!("{}{}", IDEMPOTENCY_PREFIX, key);
+    let record = serialize(&IdempotencyRecord {
+        request_hash: request_hash.to_string(),
+        response: Some(response.clone()),
+    })?;
+
+    // SET with a TTL is idempotent, so it is safe to retry
+    with_retry("complete_idempotency_key", || {
+        let mut conn = manager.clone();
+        let redis_key = redis_key.clone();
+        let record = record.clone();
+        async move {
+            conn.set_ex::<_, _, ()>(&redis_key, record, IDEMPOTENCY_TTL_SECONDS)
+                .await
+        }
+    })
+    .await?;
+    Ok(())
+}
+
+/// Give up a key after a failed request so that a retry can run again
+pub async fn release<C>(manager: &mut C, key: &str) -> Result<()>
+where
+    C: ConnectionLike + Clone + Send + Sync,
+{
+    let redis_key = format!("{}{}", IDEMPOTENCY_PREFIX, key);
+
+    // DEL is idempotent, so it is safe to retry
+    with_retry("release_idempotency_key", || {
+        let mut conn = manager.clone();
+        let redis_key = redis_key.clone();
+        async move { conn.del::<_, ()>(&redis_key).await }
+    })
+    .await?;
+    Ok(())
+}
+
+fn resolve(stored: &str, request_hash: &str) -> Result<IdempotencyState> {
+    let record: IdempotencyRecord = serde_json::from_str(stored)
+        .map_err(|e| AppError::Internal(format!("Corrupt idempotency record: {}", e)))?;
+
+    if record.request_hash != request_hash {
+        return Err(AppError::Conflict(
+            "Idempotency key was already used for a different request".to_string(),
+        ));
+    }
+
+    record
+        .response
+        .map(IdempotencyState::Replay)
+        .ok_or_else(in_progress)
+}
+
+fn in_progress() -> AppError {
+    AppError::Conflict("A request with this idempotency key is still in progress".to_string())
+}
+
+fn serialize(record: &IdempotencyRecord) -> Result<String> {
+    serde_json::to_string(record)
+        .map_err(|e| AppError::Internal(format!("Failed to serialize idempotency record: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::redis::mock::FlakyConnection;
+    use serde_json::json;
+
+    fn stored(request_hash: &str, response: Option<Value>) -> FlakyConnection {
+        let record = serde_json::to_string(&IdempotencyRecord {
+            request_hash: request_hash.to_string(),
+            response,
+        })
+        .unwrap();
+        FlakyConnection::responding(redis::Value::Data(record.into_bytes()))
+    }
+
+    #[tokio::test]
+    async fn test_repeated_key_replays_response() {
+        let mut conn = stored("hash-a", Some(json!({"total": 1})));
+
+        let state = begin(&mut conn, "key", "hash-a").await.unwrap();
+
+        assert_eq!(state, IdempotencyState::Replay(json!({"total": 1})));
+        assert_eq!(conn.calls(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_conflicting_request_rejected() {
+        let mut conn = stored("hash-a", Some(json!({"total": 1})));
+
+        let result = begin(&mut conn, "key", "hash-b").await;
+
+        assert!(matches!(result, Err(AppError::Conflict(_))));
+    }
+
+    #[tokio::test]
+    async fn test_in_progress_key_rejected() {
+        let mut conn = stored("hash-a", None);
+
+        let result = begin(&mut conn, "key", "hash-a").await;
+
+        assert!(matches!(result, Err(AppError::Conflict(_))));
+    }
+
+    #[tokio::test]
+    async fn test_complete_retries_transient_failure() {
+        let mut conn = FlakyConnection::failing(1);
+
+        complete(&mut conn, "key", "hash-a", &json!({}))
+            .await
+            .unwrap();
+
+        assert_eq!(conn.calls(), 2);
+    }
+}
@@ -0,0 +1,80 @@
+// Pending MFA login challenges using Redis
+
+use crate::errors::{AppError, Result};
+use crate::redis::retry::with_retry;
+use redis::{aio::ConnectionLike, AsyncCommands};
+use uuid::Uuid;
+
+const CHALLENGE_PREFIX: &str = "mfa_challenge:";
+
+/// Seconds a user has to complete the second login step
+pub const CHALLENGE_TTL_SECONDS: u64 = 300;
+
+/// Create a challenge for an identity that passed the password step
+pub async fn create_challenge<C>(manager: &mut C, identity_id: Uuid) -> Result<String>
+where
+    C: ConnectionLike + Clone + Send + Sync,
+{
+    let challenge_id = Uuid::new_v4().to_string();
+    let key = format!("{}{}", CHALLENGE_PREFIX, challenge_id);
+    // SET with a TTL is idempotent, so it is safe to retry
+    with_retry("create_mfa_challenge", || {
+        let mut conn = manager.clone();
+        let key = key.clone();
+        async move {
+            conn.set_ex::<_, _, ()>(&key, identity_id.to_string(), CHALLENGE_TTL_SECONDS)
+                .await
+        }
+    })
+    .await?;
+    Ok(challenge_id)
+}
+
+/// Consume a challenge, returning the identity it was issued for
+///
+/// Challenges are single use: a wrong code means logging in again. GETDEL is
+/// not retried because a retry after a lost reply would find the key gone.
+pub async fn take_challenge<C>(manager: &mut C, challenge_id: &str) -> Result<Option<Uuid>>
+where
+    C: ConnectionLike + Clone + Send + Sync,
+{
+    let key = format!("{}{}", CHALLENGE_PREFIX, challenge_id);
+    let value: Option<String> = manager.get_del(&key).await?;
+
+    value
+        .map(|v| {
+            Uuid::parse_str(&v)
+                .map_err(|e| AppError::Internal(format!("Corrupt MFA challenge: {}", e)))
+        })
+        .transpose()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::redis::mock::FlakyConnection;
+    use redis::Value;
+
+    #[tokio::test]
+    async fn test_take_challenge_returns_identity() {
+        let identity_id = Uuid::new_v4();
+        let mut conn = FlakyConnection::responding(Value::Data(identity_id.to_string().into_bytes()));
+
+        assert_eq!(take_challenge(&mut conn, "challenge").await.unwrap(), Some(identity_id));
+    }
+
+    #[tokio::test]
+    async fn test_take_missing_challenge() {
+        let mut conn = FlakyConnection::responding(Value::Nil);
+
+        assert_eq!(take_challenge(&mut conn, "challenge").await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_take_challenge_is_not_retried() {
+        let mut conn = FlakyConnection::failing(1);
+
+        assert!(take_challenge(&mut conn, "challenge").await.is_err());
+        assert_eq!(conn.calls(), 1);
+    }
+}
@@ -0,0 +1,94 @@
+// Test double for Redis connections
+
+use redis::{aio::ConnectionLike, Cmd, Pipeline, RedisError, RedisFuture, Value};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// Connection that fails with an I/O error a fixed number of times, then
+/// answers every command with a fixed value (`Value::Int(1)` by default)
+#[derive(Clone)]
+pub struct FlakyConnection {
+    failures_left: Arc<AtomicU32>,
+    calls: Arc<AtomicU32>,
+    response: Value,
+    /// Replies given, in order, before falling back to `response`
+    replies: Arc<Mutex<VecDeque<Value>>>,
+}
+
+impl FlakyConnection {
+    pub fn failing(times: u32) -> Self {
+        Self {
+            failures_left: Arc::new(AtomicU32::new(times)),
+            calls: Arc::new(AtomicU32::new(0)),
+            response: Value::Int(1),
+            replies: Arc::new(Mutex::new(VecDeque::new())),
+        }
+    }
+
+    /// Connection that always succeeds with the given reply
+    pub fn responding(response: Value) -> Self {
+        Self {
+            response,
+            ..Self::failing(0)
+        }
+    }
+
+    /// Connection that answers commands with the given replies in order,
+    /// then repeats the last one
+    pub fn replying(replies: Vec<Value>) -> Self {
+        let response = replies.last().cloned().unwrap_or(Value::Nil);
+        Self {
+            replies: Arc::new(Mutex::new(replies.into())),
+            ..Self::responding(response)
+        }
+    }
+
+    /// Connection that never succeeds, as if Redis were down
+    pub fn down() -> Self {
+        Self::failing(u32::MAX)
+    }
+
+    /// Number of commands sent so far
+    pub fn calls(&self) -> u32 {
+        self.calls.load(Ordering::SeqCst)
+    }
+}
+
+impl ConnectionLike for FlakyConnection {
+    fn req_packed_command<'a>(&'a mut self, _cmd: &'a Cmd) -> RedisFuture<'a, Value> {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+        let fail = self
+            .failures_left
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1))
+            .is_ok();
+        let response = (!fail).then(|| {
+            self.replies
+                .lock()
+                .unwrap()
+                .pop_front()
+                .unwrap_or_else(|| self.response.clone())
+        });
+        Box::pin(async move {
+            response.ok_or_else(|| {
+                RedisError::from(std::io::Error::new(
+                    std::io::ErrorKind::ConnectionReset,
+                    "connection reset",
+                ))
+            })
+        })
+    }
+
+    fn req_packed_commands<'a>(
+        &'a mut self,
+        _cmd: &'a Pipeline,
+        _offset: usize,
+        _count: usize,
+    ) -> RedisFuture<'a, Vec<Value>> {
+        Box::pin(async { Ok(vec![]) })
+    }
+
+    fn get_db(&self) -> i64 {
+        0
+    }
+}
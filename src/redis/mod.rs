@@ -1,5 +1,14 @@
 pub mod client;
 pub mod revocation;
 pub mod counters;
+pub mod retry;
+pub mod mfa_challenge;
+pub mod webauthn_state;
+pub mod oidc_code;
+pub mod oidc_login;
+pub mod account_tokens;
+pub mod idempotency;
+#[cfg(test)]
+pub(crate) mod mock;
 
 pub use client::{create_client, health_check};
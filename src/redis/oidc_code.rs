@@ -0,0 +1,86 @@
+// OIDC authorization codes using Redis
+
+use crate::errors::{AppError, Result};
+use crate::redis::retry::with_retry;
+use redis::{aio::ConnectionLike, AsyncCommands};
+use serde::{de::DeserializeOwned, Serialize};
+use uuid::Uuid;
+
+const CODE_PREFIX: &str = "oidc_code:";
+
+/// Seconds a client has to redeem an authorization code
+pub const CODE_TTL_SECONDS: u64 = 60;
+
+/// Store the grant behind a new authorization code and return the code
+pub async fn create_code<C, T>(manager: &mut C, grant: &T) -> Result<String>
+where
+    C: ConnectionLike + Clone + Send + Sync,
+    T: Serialize,
+{
+    let code = Uuid::new_v4().simple().to_string();
+    let key = format!("{}{}", CODE_PREFIX, code);
+    let value = serde_json::to_string(grant)
+        .map_err(|e| AppError::Internal(format!("Failed to serialize authorization grant: {}", e)))?;
+
+    // SET with a TTL is idempotent, so it is safe to retry
+    with_retry("create_oidc_code", || {
+        let mut conn = manager.clone();
+        let key = key.clone();
+        let value = value.clone();
+        async move { conn.set_ex::<_, _, ()>(&key, value, CODE_TTL_SECONDS).await }
+    })
+    .await?;
+    Ok(code)
+}
+
+/// Redeem an authorization code; each code can be used at most once
+///
+/// GETDEL is not retried because a retry after a lost reply would find the
+/// key gone.
+pub async fn take_code<C, T>(manager: &mut C, code: &str) -> Result<Option<T>>
+where
+    C: ConnectionLike + Clone + Send + Sync,
+    T: DeserializeOwned,
+{
+    let value: Option<String> = manager.get_del(format!("{}{}", CODE_PREFIX, code)).await?;
+
+    value
+        .map(|v| {
+            serde_json::from_str(&v)
+                .map_err(|e| AppError::Internal(format!("Corrupt authorization grant: {}", e)))
+        })
+        .transpose()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::redis::mock::FlakyConnection;
+    use redis::Value;
+
+    #[tokio::test]
+    async fn test_create_code_retries_transient_failure() {
+        let mut conn = FlakyConnection::failing(1);
+
+        let code = create_code(&mut conn, &serde_json::json!({"n": 1})).await.unwrap();
+        assert_eq!(code.len(), 32);
+        assert_eq!(conn.calls(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_take_code_is_not_retried() {
+        let mut conn = FlakyConnection::failing(1);
+
+        let result: Result<Option<serde_json::Value>> = take_code(&mut conn, "abc").await;
+        assert!(result.is_err());
+        assert_eq!(conn.calls(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_take_missing_code() {
+        let mut conn = FlakyConnection::responding(Value::Nil);
+
+        let grant: Option<serde_json::Value> = take_code(&mut conn, "abc").await.unwrap();
+        assert!(grant.is_none());
+    }
+}
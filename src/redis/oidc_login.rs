@@ -0,0 +1,86 @@
+// Pending upstream OIDC logins using Redis
+
+use crate::errors::{AppError, Result};
+use crate::oidc::upstream::PendingLogin;
+use crate::redis::retry::with_retry;
+use redis::{aio::ConnectionLike, AsyncCommands};
+use uuid::Uuid;
+
+const LOGIN_PREFIX: &str = "oidc_login:";
+
+/// Seconds a user has to complete the login at the upstream provider
+pub const LOGIN_TTL_SECONDS: u64 = 600;
+
+/// Store a pending login and return the `state` value identifying it
+pub async fn store_pending<C>(manager: &mut C, pending: &PendingLogin) -> Result<String>
+where
+    C: ConnectionLike + Clone + Send + Sync,
+{
+    let state = Uuid::new_v4().simple().to_string();
+    let key = format!("{}{}", LOGIN_PREFIX, state);
+    let value = serde_json::to_string(pending)
+        .map_err(|e| AppError::Internal(format!("Failed to serialize pending login: {}", e)))?;
+
+    // SET with a TTL is idempotent, so it is safe to retry
+    with_retry("store_oidc_login", || {
+        let mut conn = manager.clone();
+        let key = key.clone();
+        let value = value.clone();
+        async move {
+            conn.set_ex::<_, _, ()>(&key, value, LOGIN_TTL_SECONDS)
+                .await
+        }
+    })
+    .await?;
+    Ok(state)
+}
+
+/// Consume a pending login; each `state` can be used at most once
+///
+/// GETDEL is not retried because a retry after a lost reply would find the
+/// key gone.
+pub async fn take_pending<C>(manager: &mut C, state: &str) -> Result<Option<PendingLogin>>
+where
+    C: ConnectionLike + Clone + Send + Sync,
+{
+    let value: Option<String> = manager
+        .get_del(format!("{}{}", LOGIN_PREFIX, state))
+        .await?;
+
+    value
+        .map(|v| {
+            serde_json::from_str(&v)
+                .map_err(|e| AppError::Internal(format!("Corrupt pending login: {}", e)))
+        })
+        .transpose()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::redis::mock::FlakyConnection;
+    use redis::Value;
+
+    #[tokio::test]
+    async fn test_take_pending_deserializes() {
+        let pending = PendingLogin {
+            provider_id: Uuid::new_v4(),
+            nonce: "nonce".to_string(),
+            code_verifier: "verifier".to_string(),
+        };
+        let mut conn =
+            FlakyConnection::responding(Value::Data(serde_json::to_vec(&pending).unwrap()));
+
+        let taken = take_pending(&mut conn, "state").await.unwrap().unwrap();
+        assert_eq!(taken.provider_id, pending.provider_id);
+        assert_eq!(taken.code_verifier, "verifier");
+    }
+
+    #[tokio::test]
+    async fn test_take_pending_is_not_retried() {
+        let mut conn = FlakyConnection::failing(1);
+
+        assert!(take_pending(&mut conn, "state").await.is_err());
+        assert_eq!(conn.calls(), 1);
+    }
+}
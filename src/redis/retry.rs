@@ -0,0 +1,166 @@
+// Retry with backoff for transient Redis failures
+
+use crate::config::RedisRetryConfig;
+use rand::Rng;
+use redis::{ErrorKind, RedisError, RedisResult};
+use std::future::Future;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+static RETRY_CONFIG: OnceLock<RedisRetryConfig> = OnceLock::new();
+
+/// Set the process-wide retry policy (first call wins)
+pub fn configure(config: RedisRetryConfig) {
+    let _ = RETRY_CONFIG.set(config);
+}
+
+/// The configured retry policy, or the default if none was set
+pub fn retry_config() -> RedisRetryConfig {
+    RETRY_CONFIG.get().copied().unwrap_or_default()
+}
+
+/// Whether an error is likely to succeed on a later attempt
+pub fn is_transient(err: &RedisError) -> bool {
+    err.is_io_error()
+        || err.is_connection_dropped()
+        || err.is_connection_refusal()
+        || err.is_timeout()
+        || matches!(
+            err.kind(),
+            ErrorKind::IoError
+                | ErrorKind::TryAgain
+                | ErrorKind::BusyLoadingError
+                | ErrorKind::ClusterDown
+                | ErrorKind::MasterDown
+        )
+}
+
+/// Delay before retry number `attempt` (0-based): capped exponential with equal jitter
+pub fn backoff(config: &RedisRetryConfig, attempt: u32) -> Duration {
+    let exponential = config
+        .initial_backoff_ms
+        .saturating_mul(1u64 << attempt.min(16))
+        .min(config.max_backoff_ms);
+    let half = exponential / 2;
+    let jitter = if half > 0 {
+        rand::thread_rng().gen_range(0..=half)
+    } else {
+        0
+    };
+
+    Duration::from_millis(exponential - half + jitter)
+}
+
+/// Run an idempotent Redis operation, retrying transient failures
+///
+/// Only use this for operations that are safe to repeat; a retried write that
+/// actually reached Redis before the connection dropped will be applied twice.
+pub async fn with_retry<T, F, Fut>(operation: &str, op: F) -> RedisResult<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = RedisResult<T>>,
+{
+    with_retry_config(&retry_config(), operation, op).await
+}
+
+/// Same as [`with_retry`] with an explicit policy
+pub async fn with_retry_config<T, F, Fut>(
+    config: &RedisRetryConfig,
+    operation: &str,
+    mut op: F,
+) -> RedisResult<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = RedisResult<T>>,
+{
+    let mut attempt = 0;
+    loop {
+        match op().await {
+            Ok(value) => return Ok(value),
+            Err(e) if attempt < config.max_retries && is_transient(&e) => {
+                let delay = backoff(config, attempt);
+                tracing::warn!(
+                    operation,
+                    attempt = attempt + 1,
+                    delay_ms = delay.as_millis() as u64,
+                    error = %e,
+                    "Transient Redis error, retrying"
+                );
+                tokio::time::sleep(delay).await;
+                attempt += 1;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    fn fast_config(max_retries: u32) -> RedisRetryConfig {
+        RedisRetryConfig {
+            max_retries,
+            initial_backoff_ms: 1,
+            max_backoff_ms: 5,
+        }
+    }
+
+    fn io_error() -> RedisError {
+        RedisError::from(std::io::Error::new(
+            std::io::ErrorKind::ConnectionReset,
+            "connection reset",
+        ))
+    }
+
+    #[test]
+    fn test_backoff_is_capped() {
+        let config = RedisRetryConfig {
+            max_retries: 10,
+            initial_backoff_ms: 100,
+            max_backoff_ms: 400,
+        };
+
+        let first = backoff(&config, 0);
+        assert!(first >= Duration::from_millis(50) && first <= Duration::from_millis(100));
+
+        for attempt in 0..40 {
+            assert!(backoff(&config, attempt) <= Duration::from_millis(400));
+        }
+        assert!(backoff(&config, 30) >= Duration::from_millis(200));
+    }
+
+    #[test]
+    fn test_is_transient() {
+        assert!(is_transient(&io_error()));
+        assert!(is_transient(&RedisError::from((ErrorKind::TryAgain, "try again"))));
+        assert!(!is_transient(&RedisError::from((ErrorKind::TypeError, "wrong type"))));
+    }
+
+    #[tokio::test]
+    async fn test_retry_gives_up_after_max_retries() {
+        let calls = AtomicU32::new(0);
+        let result: RedisResult<()> = with_retry_config(&fast_config(2), "test", || {
+            calls.fetch_add(1, Ordering::SeqCst);
+            async { Err(io_error()) }
+        })
+        .await;
+
+        assert!(result.is_err());
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_permanent_errors_are_not_retried() {
+        let calls = AtomicU32::new(0);
+        let result: RedisResult<()> = with_retry_config(&fast_config(2), "test", || {
+            calls.fetch_add(1, Ordering::SeqCst);
+            async { Err(RedisError::from((ErrorKind::TypeError, "wrong type"))) }
+        })
+        .await;
+
+        assert!(result.is_err());
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+}
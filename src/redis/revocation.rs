@@ -1,37 +1,99 @@
 // Token revocation list using Redis
 
 use crate::errors::Result;
-use redis::{aio::ConnectionManager, AsyncCommands};
+use crate::redis::retry::with_retry;
+use redis::{aio::ConnectionLike, AsyncCommands};
 
 const REVOCATION_PREFIX: &str = "revoked:";
 
 /// Add a token to the revocation list
-pub async fn revoke_token(
-    manager: &mut ConnectionManager,
-    token_id: &str,
-    ttl_seconds: i64,
-) -> Result<()> {
+pub async fn revoke_token<C>(manager: &mut C, token_id: &str, ttl_seconds: i64) -> Result<()>
+where
+    C: ConnectionLike + Clone + Send + Sync,
+{
     let key = format!("{}{}", REVOCATION_PREFIX, token_id);
-    manager.set_ex(&key, "1", ttl_seconds as u64).await?;
+    // SET with a TTL is idempotent, so it is safe to retry
+    with_retry("revoke_token", || {
+        let mut conn = manager.clone();
+        let key = key.clone();
+        async move { conn.set_ex::<_, _, ()>(&key, "1", ttl_seconds as u64).await }
+    })
+    .await?;
     Ok(())
 }
 
 /// Check if a token is revoked
-pub async fn is_token_revoked(
-    manager: &mut ConnectionManager,
-    token_id: &str,
-) -> Result<bool> {
+pub async fn is_token_revoked<C>(manager: &mut C, token_id: &str) -> Result<bool>
+where
+    C: ConnectionLike + Clone + Send + Sync,
+{
     let key = format!("{}{}", REVOCATION_PREFIX, token_id);
-    let exists: bool = manager.exists(&key).await?;
+    let exists: bool = with_retry("is_token_revoked", || {
+        let mut conn = manager.clone();
+        let key = key.clone();
+        async move { conn.exists(&key).await }
+    })
+    .await?;
     Ok(exists)
 }
 
+/// Mark a single-use token as used, returning false if it was used already
+///
+/// Atomic (SET NX), so of concurrent uses exactly one gets `true`; the token
+/// is on the revocation list from then on. A revoked token also reports
+/// false. Not retried: a retried SET NX that had reached Redis would find
+/// its own mark and refuse the one legitimate use.
+pub async fn consume_token<C>(manager: &mut C, token_id: &str, ttl_seconds: i64) -> Result<bool>
+where
+    C: ConnectionLike + Clone + Send + Sync,
+{
+    let key = format!("{}{}", REVOCATION_PREFIX, token_id);
+    let marked: Option<String> = redis::cmd("SET")
+        .arg(&key)
+        .arg("1")
+        .arg("NX")
+        .arg("EX")
+        .arg(ttl_seconds.max(1))
+        .query_async(manager)
+        .await?;
+    Ok(marked.is_some())
+}
+
 /// Remove a token from the revocation list (when it expires naturally)
-pub async fn unrevoke_token(
-    manager: &mut ConnectionManager,
-    token_id: &str,
-) -> Result<()> {
+pub async fn unrevoke_token<C>(manager: &mut C, token_id: &str) -> Result<()>
+where
+    C: ConnectionLike + Clone + Send + Sync,
+{
     let key = format!("{}{}", REVOCATION_PREFIX, token_id);
-    manager.del(&key).await?;
+    with_retry("unrevoke_token", || {
+        let mut conn = manager.clone();
+        let key = key.clone();
+        async move { conn.del::<_, ()>(&key).await }
+    })
+    .await?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::redis::mock::FlakyConnection;
+
+    #[tokio::test]
+    async fn test_revocation_check_survives_transient_failure() {
+        let mut conn = FlakyConnection::failing(1);
+
+        let revoked = is_token_revoked(&mut conn, "token-1").await.unwrap();
+
+        assert!(revoked);
+        assert_eq!(conn.calls(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_consume_token_succeeds_once() {
+        let mut conn = FlakyConnection::replying(vec![redis::Value::Okay, redis::Value::Nil]);
+
+        assert!(consume_token(&mut conn, "token-1", 60).await.unwrap());
+        assert!(!consume_token(&mut conn, "token-1", 60).await.unwrap());
+    }
+}
@@ -0,0 +1,104 @@
+// In-progress WebAuthn ceremony state using Redis
+
+use crate::errors::{AppError, Result};
+use crate::redis::retry::with_retry;
+use redis::{aio::ConnectionLike, AsyncCommands};
+use serde::{de::DeserializeOwned, Serialize};
+use uuid::Uuid;
+
+const CEREMONY_PREFIX: &str = "webauthn:";
+
+/// Seconds a browser has to complete a ceremony
+pub const CEREMONY_TTL_SECONDS: u64 = 300;
+
+/// Which ceremony a stored state belongs to, so a registration state can
+/// never be presented to the authentication step or vice versa
+#[derive(Debug, Clone, Copy)]
+pub enum Ceremony {
+    Registration,
+    Authentication,
+}
+
+impl Ceremony {
+    fn key(&self, ceremony_id: &str) -> String {
+        let kind = match self {
+            Ceremony::Registration => "reg",
+            Ceremony::Authentication => "auth",
+        };
+        format!("{}{}:{}", CEREMONY_PREFIX, kind, ceremony_id)
+    }
+}
+
+/// Store ceremony state and return the ID the client must send back
+pub async fn store_state<C, T>(manager: &mut C, ceremony: Ceremony, state: &T) -> Result<String>
+where
+    C: ConnectionLike + Clone + Send + Sync,
+    T: Serialize,
+{
+    let ceremony_id = Uuid::new_v4().to_string();
+    let key = ceremony.key(&ceremony_id);
+    let value = serde_json::to_string(state)
+        .map_err(|e| AppError::Internal(format!("Failed to serialize WebAuthn state: {}", e)))?;
+
+    // SET with a TTL is idempotent, so it is safe to retry
+    with_retry("store_webauthn_state", || {
+        let mut conn = manager.clone();
+        let key = key.clone();
+        let value = value.clone();
+        async move { conn.set_ex::<_, _, ()>(&key, value, CEREMONY_TTL_SECONDS).await }
+    })
+    .await?;
+    Ok(ceremony_id)
+}
+
+/// Consume ceremony state; each ceremony can be finished at most once
+///
+/// GETDEL is not retried because a retry after a lost reply would find the
+/// key gone.
+pub async fn take_state<C, T>(manager: &mut C, ceremony: Ceremony, ceremony_id: &str) -> Result<Option<T>>
+where
+    C: ConnectionLike + Clone + Send + Sync,
+    T: DeserializeOwned,
+{
+    let value: Option<String> = manager.get_del(ceremony.key(ceremony_id)).await?;
+
+    value
+        .map(|v| {
+            serde_json::from_str(&v)
+                .map_err(|e| AppError::Internal(format!("Corrupt WebAuthn state: {}", e)))
+        })
+        .transpose()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::redis::mock::FlakyConnection;
+    use redis::Value;
+
+    #[test]
+    fn test_ceremony_keys_are_distinct() {
+        assert_ne!(
+            Ceremony::Registration.key("abc"),
+            Ceremony::Authentication.key("abc")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_take_state_deserializes() {
+        let mut conn = FlakyConnection::responding(Value::Data(br#"{"n":1}"#.to_vec()));
+
+        let state: Option<serde_json::Value> =
+            take_state(&mut conn, Ceremony::Registration, "abc").await.unwrap();
+        assert_eq!(state.unwrap()["n"], 1);
+    }
+
+    #[tokio::test]
+    async fn test_take_missing_state() {
+        let mut conn = FlakyConnection::responding(Value::Nil);
+
+        let state: Option<serde_json::Value> =
+            take_state(&mut conn, Ceremony::Authentication, "abc").await.unwrap();
+        assert!(state.is_none());
+    }
+}
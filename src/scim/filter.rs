@@ -0,0 +1,141 @@
+// SCIM filter parsing
+//
+// Only the `eq` comparisons IdPs use to look up a user before provisioning are
+// supported (e.g. `userName eq "alice@example.com"`); anything else is
+// rejected with `invalidFilter`.
+
+use crate::scim::{ScimError, ScimResult};
+
+/// User attributes that can be filtered on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UserAttribute {
+    UserName,
+    ExternalId,
+    DisplayName,
+    Active,
+}
+
+/// Comparison value in a filter
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FilterValue {
+    String(String),
+    Bool(bool),
+}
+
+/// A parsed `<attribute> eq <value>` filter
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EqFilter {
+    pub attribute: UserAttribute,
+    pub value: FilterValue,
+}
+
+/// Parse a user filter expression
+pub fn parse_user_filter(filter: &str) -> ScimResult<EqFilter> {
+    let filter = filter.trim();
+    let (attribute, rest) = filter
+        .split_once(char::is_whitespace)
+        .ok_or_else(|| invalid(filter))?;
+    let (operator, value) = rest
+        .trim_start()
+        .split_once(char::is_whitespace)
+        .ok_or_else(|| invalid(filter))?;
+
+    if !operator.eq_ignore_ascii_case("eq") {
+        return Err(ScimError::bad_request(
+            "invalidFilter",
+            format!("Unsupported filter operator: {}", operator),
+        ));
+    }
+
+    // Attribute names are case-insensitive (RFC 7643 section 2.1)
+    let attribute = match attribute.to_ascii_lowercase().as_str() {
+        "username" => UserAttribute::UserName,
+        "externalid" => UserAttribute::ExternalId,
+        "displayname" => UserAttribute::DisplayName,
+        "active" => UserAttribute::Active,
+        _ => {
+            return Err(ScimError::bad_request(
+                "invalidFilter",
+                format!("Unsupported filter attribute: {}", attribute),
+            ))
+        }
+    };
+
+    let value = parse_value(value.trim()).ok_or_else(|| invalid(filter))?;
+
+    match (attribute, &value) {
+        (UserAttribute::Active, FilterValue::Bool(_)) => {}
+        (UserAttribute::Active, _) | (_, FilterValue::Bool(_)) => return Err(invalid(filter)),
+        _ => {}
+    }
+
+    Ok(EqFilter { attribute, value })
+}
+
+fn parse_value(value: &str) -> Option<FilterValue> {
+    match value {
+        "true" => Some(FilterValue::Bool(true)),
+        "false" => Some(FilterValue::Bool(false)),
+        _ => {
+            let inner = value.strip_prefix('"')?.strip_suffix('"')?;
+            // JSON string rules, so escaped quotes and backslashes work
+            serde_json::from_str::<String>(&format!("\"{}\"", inner))
+                .ok()
+                .map(FilterValue::String)
+        }
+    }
+}
+
+fn invalid(filter: &str) -> ScimError {
+    ScimError::bad_request("invalidFilter", format!("Invalid filter: {}", filter))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_user_name_filter() {
+        let filter = parse_user_filter(r#"userName eq "alice@example.com""#).unwrap();
+        assert_eq!(filter.attribute, UserAttribute::UserName);
+        assert_eq!(
+            filter.value,
+            FilterValue::String("alice@example.com".to_string())
+        );
+    }
+
+    #[test]
+    fn test_attribute_and_operator_case_insensitive() {
+        let filter = parse_user_filter(r#"EXTERNALID Eq "abc""#).unwrap();
+        assert_eq!(filter.attribute, UserAttribute::ExternalId);
+    }
+
+    #[test]
+    fn test_escaped_quotes_in_value() {
+        let filter = parse_user_filter(r#"displayName eq "Alice \"Al\" Smith""#).unwrap();
+        assert_eq!(
+            filter.value,
+            FilterValue::String("Alice \"Al\" Smith".to_string())
+        );
+    }
+
+    #[test]
+    fn test_active_filter() {
+        let filter = parse_user_filter("active eq false").unwrap();
+        assert_eq!(filter.value, FilterValue::Bool(false));
+        assert!(parse_user_filter(r#"active eq "false""#).is_err());
+    }
+
+    #[test]
+    fn test_unsupported_filters_rejected() {
+        for filter in [
+            r#"userName co "alice""#,
+            r#"title eq "x""#,
+            r#"userName eq alice"#,
+            "userName",
+        ] {
+            let err = parse_user_filter(filter).unwrap_err();
+            assert_eq!(err.scim_type, Some("invalidFilter"), "{}", filter);
+        }
+    }
+}
@@ -0,0 +1,220 @@
+// SCIM 2.0 provisioning (RFC 7643 / RFC 7644)
+//
+// Lets an enterprise IdP create, update and deprovision user identities. SCIM
+// clients authenticate with an admin bearer token and operate on the token's
+// tenant.
+
+pub mod filter;
+pub mod users;
+
+use crate::errors::AppError;
+use axum::{
+    http::{header, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+
+pub const USER_SCHEMA: &str = "urn:ietf:params:scim:schemas:core:2.0:User";
+pub const LIST_RESPONSE_SCHEMA: &str = "urn:ietf:params:scim:api:messages:2.0:ListResponse";
+pub const PATCH_OP_SCHEMA: &str = "urn:ietf:params:scim:api:messages:2.0:PatchOp";
+pub const ERROR_SCHEMA: &str = "urn:ietf:params:scim:api:messages:2.0:Error";
+
+/// Media type for SCIM responses
+pub const SCIM_CONTENT_TYPE: &str = "application/scim+json";
+
+/// Largest page a list request may ask for
+pub const MAX_PAGE_SIZE: i64 = 100;
+
+/// JSON response sent with the SCIM media type
+pub struct ScimJson<T>(pub T);
+
+impl<T: Serialize> IntoResponse for ScimJson<T> {
+    fn into_response(self) -> Response {
+        let mut response = Json(self.0).into_response();
+        response.headers_mut().insert(
+            header::CONTENT_TYPE,
+            HeaderValue::from_static(SCIM_CONTENT_TYPE),
+        );
+        response
+    }
+}
+
+/// Error in the SCIM error envelope (RFC 7644 section 3.12)
+#[derive(Debug)]
+pub struct ScimError {
+    pub status: StatusCode,
+    pub scim_type: Option<&'static str>,
+    pub detail: String,
+}
+
+impl ScimError {
+    pub fn bad_request(scim_type: &'static str, detail: impl Into<String>) -> Self {
+        Self {
+            status: StatusCode::BAD_REQUEST,
+            scim_type: Some(scim_type),
+            detail: detail.into(),
+        }
+    }
+
+    pub fn not_found() -> Self {
+        Self {
+            status: StatusCode::NOT_FOUND,
+            scim_type: None,
+            detail: "Resource not found".to_string(),
+        }
+    }
+}
+
+impl From<AppError> for ScimError {
+    fn from(err: AppError) -> Self {
+        let detail = err.to_string();
+        // Reuse the standard mapping (and its logging of internal errors)
+        let status = err.into_response().status();
+
+        let scim_type = match status {
+            StatusCode::CONFLICT => Some("uniqueness"),
+            StatusCode::BAD_REQUEST => Some("invalidValue"),
+            _ => None,
+        };
+
+        Self {
+            status,
+            scim_type,
+            detail: if status.is_server_error() {
+                "Internal server error".to_string()
+            } else {
+                detail
+            },
+        }
+    }
+}
+
+impl IntoResponse for ScimError {
+    fn into_response(self) -> Response {
+        let mut body = serde_json::json!({
+            "schemas": [ERROR_SCHEMA],
+            "status": self.status.as_u16().to_string(),
+            "detail": self.detail,
+        });
+        if let Some(scim_type) = self.scim_type {
+            body["scimType"] = serde_json::Value::String(scim_type.to_string());
+        }
+
+        (self.status, ScimJson(body)).into_response()
+    }
+}
+
+pub type ScimResult<T> = std::result::Result<T, ScimError>;
+
+/// Resource metadata (RFC 7643 section 3.1)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Meta {
+    pub resource_type: String,
+    pub created: chrono::DateTime<chrono::Utc>,
+    pub last_modified: chrono::DateTime<chrono::Utc>,
+    pub location: String,
+}
+
+/// Query parameters for list requests
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListQuery {
+    pub filter: Option<String>,
+    /// 1-based index of the first result
+    pub start_index: Option<i64>,
+    pub count: Option<i64>,
+}
+
+impl ListQuery {
+    /// 1-based start index, clamped to at least 1
+    pub fn start_index(&self) -> i64 {
+        self.start_index.unwrap_or(1).max(1)
+    }
+
+    /// Page size, clamped to 0..=MAX_PAGE_SIZE
+    pub fn count(&self) -> i64 {
+        self.count.unwrap_or(MAX_PAGE_SIZE).clamp(0, MAX_PAGE_SIZE)
+    }
+}
+
+/// Paged list envelope (RFC 7644 section 3.4.2)
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListResponse<T> {
+    pub schemas: Vec<String>,
+    pub total_results: i64,
+    pub start_index: i64,
+    pub items_per_page: i64,
+    #[serde(rename = "Resources")]
+    pub resources: Vec<T>,
+}
+
+impl<T> ListResponse<T> {
+    pub fn new(resources: Vec<T>, total_results: i64, start_index: i64) -> Self {
+        Self {
+            schemas: vec![LIST_RESPONSE_SCHEMA.to_string()],
+            total_results,
+            start_index,
+            items_per_page: resources.len() as i64,
+            resources,
+        }
+    }
+}
+
+/// PATCH request body (RFC 7644 section 3.5.2)
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PatchRequest {
+    pub schemas: Vec<String>,
+    #[serde(rename = "Operations")]
+    pub operations: Vec<PatchOperation>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PatchOperation {
+    /// "add", "replace" or "remove" (matched case-insensitively, as some IdPs
+    /// send "Replace")
+    pub op: String,
+    pub path: Option<String>,
+    pub value: Option<serde_json::Value>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_app_error_maps_to_scim_error() {
+        let err = ScimError::from(AppError::IdentityAlreadyExists);
+        assert_eq!(err.status, StatusCode::CONFLICT);
+        assert_eq!(err.scim_type, Some("uniqueness"));
+
+        let err = ScimError::from(AppError::Internal("secret detail".to_string()));
+        assert_eq!(err.status, StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(err.detail, "Internal server error");
+    }
+
+    #[test]
+    fn test_list_query_clamps() {
+        let query = ListQuery {
+            filter: None,
+            start_index: Some(0),
+            count: Some(1000),
+        };
+        assert_eq!(query.start_index(), 1);
+        assert_eq!(query.count(), MAX_PAGE_SIZE);
+    }
+
+    #[test]
+    fn test_list_response_envelope() {
+        let response = ListResponse::new(vec![1, 2], 5, 1);
+        let json = serde_json::to_value(&response).unwrap();
+
+        assert_eq!(json["schemas"][0], LIST_RESPONSE_SCHEMA);
+        assert_eq!(json["totalResults"], 5);
+        assert_eq!(json["itemsPerPage"], 2);
+        assert_eq!(json["Resources"], serde_json::json!([1, 2]));
+    }
+}
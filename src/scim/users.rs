@@ -0,0 +1,750 @@
+// SCIM /Users resource
+//
+// Mapping onto identities (always `identity_type = 'user'`):
+// - `userName` is the identity's email, which is also its login
+// - `displayName` (or `name.formatted`) is the identity's name
+// - `externalId` is kept in `metadata.scim_external_id`
+// - `active` is `status = 'active'`; inactive users are `suspended`
+// - DELETE soft-deletes (`status = 'deleted'`), after which the user is gone
+//   as far as SCIM is concerned
+
+use crate::api::routes::AppState;
+use crate::auth::middleware::require_admin;
+use crate::db::{self, schema::Identity};
+use crate::domain::audit::{AuditEvent, AuditEventType};
+use crate::errors::AppError;
+use crate::scim::{
+    filter::{parse_user_filter, FilterValue, UserAttribute},
+    ListQuery, ListResponse, Meta, PatchOperation, PatchRequest, ScimError, ScimJson, ScimResult,
+    PATCH_OP_SCHEMA, USER_SCHEMA,
+};
+use axum::{
+    extract::{Path, Query, State},
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use uuid::Uuid;
+
+/// Key in `identities.metadata` holding the IdP's external ID
+const EXTERNAL_ID_KEY: &str = "scim_external_id";
+
+// ============================================================================
+// Resource representation
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScimUser {
+    #[serde(default)]
+    pub schemas: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub external_id: Option<String>,
+    pub user_name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<ScimName>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub display_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub emails: Vec<ScimEmail>,
+    #[serde(default = "default_active")]
+    pub active: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub meta: Option<Meta>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScimName {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub formatted: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub given_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub family_name: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScimEmail {
+    pub value: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub primary: Option<bool>,
+    #[serde(rename = "type", default, skip_serializing_if = "Option::is_none")]
+    pub kind: Option<String>,
+}
+
+fn default_active() -> bool {
+    true
+}
+
+impl ScimUser {
+    /// Render an identity as a SCIM user
+    pub fn from_identity(identity: &Identity) -> Self {
+        let email = identity.email.clone().unwrap_or_default();
+
+        Self {
+            schemas: vec![USER_SCHEMA.to_string()],
+            id: Some(identity.id.to_string()),
+            external_id: external_id_of(&identity.metadata),
+            user_name: email.clone(),
+            name: Some(ScimName {
+                formatted: Some(identity.name.clone()),
+                given_name: None,
+                family_name: None,
+            }),
+            display_name: Some(identity.name.clone()),
+            emails: vec![ScimEmail {
+                value: email,
+                primary: Some(true),
+                kind: Some("work".to_string()),
+            }],
+            active: identity.status == "active",
+            meta: Some(Meta {
+                resource_type: "User".to_string(),
+                created: identity.created_at,
+                last_modified: identity.updated_at,
+                location: user_location(identity.id),
+            }),
+        }
+    }
+
+    /// The identity name for this user: `displayName`, then `name.formatted`,
+    /// then given + family name, falling back to `userName`
+    pub fn identity_name(&self) -> String {
+        let name = self.name.as_ref();
+        let joined = name
+            .map(|n| {
+                [n.given_name.as_deref(), n.family_name.as_deref()]
+                    .into_iter()
+                    .flatten()
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            })
+            .filter(|n| !n.is_empty());
+
+        self.display_name
+            .clone()
+            .or_else(|| name.and_then(|n| n.formatted.clone()))
+            .or(joined)
+            .filter(|n| !n.trim().is_empty())
+            .unwrap_or_else(|| self.user_name.clone())
+    }
+
+    /// Validate a create payload
+    pub fn validate_new(&self) -> ScimResult<()> {
+        if !self.schemas.is_empty() && !self.schemas.iter().any(|s| s == USER_SCHEMA) {
+            return Err(ScimError::bad_request(
+                "invalidSyntax",
+                format!("Missing schema {}", USER_SCHEMA),
+            ));
+        }
+        validate_user_name(&self.user_name)
+    }
+}
+
+fn validate_user_name(user_name: &str) -> ScimResult<()> {
+    // userName doubles as the identity's email
+    if user_name.trim().is_empty() || !user_name.contains('@') {
+        return Err(ScimError::bad_request(
+            "invalidValue",
+            "userName must be the user's email address",
+        ));
+    }
+    Ok(())
+}
+
+fn status_for(active: bool) -> &'static str {
+    if active {
+        "active"
+    } else {
+        "suspended"
+    }
+}
+
+fn external_id_of(metadata: &Value) -> Option<String> {
+    metadata
+        .get(EXTERNAL_ID_KEY)
+        .and_then(Value::as_str)
+        .map(str::to_string)
+}
+
+fn user_location(id: Uuid) -> String {
+    format!("/scim/v2/Users/{}", id)
+}
+
+// ============================================================================
+// PATCH
+// ============================================================================
+
+/// The identity attributes SCIM can change
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UserAttributes {
+    pub user_name: String,
+    pub display_name: String,
+    pub external_id: Option<String>,
+    pub active: bool,
+}
+
+impl UserAttributes {
+    pub fn from_identity(identity: &Identity) -> Self {
+        Self {
+            user_name: identity.email.clone().unwrap_or_default(),
+            display_name: identity.name.clone(),
+            external_id: external_id_of(&identity.metadata),
+            active: identity.status == "active",
+        }
+    }
+
+    /// Apply one PATCH operation
+    ///
+    /// Attributes that do not map onto identities are ignored rather than
+    /// rejected, since IdPs routinely send the whole user (title, phone, ...).
+    pub fn apply(&mut self, operation: &PatchOperation) -> ScimResult<()> {
+        let op = operation.op.to_ascii_lowercase();
+
+        match (op.as_str(), operation.path.as_deref()) {
+            ("add" | "replace", None) => {
+                let Some(Value::Object(values)) = &operation.value else {
+                    return Err(ScimError::bad_request(
+                        "invalidValue",
+                        "Operations without a path need an object value",
+                    ));
+                };
+                for (attribute, value) in values {
+                    self.set(attribute, value)?;
+                }
+                Ok(())
+            }
+            ("add" | "replace", Some(path)) => {
+                let value = operation.value.as_ref().ok_or_else(|| {
+                    ScimError::bad_request("invalidValue", format!("Missing value for {}", path))
+                })?;
+                self.set(path, value)
+            }
+            ("remove", None) => Err(ScimError::bad_request("noTarget", "Remove requires a path")),
+            ("remove", Some(path)) => match path.to_ascii_lowercase().as_str() {
+                "externalid" => {
+                    self.external_id = None;
+                    Ok(())
+                }
+                "username" | "displayname" | "name.formatted" | "active" => Err(
+                    ScimError::bad_request("mutability", format!("{} cannot be removed", path)),
+                ),
+                _ => Ok(()),
+            },
+            _ => Err(ScimError::bad_request(
+                "invalidSyntax",
+                format!("Unsupported patch op: {}", operation.op),
+            )),
+        }
+    }
+
+    fn set(&mut self, attribute: &str, value: &Value) -> ScimResult<()> {
+        match attribute.to_ascii_lowercase().as_str() {
+            "username" => {
+                let user_name = expect_string(attribute, value)?;
+                validate_user_name(&user_name)?;
+                self.user_name = user_name;
+            }
+            "displayname" | "name.formatted" => {
+                self.display_name = expect_string(attribute, value)?;
+            }
+            "name" => {
+                if let Some(formatted) = value.get("formatted") {
+                    self.display_name = expect_string(attribute, formatted)?;
+                }
+            }
+            "externalid" => {
+                self.external_id = match value {
+                    Value::Null => None,
+                    _ => Some(expect_string(attribute, value)?),
+                };
+            }
+            "active" => {
+                self.active = match value {
+                    Value::Bool(active) => *active,
+                    // Some IdPs send booleans as "True"/"False"
+                    Value::String(s) if s.eq_ignore_ascii_case("true") => true,
+                    Value::String(s) if s.eq_ignore_ascii_case("false") => false,
+                    _ => {
+                        return Err(ScimError::bad_request(
+                            "invalidValue",
+                            "active must be a boolean",
+                        ))
+                    }
+                };
+            }
+            _ => {
+                tracing::debug!(attribute = %attribute, "Ignoring unmapped SCIM attribute");
+            }
+        }
+        Ok(())
+    }
+}
+
+fn expect_string(attribute: &str, value: &Value) -> ScimResult<String> {
+    value.as_str().map(str::to_string).ok_or_else(|| {
+        ScimError::bad_request("invalidValue", format!("{} must be a string", attribute))
+    })
+}
+
+// ============================================================================
+// Handlers
+// ============================================================================
+
+/// POST /scim/v2/Users
+pub async fn create_user(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(user): Json<ScimUser>,
+) -> ScimResult<Response> {
+    let claims = require_admin(&state, &headers).await?;
+    let tenant_id = claims.tenant_id_uuid()?;
+    user.validate_new()?;
+
+    if db::identities::exists_by_email(&state.db_pool, &user.user_name).await? {
+        return Err(AppError::IdentityAlreadyExists.into());
+    }
+
+    let metadata = match &user.external_id {
+        Some(external_id) => serde_json::json!({ EXTERNAL_ID_KEY: external_id }),
+        None => serde_json::json!({}),
+    };
+
+    let identity = sqlx::query_as!(
+        Identity,
+        r#"
+        INSERT INTO identities (tenant_id, identity_type, name, email, status, metadata, created_by)
+        VALUES ($1, 'user', $2, $3, $4, $5, $6)
+        RETURNING
+            id, tenant_id, identity_type, name, email, status,
+            parent_identity_id, task_id, task_scope, expires_at,
+            password_hash, api_key_hash, metadata, created_by, created_at,
+            updated_at, last_login_at
+        "#,
+        tenant_id,
+        user.identity_name(),
+        user.user_name,
+        status_for(user.active),
+        metadata,
+        claims.identity_id()?
+    )
+    .fetch_one(&state.db_pool)
+    .await
+    .map_err(AppError::from)?;
+
+    log_event(
+        &state,
+        &claims,
+        AuditEventType::IdentityCreated,
+        "scim_create_user",
+        identity.id,
+    )
+    .await?;
+
+    let location = HeaderValue::from_str(&user_location(identity.id))
+        .map_err(|e| AppError::Internal(format!("Invalid location header: {}", e)))?;
+    let mut response = (
+        StatusCode::CREATED,
+        ScimJson(ScimUser::from_identity(&identity)),
+    )
+        .into_response();
+    response.headers_mut().insert(header::LOCATION, location);
+    Ok(response)
+}
+
+/// GET /scim/v2/Users/:id
+pub async fn get_user(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<Uuid>,
+) -> ScimResult<ScimJson<ScimUser>> {
+    let claims = require_admin(&state, &headers).await?;
+    let identity = find_user(&state, claims.tenant_id_uuid()?, id).await?;
+
+    Ok(ScimJson(ScimUser::from_identity(&identity)))
+}
+
+/// GET /scim/v2/Users?filter=...&startIndex=...&count=...
+pub async fn list_users(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<ListQuery>,
+) -> ScimResult<ScimJson<ListResponse<ScimUser>>> {
+    let claims = require_admin(&state, &headers).await?;
+    let tenant_id = claims.tenant_id_uuid()?;
+
+    let filter = query.filter.as_deref().map(parse_user_filter).transpose()?;
+    let string_value = |attribute: UserAttribute| match &filter {
+        Some(f) if f.attribute == attribute => match &f.value {
+            FilterValue::String(s) => Some(s.clone()),
+            FilterValue::Bool(_) => None,
+        },
+        _ => None,
+    };
+    let user_name = string_value(UserAttribute::UserName);
+    let external_id = string_value(UserAttribute::ExternalId);
+    let display_name = string_value(UserAttribute::DisplayName);
+    let status = match &filter {
+        Some(f) if f.attribute == UserAttribute::Active => match f.value {
+            FilterValue::Bool(active) => Some(status_for(active).to_string()),
+            FilterValue::String(_) => None,
+        },
+        _ => None,
+    };
+
+    let start_index = query.start_index();
+    let count = query.count();
+
+    let total = sqlx::query_scalar!(
+        r#"
+        SELECT COUNT(*) as "count!"
+        FROM identities
+        WHERE tenant_id = $1 AND identity_type = 'user' AND status <> 'deleted'
+          AND ($2::text IS NULL OR email = $2)
+          AND ($3::text IS NULL OR metadata->>'scim_external_id' = $3)
+          AND ($4::text IS NULL OR name = $4)
+          AND ($5::text IS NULL OR status = $5)
+        "#,
+        tenant_id,
+        user_name,
+        external_id,
+        display_name,
+        status
+    )
+    .fetch_one(&state.db_pool)
+    .await
+    .map_err(AppError::from)?;
+
+    let identities = sqlx::query_as!(
+        Identity,
+        r#"
+        SELECT
+            id, tenant_id, identity_type, name, email, status,
+            parent_identity_id, task_id, task_scope, expires_at,
+            password_hash, api_key_hash, metadata, created_by, created_at,
+            updated_at, last_login_at
+        FROM identities
+        WHERE tenant_id = $1 AND identity_type = 'user' AND status <> 'deleted'
+          AND ($2::text IS NULL OR email = $2)
+          AND ($3::text IS NULL OR metadata->>'scim_external_id' = $3)
+          AND ($4::text IS NULL OR name = $4)
+          AND ($5::text IS NULL OR status = $5)
+        ORDER BY created_at ASC, id ASC
+        LIMIT $6 OFFSET $7
+        "#,
+        tenant_id,
+        user_name,
+        external_id,
+        display_name,
+        status,
+        count,
+        start_index - 1
+    )
+    .fetch_all(&state.db_pool)
+    .await
+    .map_err(AppError::from)?;
+
+    let resources = identities.iter().map(ScimUser::from_identity).collect();
+    Ok(ScimJson(ListResponse::new(resources, total, start_index)))
+}
+
+/// PATCH /scim/v2/Users/:id
+pub async fn patch_user(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<Uuid>,
+    Json(patch): Json<PatchRequest>,
+) -> ScimResult<ScimJson<ScimUser>> {
+    let claims = require_admin(&state, &headers).await?;
+    let tenant_id = claims.tenant_id_uuid()?;
+
+    if !patch.schemas.iter().any(|s| s == PATCH_OP_SCHEMA) {
+        return Err(ScimError::bad_request(
+            "invalidSyntax",
+            format!("Missing schema {}", PATCH_OP_SCHEMA),
+        ));
+    }
+
+    let identity = find_user(&state, tenant_id, id).await?;
+    let current = UserAttributes::from_identity(&identity);
+    let mut updated = current.clone();
+    for operation in &patch.operations {
+        updated.apply(operation)?;
+    }
+
+    if updated == current {
+        return Ok(ScimJson(ScimUser::from_identity(&identity)));
+    }
+
+    if updated.user_name != current.user_name
+        && db::identities::exists_by_email(&state.db_pool, &updated.user_name).await?
+    {
+        return Err(AppError::IdentityAlreadyExists.into());
+    }
+
+    let identity = sqlx::query_as!(
+        Identity,
+        r#"
+        UPDATE identities
+        SET email = $3,
+            name = $4,
+            status = $5,
+            metadata = CASE
+                WHEN $6::text IS NULL THEN COALESCE(metadata, '{}'::jsonb) - 'scim_external_id'
+                ELSE COALESCE(metadata, '{}'::jsonb) || jsonb_build_object('scim_external_id', $6::text)
+            END
+        WHERE id = $1 AND tenant_id = $2
+        RETURNING
+            id, tenant_id, identity_type, name, email, status,
+            parent_identity_id, task_id, task_scope, expires_at,
+            password_hash, api_key_hash, metadata, created_by, created_at,
+            updated_at, last_login_at
+        "#,
+        id,
+        tenant_id,
+        updated.user_name,
+        updated.display_name,
+        status_for(updated.active),
+        updated.external_id
+    )
+    .fetch_one(&state.db_pool)
+    .await
+    .map_err(AppError::from)?;
+
+    log_event(
+        &state,
+        &claims,
+        AuditEventType::IdentityUpdated,
+        "scim_patch_user",
+        id,
+    )
+    .await?;
+
+    Ok(ScimJson(ScimUser::from_identity(&identity)))
+}
+
+/// DELETE /scim/v2/Users/:id
+pub async fn delete_user(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<Uuid>,
+) -> ScimResult<StatusCode> {
+    let claims = require_admin(&state, &headers).await?;
+    let tenant_id = claims.tenant_id_uuid()?;
+
+    let result = sqlx::query!(
+        r#"
+        UPDATE identities
+        SET status = 'deleted'
+        WHERE id = $1 AND tenant_id = $2 AND identity_type = 'user' AND status <> 'deleted'
+        "#,
+        id,
+        tenant_id
+    )
+    .execute(&state.db_pool)
+    .await
+    .map_err(AppError::from)?;
+
+    if result.rows_affected() == 0 {
+        return Err(ScimError::not_found());
+    }
+
+    log_event(
+        &state,
+        &claims,
+        AuditEventType::IdentityDeleted,
+        "scim_delete_user",
+        id,
+    )
+    .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+async fn find_user(state: &AppState, tenant_id: Uuid, id: Uuid) -> ScimResult<Identity> {
+    sqlx::query_as!(
+        Identity,
+        r#"
+        SELECT
+            id, tenant_id, identity_type, name, email, status,
+            parent_identity_id, task_id, task_scope, expires_at,
+            password_hash, api_key_hash, metadata, created_by, created_at,
+            updated_at, last_login_at
+        FROM identities
+        WHERE id = $1 AND tenant_id = $2 AND identity_type = 'user' AND status <> 'deleted'
+        "#,
+        id,
+        tenant_id
+    )
+    .fetch_optional(&state.db_pool)
+    .await
+    .map_err(AppError::from)?
+    .ok_or_else(ScimError::not_found)
+}
+
+async fn log_event(
+    state: &AppState,
+    claims: &crate::auth::jwt::JwtClaims,
+    event_type: AuditEventType,
+    action: &str,
+    identity_id: Uuid,
+) -> ScimResult<()> {
+    let event = AuditEvent::new(
+        claims.tenant_id_uuid()?,
+        event_type,
+        action.to_string(),
+        "identity".to_string(),
+    )
+    .with_actor(claims.identity_id()?)
+    .with_resource_id(identity_id.to_string());
+    state.audit_logger.log(event).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn identity(status: &str) -> Identity {
+        Identity {
+            id: Uuid::new_v4(),
+            tenant_id: Uuid::new_v4(),
+            identity_type: "user".to_string(),
+            name: "Alice Smith".to_string(),
+            email: Some("alice@example.com".to_string()),
+            status: status.to_string(),
+            parent_identity_id: None,
+            task_id: None,
+            task_scope: None,
+            expires_at: None,
+            password_hash: None,
+            api_key_hash: None,
+            metadata: serde_json::json!({ "scim_external_id": "00u123" }),
+            created_by: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            last_login_at: None,
+        }
+    }
+
+    fn patch(json: &str) -> PatchRequest {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn test_create_payload_maps_onto_identity() {
+        let user: ScimUser = serde_json::from_str(
+            r#"{
+                "schemas": ["urn:ietf:params:scim:schemas:core:2.0:User"],
+                "externalId": "00u123",
+                "userName": "alice@example.com",
+                "name": {"givenName": "Alice", "familyName": "Smith"},
+                "emails": [{"value": "alice@example.com", "primary": true, "type": "work"}],
+                "title": "Engineer"
+            }"#,
+        )
+        .unwrap();
+
+        assert!(user.validate_new().is_ok());
+        assert_eq!(user.identity_name(), "Alice Smith");
+        assert_eq!(user.external_id.as_deref(), Some("00u123"));
+        assert!(user.active);
+        assert_eq!(status_for(user.active), "active");
+    }
+
+    #[test]
+    fn test_create_payload_requires_email_user_name() {
+        let user: ScimUser = serde_json::from_str(r#"{"userName": "alice"}"#).unwrap();
+        let err = user.validate_new().unwrap_err();
+        assert_eq!(err.scim_type, Some("invalidValue"));
+    }
+
+    #[test]
+    fn test_identity_renders_scim_envelope() {
+        let identity = identity("suspended");
+        let json = serde_json::to_value(ScimUser::from_identity(&identity)).unwrap();
+
+        assert_eq!(json["schemas"][0], USER_SCHEMA);
+        assert_eq!(json["userName"], "alice@example.com");
+        assert_eq!(json["externalId"], "00u123");
+        assert_eq!(json["active"], false);
+        assert_eq!(json["meta"]["resourceType"], "User");
+        assert_eq!(
+            json["meta"]["location"],
+            format!("/scim/v2/Users/{}", identity.id)
+        );
+    }
+
+    #[test]
+    fn test_patch_deactivates_user() {
+        let mut attributes = UserAttributes::from_identity(&identity("active"));
+        let request = patch(
+            r#"{
+                "schemas": ["urn:ietf:params:scim:api:messages:2.0:PatchOp"],
+                "Operations": [{"op": "replace", "path": "active", "value": false}]
+            }"#,
+        );
+
+        for operation in &request.operations {
+            attributes.apply(operation).unwrap();
+        }
+
+        assert!(!attributes.active);
+        assert_eq!(status_for(attributes.active), "suspended");
+    }
+
+    #[test]
+    fn test_patch_without_path_and_string_boolean() {
+        let mut attributes = UserAttributes::from_identity(&identity("active"));
+        let request = patch(
+            r#"{
+                "schemas": ["urn:ietf:params:scim:api:messages:2.0:PatchOp"],
+                "Operations": [{"op": "Replace", "value": {"active": "False", "displayName": "Alice S."}}]
+            }"#,
+        );
+
+        attributes.apply(&request.operations[0]).unwrap();
+        assert!(!attributes.active);
+        assert_eq!(attributes.display_name, "Alice S.");
+    }
+
+    #[test]
+    fn test_patch_remove() {
+        let mut attributes = UserAttributes::from_identity(&identity("active"));
+
+        let remove = |path: &str| PatchOperation {
+            op: "remove".to_string(),
+            path: Some(path.to_string()),
+            value: None,
+        };
+
+        attributes.apply(&remove("externalId")).unwrap();
+        assert_eq!(attributes.external_id, None);
+
+        let err = attributes.apply(&remove("userName")).unwrap_err();
+        assert_eq!(err.scim_type, Some("mutability"));
+    }
+
+    #[test]
+    fn test_patch_rejects_bad_values() {
+        let mut attributes = UserAttributes::from_identity(&identity("active"));
+        let op = PatchOperation {
+            op: "replace".to_string(),
+            path: Some("active".to_string()),
+            value: Some(serde_json::json!(3)),
+        };
+
+        assert!(attributes.apply(&op).is_err());
+        assert!(attributes.active);
+    }
+}
@@ -0,0 +1,118 @@
+// In-flight request tracking for graceful shutdown
+
+use axum::{extract::Request, middleware::Next, response::Response};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Notify;
+
+/// Counts requests that are currently being handled
+#[derive(Debug, Clone, Default)]
+pub struct InFlightRequests {
+    inner: Arc<Inner>,
+}
+
+#[derive(Debug, Default)]
+struct Inner {
+    count: AtomicUsize,
+    idle: Notify,
+}
+
+impl InFlightRequests {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of requests currently in flight
+    pub fn count(&self) -> usize {
+        self.inner.count.load(Ordering::SeqCst)
+    }
+
+    /// Mark a request as started; it is finished when the guard is dropped
+    pub fn start(&self) -> InFlightGuard {
+        self.inner.count.fetch_add(1, Ordering::SeqCst);
+        InFlightGuard {
+            inner: self.inner.clone(),
+        }
+    }
+
+    /// Wait until no requests are in flight
+    pub async fn wait_idle(&self) {
+        loop {
+            let idle = self.inner.idle.notified();
+            if self.count() == 0 {
+                return;
+            }
+            idle.await;
+        }
+    }
+
+    /// Wait up to `timeout` for in-flight requests to finish
+    pub async fn drain(&self, timeout: Duration) -> DrainReport {
+        let in_flight = self.count();
+        let forced = match tokio::time::timeout(timeout, self.wait_idle()).await {
+            Ok(()) => 0,
+            Err(_) => self.count(),
+        };
+
+        DrainReport {
+            drained: in_flight.saturating_sub(forced),
+            forced,
+        }
+    }
+}
+
+/// Guard that marks a request as finished when dropped (including on cancellation)
+pub struct InFlightGuard {
+    inner: Arc<Inner>,
+}
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        if self.inner.count.fetch_sub(1, Ordering::SeqCst) == 1 {
+            self.inner.idle.notify_waiters();
+        }
+    }
+}
+
+/// Outcome of draining in-flight requests at shutdown
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DrainReport {
+    /// Requests that completed within the drain window
+    pub drained: usize,
+    /// Requests still running when the window closed and were cut off
+    pub forced: usize,
+}
+
+/// Middleware that records each request in the tracker
+pub async fn track_in_flight(tracker: InFlightRequests, request: Request, next: Next) -> Response {
+    let _guard = tracker.start();
+    next.run(request).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_drain_with_no_requests() {
+        let tracker = InFlightRequests::new();
+        let report = tracker.drain(Duration::from_millis(10)).await;
+        assert_eq!(report, DrainReport::default());
+    }
+
+    #[tokio::test]
+    async fn test_drain_counts_finished_and_forced() {
+        let tracker = InFlightRequests::new();
+        let quick = tracker.start();
+        let _stuck = tracker.start();
+
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            drop(quick);
+        });
+
+        let report = tracker.drain(Duration::from_millis(200)).await;
+        assert_eq!(report, DrainReport { drained: 1, forced: 1 });
+    }
+}
@@ -0,0 +1,215 @@
+// HTTP server startup and shutdown
+pub mod drain;
+pub mod tls;
+
+use crate::auth::binding::record_client_ip;
+use crate::config::Config;
+use crate::errors::{AppError, Result};
+use axum::Router;
+use axum_server::{tls_rustls::RustlsConfig, Handle};
+use drain::{DrainReport, InFlightRequests};
+use std::future::Future;
+use std::net::SocketAddr;
+use std::time::Duration;
+
+/// Serve the router until a shutdown signal arrives, then drain in-flight requests
+///
+/// Uses TLS when `security.tls_enabled` is set, otherwise plaintext HTTP.
+pub async fn serve(app: Router, addr: SocketAddr, config: &Config) -> Result<DrainReport> {
+    let security = &config.security;
+    let listener = std::net::TcpListener::bind(addr)
+        .map_err(|e| AppError::Configuration(format!("Failed to bind {}: {}", addr, e)))?;
+    listener
+        .set_nonblocking(true)
+        .map_err(|e| AppError::Configuration(format!("Failed to configure listener: {}", e)))?;
+
+    let rustls_config = if security.tls_enabled {
+        let rustls_config = tls::rustls_config(security).await?;
+        tls::spawn_reload_on_change(
+            rustls_config.clone(),
+            security.tls_cert_path.clone(),
+            security.tls_key_path.clone(),
+        );
+
+        tracing::info!(
+            cert_path = %security.tls_cert_path,
+            "TLS enabled, listening on https://{}",
+            addr
+        );
+        Some(rustls_config)
+    } else {
+        tracing::warn!("TLS disabled, listening on plaintext http://{}", addr);
+        None
+    };
+
+    serve_listener(
+        app,
+        listener,
+        rustls_config,
+        Duration::from_secs(config.server.shutdown_timeout_seconds),
+        shutdown_signal(),
+    )
+    .await
+}
+
+/// Serve on an already bound listener until `shutdown` resolves
+///
+/// On shutdown the server stops accepting connections and gives in-flight
+/// requests up to `drain_timeout` to finish before closing them.
+pub async fn serve_listener<F>(
+    app: Router,
+    listener: std::net::TcpListener,
+    rustls_config: Option<RustlsConfig>,
+    drain_timeout: Duration,
+    shutdown: F,
+) -> Result<DrainReport>
+where
+    F: Future<Output = ()> + Send + 'static,
+{
+    let tracker = InFlightRequests::new();
+    let tracked = tracker.clone();
+    let app = app
+        .layer(axum::middleware::from_fn(record_client_ip))
+        .layer(axum::middleware::from_fn(move |request, next| {
+            drain::track_in_flight(tracked.clone(), request, next)
+        }));
+
+    let handle = Handle::new();
+    let drain_handle = handle.clone();
+    let drain_task = tokio::spawn(async move {
+        shutdown.await;
+
+        tracing::info!(
+            in_flight = tracker.count(),
+            timeout_seconds = drain_timeout.as_secs(),
+            "Shutdown requested, draining in-flight requests"
+        );
+
+        // Stop accepting; the server force-closes whatever remains after the timeout
+        drain_handle.graceful_shutdown(Some(drain_timeout));
+        let report = tracker.drain(drain_timeout).await;
+
+        tracing::info!(
+            drained = report.drained,
+            forced = report.forced,
+            "Connection draining finished"
+        );
+        report
+    });
+
+    let server = match rustls_config {
+        Some(rustls_config) => {
+            axum_server::from_tcp_rustls(listener, rustls_config)
+                .handle(handle)
+                .serve(app.into_make_service_with_connect_info::<SocketAddr>())
+                .await
+        }
+        None => {
+            axum_server::from_tcp(listener)
+                .handle(handle)
+                .serve(app.into_make_service_with_connect_info::<SocketAddr>())
+                .await
+        }
+    };
+
+    if let Err(e) = server {
+        drain_task.abort();
+        return Err(AppError::Internal(format!("Server error: {}", e)));
+    }
+
+    drain_task
+        .await
+        .map_err(|e| AppError::Internal(format!("Drain task failed: {}", e)))
+}
+
+/// Resolve on Ctrl+C or SIGTERM
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            tracing::error!("Failed to listen for Ctrl+C: {}", e);
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut signal) => {
+                signal.recv().await;
+            }
+            Err(e) => {
+                tracing::error!("Failed to listen for SIGTERM: {}", e);
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::routing::get;
+    use tokio::sync::oneshot;
+
+    fn slow_router() -> Router {
+        Router::new()
+            .route(
+                "/fast",
+                get(|| async {
+                    tokio::time::sleep(Duration::from_millis(100)).await;
+                    "fast"
+                }),
+            )
+            .route(
+                "/slow",
+                get(|| async {
+                    tokio::time::sleep(Duration::from_secs(10)).await;
+                    "slow"
+                }),
+            )
+    }
+
+    #[tokio::test]
+    async fn test_drain_finishes_fast_request_and_cuts_off_slow_one() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        listener.set_nonblocking(true).unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let (shutdown_tx, shutdown_rx) = oneshot::channel::<()>();
+        let server = tokio::spawn(serve_listener(
+            slow_router(),
+            listener,
+            None,
+            Duration::from_millis(500),
+            async {
+                let _ = shutdown_rx.await;
+            },
+        ));
+
+        let client = reqwest::Client::new();
+        let fast = tokio::spawn(client.get(format!("http://{}/fast", addr)).send());
+        let slow = tokio::spawn(client.get(format!("http://{}/slow", addr)).send());
+
+        // Let both requests reach their handlers before shutting down
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        shutdown_tx.send(()).unwrap();
+
+        let fast_response = fast.await.unwrap().unwrap();
+        assert!(fast_response.status().is_success());
+        assert_eq!(fast_response.text().await.unwrap(), "fast");
+
+        let slow_result = slow.await.unwrap();
+        assert!(slow_result.is_err(), "slow request should be cut off");
+
+        let report = server.await.unwrap().unwrap();
+        assert_eq!(report, DrainReport { drained: 1, forced: 1 });
+    }
+}
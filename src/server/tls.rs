@@ -0,0 +1,103 @@
+// TLS termination
+
+use crate::config::SecurityConfig;
+use crate::errors::{AppError, Result};
+use axum_server::tls_rustls::RustlsConfig;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+use tracing::{error, info};
+
+/// How often the certificate and key files are checked for changes
+const RELOAD_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Build a rustls server configuration from PEM-encoded certificate and key files
+pub async fn load_rustls_config(cert_path: &str, key_path: &str) -> Result<RustlsConfig> {
+    RustlsConfig::from_pem_file(cert_path, key_path)
+        .await
+        .map_err(|e| {
+            AppError::Configuration(format!(
+                "Failed to load TLS certificate '{}' and key '{}': {}",
+                cert_path, key_path, e
+            ))
+        })
+}
+
+/// Build the rustls configuration described by the security settings
+pub async fn rustls_config(security: &SecurityConfig) -> Result<RustlsConfig> {
+    load_rustls_config(&security.tls_cert_path, &security.tls_key_path).await
+}
+
+/// Reload the certificate and key whenever either file changes on disk
+///
+/// Existing connections keep their session; new handshakes use the reloaded
+/// certificate. A failed reload leaves the current certificate in place.
+pub fn spawn_reload_on_change(config: RustlsConfig, cert_path: String, key_path: String) {
+    tokio::spawn(async move {
+        let mut last_modified = latest_modification(&cert_path, &key_path);
+        let mut interval = tokio::time::interval(RELOAD_CHECK_INTERVAL);
+
+        loop {
+            interval.tick().await;
+
+            let modified = latest_modification(&cert_path, &key_path);
+            if modified.is_none() || modified == last_modified {
+                continue;
+            }
+
+            match config.reload_from_pem_file(&cert_path, &key_path).await {
+                Ok(()) => {
+                    info!("Reloaded TLS certificate from {}", cert_path);
+                    last_modified = modified;
+                }
+                Err(e) => error!("Failed to reload TLS certificate: {}", e),
+            }
+        }
+    });
+}
+
+fn latest_modification(cert_path: &str, key_path: &str) -> Option<SystemTime> {
+    let modified = |path: &str| std::fs::metadata(PathBuf::from(path)).and_then(|m| m.modified()).ok();
+    modified(cert_path).max(modified(key_path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::NamedTempFile;
+
+    fn write_pem(contents: &str) -> NamedTempFile {
+        let mut file = NamedTempFile::new().unwrap();
+        file.write_all(contents.as_bytes()).unwrap();
+        file
+    }
+
+    #[tokio::test]
+    async fn test_load_rustls_config_from_valid_pem() {
+        let cert = rcgen::generate_simple_self_signed(vec!["localhost".to_string()]).unwrap();
+        let cert_file = write_pem(&cert.serialize_pem().unwrap());
+        let key_file = write_pem(&cert.serialize_private_key_pem());
+
+        let result = load_rustls_config(
+            cert_file.path().to_str().unwrap(),
+            key_file.path().to_str().unwrap(),
+        )
+        .await;
+
+        assert!(result.is_ok(), "{:?}", result.err());
+    }
+
+    #[tokio::test]
+    async fn test_load_rustls_config_missing_file_errors() {
+        let cert = rcgen::generate_simple_self_signed(vec!["localhost".to_string()]).unwrap();
+        let key_file = write_pem(&cert.serialize_private_key_pem());
+
+        let result = load_rustls_config(
+            "/nonexistent/agent-iam/cert.pem",
+            key_file.path().to_str().unwrap(),
+        )
+        .await;
+
+        assert!(matches!(result, Err(AppError::Configuration(_))));
+    }
+}
@@ -0,0 +1,179 @@
+// Account notifications for an external mail service
+//
+// Password reset tokens are posted (HMAC-signed, like audit webhooks) to a
+// single configured endpoint that emails them to the user. The payloads carry
+// live tokens, so this endpoint is configured separately from the audit event
+// webhooks and nothing here is written to the audit log.
+
+use crate::config::{AccountWebhookConfig, WebhookConfig};
+use crate::errors::{AppError, Result};
+use crate::webhooks::payload::{
+    sign_payload, DELIVERY_ID_HEADER, SIGNATURE_HEADER, TIMESTAMP_HEADER,
+};
+use serde::Serialize;
+use std::time::Duration;
+use tracing::{error, warn};
+use uuid::Uuid;
+
+/// What an account notification asks the mail service to send
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AccountNotificationKind {
+    PasswordReset,
+}
+
+/// JSON body posted to the account webhook
+#[derive(Debug, Clone, Serialize)]
+pub struct AccountNotification {
+    /// Delivery ID, useful for deduplication on the receiving side
+    pub id: Uuid,
+    pub kind: AccountNotificationKind,
+    pub tenant_id: Uuid,
+    pub identity_id: Uuid,
+    pub email: String,
+    pub token: String,
+    /// Seconds until the token expires
+    pub expires_in: u64,
+}
+
+/// Sends account notifications to the configured mail service
+pub struct AccountNotifier {
+    client: reqwest::Client,
+    endpoint: Option<AccountWebhookConfig>,
+}
+
+impl AccountNotifier {
+    pub fn new(endpoint: Option<AccountWebhookConfig>, timeout: Duration) -> Result<Self> {
+        let client = reqwest::Client::builder()
+            .timeout(timeout)
+            .build()
+            .map_err(|e| {
+                AppError::Configuration(format!("Failed to build webhook client: {}", e))
+            })?;
+
+        Ok(Self { client, endpoint })
+    }
+
+    pub fn from_config(config: &WebhookConfig) -> Result<Self> {
+        if config.account.is_none() {
+            warn!("Account webhook not configured; password reset emails cannot be sent");
+        }
+
+        Self::new(
+            config.account.clone(),
+            Duration::from_secs(config.request_timeout_seconds),
+        )
+    }
+
+    /// Send a notification in the background
+    ///
+    /// Not awaited so that response times do not reveal whether an account
+    /// exists.
+    pub fn notify(&self, notification: AccountNotification) {
+        let Some(endpoint) = self.endpoint.clone() else {
+            warn!(
+                "No account webhook configured; dropping notification for identity {}",
+                notification.identity_id
+            );
+            return;
+        };
+        let client = self.client.clone();
+
+        tokio::spawn(async move {
+            if let Err(e) = deliver(&client, &endpoint, &notification).await {
+                error!(
+                    "Account notification {} to {} failed: {}",
+                    notification.id, endpoint.url, e
+                );
+            }
+        });
+    }
+}
+
+/// Post one notification; not retried, since the user can simply ask again
+async fn deliver(
+    client: &reqwest::Client,
+    endpoint: &AccountWebhookConfig,
+    notification: &AccountNotification,
+) -> Result<()> {
+    let body = serde_json::to_vec(notification).map_err(|e| {
+        AppError::Internal(format!("Failed to serialize account notification: {}", e))
+    })?;
+    let timestamp = chrono::Utc::now().timestamp();
+    let signature = sign_payload(&endpoint.secret, timestamp, &body)?;
+
+    client
+        .post(&endpoint.url)
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .header(SIGNATURE_HEADER, signature)
+        .header(TIMESTAMP_HEADER, timestamp.to_string())
+        .header(DELIVERY_ID_HEADER, notification.id.to_string())
+        .body(body)
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| AppError::Internal(format!("Account webhook request failed: {}", e)))?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::webhooks::payload::verify_payload_signature;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    const SECRET: &str = "test-account-secret";
+
+    #[tokio::test]
+    async fn test_notification_is_signed() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/mail"))
+            .respond_with(ResponseTemplate::new(202))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let endpoint = AccountWebhookConfig {
+            url: format!("{}/mail", server.uri()),
+            secret: SECRET.to_string(),
+        };
+        let notification = AccountNotification {
+            id: Uuid::new_v4(),
+            kind: AccountNotificationKind::PasswordReset,
+            tenant_id: Uuid::new_v4(),
+            identity_id: Uuid::new_v4(),
+            email: "alice@example.com".to_string(),
+            token: "reset-token".to_string(),
+            expires_in: 3600,
+        };
+
+        deliver(&reqwest::Client::new(), &endpoint, &notification)
+            .await
+            .unwrap();
+
+        let requests = server.received_requests().await.unwrap();
+        let request = &requests[0];
+        let body: serde_json::Value = serde_json::from_slice(&request.body).unwrap();
+        assert_eq!(body["kind"], "password_reset");
+        assert_eq!(body["token"], "reset-token");
+
+        let header = |name: &str| {
+            request
+                .headers
+                .get(&name.into())
+                .map(|values| values.last().as_str().to_string())
+                .unwrap_or_default()
+        };
+        let timestamp: i64 = header(TIMESTAMP_HEADER).parse().unwrap();
+        assert!(verify_payload_signature(
+            SECRET,
+            timestamp,
+            &request.body,
+            &header(SIGNATURE_HEADER)
+        )
+        .unwrap());
+    }
+}
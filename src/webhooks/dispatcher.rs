@@ -0,0 +1,381 @@
+// Webhook delivery with a bounded queue and exponential backoff
+
+use crate::config::{WebhookConfig, WebhookEndpointConfig};
+use crate::domain::audit::AuditEvent;
+use crate::errors::{AppError, Result};
+use crate::webhooks::payload::{
+    sign_payload, WebhookPayload, DELIVERY_ID_HEADER, SIGNATURE_HEADER, TIMESTAMP_HEADER,
+};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{mpsc, Semaphore};
+use tracing::{debug, error, info, warn};
+
+/// Retry settings for a single delivery
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    pub max_retries: u32,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+}
+
+impl RetryPolicy {
+    /// Backoff before the given retry attempt (1-based), doubling each time
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        self.initial_backoff
+            .saturating_mul(factor)
+            .min(self.max_backoff)
+    }
+}
+
+impl From<&WebhookConfig> for RetryPolicy {
+    fn from(config: &WebhookConfig) -> Self {
+        Self {
+            max_retries: config.max_retries,
+            initial_backoff: Duration::from_millis(config.initial_backoff_ms),
+            max_backoff: Duration::from_millis(config.max_backoff_ms),
+        }
+    }
+}
+
+/// Fans selected audit events out to configured webhook endpoints
+///
+/// Events are queued without blocking the caller. When the queue is full new
+/// events are dropped (and logged) rather than applying backpressure to the
+/// request path.
+pub struct WebhookDispatcher {
+    sender: mpsc::Sender<WebhookPayload>,
+    endpoints: Arc<Vec<WebhookEndpointConfig>>,
+}
+
+impl WebhookDispatcher {
+    /// Create a dispatcher and spawn its delivery worker
+    pub fn new(config: &WebhookConfig) -> Result<Self> {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(config.request_timeout_seconds))
+            .build()
+            .map_err(|e| AppError::Configuration(format!("Failed to build webhook client: {}", e)))?;
+
+        let (sender, receiver) = mpsc::channel(config.queue_size.max(1));
+        let endpoints = Arc::new(config.endpoints.clone());
+
+        tokio::spawn(delivery_worker(
+            receiver,
+            client,
+            endpoints.clone(),
+            RetryPolicy::from(config),
+            config.max_concurrent_deliveries.max(1),
+        ));
+
+        info!(
+            "Webhook dispatcher started ({} endpoints, queue_size={})",
+            endpoints.len(),
+            config.queue_size
+        );
+
+        Ok(Self { sender, endpoints })
+    }
+
+    /// Queue an audit event for delivery to every endpoint subscribed to its type
+    ///
+    /// Returns true if the event was queued, false if no endpoint wants it or
+    /// the queue is full.
+    pub fn dispatch(&self, event: &AuditEvent) -> bool {
+        let subscribed = self
+            .endpoints
+            .iter()
+            .any(|endpoint| endpoint.event_types.contains(&event.event_type));
+        if !subscribed {
+            return false;
+        }
+
+        match self.sender.try_send(WebhookPayload::from(event)) {
+            Ok(()) => true,
+            Err(mpsc::error::TrySendError::Full(payload)) => {
+                warn!(
+                    "Webhook queue full, dropping {} event {}",
+                    payload.event_type.as_str(),
+                    payload.id
+                );
+                false
+            }
+            Err(mpsc::error::TrySendError::Closed(_)) => {
+                error!("Webhook delivery worker has stopped");
+                false
+            }
+        }
+    }
+}
+
+/// Background worker that delivers queued payloads
+async fn delivery_worker(
+    mut receiver: mpsc::Receiver<WebhookPayload>,
+    client: reqwest::Client,
+    endpoints: Arc<Vec<WebhookEndpointConfig>>,
+    retry: RetryPolicy,
+    max_concurrent: usize,
+) {
+    let permits = Arc::new(Semaphore::new(max_concurrent));
+
+    while let Some(payload) = receiver.recv().await {
+        for endpoint in endpoints
+            .iter()
+            .filter(|endpoint| endpoint.event_types.contains(&payload.event_type))
+        {
+            // Bound in-flight deliveries; waiting here backs up into the queue
+            let Ok(permit) = permits.clone().acquire_owned().await else {
+                return;
+            };
+
+            let client = client.clone();
+            let endpoint = endpoint.clone();
+            let payload = payload.clone();
+            let retry = retry.clone();
+
+            tokio::spawn(async move {
+                if let Err(e) = deliver(&client, &endpoint, &payload, &retry).await {
+                    error!("Webhook delivery {} to {} failed: {}", payload.id, endpoint.url, e);
+                }
+                drop(permit);
+            });
+        }
+    }
+
+    warn!("Webhook queue closed, delivery worker exiting");
+}
+
+/// Deliver a payload to one endpoint, retrying server errors with backoff
+///
+/// Client errors (4xx other than 429) are not retried since repeating the same
+/// request will not change the outcome.
+pub async fn deliver(
+    client: &reqwest::Client,
+    endpoint: &WebhookEndpointConfig,
+    payload: &WebhookPayload,
+    retry: &RetryPolicy,
+) -> Result<()> {
+    let body = serde_json::to_vec(payload)
+        .map_err(|e| AppError::Internal(format!("Failed to serialize webhook payload: {}", e)))?;
+
+    let mut attempt = 0;
+    loop {
+        let timestamp = chrono::Utc::now().timestamp();
+        let signature = sign_payload(&endpoint.secret, timestamp, &body)?;
+
+        let result = client
+            .post(&endpoint.url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(SIGNATURE_HEADER, signature)
+            .header(TIMESTAMP_HEADER, timestamp.to_string())
+            .header(DELIVERY_ID_HEADER, payload.id.to_string())
+            .body(body.clone())
+            .send()
+            .await;
+
+        let failure = match result {
+            Ok(response) if response.status().is_success() => {
+                debug!("Delivered webhook {} to {}", payload.id, endpoint.url);
+                return Ok(());
+            }
+            Ok(response) => {
+                let status = response.status();
+                if status.is_client_error() && status != reqwest::StatusCode::TOO_MANY_REQUESTS {
+                    return Err(AppError::Internal(format!(
+                        "Webhook endpoint rejected delivery with status {}",
+                        status
+                    )));
+                }
+                format!("status {}", status)
+            }
+            Err(e) => e.to_string(),
+        };
+
+        attempt += 1;
+        if attempt > retry.max_retries {
+            return Err(AppError::Internal(format!(
+                "Webhook delivery gave up after {} attempts: {}",
+                attempt, failure
+            )));
+        }
+
+        let backoff = retry.backoff(attempt);
+        warn!(
+            "Webhook delivery {} to {} failed ({}), retrying in {:?}",
+            payload.id, endpoint.url, failure, backoff
+        );
+        tokio::time::sleep(backoff).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::audit::AuditEventType;
+    use crate::webhooks::payload::verify_payload_signature;
+    use uuid::Uuid;
+    use wiremock::matchers::{header_exists, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    const SECRET: &str = "test-webhook-secret";
+
+    fn endpoint(server: &MockServer, event_types: Vec<AuditEventType>) -> WebhookEndpointConfig {
+        WebhookEndpointConfig {
+            url: format!("{}/hooks", server.uri()),
+            secret: SECRET.to_string(),
+            event_types,
+        }
+    }
+
+    fn header(request: &wiremock::Request, name: &'static str) -> String {
+        request
+            .headers
+            .get(&name.into())
+            .map(|values| values.last().as_str().to_string())
+            .unwrap_or_default()
+    }
+
+    fn fast_retry() -> RetryPolicy {
+        RetryPolicy {
+            max_retries: 3,
+            initial_backoff: Duration::from_millis(10),
+            max_backoff: Duration::from_millis(50),
+        }
+    }
+
+    fn create_test_event(event_type: AuditEventType) -> AuditEvent {
+        AuditEvent::new(
+            Uuid::new_v4(),
+            event_type,
+            "suspend".to_string(),
+            "identity".to_string(),
+        )
+        .with_resource_id(Uuid::new_v4().to_string())
+    }
+
+    #[test]
+    fn test_backoff_is_exponential_and_capped() {
+        let retry = RetryPolicy {
+            max_retries: 10,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_millis(1000),
+        };
+
+        assert_eq!(retry.backoff(1), Duration::from_millis(100));
+        assert_eq!(retry.backoff(2), Duration::from_millis(200));
+        assert_eq!(retry.backoff(3), Duration::from_millis(400));
+        assert_eq!(retry.backoff(5), Duration::from_millis(1000));
+    }
+
+    #[tokio::test]
+    async fn test_delivery_sends_signed_payload() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/hooks"))
+            .and(header_exists(SIGNATURE_HEADER))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let endpoint = endpoint(&server, vec![AuditEventType::IdentityUpdated]);
+        let event = create_test_event(AuditEventType::IdentityUpdated);
+        let payload = WebhookPayload::from(&event);
+
+        deliver(&reqwest::Client::new(), &endpoint, &payload, &fast_retry())
+            .await
+            .unwrap();
+
+        let requests = server.received_requests().await.unwrap();
+        assert_eq!(requests.len(), 1);
+        let request = &requests[0];
+
+        let received: WebhookPayload = serde_json::from_slice(&request.body).unwrap();
+        assert_eq!(received, payload);
+        assert_eq!(received.event_type, AuditEventType::IdentityUpdated);
+        assert_eq!(received.resource_id, event.resource_id);
+
+        let timestamp: i64 = header(request, TIMESTAMP_HEADER).parse().unwrap();
+        let signature = header(request, SIGNATURE_HEADER);
+        assert!(verify_payload_signature(SECRET, timestamp, &request.body, &signature).unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_delivery_retries_on_server_error() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(500))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&server)
+            .await;
+
+        let endpoint = endpoint(&server, vec![AuditEventType::PolicyUpdated]);
+        let payload = WebhookPayload::from(&create_test_event(AuditEventType::PolicyUpdated));
+
+        deliver(&reqwest::Client::new(), &endpoint, &payload, &fast_retry())
+            .await
+            .unwrap();
+
+        let requests = server.received_requests().await.unwrap();
+        assert_eq!(requests.len(), 2);
+
+        // Retries carry the same delivery ID so receivers can deduplicate
+        assert_eq!(
+            header(&requests[0], DELIVERY_ID_HEADER),
+            payload.id.to_string()
+        );
+        assert_eq!(
+            header(&requests[1], DELIVERY_ID_HEADER),
+            payload.id.to_string()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_delivery_does_not_retry_client_error() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(400))
+            .mount(&server)
+            .await;
+
+        let endpoint = endpoint(&server, vec![AuditEventType::PolicyUpdated]);
+        let payload = WebhookPayload::from(&create_test_event(AuditEventType::PolicyUpdated));
+
+        let result = deliver(&reqwest::Client::new(), &endpoint, &payload, &fast_retry()).await;
+        assert!(result.is_err());
+        assert_eq!(server.received_requests().await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_dispatch_only_subscribed_event_types() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&server)
+            .await;
+
+        let config = WebhookConfig {
+            enabled: true,
+            queue_size: 10,
+            max_concurrent_deliveries: 2,
+            max_retries: 0,
+            initial_backoff_ms: 10,
+            max_backoff_ms: 10,
+            request_timeout_seconds: 5,
+            endpoints: vec![endpoint(&server, vec![AuditEventType::IdentityUpdated])],
+            account: None,
+        };
+        let dispatcher = WebhookDispatcher::new(&config).unwrap();
+
+        assert!(!dispatcher.dispatch(&create_test_event(AuditEventType::Authentication)));
+        assert!(dispatcher.dispatch(&create_test_event(AuditEventType::IdentityUpdated)));
+
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        let requests = server.received_requests().await.unwrap();
+        assert_eq!(requests.len(), 1);
+    }
+}
@@ -0,0 +1,8 @@
+// Outbound webhook notifications for IAM events
+pub mod account;
+pub mod dispatcher;
+pub mod payload;
+
+pub use account::AccountNotifier;
+pub use dispatcher::WebhookDispatcher;
+pub use payload::WebhookPayload;
@@ -0,0 +1,111 @@
+// Webhook payloads and HMAC signatures
+
+use crate::domain::audit::{AuditEvent, AuditEventType, Decision};
+use crate::errors::{AppError, Result};
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use uuid::Uuid;
+
+/// Header carrying the hex-encoded HMAC-SHA256 signature
+pub const SIGNATURE_HEADER: &str = "X-Agent-IAM-Signature";
+/// Header carrying the Unix timestamp included in the signature
+pub const TIMESTAMP_HEADER: &str = "X-Agent-IAM-Timestamp";
+/// Header carrying the unique delivery ID (stable across retries)
+pub const DELIVERY_ID_HEADER: &str = "X-Agent-IAM-Delivery";
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// JSON body delivered to webhook endpoints
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct WebhookPayload {
+    /// Delivery ID, useful for deduplication on the receiving side
+    pub id: Uuid,
+    pub event_type: AuditEventType,
+    pub tenant_id: Uuid,
+    pub actor_identity_id: Option<Uuid>,
+    pub action: String,
+    pub resource_type: String,
+    pub resource_id: Option<String>,
+    pub decision: Option<Decision>,
+    pub metadata: serde_json::Value,
+    pub occurred_at: DateTime<Utc>,
+}
+
+impl From<&AuditEvent> for WebhookPayload {
+    fn from(event: &AuditEvent) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            event_type: event.event_type,
+            tenant_id: event.tenant_id,
+            actor_identity_id: event.actor_identity_id,
+            action: event.action.clone(),
+            resource_type: event.resource_type.clone(),
+            resource_id: event.resource_id.clone(),
+            decision: event.decision,
+            metadata: event.metadata.clone(),
+            occurred_at: event.timestamp,
+        }
+    }
+}
+
+/// Sign a payload body for the given timestamp
+///
+/// The signature covers `"{timestamp}.{body}"` so receivers can reject
+/// replays of old deliveries by checking the timestamp header.
+pub fn sign_payload(secret: &str, timestamp: i64, body: &[u8]) -> Result<String> {
+    let mut mac = HmacSha256::new_from_slice(secret.as_bytes())
+        .map_err(|e| AppError::Cryptographic(format!("Invalid webhook secret: {}", e)))?;
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body);
+    Ok(format!("sha256={}", hex::encode(mac.finalize().into_bytes())))
+}
+
+/// Verify a signature produced by `sign_payload` in constant time
+pub fn verify_payload_signature(
+    secret: &str,
+    timestamp: i64,
+    body: &[u8],
+    signature: &str,
+) -> Result<bool> {
+    let Some(encoded) = signature.strip_prefix("sha256=") else {
+        return Ok(false);
+    };
+    let Ok(expected) = hex::decode(encoded) else {
+        return Ok(false);
+    };
+
+    let mut mac = HmacSha256::new_from_slice(secret.as_bytes())
+        .map_err(|e| AppError::Cryptographic(format!("Invalid webhook secret: {}", e)))?;
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body);
+    Ok(mac.verify_slice(&expected).is_ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_signature_round_trip() {
+        let body = br#"{"event_type":"identity_updated"}"#;
+        let signature = sign_payload("webhook-secret", 1_700_000_000, body).unwrap();
+
+        assert!(signature.starts_with("sha256="));
+        assert!(verify_payload_signature("webhook-secret", 1_700_000_000, body, &signature).unwrap());
+    }
+
+    #[test]
+    fn test_signature_rejects_tampering() {
+        let body = br#"{"event_type":"identity_updated"}"#;
+        let signature = sign_payload("webhook-secret", 1_700_000_000, body).unwrap();
+
+        assert!(!verify_payload_signature("other-secret", 1_700_000_000, body, &signature).unwrap());
+        assert!(!verify_payload_signature("webhook-secret", 1_700_000_001, body, &signature).unwrap());
+        assert!(!verify_payload_signature("webhook-secret", 1_700_000_000, b"{}", &signature).unwrap());
+        assert!(!verify_payload_signature("webhook-secret", 1_700_000_000, body, "garbage").unwrap());
+    }
+}